@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use testore_core::{AllocationParams, DuplicatePolicy, InactiveExclusion, MinerAccount, Snapshot};
+
+/// Golden-file regression tests for allocation outputs
+///
+/// Runs the allocation engine over `fixtures/miners.json` and compares the
+/// result byte-for-byte with the checked-in golden files. Any change to token
+/// math shows up as a failing diff here. Beyond the defaults, the cases pin
+/// each duplicate policy (over `fixtures/duplicates.json`), the recency rule,
+/// the liveness boost and the snapshot text the bridge writes.
+///
+/// After an intentional change, regenerate with:
+/// `UPDATE_GOLDEN=1 cargo test --test golden_test`
+/// and review the fixture diff like any other code change.
+
+#[derive(Debug, Deserialize)]
+struct MinerFixture {
+    authority: String,
+    total_hashes: u64,
+    rounds_completed: u32,
+    last_hash_at: i64,
+    current_streak: u32,
+    best_difficulty: u8,
+}
+
+#[derive(Debug, Serialize)]
+struct AllocationReport {
+    total_miners: usize,
+    total_tokens: u64,
+    allocations: BTreeMap<String, u64>,
+}
+
+impl AllocationReport {
+    fn new(allocations: &HashMap<Pubkey, u64>) -> Self {
+        Self {
+            total_miners: allocations.len(),
+            total_tokens: allocations.values().sum(),
+            allocations: allocations
+                .iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect(),
+        }
+    }
+}
+
+/// Outcome of one duplicate policy
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum PolicyReport {
+    Allocated(AllocationReport),
+    Refused(String),
+}
+
+#[derive(Debug, Serialize)]
+struct InactiveReport {
+    inactive: Option<InactiveExclusion>,
+    allocations: AllocationReport,
+}
+
+#[derive(Debug, Serialize)]
+struct LivenessReport {
+    boosted: usize,
+    allocations: AllocationReport,
+}
+
+/// Cluster time for the recency cases: one day after the midpoint of the
+/// fixtures' last proofs, so a one-day window keeps the newer half
+const NOW: i64 = 1_718_000_550 + 24 * 60 * 60;
+
+/// Fixture miners that count as verified live
+const LIVE: [&str; 3] = [
+    "k7FaK87WHGVXzkaoHb7CdVPgkKDQhZ29VLDeBVbDfYn",
+    "LbUiWL3xVV8hTFYBVdbTNrpDo41NKS6o3LHHuDzjfcY",
+    // No allocation to boost
+    "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+];
+
+#[test]
+fn test_allocations_match_golden_json() {
+    let (_, allocations) = run_fixture();
+
+    let rendered =
+        serde_json::to_string_pretty(&AllocationReport::new(&allocations)).unwrap() + "\n";
+    assert_golden("allocations.golden.json", &rendered);
+}
+
+#[test]
+fn test_allocations_match_golden_csv() {
+    let (miners, allocations) = run_fixture();
+
+    let hashes: HashMap<Pubkey, u64> = miners
+        .iter()
+        .map(|m| (m.authority, m.total_hashes))
+        .collect();
+
+    let mut rows: Vec<_> = allocations.iter().collect();
+    rows.sort_by(|a, b| b.1.cmp(a.1).then(a.0.to_string().cmp(&b.0.to_string())));
+
+    let mut rendered = String::from("authority,total_hashes,tokens\n");
+    for (authority, tokens) in rows {
+        rendered.push_str(&format!("{},{},{}\n", authority, hashes[authority], tokens));
+    }
+
+    assert_golden("allocations.golden.csv", &rendered);
+}
+
+#[test]
+fn test_duplicate_policies_match_golden() {
+    let policies = [
+        ("sum", DuplicatePolicy::Sum),
+        ("max", DuplicatePolicy::Max),
+        ("reject", DuplicatePolicy::Reject),
+    ];
+
+    let mut reports = BTreeMap::new();
+    for (name, duplicate_policy) in policies {
+        let params = AllocationParams {
+            duplicate_policy,
+            ..AllocationParams::default()
+        };
+        let miners = load_miners("duplicates.json");
+        let report = match run_airdrop(miners, &params, 0, &BTreeSet::new()) {
+            Ok(airdrop) => PolicyReport::Allocated(AllocationReport::new(&airdrop.allocations)),
+            Err(reason) => PolicyReport::Refused(reason),
+        };
+        reports.insert(name, report);
+    }
+
+    let rendered = serde_json::to_string_pretty(&reports).unwrap() + "\n";
+    assert_golden("duplicates.golden.json", &rendered);
+}
+
+#[test]
+fn test_inactive_exclusion_matches_golden() {
+    // A short top N shows the rule runs before ranking
+    let params = AllocationParams {
+        max_inactive_days: 1,
+        top_miners: 4,
+        ..AllocationParams::default()
+    };
+    let airdrop = run_airdrop(load_miners("miners.json"), &params, NOW, &BTreeSet::new()).unwrap();
+
+    let report = InactiveReport {
+        inactive: airdrop.inactive,
+        allocations: AllocationReport::new(&airdrop.allocations),
+    };
+    let rendered = serde_json::to_string_pretty(&report).unwrap() + "\n";
+    assert_golden("inactive.golden.json", &rendered);
+}
+
+#[test]
+fn test_liveness_boost_matches_golden() {
+    let params = AllocationParams {
+        liveness_boost_bps: 2_500,
+        ..AllocationParams::default()
+    };
+    let airdrop = run_airdrop(load_miners("miners.json"), &params, 0, &live(&LIVE)).unwrap();
+
+    let report = LivenessReport {
+        boosted: airdrop.boosted,
+        allocations: AllocationReport::new(&airdrop.allocations),
+    };
+    let rendered = serde_json::to_string_pretty(&report).unwrap() + "\n";
+    assert_golden("liveness.golden.json", &rendered);
+}
+
+#[test]
+fn test_snapshot_text_matches_golden() {
+    let params = AllocationParams {
+        liveness_boost_bps: 2_500,
+        max_inactive_days: 1,
+        ..AllocationParams::default()
+    };
+    let live = live(&LIVE[..1]);
+    let mut airdrop = run_airdrop(load_miners("miners.json"), &params, NOW, &live).unwrap();
+
+    let flagged = Pubkey::from_str("gBxS1f6uyyGPuW5MzGBukidSb71jdsCb5fZaoSzULE5").unwrap();
+    let reason = "flagged: shared rig".to_string();
+    assert!(airdrop.allocations.remove(&flagged).is_some());
+
+    let snapshot = Snapshot::new(&airdrop.allocations, &params)
+        .with_inputs(&airdrop.leaderboard, &live)
+        .with_inactive(airdrop.inactive)
+        .with_excluded([(&flagged, &reason)]);
+    let snapshot = Snapshot {
+        round_number: Some(42),
+        slot: Some(123_456),
+        ..snapshot
+    };
+    snapshot.reproduce().unwrap();
+
+    assert_golden("snapshot.golden.txt", &snapshot.to_text().unwrap());
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures")
+}
+
+/// Encode fixtures as raw account data and push them through the same
+/// parse → rank → allocate path the bridge uses
+fn run_fixture() -> (Vec<MinerAccount>, HashMap<Pubkey, u64>) {
+    let mut miners = load_miners("miners.json");
+    testore_core::rank_miners(&mut miners);
+
+    let allocations = testore_core::calculate_allocations(&miners, &AllocationParams::default());
+    (miners, allocations)
+}
+
+/// Parse every miner in `fixtures/<name>` from its raw account data
+fn load_miners(name: &str) -> Vec<MinerAccount> {
+    let fixtures: Vec<MinerFixture> =
+        serde_json::from_str(&fs::read_to_string(fixtures_dir().join(name)).unwrap()).unwrap();
+
+    fixtures
+        .iter()
+        .map(|f| testore_core::parse_miner_account(&encode_miner(f)).unwrap())
+        .collect()
+}
+
+fn live(authorities: &[&str]) -> BTreeSet<Pubkey> {
+    authorities
+        .iter()
+        .map(|authority| Pubkey::from_str(authority).unwrap())
+        .collect()
+}
+
+struct Airdrop {
+    leaderboard: Vec<MinerAccount>,
+    allocations: HashMap<Pubkey, u64>,
+    inactive: Option<InactiveExclusion>,
+    boosted: usize,
+}
+
+/// The bridge's airdrop steps, in its order, minus RPC and score decay:
+/// merge duplicates → drop inactive → rank → allocate → boost live miners
+///
+/// Fails with the reason when the duplicate policy refuses the miners.
+fn run_airdrop(
+    miners: Vec<MinerAccount>,
+    params: &AllocationParams,
+    now: i64,
+    live: &BTreeSet<Pubkey>,
+) -> Result<Airdrop, String> {
+    let (mut leaderboard, _) =
+        testore_core::consolidate_duplicates(miners, params.duplicate_policy)
+            .map_err(|err| err.to_string())?;
+    let inactive = testore_core::exclude_inactive(&mut leaderboard, params, now);
+    testore_core::rank_miners_by(&mut leaderboard, params.metric);
+    leaderboard.truncate(params.top_miners);
+
+    let mut allocations = testore_core::calculate_allocations(&leaderboard, params);
+    let boosted = testore_core::apply_liveness_boost(&mut allocations, live, params);
+    Ok(Airdrop {
+        leaderboard,
+        allocations,
+        inactive,
+        boosted,
+    })
+}
+
+fn encode_miner(fixture: &MinerFixture) -> Vec<u8> {
+    let mut data = testore_core::miner_discriminator().to_vec();
+    data.extend_from_slice(Pubkey::from_str(&fixture.authority).unwrap().as_ref());
+    data.extend_from_slice(&fixture.total_hashes.to_le_bytes());
+    data.extend_from_slice(&fixture.rounds_completed.to_le_bytes());
+    data.extend_from_slice(&fixture.last_hash_at.to_le_bytes());
+    data.extend_from_slice(&fixture.current_streak.to_le_bytes());
+    data.push(fixture.best_difficulty);
+    data.push(255); // bump
+    data
+}
+
+fn assert_golden(name: &str, actual: &str) {
+    let path = fixtures_dir().join(name);
+
+    if std::env::var("UPDATE_GOLDEN").is_ok() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap();
+    assert!(
+        expected == actual,
+        "{} is out of date.\n--- expected\n{}\n--- actual\n{}",
+        path.display(),
+        expected,
+        actual
+    );
+}
@@ -1,4 +1,28 @@
+use anchor_lang::{
+    prelude::AccountInfo, solana_program::entrypoint::ProgramResult, AccountDeserialize,
+    AnchorDeserialize, Event,
+};
 use anyhow::Result;
+use base64::Engine;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    clock::Clock,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+use testore_program::{
+    client::{self, ToInstruction},
+    commitment_hash, pad_metadata, ErrorCode, GlobalParams, GlobalRound, LivenessProof, Miner,
+    MinerArchive, MinerAttestation, MinerMetadata, MinerRankInputs, MinerRegion, PoolLeader,
+    RejectionCounts, RejectionReason, RewardPool, RewardPreview, RoundScore, ScoreBucket,
+    ScoreDecay, ScoringMigration, ScoringPhase, StatsRoot, WorkerProof, ATTESTATION_VERSION,
+    BPS_DENOMINATOR, DEFAULT_ROUND_DURATION, NO_SCORE_BUCKET, REVEAL_DELAY_SLOTS,
+    SCORE_BUCKET_COUNT,
+};
 
 /// Integration tests for TestORE
 /// 
@@ -7,6 +31,7 @@ use anyhow::Result;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     /// Test proof validation logic
     #[test]
@@ -15,20 +40,20 @@ mod tests {
         
         // All zeros should pass any difficulty
         let easy_hash = [0u8; 32];
-        assert!(check_difficulty(&easy_hash, 8));
-        assert!(check_difficulty(&easy_hash, 16));
+        assert!(testore_program::check_difficulty(&easy_hash, 8));
+        assert!(testore_program::check_difficulty(&easy_hash, 16));
 
         // Hash starting with 0xFF should fail
         let mut hard_hash = [0u8; 32];
         hard_hash[0] = 0xFF;
-        assert!(!check_difficulty(&hard_hash, 1));
+        assert!(!testore_program::check_difficulty(&hard_hash, 1));
 
         // Hash with exactly 8 leading zero bits
         let mut medium_hash = [0u8; 32];
         medium_hash[0] = 0b00000000;
         medium_hash[1] = 0b10000000;
-        assert!(check_difficulty(&medium_hash, 8));
-        assert!(!check_difficulty(&medium_hash, 9));
+        assert!(testore_program::check_difficulty(&medium_hash, 8));
+        assert!(!testore_program::check_difficulty(&medium_hash, 9));
     }
 
     /// Test hash generation
@@ -57,8 +82,8 @@ mod tests {
     fn test_difficulty_calculation() {
         let mut hash = [0u8; 32];
         
-        // All zeros = 256 bits difficulty
-        assert_eq!(calculate_difficulty(&hash), 256);
+        // All zeros = 256 bits difficulty, saturated to u8::MAX
+        assert_eq!(calculate_difficulty(&hash), u8::MAX);
 
         // First bit is 1 = 0 difficulty
         hash[0] = 0b10000000;
@@ -70,6 +95,90 @@ mod tests {
         assert_eq!(calculate_difficulty(&hash), 9);
     }
 
+    proptest! {
+        /// `check_difficulty` agrees with the leading-zero count for any hash
+        #[test]
+        fn prop_check_difficulty_matches_calculation(
+            hash in any::<[u8; 32]>(),
+            difficulty in any::<u8>(),
+        ) {
+            prop_assert_eq!(
+                testore_program::check_difficulty(&hash, difficulty),
+                calculate_difficulty(&hash) >= difficulty
+            );
+        }
+
+        /// Same property, biased towards hashes with long zero prefixes
+        #[test]
+        fn prop_check_difficulty_matches_on_zero_prefixes(
+            zero_bytes in 0usize..=32,
+            tail in any::<[u8; 32]>(),
+            difficulty in any::<u8>(),
+        ) {
+            let mut hash = tail;
+            hash[..zero_bytes].fill(0);
+            prop_assert_eq!(
+                testore_program::check_difficulty(&hash, difficulty),
+                calculate_difficulty(&hash) >= difficulty
+            );
+        }
+
+        /// Decay only ever takes score away, and no rate is a free pass
+        #[test]
+        fn prop_decay_never_increases_score(
+            score in any::<u64>(),
+            rounds in any::<u64>(),
+            decay_bps in 0..=BPS_DENOMINATOR,
+        ) {
+            let decayed = testore_program::decayed_score(score, rounds, decay_bps);
+            prop_assert!(decayed <= score);
+            if decay_bps > 0 && rounds > 0 && score > 0 {
+                prop_assert!(decayed < score);
+            }
+        }
+
+        /// Idling longer never leaves a miner with more
+        #[test]
+        fn prop_decay_is_monotonic_in_idle_rounds(
+            score in any::<u64>(),
+            rounds in 0u64..1_000_000,
+            extra in 0u64..1_000_000,
+            decay_bps in 0..=BPS_DENOMINATOR,
+        ) {
+            prop_assert!(
+                testore_program::decayed_score(score, rounds + extra, decay_bps)
+                    <= testore_program::decayed_score(score, rounds, decay_bps)
+            );
+        }
+
+        /// Even the slowest rate wipes out any score after a long enough
+        /// absence (0.9999^500_000 < 2^-64), without overflowing on the way
+        #[test]
+        fn prop_long_idle_periods_decay_to_zero(
+            score in any::<u64>(),
+            rounds in 500_000u64..=u64::MAX,
+            decay_bps in 1..=BPS_DENOMINATOR,
+        ) {
+            prop_assert_eq!(testore_program::decayed_score(score, rounds, decay_bps), 0);
+        }
+
+        /// Idle time between proofs converts to whole rounds
+        #[test]
+        fn prop_idle_rounds_counts_whole_rounds(
+            last_hash_at in 0i64..1_000_000_000,
+            elapsed in 0i64..1_000_000_000,
+            round_duration in 1i64..1_000_000,
+        ) {
+            let idle = testore_program::idle_rounds(
+                last_hash_at,
+                last_hash_at + elapsed,
+                round_duration,
+            );
+            prop_assert!(idle as i64 * round_duration <= elapsed);
+            prop_assert!((idle as i64 + 1) * round_duration > elapsed);
+        }
+    }
+
     /// Test airdrop allocation calculation
     #[test]
     fn test_airdrop_allocation() {
@@ -92,26 +201,7 @@ mod tests {
     }
 
     // Helper functions for tests
-    fn check_difficulty(hash: &[u8; 32], difficulty: u8) -> bool {
-        let required_zeros = difficulty as usize;
-
-        for (i, byte) in hash.iter().enumerate() {
-            let leading_zeros = byte.leading_zeros() as usize;
-            let bit_pos = i * 8;
-
-            if bit_pos + leading_zeros < required_zeros {
-                return false;
-            }
-
-            if leading_zeros < 8 {
-                return bit_pos + leading_zeros >= required_zeros;
-            }
-        }
-
-        true
-    }
-
-    fn hash_proof(
+    pub(super) fn hash_proof(
         authority: &solana_sdk::pubkey::Pubkey,
         challenge: &[u8; 32],
         nonce: u64,
@@ -125,7 +215,7 @@ mod tests {
         hasher.finalize().into()
     }
 
-    fn calculate_difficulty(hash: &[u8; 32]) -> u8 {
+    pub(super) fn calculate_difficulty(hash: &[u8; 32]) -> u8 {
         let mut difficulty = 0u8;
 
         for byte in hash.iter() {
@@ -144,17 +234,1729 @@ mod tests {
     }
 }
 
-/// Integration test placeholder
-/// 
-/// TODO: Add end-to-end tests that:
-/// 1. Deploy program to localnet
-/// 2. Initialize miner
-/// 3. Submit proof
-/// 4. Verify state changes
+/// End-to-end mining workflow against the program running in `ProgramTest`
+///
+/// 1. Initialize global round and a miner
+/// 2. Mine valid nonces in-test and submit them
+/// 3. Verify Miner/GlobalRound state, including the 10-hash streak rollover
+/// 4. Verify the difficulty and rate-limit rejection paths
 #[tokio::test]
-#[ignore]  // Requires localnet setup
 async fn test_full_mining_workflow() -> Result<()> {
-    println!("Full integration test requires localnet validator");
-    println!("Run: solana-test-validator");
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+
+    initialize(&mut ctx).await?;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    assert_eq!(round.round_number, 1);
+    assert_eq!(round.min_difficulty, 8);
+    assert_eq!(round.admin, authority);
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.authority, authority);
+    assert_eq!(miner.total_hashes, 0);
+
+    // Ten accepted proofs complete one round and reset the streak
+    let difficulty = round.min_difficulty;
+    let mut nonce = 0;
+    for i in 1..=10u64 {
+        nonce = mine_nonce(&authority, &round.current_challenge, difficulty, nonce);
+        advance_clock(&mut ctx, 1).await;
+        send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)]).await?;
+        nonce += 1;
+
+        let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+        assert_eq!(miner.total_hashes, i);
+        assert_eq!(miner.current_streak, (i % 10) as u32);
+    }
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.rounds_completed, 1);
+    assert_eq!(miner.best_difficulty, difficulty);
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    assert_eq!(round.total_hashes_submitted, 10);
+    assert_eq!(round.total_rounds_completed, 1);
+
+    // Hash that doesn't meet the claimed difficulty
+    let weak_nonce = find_nonce(&authority, &round.current_challenge, nonce, |hash| {
+        tests::calculate_difficulty(hash) < difficulty
+    });
+    advance_clock(&mut ctx, 1).await;
+    let err = send(
+        &mut ctx,
+        &[submit_proof_ix(&authority, weak_nonce, difficulty)],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, ErrorCode::InsufficientDifficulty);
+
+    // Valid hash, but claimed difficulty is below the round minimum
+    let err = send(&mut ctx, &[submit_proof_ix(&authority, weak_nonce, 0)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::DifficultyTooLow);
+
+    // Second proof within the same second is rate limited
+    nonce = mine_nonce(
+        &authority,
+        &round.current_challenge,
+        difficulty,
+        weak_nonce + 1,
+    );
+    send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)]).await?;
+    nonce = mine_nonce(&authority, &round.current_challenge, difficulty, nonce + 1);
+    let err = send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::TooManySubmissions);
+
+    // Rejected submissions leave state untouched
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.total_hashes, 11);
+    assert_eq!(miner.current_streak, 1);
+
+    // Proofs against a round past its duration are refused
+    advance_clock(&mut ctx, round.round_duration + 1).await;
+    let err = send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::RoundExpired);
+
+    Ok(())
+}
+
+/// Commit-reveal submission: delay, mismatch and replay checks
+#[tokio::test]
+async fn test_commit_reveal_workflow() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let difficulty = round.min_difficulty;
+    let nonce = mine_nonce(&authority, &round.current_challenge, difficulty, 0);
+    let salt = [42u8; 32];
+
+    send(
+        &mut ctx,
+        &[commit_proof_ix(&authority, commitment_hash(nonce, &salt))],
+    )
+    .await?;
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.commitment, commitment_hash(nonce, &salt));
+
+    // Same slot as the commit
+    advance_clock(&mut ctx, 1).await;
+    let err = send(
+        &mut ctx,
+        &[reveal_proof_ix(&authority, nonce, salt, difficulty)],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, ErrorCode::RevealTooEarly);
+
+    let slot = ctx.banks_client.get_root_slot().await?;
+    ctx.warp_to_slot(slot + REVEAL_DELAY_SLOTS + 1)?;
+    advance_clock(&mut ctx, 1).await;
+
+    // Wrong salt
+    let err = send(
+        &mut ctx,
+        &[reveal_proof_ix(&authority, nonce, [0u8; 32], difficulty)],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, ErrorCode::CommitmentMismatch);
+
+    send(
+        &mut ctx,
+        &[reveal_proof_ix(&authority, nonce, salt, difficulty)],
+    )
+    .await?;
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.total_hashes, 1);
+    assert_eq!(miner.commitment, [0u8; 32]);
+
+    // The commitment is consumed by the reveal
+    advance_clock(&mut ctx, 1).await;
+    let err = send(
+        &mut ctx,
+        &[reveal_proof_ix(&authority, nonce + 1, salt, difficulty)],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, ErrorCode::NoPendingCommitment);
+
+    Ok(())
+}
+
+/// Re-initialization is refused; parameters change via update_global_params
+#[tokio::test]
+async fn test_global_round_reinit_and_params() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let err = send(&mut ctx, &[init_global_round_ix(&authority)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::AlreadyInitialized);
+
+    let before: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+
+    let params = GlobalParams {
+        min_difficulty: Some(10),
+        round_duration: Some(600),
+        ..GlobalParams::default()
+    };
+    send(&mut ctx, &[update_global_params_ix(&authority, params)]).await?;
+
+    let after: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    assert_eq!(after.min_difficulty, 10);
+    assert_eq!(after.round_duration, 600);
+    assert_eq!(after.tier_step, before.tier_step);
+    assert_eq!(after.round_number, before.round_number);
+    assert_eq!(after.current_challenge, before.current_challenge);
+
+    // Minimums past one byte of leading zeros can still be mined
+    let nonce = mine_nonce(&authority, &after.current_challenge, 10, 0);
+    advance_clock(&mut ctx, 1).await;
+    send(&mut ctx, &[submit_proof_ix(&authority, nonce, 10)]).await?;
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.total_hashes, 1);
+
+    // ...and survive a rotation
+    send(&mut ctx, &[rotate_round_ix(&authority)]).await?;
+    let rotated: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    assert_eq!(rotated.min_difficulty, 10);
+    let nonce = mine_nonce(&authority, &rotated.current_challenge, 10, 0);
+    advance_clock(&mut ctx, 1).await;
+    send(&mut ctx, &[submit_proof_ix(&authority, nonce, 10)]).await?;
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.total_hashes, 2);
+
+    let params = GlobalParams {
+        min_difficulty: Some(0),
+        ..GlobalParams::default()
+    };
+    let err = send(&mut ctx, &[update_global_params_ix(&authority, params)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::InvalidParams);
+
     Ok(())
 }
+
+/// A tier floor past one byte of leading zeros can still be mined
+#[tokio::test]
+async fn test_tier_floor_above_one_byte() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let params = GlobalParams {
+        tier_step: Some(2),
+        ..GlobalParams::default()
+    };
+    send(&mut ctx, &[update_global_params_ix(&authority, params)]).await?;
+    send(&mut ctx, &[set_miner_tier_ix(&authority, &authority, 1)]).await?;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let floor = round.min_difficulty + 2;
+    assert!(floor >= 9);
+
+    let nonce = mine_nonce(&authority, &round.current_challenge, floor, 0);
+    advance_clock(&mut ctx, 1).await;
+    let err = send(
+        &mut ctx,
+        &[submit_proof_ix(&authority, nonce, round.min_difficulty)],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, ErrorCode::BelowTierDifficulty);
+
+    send(&mut ctx, &[submit_proof_ix(&authority, nonce, floor)]).await?;
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.total_hashes, 1);
+    assert_eq!(miner.best_difficulty, floor);
+
+    Ok(())
+}
+
+/// View instructions return derived values through return data
+#[tokio::test]
+async fn test_view_instructions() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+
+    let inputs: MinerRankInputs =
+        simulate_view(&mut ctx, get_miner_rank_inputs_ix(&authority)).await;
+    assert_eq!(inputs.authority, authority);
+    assert_eq!(inputs.total_hashes, 0);
+    assert_eq!(inputs.score, 0);
+    assert_eq!(inputs.tier, 0);
+
+    let preview: RewardPreview = simulate_view(
+        &mut ctx,
+        preview_round_reward_ix(&authority, round.min_difficulty),
+    )
+    .await;
+    assert!(preview.counts);
+    assert_eq!(preview.min_difficulty, round.min_difficulty);
+    assert_eq!(preview.hashes_credited, 1);
+    assert!(!preview.completes_round);
+    assert!(preview.seconds_until_next_submission <= 1);
+    assert!(preview.seconds_until_round_end > 0);
+    assert!(preview.seconds_until_round_end <= round.round_duration);
+
+    let preview: RewardPreview =
+        simulate_view(&mut ctx, preview_round_reward_ix(&authority, 1)).await;
+    assert!(!preview.counts);
+    assert_eq!(preview.hashes_credited, 0);
+
+    Ok(())
+}
+
+/// Reported rejections are counted per round and reset on rotation
+#[tokio::test]
+async fn test_rejection_telemetry() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    send(
+        &mut ctx,
+        &[
+            report_rejection_ix(&authority, RejectionReason::Difficulty),
+            report_rejection_ix(&authority, RejectionReason::RateLimited),
+            report_rejection_ix(&authority, RejectionReason::RateLimited),
+        ],
+    )
+    .await?;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    assert_eq!(
+        round.rejections,
+        RejectionCounts {
+            difficulty: 1,
+            rate_limited: 2,
+            stale: 0,
+        }
+    );
+
+    send(&mut ctx, &[rotate_round_ix(&authority)]).await?;
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    assert_eq!(round.rejections, RejectionCounts::default());
+
+    Ok(())
+}
+
+/// A clock stepping backwards doesn't lock the miner out
+#[tokio::test]
+async fn test_clock_skew_recovery() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let difficulty = round.min_difficulty;
+
+    let nonce = mine_nonce(&authority, &round.current_challenge, difficulty, 0);
+    advance_clock(&mut ctx, 60).await;
+    send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)]).await?;
+    let stamped: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+
+    // Clock steps back behind the recorded submission
+    advance_clock(&mut ctx, -30).await;
+    let nonce = mine_nonce(&authority, &round.current_challenge, difficulty, nonce + 1);
+    send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)]).await?;
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.total_hashes, 2);
+    assert!(miner.last_hash_at < stamped.last_hash_at);
+
+    // reset_timing clamps a future timestamp to the current clock
+    advance_clock(&mut ctx, -30).await;
+    send(&mut ctx, &[reset_timing_ix(&authority)]).await?;
+    let clock: Clock = ctx.banks_client.get_sysvar().await?;
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.last_hash_at, clock.unix_timestamp);
+
+    Ok(())
+}
+
+/// ScoreBucket counts follow miners across power-of-two boundaries
+#[tokio::test]
+async fn test_score_buckets() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let buckets: Vec<_> = (0..=3)
+        .map(|i| init_score_bucket_ix(&authority, i))
+        .collect();
+    send(&mut ctx, &buckets).await?;
+
+    let err = send(
+        &mut ctx,
+        &[init_score_bucket_ix(&authority, SCORE_BUCKET_COUNT)],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, ErrorCode::InvalidScoreBucket);
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let difficulty = round.min_difficulty;
+    let mut nonce = 0;
+    let next_nonce =
+        |start: u64| mine_nonce(&authority, &round.current_challenge, difficulty, start);
+
+    // First proof places the miner in bucket 1
+    nonce = next_nonce(nonce);
+    advance_clock(&mut ctx, 1).await;
+    send(
+        &mut ctx,
+        &[submit_proof_with_buckets_ix(
+            &authority, nonce, difficulty, None, 1,
+        )],
+    )
+    .await?;
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.score_bucket, 1);
+    let bucket: ScoreBucket = fetch(&mut ctx, score_bucket_pda(1)).await;
+    assert_eq!(bucket.member_count, 1);
+
+    // Second proof crosses into bucket 2; the wrong destination is refused
+    nonce = next_nonce(nonce + 1);
+    advance_clock(&mut ctx, 1).await;
+    let err = send(
+        &mut ctx,
+        &[submit_proof_with_buckets_ix(
+            &authority,
+            nonce,
+            difficulty,
+            Some(1),
+            3,
+        )],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, ErrorCode::WrongScoreBucket);
+
+    send(
+        &mut ctx,
+        &[submit_proof_with_buckets_ix(
+            &authority,
+            nonce,
+            difficulty,
+            Some(1),
+            2,
+        )],
+    )
+    .await?;
+
+    let one: ScoreBucket = fetch(&mut ctx, score_bucket_pda(1)).await;
+    let two: ScoreBucket = fetch(&mut ctx, score_bucket_pda(2)).await;
+    assert_eq!((one.member_count, two.member_count), (0, 1));
+
+    // Proofs without buckets leave the miner behind until it is synced
+    for _ in 0..2 {
+        nonce = next_nonce(nonce + 1);
+        advance_clock(&mut ctx, 1).await;
+        send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)]).await?;
+    }
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.total_hashes, 4);
+    assert_eq!(miner.score_bucket, 2);
+
+    send(&mut ctx, &[sync_score_bucket_ix(&authority, Some(2), 3)]).await?;
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.score_bucket, 3);
+    let two: ScoreBucket = fetch(&mut ctx, score_bucket_pda(2)).await;
+    let three: ScoreBucket = fetch(&mut ctx, score_bucket_pda(3)).await;
+    assert_eq!((two.member_count, three.member_count), (0, 1));
+
+    Ok(())
+}
+
+/// Attestations copy miner stats and can be refreshed
+#[tokio::test]
+async fn test_miner_attestation() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    send(&mut ctx, &[attest_miner_ix(&authority, &authority)]).await?;
+
+    let attestation: MinerAttestation = fetch(&mut ctx, attestation_pda(&authority)).await;
+    assert_eq!(attestation.version, ATTESTATION_VERSION);
+    assert_eq!(attestation.authority, authority);
+    assert_eq!(attestation.total_hashes, 0);
+    let first_slot = attestation.slot;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let nonce = mine_nonce(
+        &authority,
+        &round.current_challenge,
+        round.min_difficulty,
+        0,
+    );
+    advance_clock(&mut ctx, 1).await;
+    send(
+        &mut ctx,
+        &[submit_proof_ix(&authority, nonce, round.min_difficulty)],
+    )
+    .await?;
+
+    ctx.warp_to_slot(first_slot + 10)?;
+    send(&mut ctx, &[attest_miner_ix(&authority, &authority)]).await?;
+
+    let attestation: MinerAttestation = fetch(&mut ctx, attestation_pda(&authority)).await;
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(attestation.total_hashes, 1);
+    assert_eq!(attestation.score, miner.ranking_score());
+    assert_eq!(attestation.best_difficulty, round.min_difficulty);
+    assert!(attestation.slot > first_slot);
+
+    // A version 1 attestation (no score) grows to the current layout
+    truncate_account(&mut ctx, attestation_pda(&authority), 71).await;
+    ctx.warp_to_slot(attestation.slot + 10)?;
+    send(&mut ctx, &[attest_miner_ix(&authority, &authority)]).await?;
+
+    let attestation: MinerAttestation = fetch(&mut ctx, attestation_pda(&authority)).await;
+    assert_eq!(attestation.version, ATTESTATION_VERSION);
+    assert_eq!(attestation.score, miner.ranking_score());
+
+    Ok(())
+}
+
+/// Liveness challenges record the answer time and reject stale challenges
+#[tokio::test]
+async fn test_verify_liveness() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await?;
+    let expires_at = clock.unix_timestamp + 60;
+    send(&mut ctx, &[verify_liveness_ix(&authority, expires_at)]).await?;
+
+    let liveness: LivenessProof = fetch(&mut ctx, liveness_pda(&authority)).await;
+    assert_eq!(liveness.authority, authority);
+    assert_eq!(liveness.verified_at, clock.unix_timestamp);
+    assert_eq!(liveness.verifications, 1);
+
+    advance_clock(&mut ctx, 61).await;
+    let err = send(&mut ctx, &[verify_liveness_ix(&authority, expires_at)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::LivenessChallengeExpired);
+
+    // Once an issuer is configured, only its signature counts
+    let issuer = Keypair::new();
+    send(
+        &mut ctx,
+        &[configure_liveness_issuer_ix(&authority, &issuer.pubkey())],
+    )
+    .await?;
+    let expires_at = clock.unix_timestamp + 61 + 60;
+    let err = send(&mut ctx, &[verify_liveness_ix(&authority, expires_at)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::UnknownLivenessIssuer);
+
+    let ix = client::VerifyLiveness::new(issuer.pubkey(), authority, expires_at).instruction();
+    send_with_signers(&mut ctx, &[ix], &[&issuer]).await?;
+    let liveness: LivenessProof = fetch(&mut ctx, liveness_pda(&authority)).await;
+    assert_eq!(liveness.verifications, 2);
+
+    Ok(())
+}
+
+/// Pooled proofs fund the pot, which the leaders split after rotation
+#[tokio::test]
+async fn test_reward_pool() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let nonce = mine_nonce(
+        &authority,
+        &round.current_challenge,
+        round.min_difficulty,
+        0,
+    );
+    advance_clock(&mut ctx, 1).await;
+    // No pool configured yet
+    let result = send(
+        &mut ctx,
+        &[submit_pooled_proof_ix(
+            &authority,
+            nonce,
+            round.min_difficulty,
+        )],
+    )
+    .await;
+    assert!(result.is_err());
+
+    send(&mut ctx, &[configure_reward_pool_ix(&authority, 5_000, 3)]).await?;
+    send(
+        &mut ctx,
+        &[submit_pooled_proof_ix(
+            &authority,
+            nonce,
+            round.min_difficulty,
+        )],
+    )
+    .await?;
+
+    let pool: RewardPool = fetch(&mut ctx, reward_pool_pda()).await;
+    assert_eq!(pool.pot, 5_000);
+    assert_eq!(pool.leader_count, 1);
+    assert_eq!(
+        pool.leaders[0],
+        PoolLeader {
+            authority,
+            score: 1
+        }
+    );
+    let round_score: RoundScore = fetch(&mut ctx, round_score_pda(&authority)).await;
+    assert_eq!((round_score.round_number, round_score.score), (1, 1));
+
+    let err = send(&mut ctx, &[settle_reward_pool_ix(vec![authority])])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::RoundNotOver);
+
+    send(&mut ctx, &[rotate_round_ix(&authority)]).await?;
+    let err = send(&mut ctx, &[settle_reward_pool_ix(vec![])])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::WrongPoolWinners);
+
+    let pool_before = ctx.banks_client.get_balance(reward_pool_pda()).await?;
+    send(&mut ctx, &[settle_reward_pool_ix(vec![authority])]).await?;
+
+    let pool: RewardPool = fetch(&mut ctx, reward_pool_pda()).await;
+    assert_eq!((pool.pot, pool.leader_count, pool.round_number), (0, 0, 2));
+    assert_eq!(
+        ctx.banks_client.get_balance(reward_pool_pda()).await?,
+        pool_before - 5_000
+    );
+
+    Ok(())
+}
+
+/// Stats roots are overwritten in place with an increasing sequence
+/// Display names are validated, charged for and stored per miner
+#[tokio::test]
+async fn test_set_metadata() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    let admin = Keypair::new();
+    initialize(&mut ctx).await?;
+    let params = GlobalParams {
+        admin: Some(admin.pubkey()),
+        ..GlobalParams::default()
+    };
+    send(&mut ctx, &[update_global_params_ix(&authority, params)]).await?;
+
+    let err = send(
+        &mut ctx,
+        &[set_metadata_ix(
+            &authority,
+            &admin.pubkey(),
+            "shit miner",
+            "",
+        )],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, ErrorCode::ProfaneMetadataName);
+    let err = send(
+        &mut ctx,
+        &[set_metadata_ix(
+            &authority,
+            &admin.pubkey(),
+            "rig",
+            "http://rig",
+        )],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, ErrorCode::InvalidMetadataUrl);
+
+    send(
+        &mut ctx,
+        &[set_metadata_ix(
+            &authority,
+            &admin.pubkey(),
+            "Rig Farm",
+            "https://example.com/rig",
+        )],
+    )
+    .await?;
+
+    let metadata: MinerMetadata = fetch(&mut ctx, metadata_pda(&authority)).await;
+    assert_eq!(metadata.authority, authority);
+    assert_eq!(metadata.name, pad_metadata::<32>("Rig Farm").unwrap());
+    let fee = ctx.banks_client.get_balance(admin.pubkey()).await.unwrap();
+    assert_eq!(fee, testore_program::METADATA_UPDATE_FEE);
+
+    Ok(())
+}
+
+/// Region reports are validated and can be cleared
+#[tokio::test]
+async fn test_report_region() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let err = send(&mut ctx, &[report_region_ix(&authority, *b"de", 0)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::InvalidRegion);
+
+    send(&mut ctx, &[report_region_ix(&authority, *b"DE", 2)]).await?;
+    let region: MinerRegion = fetch(&mut ctx, region_pda(&authority)).await;
+    assert_eq!(region.authority, authority);
+    assert_eq!((region.region, region.latency_bucket), (*b"DE", 2));
+
+    send(&mut ctx, &[report_region_ix(&authority, [0; 2], 0)]).await?;
+    let region: MinerRegion = fetch(&mut ctx, region_pda(&authority)).await;
+    assert_eq!(region.region, [0; 2]);
+
+    Ok(())
+}
+
+/// Proofs past the per-round cap land in capped_score, not total_hashes
+#[tokio::test]
+async fn test_round_score_cap() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let params = GlobalParams {
+        max_round_proofs: Some(2),
+        ..GlobalParams::default()
+    };
+    send(&mut ctx, &[update_global_params_ix(&authority, params)]).await?;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    assert_eq!(round.max_round_proofs, 2);
+    let difficulty = round.min_difficulty;
+    let mut nonce = 0;
+    for _ in 0..3 {
+        nonce = mine_nonce(&authority, &round.current_challenge, difficulty, nonce);
+        advance_clock(&mut ctx, 1).await;
+        send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)]).await?;
+        nonce += 1;
+    }
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!((miner.total_hashes, miner.capped_score), (2, 1));
+    assert_eq!((miner.round_number, miner.round_proofs), (1, 3));
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    assert_eq!(round.total_hashes_submitted, 3);
+
+    Ok(())
+}
+
+/// A gated registration needs the gatekeeper's co-signature
+#[tokio::test]
+async fn test_registration_gate() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    let gatekeeper = Keypair::new();
+    send(&mut ctx, &[init_global_round_ix(&authority)]).await?;
+    send(
+        &mut ctx,
+        &[configure_registration_gate_ix(
+            &authority,
+            &gatekeeper.pubkey(),
+            true,
+        )],
+    )
+    .await?;
+
+    let err = send(&mut ctx, &[init_miner_ix(&authority)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::RegistrationNotApproved);
+
+    // Someone else's co-signature doesn't count
+    let impostor = Keypair::new();
+    let err = send_with_signers(
+        &mut ctx,
+        &[gated_init_miner_ix(&authority, &impostor.pubkey())],
+        &[&impostor],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, ErrorCode::RegistrationNotApproved);
+
+    send_with_signers(
+        &mut ctx,
+        &[gated_init_miner_ix(&authority, &gatekeeper.pubkey())],
+        &[&gatekeeper],
+    )
+    .await?;
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.authority, authority);
+
+    // Disabling the gate reopens permissionless registration
+    send(
+        &mut ctx,
+        &[configure_registration_gate_ix(
+            &authority,
+            &gatekeeper.pubkey(),
+            false,
+        )],
+    )
+    .await?;
+    let other = Keypair::new();
+    send_with_signers(
+        &mut ctx,
+        &[
+            system_instruction::transfer(&authority, &other.pubkey(), LAMPORTS_PER_SOL),
+            init_miner_ix(&other.pubkey()),
+        ],
+        &[&other],
+    )
+    .await?;
+
+    // ...including for clients from before the optional accounts
+    let legacy = Keypair::new();
+    let mut ix = init_miner_ix(&legacy.pubkey());
+    ix.accounts.truncate(3);
+    send_with_signers(
+        &mut ctx,
+        &[
+            system_instruction::transfer(&authority, &legacy.pubkey(), LAMPORTS_PER_SOL),
+            ix,
+        ],
+        &[&legacy],
+    )
+    .await?;
+    let miner: Miner = fetch(&mut ctx, miner_pda(&legacy.pubkey())).await;
+    assert_eq!(miner.authority, legacy.pubkey());
+
+    Ok(())
+}
+
+/// Idle rounds cost a share of score on the next proof; total_hashes and
+/// idle time from before decay was configured are left alone
+#[tokio::test]
+async fn test_score_decay() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let difficulty = round.min_difficulty;
+    let mut nonce = 0;
+    for _ in 0..4 {
+        nonce = mine_nonce(&authority, &round.current_challenge, difficulty, nonce);
+        advance_clock(&mut ctx, 1).await;
+        send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)]).await?;
+        nonce += 1;
+    }
+    send(
+        &mut ctx,
+        &[
+            migrate_scoring_ix(&authority),
+            convert_stats_ix(&authority, &authority),
+            resume_submissions_ix(&authority),
+        ],
+    )
+    .await?;
+
+    // Idle before decay exists: configuring it doesn't charge the gap
+    advance_clock(&mut ctx, 2 * round.round_duration + 1).await;
+    send(&mut ctx, &[rotate_round_ix(&authority)]).await?;
+    let err = send(
+        &mut ctx,
+        &[configure_score_decay_ix(&authority, BPS_DENOMINATOR)],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, ErrorCode::InvalidParams);
+    send(&mut ctx, &[configure_score_decay_ix(&authority, 5_000)]).await?;
+    let score_decay: ScoreDecay = fetch(&mut ctx, score_decay_pda()).await;
+    let clock: Clock = ctx.banks_client.get_sysvar().await?;
+    assert_eq!(score_decay.decay_bps, 5_000);
+    assert_eq!(score_decay.configured_at, clock.unix_timestamp);
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let nonce = mine_nonce(&authority, &round.current_challenge, difficulty, 0);
+
+    // Once configured, leaving the decay account off can't dodge it
+    let ix = omit_account(
+        submit_proof_ix(&authority, nonce, difficulty),
+        score_decay_pda(),
+    );
+    let err = send(&mut ctx, &[ix]).await.unwrap_err();
+    assert_custom_error(err, ErrorCode::MissingConfigAccount);
+
+    send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)]).await?;
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!((miner.total_hashes, miner.score), (5, 5));
+
+    // Two full rounds away halves the score twice before the new proof counts
+    advance_clock(&mut ctx, 2 * round.round_duration + 1).await;
+    send(&mut ctx, &[rotate_round_ix(&authority)]).await?;
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let nonce = mine_nonce(&authority, &round.current_challenge, difficulty, 0);
+    send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)]).await?;
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!((miner.total_hashes, miner.score), (6, 5 / 4 + 1));
+
+    Ok(())
+}
+
+/// Tagged proofs count like plain ones and log the worker tag as an event
+#[tokio::test]
+async fn test_submit_tagged_proof() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let difficulty = round.min_difficulty;
+    let nonce = mine_nonce(&authority, &round.current_challenge, difficulty, 0);
+    let worker_tag = [7u8; 32];
+    advance_clock(&mut ctx, 1).await;
+
+    let blockhash = ctx.get_new_latest_blockhash().await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[submit_tagged_proof_ix(
+            &authority, nonce, difficulty, worker_tag,
+        )],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        blockhash,
+    );
+    let result = ctx
+        .banks_client
+        .process_transaction_with_metadata(tx)
+        .await?;
+    result.result?;
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.total_hashes, 1);
+
+    // The event is the only place the tag ends up
+    let event = WorkerProof {
+        authority,
+        worker_tag,
+        round_number: round.round_number,
+        difficulty,
+        counted: true,
+    };
+    let logged = format!(
+        "Program data: {}",
+        base64::engine::general_purpose::STANDARD.encode(event.data())
+    );
+    let logs = result.metadata.unwrap().log_messages;
+    assert!(logs.contains(&logged), "{:?}", logs);
+
+    Ok(())
+}
+
+/// Freeze, convert and resume: existing hashes carry over into score
+#[tokio::test]
+async fn test_scoring_migration() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let difficulty = round.min_difficulty;
+    let mut nonce = 0;
+    for _ in 0..2 {
+        nonce = mine_nonce(&authority, &round.current_challenge, difficulty, nonce);
+        advance_clock(&mut ctx, 1).await;
+        send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)]).await?;
+        nonce += 1;
+    }
+
+    // Clients from before the optional config accounts still submit
+    nonce = mine_nonce(&authority, &round.current_challenge, difficulty, nonce);
+    advance_clock(&mut ctx, 1).await;
+    let mut ix = submit_proof_ix(&authority, nonce, difficulty);
+    ix.accounts.truncate(3);
+    send(&mut ctx, &[ix]).await?;
+    nonce += 1;
+
+    send(&mut ctx, &[migrate_scoring_ix(&authority)]).await?;
+    let migration: ScoringMigration = fetch(&mut ctx, scoring_migration_pda()).await;
+    assert_eq!(migration.phase, ScoringPhase::Frozen);
+
+    nonce = mine_nonce(&authority, &round.current_challenge, difficulty, nonce);
+    advance_clock(&mut ctx, 1).await;
+    let err = send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::SubmissionsFrozen);
+
+    // ...but can't dodge the freeze once the migration has started
+    let ix = omit_account(
+        submit_proof_ix(&authority, nonce, difficulty),
+        scoring_migration_pda(),
+    );
+    let err = send(&mut ctx, &[ix]).await.unwrap_err();
+    assert_custom_error(err, ErrorCode::MissingConfigAccount);
+
+    // A miner still in the first layout is grown before converting
+    truncate_account(&mut ctx, miner_pda(&authority), 66).await;
+    send(&mut ctx, &[convert_stats_ix(&authority, &authority)]).await?;
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert!(miner.score_converted);
+    assert_eq!((miner.total_hashes, miner.score), (3, 3));
+    assert_eq!(miner.score_bucket, NO_SCORE_BUCKET);
+    let err = send(&mut ctx, &[convert_stats_ix(&authority, &authority)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::AlreadyConverted);
+
+    send(&mut ctx, &[resume_submissions_ix(&authority)]).await?;
+    let err = send(&mut ctx, &[resume_submissions_ix(&authority)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::InvalidParams);
+
+    send(&mut ctx, &[submit_proof_ix(&authority, nonce, difficulty)]).await?;
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!((miner.total_hashes, miner.score), (4, 4));
+    let migration: ScoringMigration = fetch(&mut ctx, scoring_migration_pda()).await;
+    assert_eq!(migration.phase, ScoringPhase::Score);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_post_stats_root() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let admin = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+
+    send(&mut ctx, &[post_stats_root_ix(&admin, [1; 32], 1)]).await?;
+    let stats_root: StatsRoot = fetch(&mut ctx, stats_root_pda()).await;
+    assert_eq!(stats_root.root, [1; 32]);
+    assert_eq!(stats_root.miner_count, 1);
+    assert_eq!(stats_root.sequence, 1);
+
+    send(&mut ctx, &[post_stats_root_ix(&admin, [2; 32], 5)]).await?;
+    let stats_root: StatsRoot = fetch(&mut ctx, stats_root_pda()).await;
+    assert_eq!(stats_root.root, [2; 32]);
+    assert_eq!(stats_root.miner_count, 5);
+    assert_eq!(stats_root.sequence, 2);
+
+    Ok(())
+}
+
+/// Idle miners close into the sweep wallet, leaving an archive behind
+#[tokio::test]
+async fn test_gc_miner() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    let sweep_wallet = Pubkey::new_unique();
+    initialize(&mut ctx).await?;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let nonce = mine_nonce(
+        &authority,
+        &round.current_challenge,
+        round.min_difficulty,
+        0,
+    );
+    advance_clock(&mut ctx, 1).await;
+    send(
+        &mut ctx,
+        &[submit_proof_ix(&authority, nonce, round.min_difficulty)],
+    )
+    .await?;
+    send(
+        &mut ctx,
+        &[
+            attest_miner_ix(&authority, &authority),
+            report_region_ix(&authority, *b"DE", 2),
+        ],
+    )
+    .await?;
+
+    send(
+        &mut ctx,
+        &[configure_gc_policy_ix(&authority, 0, &sweep_wallet)],
+    )
+    .await?;
+    let err = send(&mut ctx, &[gc_miner_ix(&authority, &sweep_wallet)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::GcDisabled);
+
+    send(
+        &mut ctx,
+        &[configure_gc_policy_ix(&authority, 30, &sweep_wallet)],
+    )
+    .await?;
+    let err = send(&mut ctx, &[gc_miner_ix(&authority, &sweep_wallet)])
+        .await
+        .unwrap_err();
+    assert_custom_error(err, ErrorCode::MinerStillActive);
+
+    // Rent may only go to the policy's wallet
+    advance_clock(&mut ctx, 30 * 24 * 60 * 60).await;
+    assert!(
+        send(&mut ctx, &[gc_miner_ix(&authority, &Pubkey::new_unique())])
+            .await
+            .is_err()
+    );
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    let mut rent = 0;
+    for address in [
+        miner_pda(&authority),
+        attestation_pda(&authority),
+        region_pda(&authority),
+    ] {
+        rent += ctx.banks_client.get_balance(address).await?;
+    }
+    send(&mut ctx, &[gc_miner_ix(&authority, &sweep_wallet)]).await?;
+    for address in [
+        miner_pda(&authority),
+        attestation_pda(&authority),
+        region_pda(&authority),
+    ] {
+        assert!(ctx.banks_client.get_account(address).await?.is_none());
+    }
+
+    // The archive keeps the stats, paid for out of the miner's rent
+    let archive: MinerArchive = fetch(&mut ctx, miner_archive_pda(&authority)).await;
+    assert_eq!(archive.authority, authority);
+    assert_eq!(archive.total_hashes, 1);
+    assert_eq!(archive.total_hashes, miner.total_hashes);
+    assert_eq!(archive.last_hash_at, miner.last_hash_at);
+    let archive_rent = ctx
+        .banks_client
+        .get_balance(miner_archive_pda(&authority))
+        .await?;
+    assert_eq!(
+        ctx.banks_client.get_balance(sweep_wallet).await?,
+        rent - archive_rent
+    );
+
+    // Registering again restores the stats instead of starting at zero
+    send(&mut ctx, &[init_miner_ix(&authority)]).await?;
+    let restored: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(restored.total_hashes, 1);
+    assert_eq!(restored.rounds_completed, miner.rounds_completed);
+    assert_eq!(restored.best_difficulty, miner.best_difficulty);
+    assert_eq!(restored.score_bucket, NO_SCORE_BUCKET);
+    assert!(ctx
+        .banks_client
+        .get_account(miner_archive_pda(&authority))
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+/// Accounts created before fields were appended grow to the current layout
+#[tokio::test]
+async fn test_upgrade_old_layouts() -> Result<()> {
+    let mut ctx = program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+    initialize(&mut ctx).await?;
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+
+    // Plant both accounts at their first deployed size
+    truncate_account(&mut ctx, miner_pda(&authority), 66).await;
+    truncate_account(&mut ctx, global_round_pda(), 106).await;
+
+    let nonce = mine_nonce(
+        &authority,
+        &round.current_challenge,
+        round.min_difficulty,
+        0,
+    );
+    advance_clock(&mut ctx, 1).await;
+    let err = send(
+        &mut ctx,
+        &[submit_proof_ix(&authority, nonce, round.min_difficulty)],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into()
+            )
+        )
+    );
+
+    send(
+        &mut ctx,
+        &[
+            upgrade_global_round_ix(&authority),
+            upgrade_miner_ix(&authority, &authority),
+        ],
+    )
+    .await?;
+
+    let upgraded: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    assert_eq!(upgraded.admin, authority);
+    assert_eq!(upgraded.round_number, round.round_number);
+    assert_eq!(upgraded.round_duration, DEFAULT_ROUND_DURATION);
+    assert_eq!(upgraded.tier_step, 0);
+    assert_eq!(upgraded.max_round_proofs, 0);
+
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.authority, authority);
+    assert_eq!(miner.score_bucket, NO_SCORE_BUCKET);
+    assert!(!miner.score_converted);
+
+    // Mining carries on, and a second upgrade changes nothing
+    send(
+        &mut ctx,
+        &[submit_proof_ix(&authority, nonce, round.min_difficulty)],
+    )
+    .await?;
+    send(&mut ctx, &[upgrade_miner_ix(&authority, &authority)]).await?;
+    let miner: Miner = fetch(&mut ctx, miner_pda(&authority)).await;
+    assert_eq!(miner.total_hashes, 1);
+
+    Ok(())
+}
+
+// ============================================================================
+// Compute Budget Regression
+// ============================================================================
+
+// Upper bounds on compute units per instruction. Raise them deliberately in
+// the same change that makes an instruction more expensive.
+const MAX_CU_INITIALIZE_GLOBAL_ROUND: u64 = 30_000;
+const MAX_CU_INITIALIZE_MINER: u64 = 30_000;
+const MAX_CU_SUBMIT_PROOF: u64 = 30_000;
+const MAX_CU_COMMIT_PROOF: u64 = 15_000;
+const MAX_CU_REVEAL_PROOF: u64 = 35_000;
+const MAX_CU_ROTATE_ROUND: u64 = 15_000;
+const MAX_CU_UPDATE_GLOBAL_PARAMS: u64 = 10_000;
+const MAX_CU_SET_MINER_TIER: u64 = 15_000;
+
+/// Every instruction stays within its compute budget on the SBF build
+#[tokio::test]
+async fn test_instruction_compute_budgets() -> Result<()> {
+    let mut ctx = bpf_program_test().start_with_context().await;
+    let authority = ctx.payer.pubkey();
+
+    send_metered(
+        &mut ctx,
+        "initialize_global_round",
+        init_global_round_ix(&authority),
+        MAX_CU_INITIALIZE_GLOBAL_ROUND,
+    )
+    .await;
+    send_metered(
+        &mut ctx,
+        "initialize_miner",
+        init_miner_ix(&authority),
+        MAX_CU_INITIALIZE_MINER,
+    )
+    .await;
+
+    let round: GlobalRound = fetch(&mut ctx, global_round_pda()).await;
+    let difficulty = round.min_difficulty;
+
+    let nonce = mine_nonce(&authority, &round.current_challenge, difficulty, 0);
+    advance_clock(&mut ctx, 1).await;
+    send_metered(
+        &mut ctx,
+        "submit_proof",
+        submit_proof_ix(&authority, nonce, difficulty),
+        MAX_CU_SUBMIT_PROOF,
+    )
+    .await;
+
+    let nonce = mine_nonce(&authority, &round.current_challenge, difficulty, nonce + 1);
+    let salt = [42u8; 32];
+    send_metered(
+        &mut ctx,
+        "commit_proof",
+        commit_proof_ix(&authority, commitment_hash(nonce, &salt)),
+        MAX_CU_COMMIT_PROOF,
+    )
+    .await;
+
+    let slot = ctx.banks_client.get_root_slot().await?;
+    ctx.warp_to_slot(slot + REVEAL_DELAY_SLOTS + 1)?;
+    advance_clock(&mut ctx, 1).await;
+    send_metered(
+        &mut ctx,
+        "reveal_proof",
+        reveal_proof_ix(&authority, nonce, salt, difficulty),
+        MAX_CU_REVEAL_PROOF,
+    )
+    .await;
+
+    send_metered(
+        &mut ctx,
+        "rotate_round",
+        rotate_round_ix(&authority),
+        MAX_CU_ROTATE_ROUND,
+    )
+    .await;
+    send_metered(
+        &mut ctx,
+        "update_global_params",
+        update_global_params_ix(
+            &authority,
+            GlobalParams {
+                tier_step: Some(2),
+                ..GlobalParams::default()
+            },
+        ),
+        MAX_CU_UPDATE_GLOBAL_PARAMS,
+    )
+    .await;
+    send_metered(
+        &mut ctx,
+        "set_miner_tier",
+        set_miner_tier_ix(&authority, &authority, 1),
+        MAX_CU_SET_MINER_TIER,
+    )
+    .await;
+
+    Ok(())
+}
+
+// ============================================================================
+// Harness Helpers
+// ============================================================================
+
+fn program_test() -> ProgramTest {
+    ProgramTest::new(
+        "testore_program",
+        testore_program::ID,
+        processor!(process_instruction),
+    )
+}
+
+// Anchor's entrypoint ties the accounts slice to the `'info` lifetime, which
+// `processor!` can't express, so leak a copy for the duration of the test.
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    testore_program::entry(program_id, accounts, data)
+}
+
+/// Create the global round (payer doubles as admin) and the payer's miner
+async fn initialize(ctx: &mut ProgramTestContext) -> Result<()> {
+    let authority = ctx.payer.pubkey();
+
+    Ok(send(
+        ctx,
+        &[init_global_round_ix(&authority), init_miner_ix(&authority)],
+    )
+    .await?)
+}
+
+/// Same program, loaded from the SBF build so compute units are real
+///
+/// Requires `anchor build` (or `cargo build-sbf`) first.
+fn bpf_program_test() -> ProgramTest {
+    let mut test = ProgramTest::new("testore_program", testore_program::ID, None);
+    test.prefer_bpf(true);
+    test
+}
+
+fn global_round_pda() -> Pubkey {
+    client::global_round_address(&testore_program::ID)
+}
+
+fn miner_pda(authority: &Pubkey) -> Pubkey {
+    client::miner_address(&testore_program::ID, authority)
+}
+
+fn score_bucket_pda(index: u8) -> Pubkey {
+    client::score_bucket_address(&testore_program::ID, index)
+}
+
+fn init_global_round_ix(admin: &Pubkey) -> Instruction {
+    client::InitializeGlobalRound::new(*admin).instruction()
+}
+
+fn init_miner_ix(authority: &Pubkey) -> Instruction {
+    client::InitializeMiner::new(*authority).instruction()
+}
+
+fn gated_init_miner_ix(authority: &Pubkey, gatekeeper: &Pubkey) -> Instruction {
+    client::InitializeMiner::new(*authority)
+        .gatekeeper(*gatekeeper)
+        .instruction()
+}
+
+fn submit_proof_ix(authority: &Pubkey, nonce: u64, difficulty: u8) -> Instruction {
+    client::SubmitProof::new(*authority, nonce, difficulty).instruction()
+}
+
+fn submit_proof_with_buckets_ix(
+    authority: &Pubkey,
+    nonce: u64,
+    difficulty: u8,
+    from_bucket: Option<u8>,
+    to_bucket: u8,
+) -> Instruction {
+    client::SubmitProof::new(*authority, nonce, difficulty)
+        .buckets(from_bucket, to_bucket)
+        .instruction()
+}
+
+fn report_rejection_ix(authority: &Pubkey, reason: RejectionReason) -> Instruction {
+    client::ReportRejection::new(*authority, reason).instruction()
+}
+
+fn reset_timing_ix(authority: &Pubkey) -> Instruction {
+    client::ResetTiming::new(*authority).instruction()
+}
+
+fn commit_proof_ix(authority: &Pubkey, commitment: [u8; 32]) -> Instruction {
+    client::CommitProof::new(*authority, commitment).instruction()
+}
+
+fn reveal_proof_ix(authority: &Pubkey, nonce: u64, salt: [u8; 32], difficulty: u8) -> Instruction {
+    client::RevealProof::new(*authority, nonce, salt, difficulty).instruction()
+}
+
+fn upgrade_miner_ix(payer: &Pubkey, authority: &Pubkey) -> Instruction {
+    client::UpgradeMiner::new(*payer, *authority).instruction()
+}
+
+fn upgrade_global_round_ix(admin: &Pubkey) -> Instruction {
+    client::UpgradeGlobalRound::new(*admin).instruction()
+}
+
+fn rotate_round_ix(admin: &Pubkey) -> Instruction {
+    client::RotateRound::new(*admin).instruction()
+}
+
+fn update_global_params_ix(admin: &Pubkey, params: GlobalParams) -> Instruction {
+    client::UpdateGlobalParams::new(*admin, params).instruction()
+}
+
+fn set_miner_tier_ix(admin: &Pubkey, authority: &Pubkey, tier: u8) -> Instruction {
+    client::SetMinerTier::new(*admin, *authority, tier).instruction()
+}
+
+fn init_score_bucket_ix(payer: &Pubkey, index: u8) -> Instruction {
+    client::InitializeScoreBucket::new(*payer, index).instruction()
+}
+
+fn sync_score_bucket_ix(authority: &Pubkey, from_bucket: Option<u8>, to_bucket: u8) -> Instruction {
+    client::SyncScoreBucket::new(*authority, from_bucket, to_bucket).instruction()
+}
+
+fn attestation_pda(authority: &Pubkey) -> Pubkey {
+    client::attestation_address(&testore_program::ID, authority)
+}
+
+fn attest_miner_ix(payer: &Pubkey, authority: &Pubkey) -> Instruction {
+    client::AttestMiner::new(*payer, *authority).instruction()
+}
+
+fn liveness_pda(authority: &Pubkey) -> Pubkey {
+    client::liveness_address(&testore_program::ID, authority)
+}
+
+fn verify_liveness_ix(authority: &Pubkey, expires_at: i64) -> Instruction {
+    client::VerifyLiveness::new(*authority, *authority, expires_at).instruction()
+}
+
+fn configure_liveness_issuer_ix(admin: &Pubkey, issuer: &Pubkey) -> Instruction {
+    client::ConfigureLivenessIssuer::new(*admin, *issuer).instruction()
+}
+
+fn stats_root_pda() -> Pubkey {
+    client::stats_root_address(&testore_program::ID)
+}
+
+fn post_stats_root_ix(admin: &Pubkey, root: [u8; 32], miner_count: u32) -> Instruction {
+    client::PostStatsRoot::new(*admin, root, miner_count).instruction()
+}
+
+fn reward_pool_pda() -> Pubkey {
+    client::reward_pool_address(&testore_program::ID)
+}
+
+fn round_score_pda(authority: &Pubkey) -> Pubkey {
+    client::round_score_address(&testore_program::ID, authority)
+}
+
+fn configure_reward_pool_ix(admin: &Pubkey, fee_lamports: u64, top_k: u8) -> Instruction {
+    client::ConfigureRewardPool::new(*admin, fee_lamports, top_k).instruction()
+}
+
+fn submit_tagged_proof_ix(
+    authority: &Pubkey,
+    nonce: u64,
+    difficulty: u8,
+    worker_tag: [u8; 32],
+) -> Instruction {
+    client::SubmitProof::new(*authority, nonce, difficulty)
+        .worker_tag(worker_tag)
+        .instruction()
+}
+
+fn submit_pooled_proof_ix(authority: &Pubkey, nonce: u64, difficulty: u8) -> Instruction {
+    client::SubmitPooledProof::new(*authority, nonce, difficulty).instruction()
+}
+
+fn settle_reward_pool_ix(winners: Vec<Pubkey>) -> Instruction {
+    client::SettleRewardPool::new(winners).instruction()
+}
+
+fn metadata_pda(authority: &Pubkey) -> Pubkey {
+    client::metadata_address(&testore_program::ID, authority)
+}
+
+fn set_metadata_ix(authority: &Pubkey, admin: &Pubkey, name: &str, url: &str) -> Instruction {
+    client::SetMetadata::new(
+        *authority,
+        *admin,
+        pad_metadata(name).unwrap(),
+        pad_metadata(url).unwrap(),
+    )
+    .instruction()
+}
+
+fn region_pda(authority: &Pubkey) -> Pubkey {
+    client::region_address(&testore_program::ID, authority)
+}
+
+fn report_region_ix(authority: &Pubkey, region: [u8; 2], latency_bucket: u8) -> Instruction {
+    client::ReportRegion::new(*authority, region, latency_bucket).instruction()
+}
+
+fn score_decay_pda() -> Pubkey {
+    client::score_decay_address(&testore_program::ID)
+}
+
+fn configure_score_decay_ix(admin: &Pubkey, decay_bps: u16) -> Instruction {
+    client::ConfigureScoreDecay::new(*admin, decay_bps).instruction()
+}
+
+fn configure_registration_gate_ix(
+    admin: &Pubkey,
+    gatekeeper: &Pubkey,
+    enabled: bool,
+) -> Instruction {
+    client::ConfigureRegistrationGate::new(*admin, *gatekeeper, enabled).instruction()
+}
+
+fn scoring_migration_pda() -> Pubkey {
+    client::scoring_migration_address(&testore_program::ID)
+}
+
+fn migrate_scoring_ix(admin: &Pubkey) -> Instruction {
+    client::MigrateScoring::new(*admin).instruction()
+}
+
+fn convert_stats_ix(payer: &Pubkey, authority: &Pubkey) -> Instruction {
+    client::ConvertStats::new(*payer, *authority).instruction()
+}
+
+fn resume_submissions_ix(admin: &Pubkey) -> Instruction {
+    client::ResumeSubmissions::new(*admin).instruction()
+}
+
+fn configure_gc_policy_ix(
+    admin: &Pubkey,
+    inactive_days: u16,
+    sweep_wallet: &Pubkey,
+) -> Instruction {
+    client::ConfigureGcPolicy::new(*admin, inactive_days, *sweep_wallet).instruction()
+}
+
+fn gc_miner_ix(authority: &Pubkey, sweep_wallet: &Pubkey) -> Instruction {
+    client::GcMiner::new(*authority, *authority, *sweep_wallet, None).instruction()
+}
+
+fn miner_archive_pda(authority: &Pubkey) -> Pubkey {
+    client::miner_archive_address(&testore_program::ID, authority)
+}
+
+fn get_miner_rank_inputs_ix(authority: &Pubkey) -> Instruction {
+    client::GetMinerRankInputs::new(*authority).instruction()
+}
+
+fn preview_round_reward_ix(authority: &Pubkey, difficulty: u8) -> Instruction {
+    client::PreviewRoundReward::new(*authority, difficulty).instruction()
+}
+
+async fn send(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+) -> std::result::Result<(), BanksClientError> {
+    let blockhash = ctx.get_new_latest_blockhash().await?;
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await
+}
+
+/// Like [`send`], with extra signers besides the payer
+async fn send_with_signers(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> std::result::Result<(), BanksClientError> {
+    let blockhash = ctx.get_new_latest_blockhash().await?;
+    let mut tx = Transaction::new_with_payer(instructions, Some(&ctx.payer.pubkey()));
+    let mut keypairs = vec![&ctx.payer];
+    keypairs.extend_from_slice(signers);
+    tx.sign(&keypairs, blockhash);
+    ctx.banks_client.process_transaction(tx).await
+}
+
+/// Simulate a view instruction and decode its return data
+async fn simulate_view<T: AnchorDeserialize>(
+    ctx: &mut ProgramTestContext,
+    instruction: Instruction,
+) -> T {
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        blockhash,
+    );
+
+    let simulation = ctx.banks_client.simulate_transaction(tx).await.unwrap();
+    simulation.result.unwrap().unwrap();
+
+    let return_data = simulation
+        .simulation_details
+        .unwrap()
+        .return_data
+        .expect("no return data");
+    T::try_from_slice(&return_data.data).unwrap()
+}
+
+/// Simulate, check the compute budget, then actually process the instruction
+async fn send_metered(
+    ctx: &mut ProgramTestContext,
+    name: &str,
+    instruction: Instruction,
+    max_units: u64,
+) {
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        blockhash,
+    );
+
+    let simulation = ctx
+        .banks_client
+        .simulate_transaction(tx.clone())
+        .await
+        .unwrap();
+    simulation.result.unwrap().unwrap();
+
+    let units = simulation.simulation_details.unwrap().units_consumed;
+    assert!(
+        units <= max_units,
+        "{} used {} CU, budget is {}",
+        name,
+        units,
+        max_units
+    );
+
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+}
+
+async fn fetch<T: AccountDeserialize>(ctx: &mut ProgramTestContext, address: Pubkey) -> T {
+    let account = ctx
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .expect("account not found");
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+/// Cut an account's data back to `len` bytes, as if an older program version
+/// had created it
+async fn truncate_account(ctx: &mut ProgramTestContext, address: Pubkey, len: usize) {
+    let mut account = ctx
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .expect("account not found");
+    account.data.truncate(len);
+    ctx.set_account(&address, &account.into());
+}
+
+/// `ix` with the optional account at `address` passed as None
+fn omit_account(mut ix: Instruction, address: Pubkey) -> Instruction {
+    let meta = ix
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == address)
+        .expect("account not in instruction");
+    *meta = AccountMeta::new_readonly(testore_program::ID, false);
+    ix
+}
+
+async fn advance_clock(ctx: &mut ProgramTestContext, seconds: i64) {
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += seconds;
+    ctx.set_sysvar(&clock);
+}
+
+fn mine_nonce(authority: &Pubkey, challenge: &[u8; 32], difficulty: u8, start: u64) -> u64 {
+    find_nonce(authority, challenge, start, |hash| {
+        testore_program::check_difficulty(hash, difficulty)
+    })
+}
+
+fn find_nonce(
+    authority: &Pubkey,
+    challenge: &[u8; 32],
+    start: u64,
+    accept: impl Fn(&[u8; 32]) -> bool,
+) -> u64 {
+    (start..)
+        .find(|nonce| accept(&tests::hash_proof(authority, challenge, *nonce)))
+        .unwrap()
+}
+
+fn assert_custom_error(err: BanksClientError, expected: ErrorCode) {
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(expected.into()))
+    );
+}
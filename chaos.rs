@@ -0,0 +1,132 @@
+//! `--chaos`: failure injection for fork rehearsals
+//!
+//! Wraps each transfer in a fork rehearsal with random faults: RPC timeouts
+//! before a send, responses lost after a transfer landed, signing against an
+//! expired blockhash, and the process dying mid-chunk. The fork rehearsal
+//! then keeps re-running the chunk like an operator would and checks that
+//! every recipient ended up paid exactly once.
+//!
+//! Faults come from a seeded generator, so a failing run can be replayed
+//! with the same `--chaos-seed`.
+
+use crate::signer;
+use anyhow::{anyhow, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{hash::Hash, signature::Signature, transaction::Transaction};
+use std::collections::BTreeMap;
+
+/// One injected failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Fault {
+    /// The RPC call times out before the transaction goes out
+    RpcTimeout,
+
+    /// The transfer lands but the confirmation never arrives
+    LostResponse,
+
+    /// The transaction is signed against a blockhash that has expired
+    StaleBlockhash,
+
+    /// The process dies right after this transfer
+    Crash,
+}
+
+impl Fault {
+    const ALL: [Fault; 4] = [
+        Fault::RpcTimeout,
+        Fault::LostResponse,
+        Fault::StaleBlockhash,
+        Fault::Crash,
+    ];
+}
+
+/// Seeded fault generator
+pub struct Chaos {
+    rate: f64,
+    state: u64,
+
+    /// Faults injected so far
+    pub injected: BTreeMap<Fault, usize>,
+}
+
+impl Chaos {
+    /// Inject a fault into roughly `rate` (0.0 - 1.0) of transfers
+    pub fn new(rate: f64, seed: u64) -> Self {
+        Self {
+            rate: rate.clamp(0.0, 1.0),
+            // xorshift never leaves an all-zero state
+            state: seed.max(1),
+            injected: BTreeMap::new(),
+        }
+    }
+
+    /// Fault for the next transfer, if any
+    pub fn roll(&mut self) -> Option<Fault> {
+        if self.next_f64() >= self.rate {
+            return None;
+        }
+
+        let fault = Fault::ALL[(self.next_u64() % Fault::ALL.len() as u64) as usize];
+        *self.injected.entry(fault).or_default() += 1;
+        Some(fault)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        // xorshift64*
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Blockhash to sign against: the latest, or a dead one for
+/// [`Fault::StaleBlockhash`]
+pub fn blockhash(client: &RpcClient, fault: Option<Fault>) -> Result<Hash> {
+    if fault == Some(Fault::StaleBlockhash) {
+        return Ok(Hash::new_unique());
+    }
+
+    testore_core::throttle(client);
+    Ok(client.get_latest_blockhash()?)
+}
+
+/// [`signer::submit`] with `fault` applied
+pub fn submit(client: &RpcClient, tx: &Transaction, fault: Option<Fault>) -> Result<Signature> {
+    match fault {
+        Some(Fault::RpcTimeout) => Err(anyhow!("chaos: RPC timed out before sending")),
+        Some(Fault::LostResponse) => {
+            signer::submit(client, tx)?;
+            Err(anyhow!("chaos: confirmation lost after sending"))
+        }
+        _ => signer::submit(client, tx),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chaos_is_seeded() {
+        let rolls = |seed| {
+            let mut chaos = Chaos::new(0.5, seed);
+            (0..100).map(|_| chaos.roll()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(rolls(7), rolls(7));
+        assert_ne!(rolls(7), rolls(8));
+
+        let mut never = Chaos::new(0.0, 7);
+        assert!((0..100).all(|_| never.roll().is_none()));
+        assert!(never.injected.is_empty());
+
+        let mut always = Chaos::new(1.0, 7);
+        assert!((0..100).all(|_| always.roll().is_some()));
+        assert_eq!(always.injected.values().sum::<usize>(), 100);
+    }
+}
@@ -0,0 +1,1187 @@
+//! Typed instruction builders and PDA helpers for off-chain callers
+//!
+//! The single place instructions are assembled, shared by the miner client,
+//! the bridge's crank and oracle, and the integration tests:
+//!
+//! ```ignore
+//! use testore_program::client::{SubmitProof, ToInstruction};
+//!
+//! let ix = SubmitProof::new(authority, nonce, difficulty).instruction();
+//! ```
+//!
+//! `instruction()` targets the program's declared ID; use
+//! `instruction_for(&program_id)` for other deployments.
+
+use crate::{GlobalParams, RejectionReason};
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    solana_program::instruction::Instruction,
+    system_program, InstructionData, ToAccountMetas,
+};
+
+/// Build an instruction for a program deployment
+pub trait ToInstruction {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction;
+
+    fn instruction(&self) -> Instruction {
+        self.instruction_for(&crate::ID)
+    }
+}
+
+// ============================================================================
+// PDAs
+// ============================================================================
+
+pub fn miner_address(program_id: &Pubkey, authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"miner", authority.as_ref()], program_id).0
+}
+
+pub fn global_round_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"global_round"], program_id).0
+}
+
+pub fn score_bucket_address(program_id: &Pubkey, index: u8) -> Pubkey {
+    Pubkey::find_program_address(&[b"score_bucket", index.to_le_bytes().as_ref()], program_id).0
+}
+
+pub fn attestation_address(program_id: &Pubkey, authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"attestation", authority.as_ref()], program_id).0
+}
+
+pub fn liveness_address(program_id: &Pubkey, authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"liveness", authority.as_ref()], program_id).0
+}
+
+pub fn liveness_issuer_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"liveness_issuer"], program_id).0
+}
+
+pub fn stats_root_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"stats_root"], program_id).0
+}
+
+pub fn reward_pool_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"reward_pool"], program_id).0
+}
+
+pub fn round_score_address(program_id: &Pubkey, authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"round_score", authority.as_ref()], program_id).0
+}
+
+pub fn metadata_address(program_id: &Pubkey, authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"metadata", authority.as_ref()], program_id).0
+}
+
+pub fn region_address(program_id: &Pubkey, authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"region", authority.as_ref()], program_id).0
+}
+
+pub fn score_decay_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"score_decay"], program_id).0
+}
+
+pub fn registration_gate_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"registration_gate"], program_id).0
+}
+
+pub fn scoring_migration_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"scoring_migration"], program_id).0
+}
+
+pub fn gc_policy_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"gc_policy"], program_id).0
+}
+
+pub fn miner_archive_address(program_id: &Pubkey, authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"miner_archive", authority.as_ref()], program_id).0
+}
+
+fn instruction(
+    program_id: &Pubkey,
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+// ============================================================================
+// Miner Instructions
+// ============================================================================
+
+pub struct InitializeMiner {
+    pub authority: Pubkey,
+    pub gatekeeper: Option<Pubkey>,
+}
+
+impl InitializeMiner {
+    pub fn new(authority: Pubkey) -> Self {
+        Self {
+            authority,
+            gatekeeper: None,
+        }
+    }
+
+    /// Have `gatekeeper` co-sign, as a gated registration requires
+    pub fn gatekeeper(mut self, gatekeeper: Pubkey) -> Self {
+        self.gatekeeper = Some(gatekeeper);
+        self
+    }
+}
+
+impl ToInstruction for InitializeMiner {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::InitializeMiner {
+                miner: miner_address(program_id, &self.authority),
+                authority: self.authority,
+                system_program: system_program::ID,
+                registration_gate: Some(registration_gate_address(program_id)),
+                gatekeeper: self.gatekeeper,
+                archive: Some(miner_archive_address(program_id, &self.authority)),
+            },
+            crate::instruction::InitializeMiner {},
+        )
+    }
+}
+
+/// `submit_proof`, optionally moving the miner between score buckets
+pub struct SubmitProof {
+    pub authority: Pubkey,
+    pub nonce: u64,
+    pub difficulty: u8,
+    pub from_bucket: Option<u8>,
+    pub to_bucket: Option<u8>,
+    pub worker_tag: Option<[u8; 32]>,
+}
+
+impl SubmitProof {
+    pub fn new(authority: Pubkey, nonce: u64, difficulty: u8) -> Self {
+        Self {
+            authority,
+            nonce,
+            difficulty,
+            from_bucket: None,
+            to_bucket: None,
+            worker_tag: None,
+        }
+    }
+
+    /// Send as `submit_tagged_proof`, attributing the proof to a pool worker
+    pub fn worker_tag(mut self, tag: [u8; 32]) -> Self {
+        self.worker_tag = Some(tag);
+        self
+    }
+
+    /// Move the miner from `from` (None if not bucketed yet) into `to`
+    pub fn buckets(mut self, from: Option<u8>, to: u8) -> Self {
+        self.from_bucket = from;
+        self.to_bucket = Some(to);
+        self
+    }
+}
+
+impl ToInstruction for SubmitProof {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        let accounts = submit_proof_accounts(
+            program_id,
+            &self.authority,
+            self.from_bucket,
+            self.to_bucket,
+        );
+
+        match self.worker_tag {
+            Some(worker_tag) => instruction(
+                program_id,
+                accounts,
+                crate::instruction::SubmitTaggedProof {
+                    nonce: self.nonce,
+                    difficulty: self.difficulty,
+                    worker_tag,
+                },
+            ),
+            None => instruction(
+                program_id,
+                accounts,
+                crate::instruction::SubmitProof {
+                    nonce: self.nonce,
+                    difficulty: self.difficulty,
+                },
+            ),
+        }
+    }
+}
+
+pub struct CommitProof {
+    pub authority: Pubkey,
+    pub commitment: [u8; 32],
+}
+
+impl CommitProof {
+    pub fn new(authority: Pubkey, commitment: [u8; 32]) -> Self {
+        Self {
+            authority,
+            commitment,
+        }
+    }
+}
+
+impl ToInstruction for CommitProof {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::CommitProof {
+                miner: miner_address(program_id, &self.authority),
+                authority: self.authority,
+            },
+            crate::instruction::CommitProof {
+                commitment: self.commitment,
+            },
+        )
+    }
+}
+
+/// `reveal_proof`, optionally moving the miner between score buckets
+pub struct RevealProof {
+    pub authority: Pubkey,
+    pub nonce: u64,
+    pub salt: [u8; 32],
+    pub difficulty: u8,
+    pub from_bucket: Option<u8>,
+    pub to_bucket: Option<u8>,
+}
+
+impl RevealProof {
+    pub fn new(authority: Pubkey, nonce: u64, salt: [u8; 32], difficulty: u8) -> Self {
+        Self {
+            authority,
+            nonce,
+            salt,
+            difficulty,
+            from_bucket: None,
+            to_bucket: None,
+        }
+    }
+
+    /// Move the miner from `from` (None if not bucketed yet) into `to`
+    pub fn buckets(mut self, from: Option<u8>, to: u8) -> Self {
+        self.from_bucket = from;
+        self.to_bucket = Some(to);
+        self
+    }
+}
+
+impl ToInstruction for RevealProof {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            submit_proof_accounts(
+                program_id,
+                &self.authority,
+                self.from_bucket,
+                self.to_bucket,
+            ),
+            crate::instruction::RevealProof {
+                nonce: self.nonce,
+                salt: self.salt,
+                difficulty: self.difficulty,
+            },
+        )
+    }
+}
+
+/// `submit_pooled_proof`, optionally moving the miner between score buckets
+pub struct SubmitPooledProof {
+    pub authority: Pubkey,
+    pub nonce: u64,
+    pub difficulty: u8,
+    pub from_bucket: Option<u8>,
+    pub to_bucket: Option<u8>,
+}
+
+impl SubmitPooledProof {
+    pub fn new(authority: Pubkey, nonce: u64, difficulty: u8) -> Self {
+        Self {
+            authority,
+            nonce,
+            difficulty,
+            from_bucket: None,
+            to_bucket: None,
+        }
+    }
+
+    /// Move the miner from `from` (None if not bucketed yet) into `to`
+    pub fn buckets(mut self, from: Option<u8>, to: u8) -> Self {
+        self.from_bucket = from;
+        self.to_bucket = Some(to);
+        self
+    }
+}
+
+impl ToInstruction for SubmitPooledProof {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::SubmitPooledProof {
+                miner: miner_address(program_id, &self.authority),
+                global_round: global_round_address(program_id),
+                reward_pool: reward_pool_address(program_id),
+                round_score: round_score_address(program_id, &self.authority),
+                authority: self.authority,
+                system_program: system_program::ID,
+                score_decay: score_decay_address(program_id),
+                scoring_migration: scoring_migration_address(program_id),
+                from_bucket: self
+                    .from_bucket
+                    .map(|index| score_bucket_address(program_id, index)),
+                to_bucket: self
+                    .to_bucket
+                    .map(|index| score_bucket_address(program_id, index)),
+            },
+            crate::instruction::SubmitPooledProof {
+                nonce: self.nonce,
+                difficulty: self.difficulty,
+            },
+        )
+    }
+}
+
+fn submit_proof_accounts(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    from_bucket: Option<u8>,
+    to_bucket: Option<u8>,
+) -> crate::accounts::SubmitProof {
+    crate::accounts::SubmitProof {
+        miner: miner_address(program_id, authority),
+        global_round: global_round_address(program_id),
+        authority: *authority,
+        from_bucket: from_bucket.map(|index| score_bucket_address(program_id, index)),
+        to_bucket: to_bucket.map(|index| score_bucket_address(program_id, index)),
+        score_decay: Some(score_decay_address(program_id)),
+        scoring_migration: Some(scoring_migration_address(program_id)),
+    }
+}
+
+pub struct ReportRejection {
+    pub authority: Pubkey,
+    pub reason: RejectionReason,
+}
+
+impl ReportRejection {
+    pub fn new(authority: Pubkey, reason: RejectionReason) -> Self {
+        Self { authority, reason }
+    }
+}
+
+impl ToInstruction for ReportRejection {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::ReportRejection {
+                miner: miner_address(program_id, &self.authority),
+                global_round: global_round_address(program_id),
+                authority: self.authority,
+            },
+            crate::instruction::ReportRejection {
+                reason: self.reason,
+            },
+        )
+    }
+}
+
+pub struct ResetTiming {
+    pub authority: Pubkey,
+}
+
+impl ResetTiming {
+    pub fn new(authority: Pubkey) -> Self {
+        Self { authority }
+    }
+}
+
+impl ToInstruction for ResetTiming {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::ResetTiming {
+                miner: miner_address(program_id, &self.authority),
+                authority: self.authority,
+            },
+            crate::instruction::ResetTiming {},
+        )
+    }
+}
+
+/// `upgrade_miner` for the miner owned by `authority`, paid for by `payer`
+pub struct UpgradeMiner {
+    pub payer: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl UpgradeMiner {
+    pub fn new(payer: Pubkey, authority: Pubkey) -> Self {
+        Self { payer, authority }
+    }
+}
+
+impl ToInstruction for UpgradeMiner {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::UpgradeMiner {
+                miner: miner_address(program_id, &self.authority),
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            crate::instruction::UpgradeMiner {
+                authority: self.authority,
+            },
+        )
+    }
+}
+
+pub struct SyncScoreBucket {
+    pub authority: Pubkey,
+    pub from_bucket: Option<u8>,
+    pub to_bucket: u8,
+}
+
+impl SyncScoreBucket {
+    pub fn new(authority: Pubkey, from_bucket: Option<u8>, to_bucket: u8) -> Self {
+        Self {
+            authority,
+            from_bucket,
+            to_bucket,
+        }
+    }
+}
+
+impl ToInstruction for SyncScoreBucket {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::SyncScoreBucket {
+                miner: miner_address(program_id, &self.authority),
+                from_bucket: self
+                    .from_bucket
+                    .map(|index| score_bucket_address(program_id, index)),
+                to_bucket: score_bucket_address(program_id, self.to_bucket),
+            },
+            crate::instruction::SyncScoreBucket {},
+        )
+    }
+}
+
+pub struct AttestMiner {
+    pub payer: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl AttestMiner {
+    pub fn new(payer: Pubkey, authority: Pubkey) -> Self {
+        Self { payer, authority }
+    }
+}
+
+impl ToInstruction for AttestMiner {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::AttestMiner {
+                miner: miner_address(program_id, &self.authority),
+                attestation: attestation_address(program_id, &self.authority),
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            crate::instruction::AttestMiner {},
+        )
+    }
+}
+
+/// A liveness challenge, signed by `admin` and countersigned by `authority`
+/// `verify_liveness`, signed by `issuer` (the configured liveness issuer,
+/// or the GlobalRound admin while there is none) and `authority`
+pub struct VerifyLiveness {
+    pub issuer: Pubkey,
+    pub authority: Pubkey,
+    pub expires_at: i64,
+}
+
+impl VerifyLiveness {
+    pub fn new(issuer: Pubkey, authority: Pubkey, expires_at: i64) -> Self {
+        Self {
+            issuer,
+            authority,
+            expires_at,
+        }
+    }
+}
+
+impl ToInstruction for VerifyLiveness {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::VerifyLiveness {
+                miner: miner_address(program_id, &self.authority),
+                liveness: liveness_address(program_id, &self.authority),
+                global_round: global_round_address(program_id),
+                issuer: self.issuer,
+                authority: self.authority,
+                system_program: system_program::ID,
+                liveness_issuer: Some(liveness_issuer_address(program_id)),
+            },
+            crate::instruction::VerifyLiveness {
+                expires_at: self.expires_at,
+            },
+        )
+    }
+}
+
+pub struct ConfigureLivenessIssuer {
+    pub admin: Pubkey,
+    pub issuer: Pubkey,
+}
+
+impl ConfigureLivenessIssuer {
+    pub fn new(admin: Pubkey, issuer: Pubkey) -> Self {
+        Self { admin, issuer }
+    }
+}
+
+impl ToInstruction for ConfigureLivenessIssuer {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::ConfigureLivenessIssuer {
+                liveness_issuer: liveness_issuer_address(program_id),
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+                system_program: system_program::ID,
+            },
+            crate::instruction::ConfigureLivenessIssuer {
+                issuer: self.issuer,
+            },
+        )
+    }
+}
+
+/// `settle_reward_pool`, paying `winners` (the pool's leaders, in order)
+pub struct SettleRewardPool {
+    pub winners: Vec<Pubkey>,
+}
+
+impl SettleRewardPool {
+    pub fn new(winners: Vec<Pubkey>) -> Self {
+        Self { winners }
+    }
+}
+
+impl ToInstruction for SettleRewardPool {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        let mut ix = instruction(
+            program_id,
+            crate::accounts::SettleRewardPool {
+                reward_pool: reward_pool_address(program_id),
+                global_round: global_round_address(program_id),
+            },
+            crate::instruction::SettleRewardPool {},
+        );
+        ix.accounts.extend(
+            self.winners
+                .iter()
+                .map(|winner| AccountMeta::new(*winner, false)),
+        );
+        ix
+    }
+}
+
+/// `convert_stats` for the miner owned by `authority`; anyone may send it
+///
+/// `payer` covers the rent of growing a miner still in an older layout.
+pub struct ConvertStats {
+    pub payer: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl ConvertStats {
+    pub fn new(payer: Pubkey, authority: Pubkey) -> Self {
+        Self { payer, authority }
+    }
+}
+
+impl ToInstruction for ConvertStats {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::ConvertStats {
+                miner: miner_address(program_id, &self.authority),
+                scoring_migration: scoring_migration_address(program_id),
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            crate::instruction::ConvertStats {
+                authority: self.authority,
+            },
+        )
+    }
+}
+
+/// `gc_miner` for the miner owned by `authority`; anyone may send it
+///
+/// `sweep_wallet` must match the GC policy, and `score_bucket` the miner's
+/// recorded bucket, if it has one.
+pub struct GcMiner {
+    pub payer: Pubkey,
+    pub authority: Pubkey,
+    pub sweep_wallet: Pubkey,
+    pub score_bucket: Option<u8>,
+}
+
+impl GcMiner {
+    pub fn new(
+        payer: Pubkey,
+        authority: Pubkey,
+        sweep_wallet: Pubkey,
+        score_bucket: Option<u8>,
+    ) -> Self {
+        Self {
+            payer,
+            authority,
+            sweep_wallet,
+            score_bucket,
+        }
+    }
+}
+
+impl ToInstruction for GcMiner {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::GcMiner {
+                gc_policy: gc_policy_address(program_id),
+                miner: miner_address(program_id, &self.authority),
+                score_bucket: self
+                    .score_bucket
+                    .map(|index| score_bucket_address(program_id, index)),
+                archive: miner_archive_address(program_id, &self.authority),
+                attestation: attestation_address(program_id, &self.authority),
+                metadata: metadata_address(program_id, &self.authority),
+                region: region_address(program_id, &self.authority),
+                liveness: liveness_address(program_id, &self.authority),
+                round_score: round_score_address(program_id, &self.authority),
+                sweep_wallet: self.sweep_wallet,
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            crate::instruction::GcMiner {},
+        )
+    }
+}
+
+/// `get_miner_rank_inputs` view
+pub struct GetMinerRankInputs {
+    pub authority: Pubkey,
+}
+
+impl GetMinerRankInputs {
+    pub fn new(authority: Pubkey) -> Self {
+        Self { authority }
+    }
+}
+
+impl ToInstruction for GetMinerRankInputs {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            view_miner_accounts(program_id, &self.authority),
+            crate::instruction::GetMinerRankInputs {},
+        )
+    }
+}
+
+/// `preview_round_reward` view
+pub struct PreviewRoundReward {
+    pub authority: Pubkey,
+    pub difficulty: u8,
+}
+
+impl PreviewRoundReward {
+    pub fn new(authority: Pubkey, difficulty: u8) -> Self {
+        Self {
+            authority,
+            difficulty,
+        }
+    }
+}
+
+impl ToInstruction for PreviewRoundReward {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            view_miner_accounts(program_id, &self.authority),
+            crate::instruction::PreviewRoundReward {
+                difficulty: self.difficulty,
+            },
+        )
+    }
+}
+
+fn view_miner_accounts(program_id: &Pubkey, authority: &Pubkey) -> crate::accounts::ViewMiner {
+    crate::accounts::ViewMiner {
+        miner: miner_address(program_id, authority),
+        global_round: global_round_address(program_id),
+    }
+}
+
+// ============================================================================
+// Admin Instructions
+// ============================================================================
+
+/// `initialize_global_round`, paid for by `authority`
+pub struct InitializeGlobalRound {
+    pub authority: Pubkey,
+    pub admin: Pubkey,
+}
+
+impl InitializeGlobalRound {
+    /// Initialize with `admin` as both payer and admin
+    pub fn new(admin: Pubkey) -> Self {
+        Self {
+            authority: admin,
+            admin,
+        }
+    }
+}
+
+impl ToInstruction for InitializeGlobalRound {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::InitializeGlobalRound {
+                global_round: global_round_address(program_id),
+                authority: self.authority,
+                system_program: system_program::ID,
+            },
+            crate::instruction::InitializeGlobalRound { admin: self.admin },
+        )
+    }
+}
+
+pub struct RotateRound {
+    pub admin: Pubkey,
+}
+
+impl RotateRound {
+    pub fn new(admin: Pubkey) -> Self {
+        Self { admin }
+    }
+}
+
+impl ToInstruction for RotateRound {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::RotateRound {
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+            },
+            crate::instruction::RotateRound {},
+        )
+    }
+}
+
+pub struct UpdateGlobalParams {
+    pub admin: Pubkey,
+    pub params: GlobalParams,
+}
+
+impl UpdateGlobalParams {
+    pub fn new(admin: Pubkey, params: GlobalParams) -> Self {
+        Self { admin, params }
+    }
+}
+
+impl ToInstruction for UpdateGlobalParams {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::UpdateGlobalParams {
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+            },
+            crate::instruction::UpdateGlobalParams {
+                params: self.params.clone(),
+            },
+        )
+    }
+}
+
+pub struct UpgradeGlobalRound {
+    pub admin: Pubkey,
+}
+
+impl UpgradeGlobalRound {
+    pub fn new(admin: Pubkey) -> Self {
+        Self { admin }
+    }
+}
+
+impl ToInstruction for UpgradeGlobalRound {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::UpgradeGlobalRound {
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+                system_program: system_program::ID,
+            },
+            crate::instruction::UpgradeGlobalRound {},
+        )
+    }
+}
+
+pub struct SetMinerTier {
+    pub admin: Pubkey,
+    pub authority: Pubkey,
+    pub tier: u8,
+}
+
+impl SetMinerTier {
+    pub fn new(admin: Pubkey, authority: Pubkey, tier: u8) -> Self {
+        Self {
+            admin,
+            authority,
+            tier,
+        }
+    }
+}
+
+impl ToInstruction for SetMinerTier {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::SetMinerTier {
+                miner: miner_address(program_id, &self.authority),
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+            },
+            crate::instruction::SetMinerTier { tier: self.tier },
+        )
+    }
+}
+
+pub struct InitializeScoreBucket {
+    pub payer: Pubkey,
+    pub index: u8,
+}
+
+impl InitializeScoreBucket {
+    pub fn new(payer: Pubkey, index: u8) -> Self {
+        Self { payer, index }
+    }
+}
+
+impl ToInstruction for InitializeScoreBucket {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::InitializeScoreBucket {
+                score_bucket: score_bucket_address(program_id, self.index),
+                payer: self.payer,
+                system_program: system_program::ID,
+            },
+            crate::instruction::InitializeScoreBucket { index: self.index },
+        )
+    }
+}
+
+/// `set_metadata`; `admin` is the GlobalRound admin, who receives the fee
+pub struct SetMetadata {
+    pub authority: Pubkey,
+    pub admin: Pubkey,
+    pub name: [u8; 32],
+    pub url: [u8; 64],
+}
+
+impl SetMetadata {
+    pub fn new(authority: Pubkey, admin: Pubkey, name: [u8; 32], url: [u8; 64]) -> Self {
+        Self {
+            authority,
+            admin,
+            name,
+            url,
+        }
+    }
+}
+
+impl ToInstruction for SetMetadata {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::SetMetadata {
+                miner: miner_address(program_id, &self.authority),
+                metadata: metadata_address(program_id, &self.authority),
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+                authority: self.authority,
+                system_program: system_program::ID,
+            },
+            crate::instruction::SetMetadata {
+                name: self.name,
+                url: self.url,
+            },
+        )
+    }
+}
+
+pub struct ReportRegion {
+    pub authority: Pubkey,
+    pub region: [u8; 2],
+    pub latency_bucket: u8,
+}
+
+impl ReportRegion {
+    pub fn new(authority: Pubkey, region: [u8; 2], latency_bucket: u8) -> Self {
+        Self {
+            authority,
+            region,
+            latency_bucket,
+        }
+    }
+}
+
+impl ToInstruction for ReportRegion {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::ReportRegion {
+                miner: miner_address(program_id, &self.authority),
+                region: region_address(program_id, &self.authority),
+                authority: self.authority,
+                system_program: system_program::ID,
+            },
+            crate::instruction::ReportRegion {
+                region: self.region,
+                latency_bucket: self.latency_bucket,
+            },
+        )
+    }
+}
+
+pub struct ConfigureScoreDecay {
+    pub admin: Pubkey,
+    pub decay_bps: u16,
+}
+
+impl ConfigureScoreDecay {
+    pub fn new(admin: Pubkey, decay_bps: u16) -> Self {
+        Self { admin, decay_bps }
+    }
+}
+
+impl ToInstruction for ConfigureScoreDecay {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::ConfigureScoreDecay {
+                score_decay: score_decay_address(program_id),
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+                system_program: system_program::ID,
+            },
+            crate::instruction::ConfigureScoreDecay {
+                decay_bps: self.decay_bps,
+            },
+        )
+    }
+}
+
+pub struct ConfigureRegistrationGate {
+    pub admin: Pubkey,
+    pub gatekeeper: Pubkey,
+    pub enabled: bool,
+}
+
+impl ConfigureRegistrationGate {
+    pub fn new(admin: Pubkey, gatekeeper: Pubkey, enabled: bool) -> Self {
+        Self {
+            admin,
+            gatekeeper,
+            enabled,
+        }
+    }
+}
+
+impl ToInstruction for ConfigureRegistrationGate {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::ConfigureRegistrationGate {
+                registration_gate: registration_gate_address(program_id),
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+                system_program: system_program::ID,
+            },
+            crate::instruction::ConfigureRegistrationGate {
+                gatekeeper: self.gatekeeper,
+                enabled: self.enabled,
+            },
+        )
+    }
+}
+
+pub struct ConfigureRewardPool {
+    pub admin: Pubkey,
+    pub fee_lamports: u64,
+    pub top_k: u8,
+}
+
+impl ConfigureRewardPool {
+    pub fn new(admin: Pubkey, fee_lamports: u64, top_k: u8) -> Self {
+        Self {
+            admin,
+            fee_lamports,
+            top_k,
+        }
+    }
+}
+
+impl ToInstruction for ConfigureRewardPool {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::ConfigureRewardPool {
+                reward_pool: reward_pool_address(program_id),
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+                system_program: system_program::ID,
+            },
+            crate::instruction::ConfigureRewardPool {
+                fee_lamports: self.fee_lamports,
+                top_k: self.top_k,
+            },
+        )
+    }
+}
+
+pub struct MigrateScoring {
+    pub admin: Pubkey,
+}
+
+impl MigrateScoring {
+    pub fn new(admin: Pubkey) -> Self {
+        Self { admin }
+    }
+}
+
+impl ToInstruction for MigrateScoring {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::MigrateScoring {
+                scoring_migration: scoring_migration_address(program_id),
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+                system_program: system_program::ID,
+            },
+            crate::instruction::MigrateScoring {},
+        )
+    }
+}
+
+pub struct ResumeSubmissions {
+    pub admin: Pubkey,
+}
+
+impl ResumeSubmissions {
+    pub fn new(admin: Pubkey) -> Self {
+        Self { admin }
+    }
+}
+
+impl ToInstruction for ResumeSubmissions {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::ResumeSubmissions {
+                scoring_migration: scoring_migration_address(program_id),
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+            },
+            crate::instruction::ResumeSubmissions {},
+        )
+    }
+}
+
+pub struct PostStatsRoot {
+    pub admin: Pubkey,
+    pub root: [u8; 32],
+    pub miner_count: u32,
+}
+
+impl PostStatsRoot {
+    pub fn new(admin: Pubkey, root: [u8; 32], miner_count: u32) -> Self {
+        Self {
+            admin,
+            root,
+            miner_count,
+        }
+    }
+}
+
+impl ToInstruction for PostStatsRoot {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::PostStatsRoot {
+                stats_root: stats_root_address(program_id),
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+                system_program: system_program::ID,
+            },
+            crate::instruction::PostStatsRoot {
+                root: self.root,
+                miner_count: self.miner_count,
+            },
+        )
+    }
+}
+
+pub struct ConfigureGcPolicy {
+    pub admin: Pubkey,
+    pub inactive_days: u16,
+    pub sweep_wallet: Pubkey,
+}
+
+impl ConfigureGcPolicy {
+    pub fn new(admin: Pubkey, inactive_days: u16, sweep_wallet: Pubkey) -> Self {
+        Self {
+            admin,
+            inactive_days,
+            sweep_wallet,
+        }
+    }
+}
+
+impl ToInstruction for ConfigureGcPolicy {
+    fn instruction_for(&self, program_id: &Pubkey) -> Instruction {
+        instruction(
+            program_id,
+            crate::accounts::ConfigureGcPolicy {
+                gc_policy: gc_policy_address(program_id),
+                global_round: global_round_address(program_id),
+                admin: self.admin,
+                system_program: system_program::ID,
+            },
+            crate::instruction::ConfigureGcPolicy {
+                inactive_days: self.inactive_days,
+                sweep_wallet: self.sweep_wallet,
+            },
+        )
+    }
+}
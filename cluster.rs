@@ -0,0 +1,116 @@
+//! `--cluster`: per-cluster profiles embedded at build time
+//!
+//! Each profile carries the program ID, default RPC and genesis hash of one
+//! deployment. Release builds bake the program IDs in with
+//! TESTORE_{TESTNET,DEVNET,LOCALNET}_PROGRAM_ID, so one binary knows where
+//! each cluster's TestORE lives and refuses an RPC from another cluster
+//! instead of reading an empty leaderboard from it.
+//!
+//! TESTNET_RPC (or the Solana CLI's RPC URL) and PROGRAM_ID still override
+//! the profile at runtime; the genesis check runs either way.
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+use std::str::FromStr;
+
+/// Genesis hash of testnet
+pub const TESTNET_GENESIS_HASH: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+
+/// Genesis hash of devnet
+pub const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+
+/// Cluster the TestORE program is read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Cluster {
+    #[default]
+    Testnet,
+    Devnet,
+    Localnet,
+}
+
+/// What a binary knows about one cluster's deployment
+#[derive(Debug, Clone, Copy)]
+pub struct Profile {
+    pub default_rpc: &'static str,
+    pub program_id: &'static str,
+
+    /// None for localnet, whose genesis changes with every reset
+    pub genesis_hash: Option<&'static str>,
+}
+
+impl Cluster {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Cluster::Testnet => "testnet",
+            Cluster::Devnet => "devnet",
+            Cluster::Localnet => "localnet",
+        }
+    }
+
+    pub fn profile(&self) -> Profile {
+        match self {
+            Cluster::Testnet => Profile {
+                default_rpc: "https://api.testnet.solana.com",
+                program_id: embedded(option_env!("TESTORE_TESTNET_PROGRAM_ID")),
+                genesis_hash: Some(TESTNET_GENESIS_HASH),
+            },
+            Cluster::Devnet => Profile {
+                default_rpc: "https://api.devnet.solana.com",
+                program_id: embedded(option_env!("TESTORE_DEVNET_PROGRAM_ID")),
+                genesis_hash: Some(DEVNET_GENESIS_HASH),
+            },
+            Cluster::Localnet => Profile {
+                default_rpc: "http://127.0.0.1:8899",
+                program_id: embedded(option_env!("TESTORE_LOCALNET_PROGRAM_ID")),
+                genesis_hash: None,
+            },
+        }
+    }
+
+    /// Program ID embedded for this cluster
+    pub fn program_id(&self) -> Result<Pubkey> {
+        let id = self.profile().program_id;
+        Pubkey::from_str(id)
+            .map_err(|e| anyhow!("Embedded {} program ID '{}': {}", self.name(), id, e))
+    }
+
+    /// Genesis hash the RPC must report, if this cluster has a fixed one
+    pub fn genesis_hash(&self) -> Result<Option<Hash>> {
+        self.profile()
+            .genesis_hash
+            .map(|hash| {
+                Hash::from_str(hash)
+                    .map_err(|e| anyhow!("Embedded {} genesis '{}': {}", self.name(), hash, e))
+            })
+            .transpose()
+    }
+}
+
+/// Builds without a cluster's program ID fall back to the program's own
+const fn embedded(id: Option<&'static str>) -> &'static str {
+    match id {
+        Some(id) => id,
+        None => "TESTORE11111111111111111111111111111111111",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_parse() {
+        for cluster in Cluster::value_variants() {
+            assert!(cluster.program_id().is_ok());
+            assert!(cluster.genesis_hash().is_ok());
+        }
+
+        assert_eq!(Cluster::Localnet.genesis_hash().unwrap(), None);
+        assert_ne!(
+            Cluster::Testnet.genesis_hash().unwrap(),
+            Cluster::Devnet.genesis_hash().unwrap()
+        );
+        assert_eq!(Cluster::from_str("devnet", true).unwrap(), Cluster::Devnet);
+    }
+}
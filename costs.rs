@@ -0,0 +1,298 @@
+//! `costs`: testnet SOL locked as rent by the program's accounts
+//!
+//! Groups every account owned by PROGRAM_ID (and LEGACY_PROGRAM_IDS) by
+//! type, projects the rent new miners will lock over `--days`, and totals
+//! what closing miners idle for `--inactive-days` would give back.
+//!
+//! Growth is measured against the leaderboard cache: the miners added since
+//! the last `leaderboard refresh`, per day. Pass `--new-miners-per-day` when
+//! there is no cache or it is too recent to say.
+
+use crate::{format_number, Config, LEADERBOARD_CACHE_PATH};
+use anchor_lang::{Discriminator, Space};
+use anyhow::Result;
+use clap::Args;
+use colored::*;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, native_token::lamports_to_sol,
+    pubkey::Pubkey,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use testore_core::LeaderboardCache;
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// Caches younger than this say too little about growth
+const MIN_GROWTH_WINDOW_SECS: i64 = 3_600;
+
+#[derive(Args)]
+pub struct CostsArgs {
+    /// Miners with no proof in this many days count as reclaimable
+    #[arg(long, default_value_t = 30)]
+    inactive_days: u64,
+
+    /// Days of growth to project
+    #[arg(long, default_value_t = 30)]
+    days: u64,
+
+    /// New miners per day (measured against the leaderboard cache by default)
+    #[arg(long)]
+    new_miners_per_day: Option<f64>,
+
+    /// Print JSON instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+/// Accounts of one type and the rent they hold
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+struct KindTotal {
+    accounts: u64,
+    bytes: u64,
+    lamports: u64,
+}
+
+/// What the program's accounts cost now, and what could come back
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct Costs {
+    /// By account type; "Other" for data no current type matches
+    kinds: BTreeMap<&'static str, KindTotal>,
+    total_lamports: u64,
+    inactive_miners: u64,
+    reclaimable_lamports: u64,
+}
+
+/// Rent growth at the current miner account size
+#[derive(Debug, Serialize)]
+struct Projection {
+    rent_per_miner: u64,
+    new_miners_per_day: f64,
+    days: u64,
+    lamports: u64,
+}
+
+#[derive(Serialize)]
+struct Report {
+    #[serde(flatten)]
+    costs: Costs,
+    projection: Option<Projection>,
+}
+
+pub fn run(config: &Config, args: &CostsArgs) -> Result<()> {
+    let client =
+        RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed());
+
+    let mut accounts = Vec::new();
+    for program_id in config.program_ids() {
+        testore_core::throttle(&client);
+        accounts.extend(client.get_program_accounts(&program_id)?);
+    }
+    let now = crate::crank::cluster_time(&client)?;
+    let costs = summarize(&accounts, now, args.inactive_days);
+
+    testore_core::throttle(&client);
+    let rent_per_miner =
+        client.get_minimum_balance_for_rent_exemption(8 + testore_program::Miner::INIT_SPACE)?;
+    // The cache merges accounts sharing an authority, so count authorities
+    let miners = accounts
+        .iter()
+        .filter_map(|(_, account)| testore_core::parse_miner_account(&account.data))
+        .map(|miner| miner.authority)
+        .collect::<BTreeSet<_>>()
+        .len() as u64;
+    let projection = args
+        .new_miners_per_day
+        .or_else(|| cached_growth(miners, chrono::Utc::now().timestamp()))
+        .map(|new_miners_per_day| Projection {
+            rent_per_miner,
+            new_miners_per_day,
+            days: args.days,
+            lamports: (new_miners_per_day * args.days as f64 * rent_per_miner as f64) as u64,
+        });
+
+    if args.json {
+        let report = Report { costs, projection };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} Rent held by {} program accounts\n",
+        "💰".bright_cyan(),
+        format_number(accounts.len() as u64)
+    );
+    println!(
+        "{:<18} {:>10} {:>12} {:>14}",
+        "Account", "Count", "Bytes", "SOL"
+    );
+    for (kind, total) in &costs.kinds {
+        println!(
+            "{:<18} {:>10} {:>12} {:>14.4}",
+            kind,
+            format_number(total.accounts),
+            format_number(total.bytes),
+            lamports_to_sol(total.lamports)
+        );
+    }
+    let total = format!(
+        "{:<18} {:>10} {:>12} {:>14.4}",
+        "Total",
+        "",
+        "",
+        lamports_to_sol(costs.total_lamports)
+    );
+    println!("{}", total.bold());
+    println!();
+
+    match &projection {
+        Some(projection) => println!(
+            "{} {:.1} new miners/day × {:.6} SOL locks {:.4} SOL more over {} days",
+            "📈".bright_cyan(),
+            projection.new_miners_per_day,
+            lamports_to_sol(projection.rent_per_miner),
+            lamports_to_sol(projection.lamports),
+            projection.days
+        ),
+        None => println!(
+            "{} Growth unknown: run `leaderboard refresh` now and again later, or pass --new-miners-per-day",
+            "📈".bright_black()
+        ),
+    }
+    println!(
+        "{} {} miners idle for {}+ days hold {:.4} SOL that closing them would reclaim",
+        "♻️".bright_green(),
+        format_number(costs.inactive_miners),
+        args.inactive_days,
+        lamports_to_sol(costs.reclaimable_lamports)
+    );
+
+    Ok(())
+}
+
+/// Every account type the program creates, by Anchor discriminator
+fn account_kinds() -> [(&'static str, [u8; 8]); 15] {
+    use testore_program::*;
+
+    [
+        ("Miner", Miner::DISCRIMINATOR),
+        ("GlobalRound", GlobalRound::DISCRIMINATOR),
+        ("ScoreBucket", ScoreBucket::DISCRIMINATOR),
+        ("MinerAttestation", MinerAttestation::DISCRIMINATOR),
+        ("LivenessProof", LivenessProof::DISCRIMINATOR),
+        ("MinerMetadata", MinerMetadata::DISCRIMINATOR),
+        ("MinerRegion", MinerRegion::DISCRIMINATOR),
+        ("ScoreDecay", ScoreDecay::DISCRIMINATOR),
+        ("RegistrationGate", RegistrationGate::DISCRIMINATOR),
+        ("ScoringMigration", ScoringMigration::DISCRIMINATOR),
+        ("RewardPool", RewardPool::DISCRIMINATOR),
+        ("RoundScore", RoundScore::DISCRIMINATOR),
+        ("StatsRoot", StatsRoot::DISCRIMINATOR),
+        ("GcPolicy", GcPolicy::DISCRIMINATOR),
+        ("MinerArchive", MinerArchive::DISCRIMINATOR),
+    ]
+}
+
+/// Group `accounts` by type and total the rent of miners idle since
+/// `inactive_days` before `now`
+fn summarize(accounts: &[(Pubkey, Account)], now: i64, inactive_days: u64) -> Costs {
+    let kinds = account_kinds();
+    let idle_since = now.saturating_sub((inactive_days as i64).saturating_mul(SECS_PER_DAY));
+
+    let mut costs = Costs::default();
+    for (_address, account) in accounts {
+        let kind = kinds
+            .iter()
+            .find(|(_, discriminator)| account.data.get(..8) == Some(&discriminator[..]))
+            .map_or("Other", |(name, _)| *name);
+        let total = costs.kinds.entry(kind).or_default();
+        total.accounts += 1;
+        total.bytes += account.data.len() as u64;
+        total.lamports += account.lamports;
+        costs.total_lamports += account.lamports;
+
+        if let Some(miner) = testore_core::parse_miner_account(&account.data) {
+            if miner.last_hash_at < idle_since {
+                costs.inactive_miners += 1;
+                costs.reclaimable_lamports += account.lamports;
+            }
+        }
+    }
+
+    costs
+}
+
+/// New miners per day since the leaderboard cache was written
+fn cached_growth(miners: u64, now: i64) -> Option<f64> {
+    let cache = LeaderboardCache::load(LEADERBOARD_CACHE_PATH).ok()?;
+    let fetched_at = chrono::DateTime::parse_from_rfc3339(&cache.fetched_at).ok()?;
+
+    growth_per_day(
+        cache.entries.len() as u64,
+        miners,
+        now - fetched_at.timestamp(),
+    )
+}
+
+fn growth_per_day(before: u64, after: u64, elapsed_secs: i64) -> Option<f64> {
+    if elapsed_secs < MIN_GROWTH_WINDOW_SECS {
+        return None;
+    }
+
+    let added = after.saturating_sub(before) as f64;
+    Some(added * SECS_PER_DAY as f64 / elapsed_secs as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testore_core::MINER_ACCOUNT_LEN;
+
+    fn account(data: Vec<u8>, lamports: u64) -> (Pubkey, Account) {
+        let account = Account {
+            lamports,
+            data,
+            owner: testore_program::ID,
+            ..Account::default()
+        };
+        (Pubkey::new_unique(), account)
+    }
+
+    fn miner(last_hash_at: i64) -> (Pubkey, Account) {
+        let mut data = vec![0; MINER_ACCOUNT_LEN];
+        data[..8].copy_from_slice(&testore_core::miner_discriminator());
+        data[52..60].copy_from_slice(&last_hash_at.to_le_bytes());
+        account(data, 1_000)
+    }
+
+    #[test]
+    fn test_summarize() {
+        let now = 100 * SECS_PER_DAY;
+        let mut global_round = testore_core::global_round_discriminator().to_vec();
+        global_round.resize(143, 0);
+        let accounts = vec![
+            miner(now),
+            miner(now - 31 * SECS_PER_DAY),
+            miner(now - 29 * SECS_PER_DAY),
+            account(global_round, 5_000),
+            account(vec![9; 16], 7),
+        ];
+
+        let costs = summarize(&accounts, now, 30);
+        assert_eq!(costs.kinds["Miner"].accounts, 3);
+        assert_eq!(costs.kinds["Miner"].lamports, 3_000);
+        assert_eq!(costs.kinds["GlobalRound"].bytes, 143);
+        assert_eq!(costs.kinds["Other"].lamports, 7);
+        assert_eq!(costs.total_lamports, 8_007);
+        assert_eq!(costs.inactive_miners, 1);
+        assert_eq!(costs.reclaimable_lamports, 1_000);
+    }
+
+    #[test]
+    fn test_growth_per_day() {
+        assert_eq!(growth_per_day(100, 110, SECS_PER_DAY / 2), Some(20.0));
+        assert_eq!(growth_per_day(100, 90, SECS_PER_DAY), Some(0.0));
+        assert_eq!(growth_per_day(100, 110, 60), None);
+    }
+}
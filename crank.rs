@@ -0,0 +1,186 @@
+//! Round rotation crank
+//!
+//! Polls GlobalRound and sends `rotate_round` once the round is past its
+//! duration, so expiry doesn't wait on someone noticing. If a reward pool is
+//! configured, the same transaction settles it for the finished round.
+//! Failed rotations are retried with backoff; when every attempt fails the
+//! crank logs an error and posts to ALERT_WEBHOOK_URL (Slack/Discord
+//! compatible) if it is set. The `watch` command alerts through the same
+//! webhook.
+
+use crate::signer::{self, TxSigner};
+use crate::Config;
+use anyhow::{anyhow, Result};
+use clap::Args;
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::from_account, clock::Clock, commitment_config::CommitmentConfig, pubkey::Pubkey,
+    signature::Signature, sysvar,
+};
+use std::time::Duration;
+use testore_core::GlobalRoundAccount;
+use testore_program::client::{RotateRound, SettleRewardPool, ToInstruction};
+
+#[derive(Args)]
+pub struct CrankArgs {
+    /// Seconds between GlobalRound checks
+    #[arg(long, default_value_t = 15)]
+    poll_secs: u64,
+
+    /// Rotation attempts before alerting
+    #[arg(long, default_value_t = 3)]
+    retries: u32,
+}
+
+/// Run the crank until interrupted
+pub async fn run(config: &Config, args: &CrankArgs) -> Result<()> {
+    let client =
+        RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed());
+
+    // rotate_round is admin-only; the admin is usually not the funding wallet
+    let crank_signer = match std::env::var("CRANK_KEYPAIR") {
+        Ok(path) => Some(signer::load_signer(&path)?),
+        Err(_) => None,
+    };
+    let signer = crank_signer.as_deref().unwrap_or(&*config.signer);
+
+    let round = testore_core::fetch_global_round(&client, &config.program_id)?;
+    if round.admin != signer.pubkey() {
+        return Err(anyhow!(
+            "Crank wallet {} is not the GlobalRound admin {}",
+            signer.pubkey(),
+            round.admin
+        ));
+    }
+
+    println!(
+        "{} Crank running - checking every {}s",
+        "⚙️".bright_cyan(),
+        args.poll_secs
+    );
+
+    loop {
+        match check_round(&client, config, signer, args).await {
+            Ok(Some(signature)) => println!("{} Rotated round: {}", "🔄".bright_green(), signature),
+            Ok(None) => {}
+            Err(e) => alert(&format!("TestORE crank failed: {:#}", e)).await,
+        }
+
+        tokio::time::sleep(Duration::from_secs(args.poll_secs)).await;
+    }
+}
+
+/// Rotate the round if it has expired, returning the rotation signature
+async fn check_round(
+    client: &RpcClient,
+    config: &Config,
+    signer: &dyn TxSigner,
+    args: &CrankArgs,
+) -> Result<Option<Signature>> {
+    let round = testore_core::fetch_global_round(client, &config.program_id)?;
+    let now = cluster_time(client)?;
+
+    if !needs_rotation(&round, now) {
+        return Ok(None);
+    }
+
+    log::info!(
+        "Round #{} ended at {}, chain time {}",
+        round.round_number,
+        round.ends_at(),
+        now
+    );
+
+    let mut last_error = None;
+    for attempt in 1..=args.retries.max(1) {
+        match send_rotation(client, &config.program_id, signer) {
+            Ok(signature) => return Ok(Some(signature)),
+            Err(e) => {
+                log::warn!("Rotation attempt {} failed: {}", attempt, e);
+                last_error = Some(e);
+                tokio::time::sleep(Duration::from_secs(2 * attempt as u64)).await;
+            }
+        }
+    }
+
+    Err(last_error
+        .unwrap()
+        .context(format!("rotating round #{}", round.round_number)))
+}
+
+fn needs_rotation(round: &GlobalRoundAccount, now: i64) -> bool {
+    now > round.ends_at()
+}
+
+/// The cluster's clock, which is what the program checks expiry against
+pub(crate) fn cluster_time(client: &RpcClient) -> Result<i64> {
+    testore_core::throttle(client);
+    let account = client.get_account(&sysvar::clock::id())?;
+    let clock: Clock =
+        from_account(&account).ok_or_else(|| anyhow!("Could not decode the clock sysvar"))?;
+
+    Ok(clock.unix_timestamp)
+}
+
+fn send_rotation(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    signer: &dyn TxSigner,
+) -> Result<Signature> {
+    let mut ixs = vec![RotateRound::new(signer.pubkey()).instruction_for(program_id)];
+    if let Some(pool) = testore_core::fetch_reward_pool(client, program_id)? {
+        let winners = pool
+            .leaders
+            .iter()
+            .map(|(authority, _)| *authority)
+            .collect();
+        ixs.push(SettleRewardPool::new(winners).instruction_for(program_id));
+    }
+
+    signer::send(client, signer, &ixs)
+}
+
+/// Log an error and forward it to ALERT_WEBHOOK_URL, if configured
+pub(crate) async fn alert(message: &str) {
+    log::error!("{}", message);
+    eprintln!("{} {}", "🚨".bright_red(), message.bright_red());
+    post_webhook(message).await;
+}
+
+/// Post `message` to ALERT_WEBHOOK_URL, if configured
+pub(crate) async fn post_webhook(message: &str) {
+    let Ok(url) = std::env::var("ALERT_WEBHOOK_URL") else {
+        return;
+    };
+
+    // Slack reads `text`, Discord reads `content`
+    let body = serde_json::json!({ "text": message, "content": message });
+    if let Err(e) = reqwest::Client::new().post(&url).json(&body).send().await {
+        log::error!("Failed to deliver alert: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_rotation() {
+        let round = GlobalRoundAccount {
+            current_challenge: [0; 32],
+            round_number: 1,
+            started_at: 1_000,
+            min_difficulty: 8,
+            total_hashes_submitted: 0,
+            total_rounds_completed: 0,
+            admin: Pubkey::new_unique(),
+            tier_step: 0,
+            round_duration: 3_600,
+            max_round_proofs: 0,
+        };
+
+        assert!(!needs_rotation(&round, 4_600));
+        assert!(needs_rotation(&round, 4_601));
+    }
+}
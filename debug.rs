@@ -0,0 +1,496 @@
+//! `debug tx <signature>`: explain a failed proof submission
+//!
+//! Fetches the transaction, decodes its submit_proof, submit_tagged_proof,
+//! reveal_proof or submit_pooled_proof instruction, recomputes the hash against the round's
+//! challenge when the round hasn't rotated since, and turns the program's
+//! custom error code into a plain reason: how many bits short the hash was,
+//! whether the challenge had gone stale or the round expired, or how long the
+//! rate limit had left to run.
+//!
+//! `debug history <authority>` lists a miner's recent proof submissions with
+//! the program error of each failed one. Both read transaction history
+//! through HISTORY_RPC when set (see history.rs), so they work past the
+//! public RPC's retention.
+
+use crate::history::{self, HistoryChain, HistoryProvider};
+use crate::Config;
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, instruction::InstructionError, message::VersionedMessage,
+    pubkey::Pubkey, signature::Signature, transaction::TransactionError,
+};
+use testore_core::{GlobalRoundAccount, MinerAccount};
+use testore_program::{
+    client::miner_address, earned_tier, hash_proof, instruction, submission_wait,
+    tier_min_difficulty, ErrorCode, MIN_SUBMISSION_INTERVAL, REVEAL_DELAY_SLOTS,
+};
+
+/// A proof landing this soon after rotation was most likely mined against
+/// the previous challenge
+const STALE_CHALLENGE_WINDOW_SECS: i64 = 120;
+
+#[derive(Subcommand)]
+pub enum DebugCommand {
+    /// Explain why a proof submission failed
+    Tx {
+        /// Signature of the failed transaction
+        signature: Signature,
+    },
+
+    /// List a miner's recent proof submissions and why any failed
+    History {
+        /// Miner wallet address
+        authority: Pubkey,
+
+        /// Most recent transactions to list
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+
+        /// Stop at this slot instead of the start of available history
+        #[arg(long)]
+        since_slot: Option<u64>,
+    },
+}
+
+pub fn run(config: &Config, command: &DebugCommand) -> Result<()> {
+    match command {
+        DebugCommand::Tx { signature } => explain_tx(config, signature),
+        DebugCommand::History {
+            authority,
+            limit,
+            since_slot,
+        } => submission_history(config, authority, *limit, *since_slot),
+    }
+}
+
+/// A decoded proof-submitting instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Submission {
+    instruction: &'static str,
+    authority: Pubkey,
+    nonce: u64,
+    difficulty: u8,
+}
+
+/// What the chain looks like now, as far as it tells us about the failure
+struct Facts {
+    /// Cluster time the transaction landed at
+    block_time: Option<i64>,
+
+    /// GlobalRound now, which may have rotated since
+    round: GlobalRoundAccount,
+
+    /// The miner's account now, if it exists
+    miner: Option<MinerAccount>,
+}
+
+impl Facts {
+    /// Whether the transaction landed in the round that is still current
+    fn same_round(&self) -> bool {
+        self.block_time
+            .is_some_and(|block_time| block_time >= self.round.started_at)
+    }
+}
+
+fn explain_tx(config: &Config, signature: &Signature) -> Result<()> {
+    let client =
+        RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed());
+
+    let tx = HistoryChain::from_config(config).transaction(signature)?;
+    let error = tx
+        .transaction
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.err.clone());
+    let message = tx
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| anyhow!("Could not decode transaction {}", signature))?
+        .message;
+
+    let (index, submission) = decode_submission(&message, &config.program_id)
+        .ok_or_else(|| anyhow!("{} has no TestORE proof submission", signature))?;
+    let round = testore_core::fetch_global_round(&client, &config.program_id)?;
+    testore_core::throttle(&client);
+    let miner = client
+        .get_account_with_commitment(
+            &miner_address(&config.program_id, &submission.authority),
+            client.commitment(),
+        )?
+        .value
+        .and_then(|account| testore_core::parse_miner_account(&account.data));
+    let facts = Facts {
+        block_time: tx.block_time,
+        round,
+        miner,
+    };
+
+    println!("{}", "═══ Submission ═══".bright_yellow().bold());
+    println!("   Transaction:  {}", signature.to_string().bright_yellow());
+    println!("   Slot:         {}", tx.slot);
+    println!("   Instruction:  #{} {}", index, submission.instruction);
+    println!("   Miner:        {}", submission.authority);
+    println!("   Nonce:        {}", submission.nonce);
+    println!("   Claimed:      {} bits", submission.difficulty);
+    println!();
+
+    let code = match error {
+        None => {
+            println!("{} The transaction succeeded.", "✅".bright_green());
+            return Ok(());
+        }
+        Some(TransactionError::InstructionError(failed, InstructionError::Custom(code)))
+            if failed as usize == index =>
+        {
+            code
+        }
+        Some(other) => {
+            println!(
+                "{} Failed outside the program: {}",
+                "❌".bright_red(),
+                other
+            );
+            return Ok(());
+        }
+    };
+
+    let Some(error) = program_error(code) else {
+        println!(
+            "{} Failed with custom error {}, which is not a proof submission error",
+            "❌".bright_red(),
+            code
+        );
+        return Ok(());
+    };
+    println!(
+        "{} {} ({}): {}",
+        "❌".bright_red(),
+        error.name().bright_red(),
+        code,
+        error
+    );
+    for reason in explain(&error, &submission, &facts) {
+        println!("   {}", reason);
+    }
+
+    Ok(())
+}
+
+fn submission_history(
+    config: &Config,
+    authority: &Pubkey,
+    limit: usize,
+    since_slot: Option<u64>,
+) -> Result<()> {
+    let provider = HistoryChain::from_config(config);
+    let miner = miner_address(&config.program_id, authority);
+    let signatures = history::backfill(&provider, &miner, since_slot, limit)?;
+
+    println!(
+        "{} {} transactions touching {} (via {})\n",
+        "📜".bright_cyan(),
+        signatures.len(),
+        authority.to_string().bright_yellow(),
+        provider.name()
+    );
+    for status in &signatures {
+        let outcome = match &status.err {
+            None => "ok".bright_green().to_string(),
+            Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+                program_error(*code)
+                    .map(|error| error.name())
+                    .unwrap_or_else(|| format!("custom error {}", code))
+                    .bright_red()
+                    .to_string()
+            }
+            Some(other) => other.to_string().bright_red().to_string(),
+        };
+        println!("   {:>12} {} {}", status.slot, status.signature, outcome);
+    }
+    if signatures.len() < limit {
+        if let Ok(slot) = provider.first_available_slot() {
+            println!("\n   History available from slot {}", slot);
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the first proof-submitting TestORE instruction in `message`
+fn decode_submission(
+    message: &VersionedMessage,
+    program_id: &Pubkey,
+) -> Option<(usize, Submission)> {
+    use anchor_lang::{AnchorDeserialize, Discriminator};
+
+    let keys = message.static_account_keys();
+    message
+        .instructions()
+        .iter()
+        .enumerate()
+        .find_map(|(index, ix)| {
+            if keys.get(ix.program_id_index as usize) != Some(program_id) || ix.data.len() < 8 {
+                return None;
+            }
+            let (discriminator, mut args) = ix.data.split_at(8);
+
+            let (name, nonce, difficulty) = match discriminator {
+                d if d == instruction::SubmitProof::DISCRIMINATOR => {
+                    let args = instruction::SubmitProof::deserialize(&mut args).ok()?;
+                    ("submit_proof", args.nonce, args.difficulty)
+                }
+                d if d == instruction::SubmitTaggedProof::DISCRIMINATOR => {
+                    let args = instruction::SubmitTaggedProof::deserialize(&mut args).ok()?;
+                    ("submit_tagged_proof", args.nonce, args.difficulty)
+                }
+                d if d == instruction::RevealProof::DISCRIMINATOR => {
+                    let args = instruction::RevealProof::deserialize(&mut args).ok()?;
+                    ("reveal_proof", args.nonce, args.difficulty)
+                }
+                d if d == instruction::SubmitPooledProof::DISCRIMINATOR => {
+                    let args = instruction::SubmitPooledProof::deserialize(&mut args).ok()?;
+                    ("submit_pooled_proof", args.nonce, args.difficulty)
+                }
+                _ => return None,
+            };
+
+            // The Miner PDA comes first; the authority is whichever account derives it
+            let accounts: Vec<Pubkey> = ix
+                .accounts
+                .iter()
+                .filter_map(|&i| keys.get(i as usize).copied())
+                .collect();
+            let miner = *accounts.first()?;
+            let authority = accounts
+                .into_iter()
+                .find(|key| miner_address(program_id, key) == miner)?;
+
+            Some((
+                index,
+                Submission {
+                    instruction: name,
+                    authority,
+                    nonce,
+                    difficulty,
+                },
+            ))
+        })
+}
+
+/// Proof submission errors worth explaining, by custom error code
+fn program_error(code: u32) -> Option<ErrorCode> {
+    [
+        ErrorCode::InsufficientDifficulty,
+        ErrorCode::TooManySubmissions,
+        ErrorCode::DifficultyTooLow,
+        ErrorCode::BelowTierDifficulty,
+        ErrorCode::RoundExpired,
+        ErrorCode::NoPendingCommitment,
+        ErrorCode::RevealTooEarly,
+        ErrorCode::CommitmentMismatch,
+        ErrorCode::WrongScoreBucket,
+        ErrorCode::RewardPoolClosed,
+        ErrorCode::RewardPoolNotSettled,
+    ]
+    .into_iter()
+    .find(|error| u32::from(*error) == code)
+}
+
+/// Plain-language reasons for `error`, from what the chain shows now
+fn explain(error: &ErrorCode, submission: &Submission, facts: &Facts) -> Vec<String> {
+    let round = &facts.round;
+    let mut reasons = Vec::new();
+
+    match error {
+        ErrorCode::InsufficientDifficulty if facts.same_round() => {
+            let hash = hash_proof(
+                &submission.authority,
+                &round.current_challenge,
+                submission.nonce,
+            );
+            let bits = leading_zero_bits(&hash);
+            if bits < submission.difficulty {
+                reasons.push(format!(
+                    "The hash has {} leading zero bits against round #{}'s challenge, {} short of the claimed {}.",
+                    bits,
+                    round.round_number,
+                    submission.difficulty - bits,
+                    submission.difficulty
+                ));
+            }
+
+            let since_rotation = facts.block_time.unwrap_or_default() - round.started_at;
+            if since_rotation < STALE_CHALLENGE_WINDOW_SECS {
+                reasons.push(format!(
+                    "Round #{} started {}s before this landed: the nonce was most likely mined against the previous challenge. Refetch GlobalRound after every rotation.",
+                    round.round_number, since_rotation
+                ));
+            }
+        }
+        ErrorCode::InsufficientDifficulty => reasons.push(format!(
+            "The round has rotated since (now #{}), so the challenge this was checked against is gone. The usual cause is a nonce mined against a stale challenge.",
+            round.round_number
+        )),
+        ErrorCode::DifficultyTooLow => reasons.push(format!(
+            "Claimed difficulty {} is below the round minimum (now {}).",
+            submission.difficulty, round.min_difficulty
+        )),
+        ErrorCode::BelowTierDifficulty => {
+            let floor = facts
+                .miner
+                .as_ref()
+                .map(|miner| personal_min_difficulty(miner, round));
+            reasons.push(match floor {
+                Some(floor) => format!(
+                    "Claimed difficulty {} is below this miner's tier floor (now {}).",
+                    submission.difficulty, floor
+                ),
+                None => "Claimed difficulty is below this miner's tier floor.".to_string(),
+            });
+        }
+        ErrorCode::RoundExpired => reasons.push(match facts.block_time {
+            Some(block_time) if facts.same_round() => format!(
+                "Round #{} ended {}s before this landed and had not been rotated yet; proofs are refused until the crank rotates it.",
+                round.round_number,
+                block_time - round.ends_at()
+            ),
+            _ => "The round had run past its duration and had not been rotated yet; proofs are refused until the crank rotates it.".to_string(),
+        }),
+        ErrorCode::TooManySubmissions => {
+            let last_hash_at = facts.miner.as_ref().map(|miner| miner.last_hash_at);
+            reasons.push(match (last_hash_at, facts.block_time) {
+                (Some(last), Some(block_time)) if last <= block_time => format!(
+                    "The previous proof landed {}s earlier; {}s of the {}s interval were left.",
+                    block_time - last,
+                    submission_wait(last, block_time),
+                    MIN_SUBMISSION_INTERVAL
+                ),
+                _ => format!(
+                    "Proofs from one miner must land at least {}s apart.",
+                    MIN_SUBMISSION_INTERVAL
+                ),
+            });
+        }
+        ErrorCode::RevealTooEarly => reasons.push(format!(
+            "Reveals must land at least {} slots after the commit.",
+            REVEAL_DELAY_SLOTS
+        )),
+        _ => {}
+    }
+
+    reasons
+}
+
+/// Leading zero bits of a proof hash, i.e. the difficulty it meets
+fn leading_zero_bits(hash: &[u8; 32]) -> u8 {
+    let mut bits = 0u32;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits.min(u8::MAX as u32) as u8
+}
+
+/// The program's personal minimum: round minimum plus tier_step per tier
+fn personal_min_difficulty(miner: &MinerAccount, round: &GlobalRoundAccount) -> u8 {
+    let tier = miner
+        .tier
+        .unwrap_or(0)
+        .max(earned_tier(miner.rounds_completed));
+
+    tier_min_difficulty(round.min_difficulty, tier, round.tier_step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::message::Message;
+    use testore_program::client::{self, ToInstruction};
+
+    fn round() -> GlobalRoundAccount {
+        GlobalRoundAccount {
+            current_challenge: [3; 32],
+            round_number: 7,
+            started_at: 10_000,
+            min_difficulty: 8,
+            total_hashes_submitted: 0,
+            total_rounds_completed: 0,
+            admin: Pubkey::new_unique(),
+            tier_step: 0,
+            round_duration: 3_600,
+            max_round_proofs: 0,
+        }
+    }
+
+    #[test]
+    fn test_decode_submission() {
+        let program_id = testore_program::ID;
+        let authority = Pubkey::new_unique();
+        let ix = client::SubmitPooledProof::new(authority, 42, 11).instruction();
+        let message = VersionedMessage::Legacy(Message::new(&[ix], Some(&authority)));
+
+        let (index, submission) = decode_submission(&message, &program_id).unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(
+            submission,
+            Submission {
+                instruction: "submit_pooled_proof",
+                authority,
+                nonce: 42,
+                difficulty: 11,
+            }
+        );
+        assert!(decode_submission(&message, &Pubkey::new_unique()).is_none());
+    }
+
+    #[test]
+    fn test_explain() {
+        let submission = Submission {
+            instruction: "submit_proof",
+            authority: Pubkey::new_unique(),
+            nonce: 0,
+            difficulty: 255,
+        };
+        let facts = Facts {
+            block_time: Some(10_030),
+            round: round(),
+            miner: None,
+        };
+
+        // Same round, right after rotation: short bits plus a stale challenge
+        let reasons = explain(&ErrorCode::InsufficientDifficulty, &submission, &facts);
+        assert_eq!(reasons.len(), 2);
+        assert!(reasons[1].contains("previous challenge"));
+
+        // Before the current round began, the challenge can't be rechecked
+        let earlier = Facts {
+            block_time: Some(9_000),
+            ..facts
+        };
+        let reasons = explain(&ErrorCode::InsufficientDifficulty, &submission, &earlier);
+        assert!(reasons[0].contains("rotated since"));
+
+        let expired = Facts {
+            block_time: Some(13_700),
+            ..earlier
+        };
+        let reasons = explain(&ErrorCode::RoundExpired, &submission, &expired);
+        assert!(reasons[0].contains("ended 100s before"));
+
+        assert_eq!(leading_zero_bits(&[0; 32]), u8::MAX);
+        let mut hash = [0xff; 32];
+        hash[0] = 0;
+        hash[1] = 0b0001_0000;
+        assert_eq!(leading_zero_bits(&hash), 11);
+        assert_eq!(
+            program_error(ErrorCode::RoundExpired.into()).map(|e| e.name()),
+            Some("RoundExpired".to_string())
+        );
+    }
+}
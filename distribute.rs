@@ -0,0 +1,312 @@
+//! Mainnet TESTORE distribution transactions
+//!
+//! Each recipient gets its associated token account created (idempotently)
+//! and a `transfer_checked` from the funding wallet, optionally followed by a
+//! Memo so recipients can tell the airdrop from a scam. Wallets excluded from
+//! the airdrop can be sent a zero-lamport transfer carrying a Memo that
+//! explains why.
+//!
+//! Failures caused by the recipient's token account (frozen, or an address
+//! that isn't a token account we can create) are permanent and get the
+//! recipient quarantined instead of retried every run.
+//!
+//! Transfers are recorded as pending before they are broadcast; a retry
+//! checks the pending transaction with [`check_pending`] before signing a
+//! new one.
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Signature,
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    error::AssociatedTokenAccountError, get_associated_token_address,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token::error::TokenError;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use testore_core::PendingTransfer;
+
+/// Fee of every transaction this module builds
+///
+/// They carry one signature (the funding wallet's) and no priority fee, so
+/// the fee is always the base 5000 lamports per signature.
+pub const TRANSACTION_FEE_LAMPORTS: u64 = 5_000;
+
+/// Wallets excluded from the airdrop, with the reason shown to them
+pub type FlaggedWallets = HashMap<Pubkey, String>;
+
+/// Load a `{ "<wallet>": "<reason>" }` JSON file
+pub fn load_flagged(path: impl AsRef<Path>) -> Result<FlaggedWallets> {
+    let raw: HashMap<String, String> = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+    raw.into_iter()
+        .map(|(wallet, reason)| Ok((wallet.parse()?, reason)))
+        .collect()
+}
+
+/// Decimals of the TESTORE mint
+pub fn mint_decimals(client: &RpcClient, mint: &Pubkey) -> Result<u8> {
+    testore_core::throttle(client);
+    let account = client
+        .get_account(mint)
+        .map_err(|e| anyhow!("Could not fetch mint {}: {}", mint, e))?;
+
+    Ok(spl_token::state::Mint::unpack(&account.data)?.decimals)
+}
+
+/// Instructions sending `tokens` whole TESTORE to `recipient`
+pub fn transfer_instructions(
+    funding: &Pubkey,
+    mint: &Pubkey,
+    decimals: u8,
+    recipient: &Pubkey,
+    tokens: u64,
+    memo: Option<&str>,
+) -> Result<Vec<Instruction>> {
+    let amount = 10u64
+        .checked_pow(decimals as u32)
+        .and_then(|scale| tokens.checked_mul(scale))
+        .ok_or_else(|| anyhow!("{} TESTORE overflows the mint's base units", tokens))?;
+
+    let source = get_associated_token_address(funding, mint);
+    let destination = get_associated_token_address(recipient, mint);
+
+    let mut instructions = vec![
+        create_associated_token_account_idempotent(funding, recipient, mint, &spl_token::id()),
+        spl_token::instruction::transfer_checked(
+            &spl_token::id(),
+            &source,
+            mint,
+            &destination,
+            funding,
+            &[],
+            amount,
+            decimals,
+        )?,
+    ];
+
+    if let Some(memo) = memo {
+        instructions.push(spl_memo::build_memo(memo.as_bytes(), &[funding]));
+    }
+
+    Ok(instructions)
+}
+
+/// Instructions for a zero-amount transfer that only carries `memo`
+///
+/// The zero-lamport system transfer is what makes the transaction show up in
+/// the recipient's history.
+pub fn notice_instructions(funding: &Pubkey, recipient: &Pubkey, memo: &str) -> Vec<Instruction> {
+    vec![
+        system_instruction::transfer(funding, recipient, 0),
+        spl_memo::build_memo(memo.as_bytes(), &[funding]),
+    ]
+}
+
+/// Memo explaining why `reason` excluded a wallet
+pub fn exclusion_memo(memo: Option<&str>, reason: &str) -> String {
+    format!(
+        "{} - not eligible: {}",
+        memo.unwrap_or("TestORE testnet airdrop"),
+        reason
+    )
+}
+
+/// What became of a transfer that was signed but never confirmed
+pub enum PendingOutcome {
+    /// Landed successfully; the recipient is paid
+    Landed(Signature),
+
+    /// Landed and failed, so nothing was paid
+    Failed(TransactionError),
+
+    /// Not seen yet but could still land; send this same transaction again
+    Rebroadcast(Transaction),
+
+    /// Never landed and its blockhash has expired; safe to sign a new one
+    Expired,
+}
+
+/// Keep a signed transfer in the ledger before broadcasting it
+pub fn encode_pending(tx: &Transaction) -> Result<PendingTransfer> {
+    Ok(PendingTransfer {
+        signature: tx.signatures[0].to_string(),
+        transaction: base64::engine::general_purpose::STANDARD.encode(bincode::serialize(tx)?),
+    })
+}
+
+/// Find out whether a pending transfer landed
+pub fn check_pending(client: &RpcClient, pending: &PendingTransfer) -> Result<PendingOutcome> {
+    let tx: Transaction = bincode::deserialize(
+        &base64::engine::general_purpose::STANDARD.decode(&pending.transaction)?,
+    )?;
+    let signature = tx.signatures[0];
+
+    // Blockhash first: once it has expired, a missing status is final
+    testore_core::throttle(client);
+    let can_land =
+        client.is_blockhash_valid(&tx.message.recent_blockhash, CommitmentConfig::processed())?;
+    testore_core::throttle(client);
+    let status = client.get_signature_status_with_commitment_and_history(
+        &signature,
+        CommitmentConfig::confirmed(),
+        true,
+    )?;
+
+    Ok(match status {
+        Some(Ok(())) => PendingOutcome::Landed(signature),
+        Some(Err(e)) => PendingOutcome::Failed(e),
+        None if can_land => PendingOutcome::Rebroadcast(tx),
+        None => PendingOutcome::Expired,
+    })
+}
+
+/// Whether a [`transfer_instructions`] send failed for good
+///
+/// Accepts RPC errors and the errors of pending transfers that landed.
+pub fn is_permanent_failure(error: &anyhow::Error) -> bool {
+    let Some(TransactionError::InstructionError(index, error)) = error
+        .downcast_ref::<ClientError>()
+        .and_then(|e| e.get_transaction_error())
+        .or_else(|| error.downcast_ref::<TransactionError>().cloned())
+    else {
+        return false;
+    };
+
+    match (index, error) {
+        // create_associated_token_account_idempotent: the address holds an
+        // account the recipient doesn't own. Other custom errors come from
+        // the CPIs it makes, e.g. the funder running short of rent.
+        (0, InstructionError::IllegalOwner) => true,
+        (0, InstructionError::Custom(code)) => {
+            code == AssociatedTokenAccountError::InvalidOwner as u32
+        }
+        // transfer_checked: the recipient's account is frozen
+        (1, InstructionError::Custom(code)) => code == TokenError::AccountFrozen as u32,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        signature::{Keypair, Signer},
+        system_instruction::SystemError,
+    };
+
+    #[test]
+    fn test_transfer_instructions() {
+        let funding = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+
+        let ixs = transfer_instructions(&funding, &mint, 9, &recipient, 100, None).unwrap();
+        assert_eq!(ixs.len(), 2);
+        assert_eq!(ixs[0].program_id, spl_associated_token_account::id());
+        assert_eq!(ixs[1].program_id, spl_token::id());
+
+        let transfer = spl_token::instruction::TokenInstruction::unpack(&ixs[1].data).unwrap();
+        assert_eq!(
+            transfer,
+            spl_token::instruction::TokenInstruction::TransferChecked {
+                amount: 100_000_000_000,
+                decimals: 9,
+            }
+        );
+
+        let ixs =
+            transfer_instructions(&funding, &mint, 9, &recipient, 100, Some("TestORE airdrop"))
+                .unwrap();
+        assert_eq!(ixs.len(), 3);
+        assert_eq!(ixs[2].program_id, spl_memo::id());
+        assert_eq!(ixs[2].data, b"TestORE airdrop");
+
+        assert!(transfer_instructions(&funding, &mint, 19, &recipient, u64::MAX, None).is_err());
+    }
+
+    #[test]
+    fn test_is_permanent_failure() {
+        let failure = |index, error| {
+            anyhow::Error::from(ClientError::from(TransactionError::InstructionError(
+                index, error,
+            )))
+        };
+
+        assert!(is_permanent_failure(&failure(
+            1,
+            InstructionError::Custom(TokenError::AccountFrozen as u32)
+        )));
+        assert!(is_permanent_failure(&failure(
+            0,
+            InstructionError::IllegalOwner
+        )));
+        assert!(is_permanent_failure(&failure(
+            0,
+            InstructionError::Custom(AssociatedTokenAccountError::InvalidOwner as u32)
+        )));
+
+        // Our own balance running out is not the recipient's fault
+        assert!(!is_permanent_failure(&failure(
+            1,
+            InstructionError::Custom(TokenError::InsufficientFunds as u32)
+        )));
+        assert!(!is_permanent_failure(&failure(
+            0,
+            InstructionError::Custom(SystemError::ResultWithNegativeLamports as u32)
+        )));
+        assert!(!is_permanent_failure(&anyhow!("blockhash expired")));
+
+        // Pending transfers that landed and failed report the bare error
+        assert!(is_permanent_failure(&anyhow::Error::from(
+            TransactionError::InstructionError(0, InstructionError::IllegalOwner)
+        )));
+    }
+
+    #[test]
+    fn test_encode_pending() {
+        let funding = Keypair::new();
+        let ix = system_instruction::transfer(&funding.pubkey(), &Pubkey::new_unique(), 1);
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&funding.pubkey()),
+            &[&funding],
+            solana_sdk::hash::Hash::new_unique(),
+        );
+
+        let pending = encode_pending(&tx).unwrap();
+        assert_eq!(pending.signature, tx.signatures[0].to_string());
+
+        let decoded: Transaction = bincode::deserialize(
+            &base64::engine::general_purpose::STANDARD
+                .decode(&pending.transaction)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(decoded, tx);
+    }
+
+    #[test]
+    fn test_notice_instructions() {
+        let funding = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let memo = exclusion_memo(None, "sybil cluster");
+
+        let ixs = notice_instructions(&funding, &recipient, &memo);
+        assert_eq!(ixs[0].program_id, solana_sdk::system_program::id());
+        assert_eq!(
+            ixs[1].data,
+            b"TestORE testnet airdrop - not eligible: sybil cluster"
+        );
+    }
+}
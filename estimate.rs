@@ -0,0 +1,267 @@
+//! `estimate-earnings`: what a given hashrate would earn on testnet today
+//!
+//! A proof at difficulty d takes 2^d hashes on average and adds one to the
+//! miner's total_hashes if it is within the round's proof cap; submissions
+//! are also limited to one per MIN_SUBMISSION_INTERVAL. The projection uses
+//! the current GlobalRound and leaderboard and the default allocation
+//! parameters, and holds every other miner where they are now, so it is a
+//! ceiling for a crowded testnet rather than a promise.
+
+use crate::{crank, format_number, Config};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use colored::*;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use testore_core::{AllocationParams, GlobalRoundAccount};
+use testore_program::{proof_points, MIN_SUBMISSION_INTERVAL};
+
+const SECS_PER_DAY: f64 = 86_400.0;
+
+#[derive(Args)]
+pub struct EstimateArgs {
+    /// Hashes per second the miner sustains
+    #[arg(long)]
+    hashrate: f64,
+
+    /// Difficulty proofs are submitted at (the round's minimum by default)
+    #[arg(long)]
+    difficulty: Option<u8>,
+
+    /// Days of mining to project rank and airdrop for
+    #[arg(long, default_value_t = 30)]
+    days: u64,
+
+    /// Print JSON instead of a summary
+    #[arg(long)]
+    json: bool,
+}
+
+/// Expected daily output of one miner
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+struct DailyRate {
+    /// Proofs found per day at `difficulty`
+    proofs: f64,
+
+    /// Proofs that count toward total_hashes, after the interval and round cap
+    counted: f64,
+
+    /// Score added per day once scoring is live
+    score: f64,
+
+    /// Airdrop tokens per day, ignoring the minimum and top-N cut
+    tokens: f64,
+}
+
+/// The projection `estimate-earnings` prints
+#[derive(Debug, Serialize)]
+struct Estimate {
+    hashrate: f64,
+    difficulty: u8,
+    min_difficulty: u8,
+    round_duration: i64,
+    max_round_proofs: u32,
+    active_miners: usize,
+    per_day: DailyRate,
+    days: u64,
+    total_hashes: u64,
+    rank: usize,
+    total_miners: usize,
+    tokens: u64,
+}
+
+pub fn run(config: &Config, args: &EstimateArgs) -> Result<()> {
+    if !args.hashrate.is_finite() || args.hashrate <= 0.0 {
+        return Err(anyhow!("--hashrate must be positive"));
+    }
+
+    let client =
+        RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed());
+    let round = testore_core::fetch_global_round(&client, &config.program_id)?;
+    let difficulty = args.difficulty.unwrap_or(round.min_difficulty);
+    if difficulty < round.min_difficulty {
+        return Err(anyhow!(
+            "--difficulty {} is below the round's minimum of {}",
+            difficulty,
+            round.min_difficulty
+        ));
+    }
+
+    let params = AllocationParams::default();
+    let ranked =
+        testore_core::fetch_ranked_miners_across(&client, &config.program_ids(), usize::MAX)?;
+    let now = crank::cluster_time(&client)?;
+
+    let per_day = daily_rate(args.hashrate, difficulty, &round, &params);
+    let total_hashes = (per_day.counted * args.days as f64) as u64;
+    let rank = rank_with(ranked.iter().map(|m| m.total_hashes), total_hashes);
+    let estimate = Estimate {
+        hashrate: args.hashrate,
+        difficulty,
+        min_difficulty: round.min_difficulty,
+        round_duration: round.round_duration,
+        max_round_proofs: round.max_round_proofs,
+        active_miners: ranked
+            .iter()
+            .filter(|m| now - m.last_hash_at < SECS_PER_DAY as i64)
+            .count(),
+        per_day,
+        days: args.days,
+        total_hashes,
+        rank,
+        total_miners: ranked.len() + 1,
+        tokens: if rank <= params.top_miners {
+            testore_core::allocation_for(total_hashes, &params)
+        } else {
+            0
+        },
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&estimate)?);
+        return Ok(());
+    }
+
+    print(&estimate, &params);
+    Ok(())
+}
+
+fn print(estimate: &Estimate, params: &AllocationParams) {
+    println!(
+        "{} {} H/s at difficulty {} (round minimum {}, {} miners active today)\n",
+        "🧮".bright_cyan(),
+        format_number(estimate.hashrate as u64).bright_white(),
+        estimate.difficulty,
+        estimate.min_difficulty,
+        format_number(estimate.active_miners as u64)
+    );
+
+    let per_day = &estimate.per_day;
+    println!("{}", "═══ Per Day ═══".bright_yellow().bold());
+    println!("   Proofs found:    {:.1}", per_day.proofs);
+    println!("   Proofs counted:  {:.1}", per_day.counted);
+    println!("   Score:           {:.1}", per_day.score);
+    println!(
+        "   TESTORE:         {}",
+        format!("{:.2}", per_day.tokens).bright_cyan()
+    );
+    if per_day.counted < per_day.proofs {
+        println!(
+            "   {} Limited by the submission interval or the round cap; a higher --difficulty scores more",
+            "⚠️".bright_yellow()
+        );
+    }
+    println!();
+
+    println!(
+        "{}",
+        format!("═══ After {} Days ═══", estimate.days)
+            .bright_yellow()
+            .bold()
+    );
+    println!(
+        "   Total Hashes:    {}",
+        format_number(estimate.total_hashes).bright_cyan()
+    );
+    println!(
+        "   Rank:            #{} of {}",
+        estimate.rank, estimate.total_miners
+    );
+    println!(
+        "   Airdrop:         {} TESTORE",
+        format_number(estimate.tokens).bright_green().bold()
+    );
+    if estimate.total_hashes < params.minimum_hashes {
+        println!(
+            "   {} Below the {} hash minimum",
+            "⚠️".bright_yellow(),
+            format_number(params.minimum_hashes)
+        );
+    } else if estimate.rank > params.top_miners {
+        println!(
+            "   {} Outside the top {} miners",
+            "⚠️".bright_yellow(),
+            params.top_miners
+        );
+    }
+    println!("\n   Other miners are held at today's totals; they will keep mining too.");
+}
+
+/// Expected proofs, score and tokens per day for `hashrate` at `difficulty`
+fn daily_rate(
+    hashrate: f64,
+    difficulty: u8,
+    round: &GlobalRoundAccount,
+    params: &AllocationParams,
+) -> DailyRate {
+    let proofs = hashrate / 2f64.powi(difficulty as i32) * SECS_PER_DAY;
+
+    // At most one accepted submission per interval, and a capped number
+    // counted per round
+    let mut counted = proofs.min(SECS_PER_DAY / MIN_SUBMISSION_INTERVAL as f64);
+    if round.max_round_proofs > 0 && round.round_duration > 0 {
+        let rounds = SECS_PER_DAY / round.round_duration as f64;
+        counted = counted.min(round.max_round_proofs as f64 * rounds);
+    }
+
+    DailyRate {
+        proofs,
+        counted,
+        score: counted * proof_points(difficulty, round.min_difficulty) as f64,
+        tokens: counted / 1_000_000.0 * params.tokens_per_million_hashes as f64,
+    }
+}
+
+/// 1-based rank a new miner with `total_hashes` would take among `others`
+fn rank_with(others: impl IntoIterator<Item = u64>, total_hashes: u64) -> usize {
+    others.into_iter().filter(|t| *t > total_hashes).count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn round(max_round_proofs: u32) -> GlobalRoundAccount {
+        GlobalRoundAccount {
+            current_challenge: [0; 32],
+            round_number: 1,
+            started_at: 0,
+            min_difficulty: 8,
+            total_hashes_submitted: 0,
+            total_rounds_completed: 0,
+            admin: Pubkey::new_unique(),
+            tier_step: 0,
+            round_duration: 3_600,
+            max_round_proofs,
+        }
+    }
+
+    #[test]
+    fn test_daily_rate() {
+        let params = AllocationParams::default();
+
+        // 256 H/s at difficulty 10 finds a proof every 4 seconds
+        let rate = daily_rate(256.0, 10, &round(0), &params);
+        assert_eq!(rate.proofs, 21_600.0);
+        assert_eq!(rate.counted, 21_600.0);
+        assert_eq!(rate.score, 21_600.0 * 4.0);
+        assert!((rate.tokens - 2.16).abs() < 1e-9);
+
+        // Fast miners hit the submission interval, then the round cap
+        let rate = daily_rate(1e6, 8, &round(0), &params);
+        assert_eq!(rate.counted, SECS_PER_DAY / MIN_SUBMISSION_INTERVAL as f64);
+        let rate = daily_rate(1e6, 8, &round(100), &params);
+        assert_eq!(rate.counted, 2_400.0);
+        assert!(rate.proofs > rate.counted);
+    }
+
+    #[test]
+    fn test_rank_with() {
+        let others = [500, 300, 100];
+        assert_eq!(rank_with(others, 1_000), 1);
+        assert_eq!(rank_with(others, 300), 2);
+        assert_eq!(rank_with(others, 0), 4);
+    }
+}
@@ -0,0 +1,310 @@
+//! Publishable leaderboard exports
+//!
+//! `leaderboard refresh` fetches every miner once and writes the ranked
+//! leaderboard to the cache file; `leaderboard export` renders the cache as
+//! JSON, RSS or Markdown for websites and bots without touching RPC. Every
+//! export carries the time and slot the miners were read at, and a cache
+//! older than `--max-age-secs` is refused unless `--allow-stale` is passed.
+//! Miners that registered a display name with `set_metadata` are shown by
+//! name.
+//!
+//! `leaderboard regions` groups miners by the region they self-reported with
+//! `report_region`, for a rough picture of where testnet load comes from.
+
+use crate::{Config, LEADERBOARD_CACHE_PATH};
+use anyhow::{anyhow, Result};
+use clap::{Args, ValueEnum};
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use testore_core::{LeaderboardCache, LeaderboardRow};
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Number of miners to include
+    #[arg(long, default_value_t = 100)]
+    top: usize,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+    format: ExportFormat,
+
+    /// Write to this file instead of stdout
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Refuse a cache fetched longer ago than this, in seconds
+    #[arg(long, default_value_t = 3_600)]
+    max_age_secs: i64,
+
+    /// Export the cache however old it is
+    #[arg(long)]
+    allow_stale: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    Json,
+    Rss,
+    Markdown,
+}
+
+/// Fetch all miners from testnet and rewrite the leaderboard cache
+pub fn refresh_cache(config: &Config) -> Result<()> {
+    let client =
+        RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed());
+
+    testore_core::throttle(&client);
+    let slot = client.get_slot()?;
+    let miners =
+        testore_core::fetch_ranked_miners_across(&client, &config.program_ids(), usize::MAX)?;
+    let mut metadata = HashMap::new();
+    for program_id in config.program_ids() {
+        metadata.extend(testore_core::fetch_miner_metadata(&client, &program_id)?);
+    }
+    LeaderboardCache::new(&miners)
+        .with_slot(slot)
+        .with_metadata(&metadata)
+        .save(LEADERBOARD_CACHE_PATH)?;
+
+    println!(
+        "{} Cached {} miners to {}",
+        "💾".bright_cyan(),
+        miners.len().to_string().bright_cyan(),
+        LEADERBOARD_CACHE_PATH.bright_yellow()
+    );
+    Ok(())
+}
+
+/// Print miners, hashes and difficulty per self-reported region
+pub fn regions(config: &Config, json: bool) -> Result<()> {
+    let client =
+        RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed());
+
+    let miners =
+        testore_core::fetch_ranked_miners_across(&client, &config.program_ids(), usize::MAX)?;
+    let mut reports = HashMap::new();
+    for program_id in config.program_ids() {
+        reports.extend(testore_core::fetch_miner_regions(&client, &program_id)?);
+    }
+    let stats = testore_core::region_stats(&miners, &reports);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    println!(
+        "{:<12} {:>8} {:>20} {:>16}",
+        "Region", "Miners", "Hashes", "Mean best diff"
+    );
+    for (region, entry) in &stats {
+        println!(
+            "{:<12} {:>8} {:>20} {:>16.1}",
+            region,
+            entry.miners,
+            crate::format_number(entry.total_hashes),
+            entry.mean_best_difficulty
+        );
+    }
+    Ok(())
+}
+
+/// Render the cached leaderboard
+pub fn export(args: &ExportArgs) -> Result<()> {
+    let cache = LeaderboardCache::load(LEADERBOARD_CACHE_PATH).map_err(|e| {
+        anyhow!(
+            "Could not read {} ({}); run `leaderboard refresh` first",
+            LEADERBOARD_CACHE_PATH,
+            e
+        )
+    })?;
+
+    let age_secs = cache.age_secs(chrono::Utc::now())?;
+    if age_secs > args.max_age_secs && !args.allow_stale {
+        return Err(anyhow!(
+            "{} is {}s old, past --max-age-secs {}; run `leaderboard refresh` or pass --allow-stale",
+            LEADERBOARD_CACHE_PATH,
+            age_secs,
+            args.max_age_secs
+        ));
+    }
+
+    let rendered = render(&cache, args.top, args.format)?;
+
+    match &args.output {
+        Some(path) => fs::write(path, rendered)?,
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+fn render(cache: &LeaderboardCache, top: usize, format: ExportFormat) -> Result<String> {
+    let entries = &cache.entries[..top.min(cache.entries.len())];
+
+    Ok(match format {
+        ExportFormat::Json => {
+            let export = LeaderboardCache {
+                fetched_at: cache.fetched_at.clone(),
+                slot: cache.slot,
+                entries: entries.to_vec(),
+            };
+            serde_json::to_string_pretty(&export)? + "\n"
+        }
+        ExportFormat::Markdown => render_markdown(cache, entries),
+        ExportFormat::Rss => render_rss(cache, entries),
+    })
+}
+
+fn render_markdown(cache: &LeaderboardCache, entries: &[LeaderboardRow]) -> String {
+    let mut out = format!(
+        "## TestORE Leaderboard\n\n_Updated {}{}_\n\n",
+        cache.fetched_at,
+        cache
+            .slot
+            .map(|slot| format!(" (slot {})", slot))
+            .unwrap_or_default()
+    );
+    out.push_str("| Rank | Miner | Hashes | Rounds | Best Difficulty | Last Active |\n");
+    out.push_str("|-----:|-------|-------:|-------:|----------------:|-------------|\n");
+
+    for entry in entries {
+        let miner = match &entry.name {
+            Some(name) => format!("{} (`{}`)", name.replace('_', "\\_"), entry.authority),
+            None => format!("`{}`", entry.authority),
+        };
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            entry.rank,
+            miner,
+            crate::format_number(entry.total_hashes),
+            crate::format_number(entry.rounds_completed as u64),
+            entry.best_difficulty,
+            last_active(entry.last_hash_at)
+        ));
+    }
+
+    out
+}
+
+fn render_rss(cache: &LeaderboardCache, entries: &[LeaderboardRow]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">\n<channel>\n",
+    );
+    out.push_str("  <title>TestORE Leaderboard</title>\n");
+    out.push_str("  <description>Top TestORE miners on Solana testnet</description>\n");
+    out.push_str(&format!(
+        "  <lastBuildDate>{}</lastBuildDate>\n",
+        escape_xml(&cache.fetched_at)
+    ));
+
+    for entry in entries {
+        out.push_str("  <item>\n");
+        out.push_str(&format!(
+            "    <title>#{} {}</title>\n",
+            entry.rank,
+            escape_xml(entry.name.as_ref().unwrap_or(&entry.authority))
+        ));
+        if let Some(url) = &entry.url {
+            out.push_str(&format!("    <link>{}</link>\n", escape_xml(url)));
+        }
+        out.push_str(&format!(
+            "    <description>{} hashes, {} rounds, best difficulty {}, last active {}</description>\n",
+            crate::format_number(entry.total_hashes),
+            crate::format_number(entry.rounds_completed as u64),
+            entry.best_difficulty,
+            last_active(entry.last_hash_at)
+        ));
+        out.push_str(&format!(
+            "    <guid isPermaLink=\"false\">{}-{}</guid>\n",
+            escape_xml(&entry.authority),
+            entry.total_hashes
+        ));
+        out.push_str("  </item>\n");
+    }
+
+    out.push_str("</channel>\n</rss>\n");
+    out
+}
+
+fn last_active(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> LeaderboardCache {
+        let row = |rank, authority: &str, total_hashes| LeaderboardRow {
+            rank,
+            authority: authority.to_string(),
+            total_hashes,
+            rounds_completed: 12,
+            best_difficulty: 9,
+            last_hash_at: 1_700_000_000,
+            name: None,
+            url: None,
+        };
+
+        LeaderboardCache {
+            fetched_at: "2024-01-01T00:00:00+00:00".to_string(),
+            slot: Some(250_000_000),
+            entries: vec![row(1, "Alice", 2_500_000), row(2, "B<o>b", 1_000)],
+        }
+    }
+
+    #[test]
+    fn test_render_respects_top() {
+        let json = render(&cache(), 1, ExportFormat::Json).unwrap();
+        let parsed: LeaderboardCache = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.entries[0].authority, "Alice");
+
+        // Asking for more than cached is fine
+        let json = render(&cache(), 100, ExportFormat::Json).unwrap();
+        let parsed: LeaderboardCache = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let markdown = render(&cache(), 100, ExportFormat::Markdown).unwrap();
+        assert!(markdown.contains("| 1 | `Alice` | 2,500,000 | 12 | 9 | 2023-11-14 22:13 UTC |\n"));
+    }
+
+    #[test]
+    fn test_render_registered_names() {
+        let mut cache = cache();
+        cache.entries[0].name = Some("Rig_Farm".to_string());
+        cache.entries[0].url = Some("https://example.com/?a=1&b=2".to_string());
+
+        let markdown = render(&cache, 100, ExportFormat::Markdown).unwrap();
+        assert!(markdown.contains("| 1 | Rig\\_Farm (`Alice`) |"));
+
+        let rss = render(&cache, 100, ExportFormat::Rss).unwrap();
+        assert!(rss.contains("<title>#1 Rig_Farm</title>"));
+        assert!(rss.contains("<link>https://example.com/?a=1&amp;b=2</link>"));
+        assert!(rss.contains("<title>#2 B&lt;o&gt;b</title>"));
+    }
+
+    #[test]
+    fn test_render_rss_escapes() {
+        let rss = render(&cache(), 100, ExportFormat::Rss).unwrap();
+        assert!(rss.contains("<title>#2 B&lt;o&gt;b</title>"));
+        assert!(rss.ends_with("</rss>\n"));
+    }
+}
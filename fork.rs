@@ -0,0 +1,357 @@
+//! `--fork-simulate`: rehearse a distribution on a local mainnet fork
+//!
+//! Starts `solana-test-validator` with the mint, the funding wallet and its
+//! token account, and the largest recipients' wallets and token accounts
+//! cloned from MAINNET_RPC, then sends the whole chunk there exactly as
+//! `--execute` would. Outcomes go to FORK_REPORT_PATH; the real ledger is
+//! left untouched.
+//!
+//! With `--chaos`, the chunk is re-run under injected faults (see chaos.rs)
+//! until it settles, and the rehearsal fails if any recipient's balance grew
+//! by more than their allocation.
+
+use crate::chaos::Chaos;
+use crate::{distribute, format_number, send_chunk, AirdropArgs, Config};
+use anyhow::{anyhow, Context, Result};
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, program_pack::Pack, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use testore_core::{Ledger, TransferStatus};
+
+const FORK_REPORT_PATH: &str = "fork_report.json";
+const FORK_RPC_PORT: u16 = 8899;
+const FORK_STARTUP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Chunk re-runs under --chaos; the last one runs without faults
+const CHAOS_MAX_PASSES: u32 = 10;
+
+/// Run `chunk` against a fresh fork and write a report of the outcomes
+pub async fn simulate(
+    config: &Config,
+    args: &AirdropArgs,
+    chunk: &[(Pubkey, u64)],
+    chunk_number: u32,
+    excluded: &[(&Pubkey, &String)],
+    ledger: &Ledger,
+) -> Result<()> {
+    let mint = config
+        .mint
+        .ok_or_else(|| anyhow!("TESTORE_MINT must be set to simulate airdrops"))?;
+    let funding = config.signer.pubkey();
+
+    let mut clones = vec![mint, funding, get_associated_token_address(&funding, &mint)];
+    for (recipient, _) in chunk.iter().take(args.fork_sample) {
+        clones.push(*recipient);
+        clones.push(get_associated_token_address(recipient, &mint));
+    }
+
+    println!(
+        "{} Forking mainnet into a local validator ({} accounts)...",
+        "🧪".bright_cyan(),
+        clones.len()
+    );
+    let _validator = ForkValidator::start(&config.mainnet_rpc, &clones)?;
+    let client = RpcClient::new_with_commitment(fork_url(), CommitmentConfig::confirmed());
+
+    // Work on a copy so simulated sends never mark anyone as paid
+    let mut simulated = ledger.clone();
+    testore_core::throttle(&client);
+    let lamports_before = client.get_balance(&funding)?;
+    let (failures, chaos) = match args.chaos {
+        Some(rate) => {
+            let (failures, report) = rehearse_chaos(
+                &client,
+                config,
+                args,
+                chunk,
+                chunk_number,
+                excluded,
+                &mut simulated,
+                rate,
+            )
+            .await?;
+            (failures, Some(report))
+        }
+        None => {
+            let failures = send_chunk(
+                &client,
+                config,
+                args,
+                chunk,
+                chunk_number,
+                excluded,
+                &mut simulated,
+                None,
+                None,
+            )
+            .await?;
+            (failures, None)
+        }
+    };
+    testore_core::throttle(&client);
+    let fees = lamports_before.saturating_sub(client.get_balance(&funding)?);
+
+    let failed: Vec<_> = chunk
+        .iter()
+        .filter_map(|(recipient, tokens)| {
+            let entry = simulated.entries.get(&recipient.to_string())?;
+            (entry.status != TransferStatus::Sent).then(|| {
+                serde_json::json!({
+                    "recipient": recipient.to_string(),
+                    "tokens": tokens,
+                    "error": entry.detail,
+                })
+            })
+        })
+        .collect();
+
+    let report = serde_json::json!({
+        "chunk": chunk_number,
+        "recipients": chunk.len(),
+        "tokens": chunk.iter().map(|(_, tokens)| tokens).sum::<u64>(),
+        "sent": chunk.len() - failed.len(),
+        "failed": failed,
+        "failed_transactions": failures,
+        "fee_lamports": fees,
+        "cloned_accounts": clones.len(),
+        "chaos": chaos,
+        "simulated_at": chrono::Utc::now().to_rfc3339(),
+    });
+    std::fs::write(FORK_REPORT_PATH, serde_json::to_string_pretty(&report)?)?;
+
+    println!(
+        "\n{} Fork rehearsal: {} sent, {} failed, {} lamports in fees",
+        "🧪".bright_cyan(),
+        format_number((chunk.len() - failed.len()) as u64).bright_green(),
+        failed.len().to_string().bright_red(),
+        format_number(fees)
+    );
+    println!("   Report saved to: {}\n", FORK_REPORT_PATH.bright_yellow());
+
+    let double_paid = report["chaos"]["double_paid"]
+        .as_array()
+        .map_or(0, |paid| paid.len());
+    if double_paid > 0 {
+        return Err(anyhow!(
+            "{} recipients were paid more than once under --chaos",
+            double_paid
+        ));
+    }
+
+    Ok(())
+}
+
+/// Re-run `chunk` under injected faults until it settles, then compare each
+/// recipient's balance change with their allocation
+#[allow(clippy::too_many_arguments)]
+async fn rehearse_chaos(
+    client: &RpcClient,
+    config: &Config,
+    args: &AirdropArgs,
+    chunk: &[(Pubkey, u64)],
+    chunk_number: u32,
+    excluded: &[(&Pubkey, &String)],
+    ledger: &mut Ledger,
+    rate: f64,
+) -> Result<(usize, serde_json::Value)> {
+    let mint = config
+        .mint
+        .ok_or_else(|| anyhow!("TESTORE_MINT must be set to simulate airdrops"))?;
+    let seed = match args.chaos_seed {
+        Some(seed) => seed,
+        None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos() as u64,
+    };
+    let mut chaos = Chaos::new(rate, seed);
+
+    println!(
+        "{} Chaos rehearsal: faults in {:.0}% of transfers (--chaos-seed {})\n",
+        "🌪️".bright_magenta(),
+        rate * 100.0,
+        seed
+    );
+
+    let unsettled = |ledger: &Ledger| -> Vec<(Pubkey, u64)> {
+        chunk
+            .iter()
+            .filter(|(recipient, _)| {
+                !matches!(
+                    ledger.entries.get(&recipient.to_string()).map(|e| e.status),
+                    Some(TransferStatus::Sent | TransferStatus::Quarantined)
+                )
+            })
+            .copied()
+            .collect()
+    };
+    let before = token_balances(client, &mint, chunk)?;
+
+    let mut failures = 0;
+    let mut passes = 0;
+    let mut aborted = 0;
+    while passes < CHAOS_MAX_PASSES {
+        let remaining = unsettled(ledger);
+        if remaining.is_empty() {
+            break;
+        }
+        passes += 1;
+
+        // The last pass runs clean so even a high rate settles
+        let faults = (passes < CHAOS_MAX_PASSES).then_some(&mut chaos);
+        match send_chunk(
+            client,
+            config,
+            args,
+            &remaining,
+            chunk_number,
+            excluded,
+            ledger,
+            None,
+            faults,
+        )
+        .await
+        {
+            Ok(failed) => failures = failed,
+            Err(e) => {
+                aborted += 1;
+                println!(
+                    "   {} Pass {} aborted: {:#}",
+                    "🌪️".bright_magenta(),
+                    passes,
+                    e
+                );
+            }
+        }
+    }
+
+    let after = token_balances(client, &mint, chunk)?;
+    let scale = 10u64.pow(distribute::mint_decimals(client, &mint)? as u32);
+    let double_paid: Vec<_> = chunk
+        .iter()
+        .filter(|(recipient, tokens)| {
+            after[recipient].saturating_sub(before[recipient]) > tokens.saturating_mul(scale)
+        })
+        .map(|(recipient, tokens)| {
+            serde_json::json!({
+                "recipient": recipient.to_string(),
+                "tokens": tokens,
+                "received_base_units": after[recipient] - before[recipient],
+            })
+        })
+        .collect();
+
+    let injected: HashMap<String, usize> = chaos
+        .injected
+        .iter()
+        .map(|(fault, count)| (format!("{:?}", fault), *count))
+        .collect();
+
+    Ok((
+        failures,
+        serde_json::json!({
+            "rate": rate,
+            "seed": seed,
+            "passes": passes,
+            "aborted_passes": aborted,
+            "injected": injected,
+            "unsettled": unsettled(ledger).len(),
+            "double_paid": double_paid,
+        }),
+    ))
+}
+
+/// TESTORE base units held by each recipient's token account (0 if none)
+fn token_balances(
+    client: &RpcClient,
+    mint: &Pubkey,
+    chunk: &[(Pubkey, u64)],
+) -> Result<HashMap<Pubkey, u64>> {
+    let mut balances = HashMap::new();
+
+    for batch in chunk.chunks(100) {
+        let addresses: Vec<Pubkey> = batch
+            .iter()
+            .map(|(recipient, _)| get_associated_token_address(recipient, mint))
+            .collect();
+
+        testore_core::throttle(client);
+        let accounts = client.get_multiple_accounts(&addresses)?;
+        for ((recipient, _), account) in batch.iter().zip(accounts) {
+            let amount = account
+                .and_then(|account| spl_token::state::Account::unpack(&account.data).ok())
+                .map_or(0, |account| account.amount);
+            balances.insert(*recipient, amount);
+        }
+    }
+
+    Ok(balances)
+}
+
+/// A `solana-test-validator` child process, killed on drop
+struct ForkValidator {
+    child: Child,
+    ledger_dir: PathBuf,
+}
+
+impl ForkValidator {
+    fn start(source_rpc: &str, clones: &[Pubkey]) -> Result<Self> {
+        let ledger_dir = std::env::temp_dir().join(format!("testore-fork-{}", std::process::id()));
+
+        let mut command = Command::new("solana-test-validator");
+        command
+            .arg("--reset")
+            .arg("--quiet")
+            .arg("--ledger")
+            .arg(&ledger_dir)
+            .args(["--rpc-port", &FORK_RPC_PORT.to_string()])
+            .args(["--url", source_rpc]);
+        // Recipients without a wallet or token account yet are normal
+        for address in clones {
+            command.args(["--maybe-clone", &address.to_string()]);
+        }
+
+        let child = command
+            .stdout(Stdio::null())
+            .spawn()
+            .context("starting solana-test-validator (is the Solana CLI installed?)")?;
+        let mut validator = Self { child, ledger_dir };
+        validator.wait_until_healthy()?;
+
+        Ok(validator)
+    }
+
+    fn wait_until_healthy(&mut self) -> Result<()> {
+        let client = RpcClient::new(fork_url());
+        let started = Instant::now();
+
+        while client.get_health().is_err() {
+            if let Some(status) = self.child.try_wait()? {
+                return Err(anyhow!("solana-test-validator exited early: {}", status));
+            }
+            if started.elapsed() > FORK_STARTUP_TIMEOUT {
+                return Err(anyhow!(
+                    "solana-test-validator did not become healthy within {}s",
+                    FORK_STARTUP_TIMEOUT.as_secs()
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(500));
+        }
+
+        Ok(())
+    }
+}
+
+fn fork_url() -> String {
+    format!("http://127.0.0.1:{}", FORK_RPC_PORT)
+}
+
+impl Drop for ForkValidator {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.ledger_dir);
+    }
+}
@@ -0,0 +1,162 @@
+//! `gc`: close Miner accounts idle past the GcPolicy into its sweep wallet
+//!
+//! `policy` sets the idle window and sweep wallet (admin; 0 days turns
+//! collection off). `run` sends the permissionless `gc_miner` for every
+//! miner idle for the window. Each leaves a MinerArchive that rankings and
+//! airdrops keep counting, and that a re-registration restores; the signer
+//! fronts the archive's rent and gets it back from the miner's.
+
+use crate::signer;
+use crate::{format_number, Config};
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use testore_core::{GcPolicyAccount, MinerAccount};
+use testore_program::client::{ConfigureGcPolicy, GcMiner, ToInstruction};
+
+#[derive(Subcommand)]
+pub enum GcCommand {
+    /// Set the idle window and sweep wallet (admin)
+    Policy {
+        /// Days without a proof before a miner may be closed (0 = off)
+        #[arg(long)]
+        inactive_days: u16,
+
+        /// Wallet that receives the reclaimed rent
+        #[arg(long)]
+        sweep_wallet: Pubkey,
+    },
+
+    /// Close every collectable miner
+    Run {
+        /// gc_miner instructions per transaction (each adds about eight
+        /// accounts)
+        #[arg(long, default_value_t = 3)]
+        batch: usize,
+    },
+
+    /// Show the policy and how many miners are collectable
+    Status,
+}
+
+pub fn run(config: &Config, command: &GcCommand) -> Result<()> {
+    let client =
+        RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed());
+
+    match command {
+        GcCommand::Policy {
+            inactive_days,
+            sweep_wallet,
+        } => policy(&client, config, *inactive_days, sweep_wallet),
+        GcCommand::Run { batch } => collect(&client, config, *batch),
+        GcCommand::Status => status(&client, config),
+    }
+}
+
+fn policy(
+    client: &RpcClient,
+    config: &Config,
+    inactive_days: u16,
+    sweep_wallet: &Pubkey,
+) -> Result<()> {
+    crate::migrate::with_admin(client, config, |client, admin| {
+        let ix = ConfigureGcPolicy::new(admin.pubkey(), inactive_days, *sweep_wallet)
+            .instruction_for(&config.program_id);
+        let signature = signer::send(client, admin, &[ix])?;
+        if inactive_days == 0 {
+            println!(
+                "{} Miner collection off: {}",
+                "⏸️".bright_yellow(),
+                signature
+            );
+        } else {
+            println!(
+                "{} Miners idle {}+ days are collectable into {}: {}",
+                "♻️".bright_green(),
+                inactive_days,
+                sweep_wallet,
+                signature
+            );
+        }
+        Ok(())
+    })
+}
+
+fn collect(client: &RpcClient, config: &Config, batch: usize) -> Result<()> {
+    let plan = plan(client, config)?;
+    if plan.collectable.is_empty() {
+        println!("{} No miners to collect", "✅".bright_green());
+        return Ok(());
+    }
+
+    println!(
+        "{} Closing {} miners into {}, {} per transaction",
+        "♻️".bright_cyan(),
+        plan.collectable.len(),
+        plan.policy.sweep_wallet,
+        batch.max(1)
+    );
+    for chunk in plan.collectable.chunks(batch.max(1)) {
+        let instructions: Vec<_> = chunk
+            .iter()
+            .map(|miner| {
+                GcMiner::new(
+                    config.signer.pubkey(),
+                    miner.authority,
+                    plan.policy.sweep_wallet,
+                    miner.score_bucket,
+                )
+                .instruction_for(&config.program_id)
+            })
+            .collect();
+        let signature = signer::send(client, &*config.signer, &instructions)?;
+        println!("   {} miners: {}", chunk.len(), signature);
+    }
+
+    Ok(())
+}
+
+fn status(client: &RpcClient, config: &Config) -> Result<()> {
+    let plan = plan(client, config)?;
+
+    println!("Inactive days: {}", plan.policy.inactive_days);
+    println!("Sweep wallet:  {}", plan.policy.sweep_wallet);
+    println!(
+        "Collectable:   {}",
+        format_number(plan.collectable.len() as u64)
+    );
+    println!("Archived:      {}", format_number(plan.archived as u64));
+
+    Ok(())
+}
+
+/// What `gc_miner` would accept now
+struct Plan {
+    policy: GcPolicyAccount,
+    collectable: Vec<MinerAccount>,
+
+    /// Miners closed earlier, kept as MinerArchives
+    archived: usize,
+}
+
+fn plan(client: &RpcClient, config: &Config) -> Result<Plan> {
+    let policy = testore_core::fetch_gc_policy(client, &config.program_id)?
+        .ok_or_else(|| anyhow!("No GC policy; run `gc policy` first"))?;
+    if policy.inactive_days == 0 {
+        return Err(anyhow!("Miner collection is off (inactive_days = 0)"));
+    }
+
+    let miners = testore_core::fetch_miners(client, &config.program_id)?;
+    let now = crate::crank::cluster_time(client)?;
+
+    Ok(Plan {
+        policy,
+        collectable: testore_core::collectable_miners(&miners, &policy, now),
+        archived: miners
+            .iter()
+            .filter(|miner| miner.archived_at.is_some())
+            .count(),
+    })
+}
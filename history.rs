@@ -0,0 +1,333 @@
+//! Transaction history through archival RPC providers
+//!
+//! Public testnet RPC keeps only a few days of transaction history, so
+//! `debug` lookups of older transactions and backfills of a miner's
+//! submissions fail there. [`HistoryProvider`] abstracts where history comes
+//! from; [`RpcHistory`] reads it from any endpoint that serves the archival
+//! JSON-RPC methods (`getSignaturesForAddress`, `getTransaction`), which is
+//! what the paid providers expose. HISTORY_RPC lists such endpoints, tried in
+//! order before TESTNET_RPC.
+//!
+//! Account state at a past slot is not part of JSON-RPC history; snapshots
+//! record the slot their miners were read at instead.
+
+use crate::Config;
+use anyhow::{anyhow, Result};
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_sdk::{
+    clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::str::FromStr;
+
+/// Most signatures `getSignaturesForAddress` returns per call
+const SIGNATURE_PAGE: usize = 1_000;
+
+/// A source of confirmed transaction history
+pub trait HistoryProvider {
+    /// Endpoint or chain of endpoints, for messages
+    fn name(&self) -> String;
+
+    /// Oldest slot the provider still has transactions for
+    fn first_available_slot(&self) -> Result<Slot>;
+
+    /// Up to `limit` signatures involving `address`, newest first, starting
+    /// before `before` when given
+    fn signatures(
+        &self,
+        address: &Pubkey,
+        before: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>>;
+
+    /// A confirmed transaction, base64 encoded
+    fn transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta>;
+}
+
+/// History from a JSON-RPC endpoint
+pub struct RpcHistory {
+    client: RpcClient,
+}
+
+impl RpcHistory {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: RpcClient::new_with_commitment(url.into(), CommitmentConfig::confirmed()),
+        }
+    }
+}
+
+impl HistoryProvider for RpcHistory {
+    fn name(&self) -> String {
+        self.client.url()
+    }
+
+    fn first_available_slot(&self) -> Result<Slot> {
+        testore_core::throttle(&self.client);
+        Ok(self.client.get_first_available_block()?)
+    }
+
+    fn signatures(
+        &self,
+        address: &Pubkey,
+        before: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        testore_core::throttle(&self.client);
+        Ok(self.client.get_signatures_for_address_with_config(
+            address,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: None,
+                limit: Some(limit.min(SIGNATURE_PAGE)),
+                commitment: Some(CommitmentConfig::confirmed()),
+            },
+        )?)
+    }
+
+    fn transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        testore_core::throttle(&self.client);
+        Ok(self.client.get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?)
+    }
+}
+
+/// Providers tried in order until one answers
+///
+/// A pruned node reports a transaction it no longer has as an error, so
+/// falling through to the next provider is enough to reach archived history.
+pub struct HistoryChain {
+    providers: Vec<Box<dyn HistoryProvider>>,
+}
+
+impl HistoryChain {
+    pub fn new(providers: Vec<Box<dyn HistoryProvider>>) -> Self {
+        Self { providers }
+    }
+
+    /// HISTORY_RPC endpoints, then TESTNET_RPC
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config
+                .history_rpcs
+                .iter()
+                .chain(std::iter::once(&config.testnet_rpc))
+                .map(|url| Box::new(RpcHistory::new(url.clone())) as Box<dyn HistoryProvider>)
+                .collect(),
+        )
+    }
+
+    fn first_ok<T>(&self, f: impl Fn(&dyn HistoryProvider) -> Result<T>) -> Result<T> {
+        let mut errors = Vec::new();
+        for provider in &self.providers {
+            match f(provider.as_ref()) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    log::debug!("History provider {} failed: {}", provider.name(), e);
+                    errors.push(format!("{}: {}", provider.name(), e));
+                }
+            }
+        }
+
+        Err(anyhow!(
+            "No history provider answered ({})",
+            errors.join("; ")
+        ))
+    }
+}
+
+impl HistoryProvider for HistoryChain {
+    fn name(&self) -> String {
+        self.providers
+            .iter()
+            .map(|provider| provider.name())
+            .collect::<Vec<_>>()
+            .join(" → ")
+    }
+
+    /// The oldest slot any provider has
+    fn first_available_slot(&self) -> Result<Slot> {
+        self.providers
+            .iter()
+            .filter_map(|provider| provider.first_available_slot().ok())
+            .min()
+            .ok_or_else(|| anyhow!("No history provider answered"))
+    }
+
+    fn signatures(
+        &self,
+        address: &Pubkey,
+        before: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.first_ok(|provider| provider.signatures(address, before, limit))
+    }
+
+    fn transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        self.first_ok(|provider| provider.transaction(signature))
+    }
+}
+
+/// Up to `limit` signatures involving `address`, newest first, paging back
+/// until `until_slot` (inclusive) or the start of the provider's history
+pub fn backfill(
+    provider: &dyn HistoryProvider,
+    address: &Pubkey,
+    until_slot: Option<Slot>,
+    limit: usize,
+) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+    let mut signatures = Vec::new();
+    let mut before = None;
+
+    while signatures.len() < limit {
+        let wanted = (limit - signatures.len()).min(SIGNATURE_PAGE);
+        let page = provider.signatures(address, before, wanted)?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature)?);
+
+        let full = page.len() == wanted;
+        let reached = until_slot.is_some_and(|until| last.slot < until);
+        signatures.extend(
+            page.into_iter()
+                .filter(|status| until_slot.map_or(true, |until| status.slot >= until)),
+        );
+        if reached || !full {
+            break;
+        }
+    }
+
+    Ok(signatures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serves signatures for slots `oldest..=newest`, one per slot
+    struct Fake {
+        oldest: Slot,
+        newest: Slot,
+        fails: bool,
+    }
+
+    impl Fake {
+        fn signature(slot: Slot) -> Signature {
+            let mut bytes = [0u8; 64];
+            bytes[..8].copy_from_slice(&slot.to_le_bytes());
+            Signature::from(bytes)
+        }
+    }
+
+    impl HistoryProvider for Fake {
+        fn name(&self) -> String {
+            format!("fake {}..={}", self.oldest, self.newest)
+        }
+
+        fn first_available_slot(&self) -> Result<Slot> {
+            Ok(self.oldest)
+        }
+
+        fn signatures(
+            &self,
+            _address: &Pubkey,
+            before: Option<Signature>,
+            limit: usize,
+        ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+            if self.fails {
+                return Err(anyhow!("history pruned"));
+            }
+
+            let start = before
+                .map(|signature| u64::from_le_bytes(signature.as_ref()[..8].try_into().unwrap()))
+                .unwrap_or(self.newest + 1);
+            Ok((self.oldest..start)
+                .rev()
+                .take(limit)
+                .map(|slot| RpcConfirmedTransactionStatusWithSignature {
+                    signature: Self::signature(slot).to_string(),
+                    slot,
+                    err: None,
+                    memo: None,
+                    block_time: None,
+                    confirmation_status: None,
+                })
+                .collect())
+        }
+
+        fn transaction(
+            &self,
+            _signature: &Signature,
+        ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+            Err(anyhow!("not served by the fake"))
+        }
+    }
+
+    #[test]
+    fn test_chain_falls_back() {
+        let chain = HistoryChain::new(vec![
+            Box::new(Fake {
+                oldest: 0,
+                newest: 10,
+                fails: true,
+            }),
+            Box::new(Fake {
+                oldest: 5,
+                newest: 10,
+                fails: false,
+            }),
+        ]);
+
+        let signatures = chain.signatures(&Pubkey::new_unique(), None, 3).unwrap();
+        assert_eq!(
+            signatures.iter().map(|s| s.slot).collect::<Vec<_>>(),
+            vec![10, 9, 8]
+        );
+        assert_eq!(chain.first_available_slot().unwrap(), 0);
+        assert!(chain.transaction(&Signature::default()).is_err());
+    }
+
+    #[test]
+    fn test_backfill_pages_until_slot() {
+        let provider = Fake {
+            oldest: 0,
+            newest: 2_499,
+            fails: false,
+        };
+        let address = Pubkey::new_unique();
+
+        // Pages past SIGNATURE_PAGE until the limit
+        let signatures = backfill(&provider, &address, None, 2_200).unwrap();
+        assert_eq!(signatures.len(), 2_200);
+        assert_eq!(signatures.last().unwrap().slot, 300);
+
+        // Stops at until_slot, keeping it
+        let signatures = backfill(&provider, &address, Some(1_200), usize::MAX).unwrap();
+        assert_eq!(signatures.len(), 1_300);
+        assert_eq!(signatures.last().unwrap().slot, 1_200);
+
+        // And at the start of the provider's history
+        let signatures = backfill(&provider, &address, None, usize::MAX).unwrap();
+        assert_eq!(signatures.len(), 2_500);
+    }
+}
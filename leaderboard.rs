@@ -1,7 +1,9 @@
 use anyhow::Result;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
 use std::sync::Arc;
+use testore_core::{MinerAccount, MinerMetadataAccount};
 
 #[derive(Debug, Clone)]
 pub struct LeaderboardEntry {
@@ -9,6 +11,25 @@ pub struct LeaderboardEntry {
     pub total_hashes: u64,
     pub rounds_completed: u32,
     pub best_difficulty: u8,
+
+    /// Display name registered with `set_metadata`, if any
+    pub name: Option<String>,
+
+    /// Profile link registered with `set_metadata`, if any
+    pub url: Option<String>,
+}
+
+impl From<MinerAccount> for LeaderboardEntry {
+    fn from(miner: MinerAccount) -> Self {
+        Self {
+            pubkey: miner.authority,
+            total_hashes: miner.total_hashes,
+            rounds_completed: miner.rounds_completed,
+            best_difficulty: miner.best_difficulty,
+            name: None,
+            url: None,
+        }
+    }
 }
 
 /// Fetch and parse the leaderboard from on-chain miner accounts
@@ -17,42 +38,42 @@ pub async fn fetch_leaderboard(
     program_id: &Pubkey,
     limit: usize,
 ) -> Result<Vec<LeaderboardEntry>> {
-    // Fetch all program accounts (miner PDAs)
-    let accounts = rpc_client.get_program_accounts(program_id)?;
-
-    let mut miners: Vec<LeaderboardEntry> = accounts
-        .iter()
-        .filter_map(|(pubkey, account)| {
-            // Parse miner account data
-            // Format: [discriminator: 8] [authority: 32] [total_hashes: 8] [rounds: 4] [last_hash: 8] [streak: 4] [best_diff: 1] [bump: 1]
-            if account.data.len() >= 66 {
-                // Extract authority from account data
-                let mut authority_bytes = [0u8; 32];
-                authority_bytes.copy_from_slice(&account.data[8..40]);
-                let authority = Pubkey::new_from_array(authority_bytes);
-
-                let total_hashes = u64::from_le_bytes(account.data[40..48].try_into().ok()?);
-                let rounds_completed = u32::from_le_bytes(account.data[48..52].try_into().ok()?);
-                let best_difficulty = account.data.get(64).copied().unwrap_or(0);
-
-                Some(LeaderboardEntry {
-                    pubkey: authority,
-                    total_hashes,
-                    rounds_completed,
-                    best_difficulty,
-                })
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    // Sort by total hashes (primary) and rounds completed (secondary)
-    miners.sort_by(|a, b| {
-        b.total_hashes
-            .cmp(&a.total_hashes)
-            .then(b.rounds_completed.cmp(&a.rounds_completed))
-    });
-
-    Ok(miners.into_iter().take(limit).collect())
+    let miners = testore_core::fetch_ranked_miners(rpc_client, program_id, limit)?;
+    let metadata = testore_core::fetch_miner_metadata(rpc_client, program_id)?;
+
+    Ok(miners
+        .into_iter()
+        .map(|miner| with_metadata(miner, &metadata))
+        .collect())
+}
+
+/// Like [`fetch_leaderboard`], merging miners from several program deployments
+pub async fn fetch_leaderboard_across(
+    rpc_client: &Arc<RpcClient>,
+    program_ids: &[Pubkey],
+    limit: usize,
+) -> Result<Vec<LeaderboardEntry>> {
+    let miners = testore_core::fetch_ranked_miners_across(rpc_client, program_ids, limit)?;
+    let mut metadata = HashMap::new();
+    for program_id in program_ids {
+        metadata.extend(testore_core::fetch_miner_metadata(rpc_client, program_id)?);
+    }
+
+    Ok(miners
+        .into_iter()
+        .map(|miner| with_metadata(miner, &metadata))
+        .collect())
+}
+
+/// Attach the miner's registered display name, if any
+fn with_metadata(
+    miner: MinerAccount,
+    metadata: &HashMap<Pubkey, MinerMetadataAccount>,
+) -> LeaderboardEntry {
+    let registered = metadata.get(&miner.authority);
+    LeaderboardEntry {
+        name: registered.map(|m| m.name.clone()),
+        url: registered.and_then(|m| m.url.clone()),
+        ..LeaderboardEntry::from(miner)
+    }
 }
@@ -1,8 +1,12 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 use sha3::{Digest, Keccak256};
 
 declare_id!("TESTORE11111111111111111111111111111111111");
 
+#[cfg(not(target_os = "solana"))]
+pub mod client;
+
 /// TestORE - Solana Testnet Mining Program
 /// 
 /// This program implements proof-of-work mining on Solana testnet,
@@ -16,8 +20,22 @@ pub mod testore_program {
     /// Initialize a new miner account
     /// 
     /// Creates a PDA to track mining statistics for the caller.
-    /// Each wallet can have one miner account.
+    /// Each wallet can have one miner account. While the registration gate
+    /// is enabled, the gate's gatekeeper must co-sign. A wallet whose miner
+    /// `gc_miner` closed gets its archived stats back.
     pub fn initialize_miner(ctx: Context<InitializeMiner>) -> Result<()> {
+        if let Some(gatekeeper) =
+            registration_gatekeeper(ctx.accounts.registration_gate.as_deref())?
+        {
+            require!(
+                ctx.accounts
+                    .gatekeeper
+                    .as_ref()
+                    .is_some_and(|signer| signer.key() == gatekeeper),
+                ErrorCode::RegistrationNotApproved
+            );
+        }
+
         let miner = &mut ctx.accounts.miner;
         miner.authority = ctx.accounts.authority.key();
         miner.total_hashes = 0;
@@ -26,6 +44,37 @@ pub mod testore_program {
         miner.current_streak = 0;
         miner.best_difficulty = 0;
         miner.bump = ctx.bumps.miner;
+        miner.tier = 0;
+        miner.commitment = [0u8; 32];
+        miner.committed_slot = 0;
+        miner.score_bucket = NO_SCORE_BUCKET;
+        miner.round_number = 0;
+        miner.round_proofs = 0;
+        miner.capped_score = 0;
+        miner.score = 0;
+        miner.score_converted = false;
+
+        // A miner gc_miner closed picks up where it left off; the archive
+        // goes, so its stats are never counted twice
+        let archive_info = ctx.accounts.archive.as_ref().map(|a| a.to_account_info());
+        if let Some(archive_info) =
+            archive_info.filter(|a| a.owner == &crate::ID && !a.data_is_empty())
+        {
+            let archive: MinerArchive = load_program_account(&archive_info)?;
+            miner.total_hashes = archive.total_hashes;
+            miner.rounds_completed = archive.rounds_completed;
+            miner.best_difficulty = archive.best_difficulty;
+            miner.tier = archive.tier;
+            miner.capped_score = archive.capped_score;
+            miner.score = archive.score;
+            miner.score_converted = archive.score_converted;
+            close_program_account(&archive_info, &ctx.accounts.authority.to_account_info())?;
+            msg!(
+                "♻️ Restored {} hashes archived at {}",
+                archive.total_hashes,
+                archive.archived_at
+            );
+        }
         
         msg!("✅ Miner initialized: {}", miner.authority);
         Ok(())
@@ -36,80 +85,163 @@ pub mod testore_program {
     /// Validates that the hash meets difficulty requirements and updates
     /// miner statistics. Rate limited to 1 submission per second.
     pub fn submit_proof(
-        ctx: Context<SubmitProof>,
+        mut ctx: Context<SubmitProof>,
         nonce: u64,
         difficulty: u8,
     ) -> Result<()> {
-        let miner = &mut ctx.accounts.miner;
-        let global_round = &mut ctx.accounts.global_round;
         let clock = Clock::get()?;
+        let decay = score_decay(
+            ctx.accounts.score_decay.as_deref(),
+            &ctx.accounts.global_round,
+        )?;
+        let phase = scoring_phase(
+            ctx.accounts.scoring_migration.as_deref(),
+            &ctx.accounts.global_round,
+        )?;
+        let accounts = &mut ctx.accounts;
 
-        // Verify the proof
-        let hash = hash_proof(
-            &miner.authority,
-            &global_round.current_challenge,
+        apply_proof(
+            &mut accounts.miner,
+            &mut accounts.global_round,
+            &clock,
             nonce,
-        );
+            difficulty,
+            decay.as_ref(),
+            phase,
+        )?;
+
+        move_score_bucket(
+            &mut accounts.miner,
+            accounts.from_bucket.as_deref_mut(),
+            accounts.to_bucket.as_deref_mut(),
+        )
+    }
+
+    /// Submit a proof on behalf of one of a pool's workers
+    ///
+    /// Same as `submit_proof`, plus a `WorkerProof` event carrying the
+    /// opaque `worker_tag`. The tag is never stored, so pools can attribute
+    /// shares per worker from transaction history without paying rent for
+    /// each worker.
+    pub fn submit_tagged_proof(
+        mut ctx: Context<SubmitProof>,
+        nonce: u64,
+        difficulty: u8,
+        worker_tag: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let decay = score_decay(
+            ctx.accounts.score_decay.as_deref(),
+            &ctx.accounts.global_round,
+        )?;
+        let phase = scoring_phase(
+            ctx.accounts.scoring_migration.as_deref(),
+            &ctx.accounts.global_round,
+        )?;
+        let accounts = &mut ctx.accounts;
+
+        let counted = apply_proof(
+            &mut accounts.miner,
+            &mut accounts.global_round,
+            &clock,
+            nonce,
+            difficulty,
+            decay.as_ref(),
+            phase,
+        )?;
+
+        emit!(WorkerProof {
+            authority: accounts.miner.authority,
+            worker_tag,
+            round_number: accounts.global_round.round_number,
+            difficulty,
+            counted,
+        });
+
+        move_score_bucket(
+            &mut accounts.miner,
+            accounts.from_bucket.as_deref_mut(),
+            accounts.to_bucket.as_deref_mut(),
+        )
+    }
+
+    /// Commit to a proof without revealing it
+    ///
+    /// First half of the optional commit-reveal flow. `commitment` is
+    /// `commitment_hash(nonce, salt)`; the nonce itself only appears on-chain
+    /// in `reveal_proof`, at least `REVEAL_DELAY_SLOTS` later, so a solution
+    /// seen in a pending transaction can't be re-submitted first by someone
+    /// else. Committing again replaces the pending commitment.
+    pub fn commit_proof(ctx: Context<CommitProof>, commitment: [u8; 32]) -> Result<()> {
+        let miner = &mut ctx.accounts.miner;
+        miner.commitment = commitment;
+        miner.committed_slot = Clock::get()?.slot;
+
+        msg!("🔒 Proof committed at slot {}", miner.committed_slot);
+        Ok(())
+    }
+
+    /// Reveal a previously committed proof
+    ///
+    /// Second half of the commit-reveal flow. Checks the reveal against the
+    /// pending commitment, then applies the same rules as `submit_proof`.
+    pub fn reveal_proof(
+        ctx: Context<SubmitProof>,
+        nonce: u64,
+        salt: [u8; 32],
+        difficulty: u8,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let decay = score_decay(
+            ctx.accounts.score_decay.as_deref(),
+            &ctx.accounts.global_round,
+        )?;
+        let phase = scoring_phase(
+            ctx.accounts.scoring_migration.as_deref(),
+            &ctx.accounts.global_round,
+        )?;
+        let miner = &mut ctx.accounts.miner;
 
-        // Check if hash meets difficulty requirement
         require!(
-            check_difficulty(&hash, difficulty),
-            ErrorCode::InsufficientDifficulty
+            miner.commitment != [0u8; 32],
+            ErrorCode::NoPendingCommitment
         );
-
-        // Ensure minimum time between submissions (anti-spam)
         require!(
-            clock.unix_timestamp - miner.last_hash_at >= 1,
-            ErrorCode::TooManySubmissions
+            clock.slot >= miner.committed_slot.saturating_add(REVEAL_DELAY_SLOTS),
+            ErrorCode::RevealTooEarly
         );
-
-        // Require minimum difficulty
         require!(
-            difficulty >= global_round.min_difficulty,
-            ErrorCode::DifficultyTooLow
+            commitment_hash(nonce, &salt) == miner.commitment,
+            ErrorCode::CommitmentMismatch
         );
 
-        // Update miner stats
-        miner.total_hashes = miner.total_hashes.checked_add(1).unwrap();
-        miner.last_hash_at = clock.unix_timestamp;
-        miner.current_streak = miner.current_streak.checked_add(1).unwrap();
-        
-        if difficulty > miner.best_difficulty {
-            miner.best_difficulty = difficulty;
-        }
-
-        // Check if round completed (10 consecutive hashes)
-        if miner.current_streak >= 10 {
-            miner.rounds_completed = miner.rounds_completed.checked_add(1).unwrap();
-            miner.current_streak = 0;
-            
-            // Update global round
-            global_round.total_rounds_completed = global_round
-                .total_rounds_completed
-                .checked_add(1)
-                .unwrap();
-        }
-
-        // Update global stats
-        global_round.total_hashes_submitted = global_round
-            .total_hashes_submitted
-            .checked_add(1)
-            .unwrap();
+        // Consume the commitment so it can't be revealed twice
+        miner.commitment = [0u8; 32];
+        miner.committed_slot = 0;
 
-        msg!(
-            "⛏️ Proof accepted - Hashes: {}, Rounds: {}, Difficulty: {}",
-            miner.total_hashes,
-            miner.rounds_completed,
-            difficulty
-        );
+        apply_proof(
+            miner,
+            &mut ctx.accounts.global_round,
+            &clock,
+            nonce,
+            difficulty,
+            decay.as_ref(),
+            phase,
+        )?;
 
-        Ok(())
+        move_score_bucket(
+            miner,
+            ctx.accounts.from_bucket.as_deref_mut(),
+            ctx.accounts.to_bucket.as_deref_mut(),
+        )
     }
 
     /// Initialize the global round state
     /// 
     /// Sets up the initial mining challenge and parameters.
-    /// Should be called once during deployment.
+    /// Should be called once during deployment; calling it again fails with
+    /// `AlreadyInitialized` instead of touching cumulative stats. Use
+    /// `update_global_params` for later changes.
     pub fn initialize_global_round(
         ctx: Context<InitializeGlobalRound>,
         admin: Pubkey,
@@ -117,6 +249,12 @@ pub mod testore_program {
         let global_round = &mut ctx.accounts.global_round;
         let clock = Clock::get()?;
 
+        // round_number starts at 1, so 0 means the account was just created
+        require!(
+            global_round.round_number == 0,
+            ErrorCode::AlreadyInitialized
+        );
+
         // Generate initial challenge
         let challenge = generate_challenge(&clock);
         
@@ -128,6 +266,11 @@ pub mod testore_program {
         global_round.total_rounds_completed = 0;
         global_round.admin = admin;
         global_round.bump = ctx.bumps.global_round;
+        global_round.tier_step = 0; // Tiers disabled until configured
+        global_round.round_duration = DEFAULT_ROUND_DURATION;
+        global_round.rejections = RejectionCounts::default();
+        global_round.max_round_proofs = 0; // Uncapped until configured
+        global_round.configs = 0; // Set by the configure instructions
 
         msg!("🌍 Global round initialized - Challenge generated");
         Ok(())
@@ -142,7 +285,10 @@ pub mod testore_program {
 
         // Generate new challenge
         global_round.current_challenge = generate_challenge(&clock);
-        global_round.round_number = global_round.round_number.checked_add(1).unwrap();
+        global_round.round_number = global_round
+            .round_number
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
         global_round.started_at = clock.unix_timestamp;
         
         // Dynamic difficulty adjustment
@@ -151,236 +297,3822 @@ pub mod testore_program {
             global_round.min_difficulty = global_round
                 .min_difficulty
                 .saturating_add(1)
-                .min(16); // Cap at 16 bits
+                .min(MAX_MIN_DIFFICULTY);
         }
 
         // Reset counters
         global_round.total_hashes_submitted = 0;
+        global_round.rejections = RejectionCounts::default();
 
         msg!("🔄 Round rotated to #{}", global_round.round_number);
         Ok(())
     }
-}
-
-// ============================================================================
-// Account Contexts
-// ============================================================================
 
-#[derive(Accounts)]
-pub struct InitializeMiner<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Miner::INIT_SPACE,
-        seeds = [b"miner", authority.key().as_ref()],
-        bump
-    )]
-    pub miner: Account<'info, Miner>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
+    /// Update global round parameters
+    ///
+    /// Admin-only. Each field of `params` that is `Some` replaces the
+    /// current value; round counters and cumulative stats are left alone.
+    pub fn update_global_params(
+        ctx: Context<UpdateGlobalParams>,
+        params: GlobalParams,
+    ) -> Result<()> {
+        let global_round = &mut ctx.accounts.global_round;
 
-#[derive(Accounts)]
-pub struct SubmitProof<'info> {
-    #[account(
-        mut,
-        seeds = [b"miner", authority.key().as_ref()],
-        bump = miner.bump,
-        has_one = authority
-    )]
-    pub miner: Account<'info, Miner>,
-    
-    #[account(
-        mut,
-        seeds = [b"global_round"],
-        bump = global_round.bump
-    )]
-    pub global_round: Account<'info, GlobalRound>,
-    
-    pub authority: Signer<'info>,
-}
+        if let Some(min_difficulty) = params.min_difficulty {
+            require!(
+                (1..=MAX_MIN_DIFFICULTY).contains(&min_difficulty),
+                ErrorCode::InvalidParams
+            );
+            global_round.min_difficulty = min_difficulty;
+        }
 
-#[derive(Accounts)]
-pub struct InitializeGlobalRound<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + GlobalRound::INIT_SPACE,
-        seeds = [b"global_round"],
-        bump
-    )]
-    pub global_round: Account<'info, GlobalRound>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
+        if let Some(round_duration) = params.round_duration {
+            require!(round_duration > 0, ErrorCode::InvalidParams);
+            global_round.round_duration = round_duration;
+        }
 
-#[derive(Accounts)]
-pub struct RotateRound<'info> {
-    #[account(
-        mut,
-        seeds = [b"global_round"],
-        bump = global_round.bump,
-        has_one = admin
-    )]
-    pub global_round: Account<'info, GlobalRound>,
-    
-    pub admin: Signer<'info>,
-}
+        // Each tier adds `tier_step` bits to a miner's personal minimum
+        // difficulty; a step of 0 disables tiers
+        if let Some(tier_step) = params.tier_step {
+            global_round.tier_step = tier_step;
+        }
 
-// ============================================================================
-// Account Data Structures
-// ============================================================================
+        // Proofs past the cap in one round go to capped_score instead of
+        // total_hashes; 0 removes the cap
+        if let Some(max_round_proofs) = params.max_round_proofs {
+            global_round.max_round_proofs = max_round_proofs;
+        }
 
-/// Stores individual miner statistics
-#[account]
-#[derive(InitSpace)]
-pub struct Miner {
-    /// Wallet address of the miner
-    pub authority: Pubkey,
-    
-    /// Total number of valid hashes submitted
-    pub total_hashes: u64,
-    
-    /// Number of completed rounds (10 consecutive hashes)
-    pub rounds_completed: u32,
-    
-    /// Unix timestamp of last hash submission
-    pub last_hash_at: i64,
-    
-    /// Current consecutive hash streak
-    pub current_streak: u32,
-    
-    /// Highest difficulty achieved
-    pub best_difficulty: u8,
-    
-    /// PDA bump seed
-    pub bump: u8,
-}
+        if let Some(admin) = params.admin {
+            global_round.admin = admin;
+        }
 
-/// Global mining round state
-#[account]
-#[derive(InitSpace)]
-pub struct GlobalRound {
-    /// Current mining challenge (32 bytes)
-    pub current_challenge: [u8; 32],
-    
-    /// Round number (increments on rotation)
-    pub round_number: u64,
-    
-    /// Unix timestamp when round started
-    pub started_at: i64,
-    
-    /// Minimum required difficulty
-    pub min_difficulty: u8,
-    
-    /// Total hashes submitted this round
-    pub total_hashes_submitted: u64,
-    
-    /// Total rounds completed across all miners
-    pub total_rounds_completed: u64,
-    
-    /// Admin pubkey (can rotate rounds)
-    pub admin: Pubkey,
-    
-    /// PDA bump seed
-    pub bump: u8,
-}
+        msg!(
+            "🎚️ Global params updated - Min difficulty: {}, Duration: {}s, Tier step: {}, Round cap: {}",
+            global_round.min_difficulty,
+            global_round.round_duration,
+            global_round.tier_step,
+            global_round.max_round_proofs
+        );
+        Ok(())
+    }
 
-// ============================================================================
-// Utility Functions
-// ============================================================================
+    /// Grow a GlobalRound created by an earlier program version to the
+    /// current layout
+    ///
+    /// Admin-only; the admin pays the extra rent. Fields the account predates
+    /// get the values `initialize_global_round` would give them (see
+    /// `upgrade_global_round_fields`), so the round keeps running with the
+    /// default duration, tiers disabled and no proof cap until configured.
+    pub fn upgrade_global_round(ctx: Context<UpgradeGlobalRound>) -> Result<()> {
+        let info = ctx.accounts.global_round.to_account_info();
+        let old_len = grow_program_account(
+            &info,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+            8 + GlobalRound::INIT_SPACE,
+        )?;
+        let mut global_round: GlobalRound = load_program_account(&info)?;
+        require_keys_eq!(
+            global_round.admin,
+            ctx.accounts.admin.key(),
+            anchor_lang::error::ErrorCode::ConstraintHasOne
+        );
 
-/// Hash a proof using Keccak256 (ORE-compatible)
-/// 
-/// Formula: Keccak256(authority || challenge || nonce)
-fn hash_proof(authority: &Pubkey, challenge: &[u8; 32], nonce: u64) -> [u8; 32] {
-    let mut hasher = Keccak256::new();
-    hasher.update(authority.as_ref());
-    hasher.update(challenge);
-    hasher.update(nonce.to_le_bytes());
-    hasher.finalize().into()
-}
+        upgrade_global_round_fields(&mut global_round, old_len);
+        store_program_account(&info, &global_round)?;
 
-/// Check if a hash meets the difficulty requirement
-/// 
-/// Difficulty is measured in leading zero bits.
-/// For example, difficulty 8 means the first 8 bits must be zero.
-fn check_difficulty(hash: &[u8; 32], difficulty: u8) -> bool {
-    let required_zeros = difficulty as usize;
-    
-    for (i, byte) in hash.iter().enumerate() {
-        let leading_zeros = byte.leading_zeros() as usize;
-        let bit_pos = i * 8;
-        
-        if bit_pos + leading_zeros < required_zeros {
-            return false;
-        }
-        
-        if leading_zeros < 8 {
-            return bit_pos + leading_zeros >= required_zeros;
-        }
+        msg!(
+            "⬆️ Global round upgraded from {} to {} bytes",
+            old_len,
+            info.data_len()
+        );
+        Ok(())
     }
-    
-    true
-}
 
-/// Generate a new challenge based on clock data
-/// 
-/// Uses timestamp and slot to create pseudo-random challenge
-fn generate_challenge(clock: &Clock) -> [u8; 32] {
-    let mut hasher = Keccak256::new();
-    hasher.update(clock.unix_timestamp.to_le_bytes());
-    hasher.update(clock.slot.to_le_bytes());
-    hasher.finalize().into()
-}
+    /// Assign a miner's tier
+    ///
+    /// Admin-only. Miners still earn higher tiers through rounds_completed;
+    /// this sets a floor for farms that should not start at tier 0.
+    pub fn set_miner_tier(ctx: Context<SetMinerTier>, tier: u8) -> Result<()> {
+        require!(tier <= MAX_TIER, ErrorCode::InvalidTier);
 
-// ============================================================================
-// Error Codes
-// ============================================================================
+        let miner = &mut ctx.accounts.miner;
+        miner.tier = tier;
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Submitted hash does not meet minimum difficulty requirement")]
-    InsufficientDifficulty,
-    
-    #[msg("Too many submissions - wait at least 1 second between proofs")]
-    TooManySubmissions,
-    
-    #[msg("Difficulty is below the minimum required for this round")]
-    DifficultyTooLow,
-}
+        msg!("🎖️ Miner {} set to tier {}", miner.authority, tier);
+        Ok(())
+    }
 
-// ============================================================================
-// Tests
-// ============================================================================
+    /// Report a proof the program rejected
+    ///
+    /// Rejected submissions roll back, so they can't count themselves; miners
+    /// report them here instead. Counts are self-reported and advisory, but
+    /// show rejection pressure alongside accepted throughput.
+    pub fn report_rejection(ctx: Context<ReportRejection>, reason: RejectionReason) -> Result<()> {
+        let rejections = &mut ctx.accounts.global_round.rejections;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let counter = match reason {
+            RejectionReason::Difficulty => &mut rejections.difficulty,
+            RejectionReason::RateLimited => &mut rejections.rate_limited,
+            RejectionReason::Stale => &mut rejections.stale,
+        };
+        *counter = counter.saturating_add(1);
 
-    #[test]
-    fn test_check_difficulty() {
-        // All zeros should pass any difficulty
-        let easy_hash = [0u8; 32];
-        assert!(check_difficulty(&easy_hash, 8));
-        assert!(check_difficulty(&easy_hash, 16));
+        Ok(())
+    }
 
-        // Hash starting with 0xFF should fail
-        let mut hard_hash = [0u8; 32];
-        hard_hash[0] = 0xFF;
-        assert!(!check_difficulty(&hard_hash, 1));
+    /// Clamp a miner's last submission time to the current clock
+    ///
+    /// Escape hatch for accounts stamped by a clock that was ahead of the
+    /// cluster's current time.
+    pub fn reset_timing(ctx: Context<ResetTiming>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let miner = &mut ctx.accounts.miner;
 
-        // Hash with one leading zero bit
-        let mut medium_hash = [0u8; 32];
+        if miner.last_hash_at > now {
+            msg!(
+                "🕐 Clamped last_hash_at from {} to {}",
+                miner.last_hash_at,
+                now
+            );
+            miner.last_hash_at = now;
+        }
+
+        Ok(())
+    }
+
+    /// Grow a Miner created by an earlier program version to the current
+    /// layout
+    ///
+    /// Permissionless; the payer covers the extra rent. Fields the account
+    /// predates get the values `initialize_miner` would give them (see
+    /// `upgrade_miner_fields`). Accounts already at the current size are
+    /// rewritten unchanged.
+    pub fn upgrade_miner(ctx: Context<UpgradeMiner>, authority: Pubkey) -> Result<()> {
+        let info = ctx.accounts.miner.to_account_info();
+        let old_len = grow_program_account(
+            &info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            8 + Miner::INIT_SPACE,
+        )?;
+        let mut miner: Miner = load_program_account(&info)?;
+        upgrade_miner_fields(&mut miner, old_len);
+        store_program_account(&info, &miner)?;
+
+        msg!(
+            "⬆️ Miner {} upgraded from {} to {} bytes",
+            authority,
+            old_len,
+            info.data_len()
+        );
+        Ok(())
+    }
+
+    /// Create the ScoreBucket PDA for one power-of-two score range
+    ///
+    /// Permissionless; anyone can pay to open a bucket before the first
+    /// miner reaches it.
+    pub fn initialize_score_bucket(ctx: Context<InitializeScoreBucket>, index: u8) -> Result<()> {
+        require!(index < SCORE_BUCKET_COUNT, ErrorCode::InvalidScoreBucket);
+
+        let bucket = &mut ctx.accounts.score_bucket;
+        bucket.index = index;
+        bucket.member_count = 0;
+        bucket.bump = ctx.bumps.score_bucket;
+
+        Ok(())
+    }
+
+    /// Move a miner into the ScoreBucket matching its current score
+    ///
+    /// Permissionless. Submits only move miners when the bucket accounts are
+    /// passed along; this catches up miners whose submits didn't include them.
+    pub fn sync_score_bucket(mut ctx: Context<SyncScoreBucket>) -> Result<()> {
+        let accounts = &mut ctx.accounts;
+
+        move_score_bucket(
+            &mut accounts.miner,
+            accounts.from_bucket.as_deref_mut(),
+            Some(&mut accounts.to_bucket),
+        )
+    }
+
+    /// Write a MinerAttestation snapshot of a miner's stats
+    ///
+    /// Permissionless; the payer covers rent on first use, and when an
+    /// attestation from an earlier layout version grows to the current one.
+    /// Because only this program can write the PDA, its contents are as
+    /// trustworthy as the Miner account they were copied from.
+    pub fn attest_miner(ctx: Context<AttestMiner>) -> Result<()> {
+        let clock = Clock::get()?;
+        let miner = &ctx.accounts.miner;
+        let info = ctx.accounts.attestation.to_account_info();
+        init_or_grow_program_account(
+            &info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            8 + MinerAttestation::INIT_SPACE,
+            &[
+                b"attestation",
+                miner.authority.as_ref(),
+                &[ctx.bumps.attestation],
+            ],
+        )?;
+
+        let attestation = MinerAttestation {
+            version: ATTESTATION_VERSION,
+            authority: miner.authority,
+            total_hashes: miner.total_hashes,
+            rounds_completed: miner.rounds_completed,
+            best_difficulty: miner.best_difficulty,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+            bump: ctx.bumps.attestation,
+            score: miner.ranking_score(),
+        };
+        store_program_account(&info, &attestation)?;
+
+        msg!(
+            "📜 Attested {}: {} hashes, score {} at slot {}",
+            attestation.authority,
+            attestation.total_hashes,
+            attestation.score,
+            attestation.slot
+        );
+        Ok(())
+    }
+
+    /// Record that a miner answered a liveness challenge
+    ///
+    /// The challenge is this instruction itself: the issuer (the API
+    /// server's key, see `configure_liveness_issuer`) signs it with a short
+    /// `expires_at`, and the miner's client countersigns and submits it
+    /// before then. Farms of unattended wallets can't answer without a live
+    /// client per wallet.
+    pub fn verify_liveness(ctx: Context<VerifyLiveness>, expires_at: i64) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            liveness_challenge_open(expires_at, clock.unix_timestamp),
+            ErrorCode::LivenessChallengeExpired
+        );
+        require_keys_eq!(
+            ctx.accounts.issuer.key(),
+            liveness_issuer(
+                ctx.accounts.liveness_issuer.as_deref(),
+                &ctx.accounts.global_round
+            )?,
+            ErrorCode::UnknownLivenessIssuer
+        );
+
+        let liveness = &mut ctx.accounts.liveness;
+        liveness.authority = ctx.accounts.miner.authority;
+        liveness.verified_at = clock.unix_timestamp;
+        liveness.verifications = liveness.verifications.saturating_add(1);
+        liveness.bump = ctx.bumps.liveness;
+
+        msg!(
+            "💓 Liveness verified for {} ({} total)",
+            liveness.authority,
+            liveness.verifications
+        );
+        Ok(())
+    }
+
+    /// Set the key that signs liveness challenges
+    ///
+    /// Admin-only. Until this is called the GlobalRound admin issues them,
+    /// which keeps the admin key on the API server; moving issuance to its
+    /// own key lets the admin key stay offline.
+    pub fn configure_liveness_issuer(
+        ctx: Context<ConfigureLivenessIssuer>,
+        issuer: Pubkey,
+    ) -> Result<()> {
+        require!(issuer != Pubkey::default(), ErrorCode::InvalidParams);
+
+        let config = &mut ctx.accounts.liveness_issuer;
+        config.issuer = issuer;
+        config.bump = ctx.bumps.liveness_issuer;
+
+        msg!("💓 Liveness issuer set to {}", issuer);
+        Ok(())
+    }
+
+    /// Set the display name and link shown for a miner on leaderboards
+    ///
+    /// `name` and `url` are NUL-padded. Names must pass `valid_display_name`
+    /// and the blocklist in `is_profane`; `url` is empty or https. Every
+    /// call pays METADATA_UPDATE_FEE to the GlobalRound admin so handles
+    /// aren't free to churn or squat.
+    pub fn set_metadata(ctx: Context<SetMetadata>, name: [u8; 32], url: [u8; 64]) -> Result<()> {
+        check_metadata(&name, &url)?;
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.admin.to_account_info(),
+                },
+            ),
+            METADATA_UPDATE_FEE,
+        )?;
+
+        let metadata = &mut ctx.accounts.metadata;
+        metadata.authority = ctx.accounts.miner.authority;
+        metadata.name = name;
+        metadata.url = url;
+        metadata.updated_at = Clock::get()?.unix_timestamp;
+        metadata.bump = ctx.bumps.metadata;
+
+        msg!(
+            "🏷️ Metadata set for {}: {}",
+            metadata.authority,
+            metadata_str(&name).unwrap_or_default()
+        );
+        Ok(())
+    }
+
+    /// Self-report the miner's region and RPC latency for analytics
+    ///
+    /// Optional and unverified beyond the miner's signature: `region` is an
+    /// ISO 3166-1 alpha-2 code (all zeros clears it) and `latency_bucket` an
+    /// index into LATENCY_BUCKET_BOUNDS_MS (see `latency_bucket`). Only used
+    /// for aggregate stats, never for rewards.
+    pub fn report_region(
+        ctx: Context<ReportRegion>,
+        region: [u8; 2],
+        latency_bucket: u8,
+    ) -> Result<()> {
+        require!(valid_region(&region), ErrorCode::InvalidRegion);
+        require!(
+            latency_bucket < LATENCY_BUCKET_COUNT,
+            ErrorCode::InvalidRegion
+        );
+
+        let report = &mut ctx.accounts.region;
+        report.authority = ctx.accounts.miner.authority;
+        report.region = region;
+        report.latency_bucket = latency_bucket;
+        report.reported_at = Clock::get()?.unix_timestamp;
+        report.bump = ctx.bumps.region;
+
+        msg!(
+            "🌐 Region reported for {}: {}",
+            report.authority,
+            metadata_str(&region).unwrap_or_default()
+        );
+        Ok(())
+    }
+
+    /// Set how fast idle miners' lifetime scores decay
+    ///
+    /// Admin-only. Each full round_duration a miner goes without an accepted
+    /// proof costs `decay_bps` of its score, applied lazily by its next
+    /// proof (see `decayed_score`); total_hashes stays a plain count. Idle
+    /// time before this call is never charged, so (re)configuring doesn't
+    /// bill miners retroactively. The bridge applies the same math to idle
+    /// time up to the snapshot. 0 disables decay.
+    pub fn configure_score_decay(ctx: Context<ConfigureScoreDecay>, decay_bps: u16) -> Result<()> {
+        require!(decay_bps < BPS_DENOMINATOR, ErrorCode::InvalidParams);
+
+        let score_decay = &mut ctx.accounts.score_decay;
+        score_decay.decay_bps = decay_bps;
+        score_decay.configured_at = Clock::get()?.unix_timestamp;
+        score_decay.bump = ctx.bumps.score_decay;
+        ctx.accounts.global_round.configs |= CONFIG_SCORE_DECAY;
+
+        msg!("📉 Score decay set to {} bps per idle round", decay_bps);
+        Ok(())
+    }
+
+    /// Require a gatekeeper co-signature on `initialize_miner`
+    ///
+    /// Admin-only. The gatekeeper is an off-chain service that co-signs
+    /// registrations after its anti-abuse checks; disabling the gate makes
+    /// registration permissionless again.
+    pub fn configure_registration_gate(
+        ctx: Context<ConfigureRegistrationGate>,
+        gatekeeper: Pubkey,
+        enabled: bool,
+    ) -> Result<()> {
+        require!(
+            !enabled || gatekeeper != Pubkey::default(),
+            ErrorCode::InvalidParams
+        );
+
+        let gate = &mut ctx.accounts.registration_gate;
+        gate.gatekeeper = gatekeeper;
+        gate.enabled = enabled;
+        gate.bump = ctx.bumps.registration_gate;
+
+        msg!(
+            "🚪 Registration gate {} (gatekeeper {})",
+            if enabled { "enabled" } else { "disabled" },
+            gatekeeper
+        );
+        Ok(())
+    }
+
+    /// Create or reconfigure the round reward pool
+    ///
+    /// Admin-only. While `fee_lamports` is non-zero, miners can submit through
+    /// `submit_pooled_proof`, paying the fee into the pool; once the round
+    /// rotates, `settle_reward_pool` splits the pot among the round's top
+    /// `top_k` pooled miners. A fee of 0 closes the pool to new proofs.
+    pub fn configure_reward_pool(
+        ctx: Context<ConfigureRewardPool>,
+        fee_lamports: u64,
+        top_k: u8,
+    ) -> Result<()> {
+        require!(
+            (1..=MAX_POOL_WINNERS).contains(&top_k),
+            ErrorCode::InvalidParams
+        );
+
+        let pool = &mut ctx.accounts.reward_pool;
+        // round_number starts at 1, so 0 means the pool was just created
+        if pool.round_number == 0 {
+            pool.round_number = ctx.accounts.global_round.round_number;
+            pool.bump = ctx.bumps.reward_pool;
+        }
+        pool.fee_lamports = fee_lamports;
+        pool.top_k = top_k;
+
+        msg!(
+            "💰 Reward pool: {} lamports per proof, top {} paid",
+            fee_lamports,
+            top_k
+        );
+        Ok(())
+    }
+
+    /// Submit a proof and pay the reward pool fee
+    ///
+    /// Same rules as `submit_proof`. Each pooled proof adds one to the
+    /// miner's round score, which ranks it for this round's pool payout;
+    /// proofs sent through `submit_proof` don't compete for the pool.
+    pub fn submit_pooled_proof(
+        mut ctx: Context<SubmitPooledProof>,
+        nonce: u64,
+        difficulty: u8,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let decay = score_decay(
+            Some(ctx.accounts.score_decay.as_ref()),
+            &ctx.accounts.global_round,
+        )?;
+        let phase = scoring_phase(
+            Some(ctx.accounts.scoring_migration.as_ref()),
+            &ctx.accounts.global_round,
+        )?;
+        let round_number = ctx.accounts.global_round.round_number;
+        let fee = ctx.accounts.reward_pool.fee_lamports;
+        require!(fee > 0, ErrorCode::RewardPoolClosed);
+        require!(
+            ctx.accounts.reward_pool.round_number == round_number,
+            ErrorCode::RewardPoolNotSettled
+        );
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.reward_pool.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+
+        let accounts = &mut ctx.accounts;
+        let counted = apply_proof(
+            &mut accounts.miner,
+            &mut accounts.global_round,
+            &clock,
+            nonce,
+            difficulty,
+            decay.as_ref(),
+            phase,
+        )?;
+
+        let round_score = &mut accounts.round_score;
+        if round_score.round_number != round_number {
+            round_score.authority = accounts.miner.authority;
+            round_score.round_number = round_number;
+            round_score.score = 0;
+            round_score.bump = ctx.bumps.round_score;
+        }
+        if counted {
+            round_score.score = round_score.score.saturating_add(1);
+        }
+
+        let pool = &mut accounts.reward_pool;
+        pool.pot = pool.pot.saturating_add(fee);
+        pool.record_score(round_score.authority, round_score.score);
+
+        move_score_bucket(
+            &mut accounts.miner,
+            accounts.from_bucket.as_deref_mut(),
+            accounts.to_bucket.as_deref_mut(),
+        )
+    }
+
+    /// Pay out a finished round's reward pool
+    ///
+    /// Permissionless once the round has rotated; the crank sends it right
+    /// after `rotate_round`. Pass the pool leaders' wallets, in
+    /// `RewardPool::leaders` order, as writable remaining accounts. Each is
+    /// paid in proportion to its round score; rounding dust, and the whole
+    /// pot if nobody entered, rolls over to the next round.
+    pub fn settle_reward_pool(ctx: Context<SettleRewardPool>) -> Result<()> {
+        let round_number = ctx.accounts.global_round.round_number;
+        let pool = &mut ctx.accounts.reward_pool;
+        require!(pool.round_number < round_number, ErrorCode::RoundNotOver);
+
+        let leaders = pool.leaders[..pool.leader_count as usize].to_vec();
+        require!(
+            ctx.remaining_accounts.len() == leaders.len(),
+            ErrorCode::WrongPoolWinners
+        );
+
+        let payouts = pool_payouts(pool.pot, &leaders);
+        let pool_info = pool.to_account_info();
+        for ((leader, wallet), amount) in leaders.iter().zip(ctx.remaining_accounts).zip(&payouts) {
+            require_keys_eq!(wallet.key(), leader.authority, ErrorCode::WrongPoolWinners);
+            **pool_info.try_borrow_mut_lamports()? -= amount;
+            **wallet.try_borrow_mut_lamports()? += amount;
+        }
+
+        let paid: u64 = payouts.iter().sum();
+        msg!(
+            "🏆 Round #{} pool: {} lamports to {} miners",
+            pool.round_number,
+            paid,
+            leaders.len()
+        );
+
+        pool.pot -= paid;
+        pool.round_number = round_number;
+        pool.leader_count = 0;
+        pool.leaders = Default::default();
+        Ok(())
+    }
+
+    /// Start the one-time switch from hash counts to difficulty-weighted score
+    ///
+    /// Admin-only, and only once. Freezes proof submissions so no miner's
+    /// stats move while `convert_stats` gives every existing miner its
+    /// initial score (see `initial_score`). `resume_submissions` reopens
+    /// mining on the new metric.
+    pub fn migrate_scoring(ctx: Context<MigrateScoring>) -> Result<()> {
+        let migration = &mut ctx.accounts.scoring_migration;
+        migration.phase = ScoringPhase::Frozen;
+        migration.frozen_at = Clock::get()?.unix_timestamp;
+        migration.resumed_at = 0;
+        migration.bump = ctx.bumps.scoring_migration;
+        ctx.accounts.global_round.configs |= CONFIG_SCORING_MIGRATION;
+
+        msg!("🧊 Submissions frozen for the scoring migration");
+        Ok(())
+    }
+
+    /// Give one existing miner its initial score
+    ///
+    /// Permissionless, so anyone (usually the bridge) can convert every miner
+    /// while submissions are frozen. Miners still unconverted when mining
+    /// resumes are converted by their next proof instead, so none lose
+    /// their history. Miners in a layout from before the score fields are
+    /// grown to the current one first, as by `upgrade_miner`, with the payer
+    /// covering the extra rent.
+    pub fn convert_stats(ctx: Context<ConvertStats>, authority: Pubkey) -> Result<()> {
+        let info = ctx.accounts.miner.to_account_info();
+        let old_len = grow_program_account(
+            &info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            8 + Miner::INIT_SPACE,
+        )?;
+        let mut miner: Miner = load_program_account(&info)?;
+        upgrade_miner_fields(&mut miner, old_len);
+        require!(!miner.score_converted, ErrorCode::AlreadyConverted);
+
+        convert_miner(&mut miner);
+        store_program_account(&info, &miner)?;
+        msg!("🔁 {} converted with score {}", authority, miner.score);
+        Ok(())
+    }
+
+    /// End the scoring migration freeze
+    ///
+    /// Admin-only. From here on accepted proofs add `proof_points` to a
+    /// miner's score on top of counting in total_hashes.
+    pub fn resume_submissions(ctx: Context<ResumeSubmissions>) -> Result<()> {
+        let migration = &mut ctx.accounts.scoring_migration;
+        require!(
+            migration.phase == ScoringPhase::Frozen,
+            ErrorCode::InvalidParams
+        );
+        migration.phase = ScoringPhase::Score;
+        migration.resumed_at = Clock::get()?.unix_timestamp;
+
+        msg!("▶️ Submissions resumed on score");
+        Ok(())
+    }
+
+    /// Post a merkle root of all miner scores for cross-cluster verification
+    ///
+    /// Admin only. The root is computed off-chain (see
+    /// `testore_core::stats_merkle_root`); the program just timestamps it so
+    /// a bridge or oracle relayer can carry it to mainnet.
+    pub fn post_stats_root(
+        ctx: Context<PostStatsRoot>,
+        root: [u8; 32],
+        miner_count: u32,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let stats_root = &mut ctx.accounts.stats_root;
+
+        stats_root.root = root;
+        stats_root.miner_count = miner_count;
+        stats_root.slot = clock.slot;
+        stats_root.posted_at = clock.unix_timestamp;
+        stats_root.sequence = stats_root.sequence.saturating_add(1);
+        stats_root.bump = ctx.bumps.stats_root;
+
+        msg!(
+            "🌳 Stats root #{} posted for {} miners",
+            stats_root.sequence,
+            miner_count
+        );
+        Ok(())
+    }
+
+    /// Set when idle Miner accounts may be closed, and where their rent goes
+    ///
+    /// Admin-only. Miners with no proof for `inactive_days` become eligible
+    /// for `gc_miner`; 0 turns garbage collection off.
+    pub fn configure_gc_policy(
+        ctx: Context<ConfigureGcPolicy>,
+        inactive_days: u16,
+        sweep_wallet: Pubkey,
+    ) -> Result<()> {
+        require!(
+            inactive_days == 0 || sweep_wallet != Pubkey::default(),
+            ErrorCode::InvalidParams
+        );
+
+        let policy = &mut ctx.accounts.gc_policy;
+        policy.inactive_days = inactive_days;
+        policy.sweep_wallet = sweep_wallet;
+        policy.bump = ctx.bumps.gc_policy;
+
+        msg!(
+            "🧹 Miners idle for {} days are collectable (rent to {})",
+            inactive_days,
+            sweep_wallet
+        );
+        Ok(())
+    }
+
+    /// Close a Miner account idle for longer than the GC policy allows
+    ///
+    /// Permissionless, so a crank can sweep without the admin key. The
+    /// miner's cumulative stats move to a MinerArchive, which off-chain
+    /// rankings keep counting and `initialize_miner` restores from. Its
+    /// attestation, metadata, region, liveness and round score accounts,
+    /// where they exist, are closed with it. Rent goes to the policy's sweep
+    /// wallet, less the archive's, which is refunded to the payer; pass the
+    /// miner's ScoreBucket if it has one so the member count stays right.
+    pub fn gc_miner(mut ctx: Context<GcMiner>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let accounts = &mut ctx.accounts;
+        let policy = &accounts.gc_policy;
+        let miner = &accounts.miner;
+        require!(policy.inactive_days > 0, ErrorCode::GcDisabled);
+        require!(
+            gc_eligible(miner.last_hash_at, now, policy.inactive_days),
+            ErrorCode::MinerStillActive
+        );
+
+        if miner.score_bucket != NO_SCORE_BUCKET {
+            let bucket = accounts
+                .score_bucket
+                .as_deref_mut()
+                .ok_or(ErrorCode::WrongScoreBucket)?;
+            require!(
+                bucket.index == miner.score_bucket,
+                ErrorCode::WrongScoreBucket
+            );
+            bucket.member_count = bucket.member_count.saturating_sub(1);
+        }
+
+        let archive = &mut accounts.archive;
+        archive.authority = miner.authority;
+        archive.total_hashes = miner.total_hashes;
+        archive.rounds_completed = miner.rounds_completed;
+        archive.last_hash_at = miner.last_hash_at;
+        archive.best_difficulty = miner.best_difficulty;
+        archive.tier = miner.tier;
+        archive.capped_score = miner.capped_score;
+        archive.score = miner.score;
+        archive.score_converted = miner.score_converted;
+        archive.archived_at = now;
+        archive.bump = ctx.bumps.archive;
+
+        // The miner's rent covers its archive's; close sweeps the rest
+        let archive_rent = archive.to_account_info().lamports();
+        **miner.to_account_info().try_borrow_mut_lamports()? -= archive_rent;
+        **accounts.payer.try_borrow_mut_lamports()? += archive_rent;
+
+        let sweep_wallet = accounts.sweep_wallet.to_account_info();
+        for companion in [
+            &accounts.attestation,
+            &accounts.metadata,
+            &accounts.region,
+            &accounts.liveness,
+            &accounts.round_score,
+        ] {
+            let companion = companion.to_account_info();
+            if companion.owner == &crate::ID && !companion.data_is_empty() {
+                close_program_account(&companion, &sweep_wallet)?;
+            }
+        }
+
+        msg!(
+            "🧹 Archived and closed idle miner {} ({} hashes)",
+            miner.authority,
+            miner.total_hashes
+        );
+        Ok(())
+    }
+
+    /// View: inputs for ranking a miner
+    ///
+    /// Read-only; call through simulateTransaction and decode the return
+    /// data as `MinerRankInputs`.
+    pub fn get_miner_rank_inputs(ctx: Context<ViewMiner>) -> Result<MinerRankInputs> {
+        let miner = &ctx.accounts.miner;
+        let global_round = &ctx.accounts.global_round;
+
+        Ok(MinerRankInputs {
+            authority: miner.authority,
+            total_hashes: miner.total_hashes,
+            rounds_completed: miner.rounds_completed,
+            best_difficulty: miner.best_difficulty,
+            tier: miner.tier.max(earned_tier(miner.rounds_completed)),
+            last_hash_at: miner.last_hash_at,
+            global_total_rounds_completed: global_round.total_rounds_completed,
+            score: miner.ranking_score(),
+        })
+    }
+
+    /// View: what a proof at `difficulty` would earn right now
+    ///
+    /// Read-only; call through simulateTransaction and decode the return
+    /// data as `RewardPreview`. Doesn't check any particular nonce, only the
+    /// difficulty, rate-limit and round-expiry rules.
+    pub fn preview_round_reward(ctx: Context<ViewMiner>, difficulty: u8) -> Result<RewardPreview> {
+        let miner = &ctx.accounts.miner;
+        let global_round = &ctx.accounts.global_round;
+        let now = Clock::get()?.unix_timestamp;
+
+        let min_difficulty = personal_min_difficulty(miner, global_round);
+        let round_ends_at = global_round
+            .started_at
+            .saturating_add(global_round.round_duration);
+        let counts = difficulty >= min_difficulty
+            && !round_expired(global_round.started_at, global_round.round_duration, now);
+
+        Ok(RewardPreview {
+            counts,
+            min_difficulty,
+            hashes_credited: counts as u64,
+            completes_round: counts && miner.current_streak.saturating_add(1) >= STREAK_PER_ROUND,
+            new_best_difficulty: counts && difficulty > miner.best_difficulty,
+            seconds_until_next_submission: submission_wait(miner.last_hash_at, now),
+            seconds_until_round_end: round_ends_at.saturating_sub(now).max(0),
+        })
+    }
+}
+
+// ============================================================================
+// Account Contexts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializeMiner<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Miner::INIT_SPACE,
+        seeds = [b"miner", authority.key().as_ref()],
+        bump
+    )]
+    pub miner: Account<'info, Miner>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+
+    /// RegistrationGate config, which may not exist yet (no gate).
+    /// Optional so clients from before the gate keep registering; there is
+    /// no GlobalRound here to tell whether a gate exists, so the official
+    /// client must always pass it.
+    /// CHECK: address fixed by the seeds; read only if this program owns it
+    #[account(seeds = [b"registration_gate"], bump)]
+    pub registration_gate: Option<UncheckedAccount<'info>>,
+
+    /// The gate's gatekeeper, required while the gate is enabled
+    pub gatekeeper: Option<Signer<'info>>,
+
+    /// Stats `gc_miner` archived for this authority, which may not exist.
+    /// Optional for clients from before archiving; left off, the miner
+    /// starts fresh and the archive is kept.
+    /// CHECK: address fixed by the seeds; restored and closed only if this
+    /// program owns it
+    #[account(mut, seeds = [b"miner_archive", authority.key().as_ref()], bump)]
+    pub archive: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitProof<'info> {
+    #[account(
+        mut,
+        seeds = [b"miner", authority.key().as_ref()],
+        bump = miner.bump,
+        has_one = authority
+    )]
+    pub miner: Account<'info, Miner>,
+    
+    #[account(
+        mut,
+        seeds = [b"global_round"],
+        bump = global_round.bump
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+    
+    pub authority: Signer<'info>,
+
+    /// Bucket the miner is currently counted in (omit for new miners)
+    #[account(
+        mut,
+        seeds = [b"score_bucket", from_bucket.index.to_le_bytes().as_ref()],
+        bump = from_bucket.bump
+    )]
+    pub from_bucket: Option<Account<'info, ScoreBucket>>,
+
+    /// Bucket for the miner's score after this proof
+    #[account(
+        mut,
+        seeds = [b"score_bucket", to_bucket.index.to_le_bytes().as_ref()],
+        bump = to_bucket.bump
+    )]
+    pub to_bucket: Option<Account<'info, ScoreBucket>>,
+
+    /// ScoreDecay config, which may not exist yet (no decay). Optional and
+    /// last so clients from before decay keep working until it's configured.
+    /// CHECK: address fixed by the seeds; read only if this program owns it
+    #[account(seeds = [b"score_decay"], bump)]
+    pub score_decay: Option<UncheckedAccount<'info>>,
+
+    /// ScoringMigration state, which may not exist yet (hash counts only).
+    /// Optional like score_decay, until `migrate_scoring` has run.
+    /// CHECK: address fixed by the seeds; read only if this program owns it
+    #[account(seeds = [b"scoring_migration"], bump)]
+    pub scoring_migration: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct CommitProof<'info> {
+    #[account(
+        mut,
+        seeds = [b"miner", authority.key().as_ref()],
+        bump = miner.bump,
+        has_one = authority
+    )]
+    pub miner: Account<'info, Miner>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReportRejection<'info> {
+    #[account(
+        seeds = [b"miner", authority.key().as_ref()],
+        bump = miner.bump,
+        has_one = authority
+    )]
+    pub miner: Account<'info, Miner>,
+
+    #[account(
+        mut,
+        seeds = [b"global_round"],
+        bump = global_round.bump
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetTiming<'info> {
+    #[account(
+        mut,
+        seeds = [b"miner", authority.key().as_ref()],
+        bump = miner.bump,
+        has_one = authority
+    )]
+    pub miner: Account<'info, Miner>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(authority: Pubkey)]
+pub struct UpgradeMiner<'info> {
+    /// Miner in any earlier layout, which `Account<Miner>` can't load
+    /// CHECK: address fixed by the seeds; owner checked before growing it,
+    /// discriminator on load
+    #[account(mut, seeds = [b"miner", authority.as_ref()], bump)]
+    pub miner: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGlobalRound<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + GlobalRound::INIT_SPACE,
+        seeds = [b"global_round"],
+        bump
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RotateRound<'info> {
+    #[account(
+        mut,
+        seeds = [b"global_round"],
+        bump = global_round.bump,
+        has_one = admin
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGlobalParams<'info> {
+    #[account(
+        mut,
+        seeds = [b"global_round"],
+        bump = global_round.bump,
+        has_one = admin
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpgradeGlobalRound<'info> {
+    /// GlobalRound in any earlier layout, which `Account<GlobalRound>` can't load
+    /// CHECK: address fixed by the seeds; owner checked before growing it,
+    /// discriminator and admin on load (the growth rolls back on a mismatch)
+    #[account(mut, seeds = [b"global_round"], bump)]
+    pub global_round: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMinerTier<'info> {
+    #[account(
+        mut,
+        seeds = [b"miner", miner.authority.as_ref()],
+        bump = miner.bump
+    )]
+    pub miner: Account<'info, Miner>,
+
+    #[account(
+        seeds = [b"global_round"],
+        bump = global_round.bump,
+        has_one = admin
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct InitializeScoreBucket<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ScoreBucket::INIT_SPACE,
+        seeds = [b"score_bucket", index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub score_bucket: Account<'info, ScoreBucket>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncScoreBucket<'info> {
+    #[account(
+        mut,
+        seeds = [b"miner", miner.authority.as_ref()],
+        bump = miner.bump
+    )]
+    pub miner: Account<'info, Miner>,
+
+    #[account(
+        mut,
+        seeds = [b"score_bucket", from_bucket.index.to_le_bytes().as_ref()],
+        bump = from_bucket.bump
+    )]
+    pub from_bucket: Option<Account<'info, ScoreBucket>>,
+
+    #[account(
+        mut,
+        seeds = [b"score_bucket", to_bucket.index.to_le_bytes().as_ref()],
+        bump = to_bucket.bump
+    )]
+    pub to_bucket: Account<'info, ScoreBucket>,
+}
+
+#[derive(Accounts)]
+pub struct AttestMiner<'info> {
+    #[account(
+        seeds = [b"miner", miner.authority.as_ref()],
+        bump = miner.bump
+    )]
+    pub miner: Account<'info, Miner>,
+
+    /// Attestation of any layout version, which `Account<MinerAttestation>`
+    /// can't load
+    /// CHECK: address fixed by the seeds; created or grown, then overwritten
+    #[account(mut, seeds = [b"attestation", miner.authority.as_ref()], bump)]
+    pub attestation: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyLiveness<'info> {
+    #[account(
+        seeds = [b"miner", authority.key().as_ref()],
+        bump = miner.bump,
+        has_one = authority
+    )]
+    pub miner: Account<'info, Miner>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + LivenessProof::INIT_SPACE,
+        seeds = [b"liveness", authority.key().as_ref()],
+        bump
+    )]
+    pub liveness: Account<'info, LivenessProof>,
+
+    #[account(seeds = [b"global_round"], bump = global_round.bump)]
+    pub global_round: Account<'info, GlobalRound>,
+
+    /// Challenge issuer: the LivenessIssuer key, or the GlobalRound admin
+    /// while none is configured
+    pub issuer: Signer<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// LivenessIssuer config, which may not exist yet (the admin issues)
+    /// CHECK: address fixed by the seeds; read only if this program owns it
+    #[account(seeds = [b"liveness_issuer"], bump)]
+    pub liveness_issuer: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureLivenessIssuer<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + LivenessIssuer::INIT_SPACE,
+        seeds = [b"liveness_issuer"],
+        bump
+    )]
+    pub liveness_issuer: Account<'info, LivenessIssuer>,
+
+    #[account(
+        seeds = [b"global_round"],
+        bump = global_round.bump,
+        has_one = admin
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMetadata<'info> {
+    #[account(
+        seeds = [b"miner", authority.key().as_ref()],
+        bump = miner.bump,
+        has_one = authority
+    )]
+    pub miner: Account<'info, Miner>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + MinerMetadata::INIT_SPACE,
+        seeds = [b"metadata", authority.key().as_ref()],
+        bump
+    )]
+    pub metadata: Account<'info, MinerMetadata>,
+
+    #[account(
+        seeds = [b"global_round"],
+        bump = global_round.bump,
+        has_one = admin
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    /// Receives the update fee
+    #[account(mut)]
+    pub admin: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReportRegion<'info> {
+    #[account(
+        seeds = [b"miner", authority.key().as_ref()],
+        bump = miner.bump,
+        has_one = authority
+    )]
+    pub miner: Account<'info, Miner>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + MinerRegion::INIT_SPACE,
+        seeds = [b"region", authority.key().as_ref()],
+        bump
+    )]
+    pub region: Account<'info, MinerRegion>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureScoreDecay<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + ScoreDecay::INIT_SPACE,
+        seeds = [b"score_decay"],
+        bump
+    )]
+    pub score_decay: Account<'info, ScoreDecay>,
+
+    #[account(
+        mut,
+        seeds = [b"global_round"],
+        bump = global_round.bump,
+        has_one = admin
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRegistrationGate<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + RegistrationGate::INIT_SPACE,
+        seeds = [b"registration_gate"],
+        bump
+    )]
+    pub registration_gate: Account<'info, RegistrationGate>,
+
+    #[account(
+        seeds = [b"global_round"],
+        bump = global_round.bump,
+        has_one = admin
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRewardPool<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + RewardPool::INIT_SPACE,
+        seeds = [b"reward_pool"],
+        bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        seeds = [b"global_round"],
+        bump = global_round.bump,
+        has_one = admin
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitPooledProof<'info> {
+    #[account(
+        mut,
+        seeds = [b"miner", authority.key().as_ref()],
+        bump = miner.bump,
+        has_one = authority
+    )]
+    pub miner: Account<'info, Miner>,
+
+    #[account(
+        mut,
+        seeds = [b"global_round"],
+        bump = global_round.bump
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool"],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + RoundScore::INIT_SPACE,
+        seeds = [b"round_score", authority.key().as_ref()],
+        bump
+    )]
+    pub round_score: Account<'info, RoundScore>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// ScoreDecay config, which may not exist yet (no decay)
+    /// CHECK: address fixed by the seeds; read only if this program owns it
+    #[account(seeds = [b"score_decay"], bump)]
+    pub score_decay: UncheckedAccount<'info>,
+
+    /// ScoringMigration state, which may not exist yet (hash counts only)
+    /// CHECK: address fixed by the seeds; read only if this program owns it
+    #[account(seeds = [b"scoring_migration"], bump)]
+    pub scoring_migration: UncheckedAccount<'info>,
+
+    /// Bucket the miner is currently counted in (omit for new miners)
+    #[account(
+        mut,
+        seeds = [b"score_bucket", from_bucket.index.to_le_bytes().as_ref()],
+        bump = from_bucket.bump
+    )]
+    pub from_bucket: Option<Account<'info, ScoreBucket>>,
+
+    /// Bucket for the miner's score after this proof
+    #[account(
+        mut,
+        seeds = [b"score_bucket", to_bucket.index.to_le_bytes().as_ref()],
+        bump = to_bucket.bump
+    )]
+    pub to_bucket: Option<Account<'info, ScoreBucket>>,
+}
+
+#[derive(Accounts)]
+pub struct SettleRewardPool<'info> {
+    #[account(
+        mut,
+        seeds = [b"reward_pool"],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        seeds = [b"global_round"],
+        bump = global_round.bump
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateScoring<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ScoringMigration::INIT_SPACE,
+        seeds = [b"scoring_migration"],
+        bump
+    )]
+    pub scoring_migration: Account<'info, ScoringMigration>,
+
+    #[account(
+        mut,
+        seeds = [b"global_round"],
+        bump = global_round.bump,
+        has_one = admin
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(authority: Pubkey)]
+pub struct ConvertStats<'info> {
+    /// Miner in any layout, grown to the current one before converting
+    /// CHECK: address fixed by the seeds; owner checked before growing it,
+    /// discriminator on load
+    #[account(mut, seeds = [b"miner", authority.as_ref()], bump)]
+    pub miner: UncheckedAccount<'info>,
+
+    /// Only exists once `migrate_scoring` has run
+    #[account(
+        seeds = [b"scoring_migration"],
+        bump = scoring_migration.bump
+    )]
+    pub scoring_migration: Account<'info, ScoringMigration>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResumeSubmissions<'info> {
+    #[account(
+        mut,
+        seeds = [b"scoring_migration"],
+        bump = scoring_migration.bump
+    )]
+    pub scoring_migration: Account<'info, ScoringMigration>,
+
+    #[account(
+        seeds = [b"global_round"],
+        bump = global_round.bump,
+        has_one = admin
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PostStatsRoot<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + StatsRoot::INIT_SPACE,
+        seeds = [b"stats_root"],
+        bump
+    )]
+    pub stats_root: Account<'info, StatsRoot>,
+
+    #[account(
+        seeds = [b"global_round"],
+        bump = global_round.bump,
+        has_one = admin
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureGcPolicy<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + GcPolicy::INIT_SPACE,
+        seeds = [b"gc_policy"],
+        bump
+    )]
+    pub gc_policy: Account<'info, GcPolicy>,
+
+    #[account(
+        seeds = [b"global_round"],
+        bump = global_round.bump,
+        has_one = admin
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GcMiner<'info> {
+    #[account(
+        seeds = [b"gc_policy"],
+        bump = gc_policy.bump
+    )]
+    pub gc_policy: Account<'info, GcPolicy>,
+
+    #[account(
+        mut,
+        seeds = [b"miner", miner.authority.as_ref()],
+        bump = miner.bump,
+        close = sweep_wallet
+    )]
+    pub miner: Account<'info, Miner>,
+
+    #[account(
+        mut,
+        seeds = [b"score_bucket", score_bucket.index.to_le_bytes().as_ref()],
+        bump = score_bucket.bump
+    )]
+    pub score_bucket: Option<Account<'info, ScoreBucket>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MinerArchive::INIT_SPACE,
+        seeds = [b"miner_archive", miner.authority.as_ref()],
+        bump
+    )]
+    pub archive: Account<'info, MinerArchive>,
+
+    /// CHECK: PDA-checked; closed only if this program owns it
+    #[account(mut, seeds = [b"attestation", miner.authority.as_ref()], bump)]
+    pub attestation: UncheckedAccount<'info>,
+
+    /// CHECK: PDA-checked; closed only if this program owns it
+    #[account(mut, seeds = [b"metadata", miner.authority.as_ref()], bump)]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: PDA-checked; closed only if this program owns it
+    #[account(mut, seeds = [b"region", miner.authority.as_ref()], bump)]
+    pub region: UncheckedAccount<'info>,
+
+    /// CHECK: PDA-checked; closed only if this program owns it
+    #[account(mut, seeds = [b"liveness", miner.authority.as_ref()], bump)]
+    pub liveness: UncheckedAccount<'info>,
+
+    /// CHECK: PDA-checked; closed only if this program owns it
+    #[account(mut, seeds = [b"round_score", miner.authority.as_ref()], bump)]
+    pub round_score: UncheckedAccount<'info>,
+
+    /// CHECK: must be the policy's sweep wallet; only receives lamports
+    #[account(mut, address = gc_policy.sweep_wallet)]
+    pub sweep_wallet: UncheckedAccount<'info>,
+
+    /// Fronts the archive's rent, refunded from the miner's
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ViewMiner<'info> {
+    #[account(
+        seeds = [b"miner", miner.authority.as_ref()],
+        bump = miner.bump
+    )]
+    pub miner: Account<'info, Miner>,
+
+    #[account(
+        seeds = [b"global_round"],
+        bump = global_round.bump
+    )]
+    pub global_round: Account<'info, GlobalRound>,
+}
+
+// ============================================================================
+// Account Data Structures
+// ============================================================================
+
+/// Stores individual miner statistics
+#[account]
+#[derive(InitSpace)]
+pub struct Miner {
+    /// Wallet address of the miner
+    pub authority: Pubkey,
+    
+    /// Total number of valid hashes submitted
+    pub total_hashes: u64,
+    
+    /// Number of completed rounds (10 consecutive hashes)
+    pub rounds_completed: u32,
+    
+    /// Unix timestamp of last hash submission
+    pub last_hash_at: i64,
+    
+    /// Current consecutive hash streak
+    pub current_streak: u32,
+    
+    /// Highest difficulty achieved
+    pub best_difficulty: u8,
+    
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Tier (admin-assigned or earned via rounds_completed)
+    pub tier: u8,
+
+    /// Pending commit-reveal commitment (all zeros = none)
+    pub commitment: [u8; 32],
+
+    /// Slot the pending commitment was made in
+    pub committed_slot: u64,
+
+    /// ScoreBucket index this miner is counted in (NO_SCORE_BUCKET = none yet)
+    pub score_bucket: u8,
+
+    /// Round that round_proofs counts
+    pub round_number: u64,
+
+    /// Proofs accepted in round_number, capped or not
+    pub round_proofs: u32,
+
+    /// Proofs accepted past GlobalRound::max_round_proofs, kept out of total_hashes
+    pub capped_score: u64,
+
+    /// Difficulty-weighted score, once the scoring migration has converted
+    /// this miner (see `initial_score` and `proof_points`)
+    pub score: u64,
+
+    /// Whether score has been initialized from the hash-count stats
+    pub score_converted: bool,
+}
+
+impl Miner {
+    /// Score the leaderboard ranks by: `score` once converted, and until
+    /// then the score conversion will give it (total_hashes)
+    ///
+    /// Score buckets, attestations and rank inputs all use this, so they
+    /// agree with the ranking before, during and after the migration.
+    pub fn ranking_score(&self) -> u64 {
+        if self.score_converted {
+            self.score
+        } else {
+            initial_score(self.total_hashes)
+        }
+    }
+}
+
+/// Global mining round state
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalRound {
+    /// Current mining challenge (32 bytes)
+    pub current_challenge: [u8; 32],
+    
+    /// Round number (increments on rotation)
+    pub round_number: u64,
+    
+    /// Unix timestamp when round started
+    pub started_at: i64,
+    
+    /// Minimum required difficulty
+    pub min_difficulty: u8,
+    
+    /// Total hashes submitted this round
+    pub total_hashes_submitted: u64,
+    
+    /// Total rounds completed across all miners
+    pub total_rounds_completed: u64,
+    
+    /// Admin pubkey (can rotate rounds)
+    pub admin: Pubkey,
+    
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Extra difficulty bits required per miner tier (0 = tiers disabled)
+    pub tier_step: u8,
+
+    /// Seconds after started_at during which proofs are accepted
+    pub round_duration: i64,
+
+    /// Reported rejections this round (reset on rotation)
+    pub rejections: RejectionCounts,
+
+    /// Proofs per miner per round that count toward total_hashes (0 = no cap)
+    pub max_round_proofs: u32,
+
+    /// Config accounts that exist (`CONFIG_*` bits). Instructions that take
+    /// one as an optional account require it once its bit is set, so
+    /// leaving it off can't skip the config.
+    pub configs: u8,
+}
+
+/// GlobalRound::configs bit: the ScoreDecay account exists
+pub const CONFIG_SCORE_DECAY: u8 = 1 << 0;
+
+/// GlobalRound::configs bit: the ScoringMigration account exists
+pub const CONFIG_SCORING_MIGRATION: u8 = 1 << 1;
+
+/// Per-round rejection counters, by reason
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct RejectionCounts {
+    /// InsufficientDifficulty, DifficultyTooLow or BelowTierDifficulty
+    pub difficulty: u64,
+
+    /// TooManySubmissions
+    pub rate_limited: u64,
+
+    /// RoundExpired or a proof against an old challenge
+    pub stale: u64,
+}
+
+/// Why a proof was rejected, as reported through `report_rejection`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RejectionReason {
+    Difficulty,
+    RateLimited,
+    Stale,
+}
+
+/// Member count for one power-of-two range of total_hashes
+///
+/// Bucket `i` holds miners with `2^(i-1) <= total_hashes < 2^i`; bucket 0
+/// holds miners with no hashes. Off-chain consumers read all buckets to
+/// estimate a miner's percentile without scanning every Miner account.
+#[account]
+#[derive(InitSpace)]
+pub struct ScoreBucket {
+    /// Bucket index (see `score_bucket_index`)
+    pub index: u8,
+
+    /// Miners currently counted in this bucket
+    pub member_count: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Program-written snapshot of a miner's stats for other programs to consume
+///
+/// Stable layout: fields are only ever appended, and `version` is bumped when
+/// they are. Consumers should check the account owner, the PDA seeds
+/// `[b"attestation", authority]` and `version` before trusting the data.
+#[account]
+#[derive(InitSpace)]
+pub struct MinerAttestation {
+    /// Layout version (ATTESTATION_VERSION at write time)
+    pub version: u8,
+
+    /// Wallet address of the attested miner
+    pub authority: Pubkey,
+
+    /// Miner::total_hashes at `slot`
+    pub total_hashes: u64,
+
+    /// Miner::rounds_completed at `slot`
+    pub rounds_completed: u32,
+
+    /// Miner::best_difficulty at `slot`
+    pub best_difficulty: u8,
+
+    /// Slot the snapshot was taken in
+    pub slot: u64,
+
+    /// Unix timestamp the snapshot was taken at
+    pub timestamp: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// `Miner::ranking_score` at `slot` (version 2 on)
+    pub score: u64,
+}
+
+/// Latest answered liveness challenge for a miner
+#[account]
+#[derive(InitSpace)]
+pub struct LivenessProof {
+    /// Wallet address of the miner
+    pub authority: Pubkey,
+
+    /// Unix timestamp of the latest answered challenge
+    pub verified_at: i64,
+
+    /// Challenges answered so far
+    pub verifications: u32,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Key that signs liveness challenges (see `configure_liveness_issuer`)
+#[account]
+#[derive(InitSpace)]
+pub struct LivenessIssuer {
+    pub issuer: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Public display name and link for a miner (see `set_metadata`)
+#[account]
+#[derive(InitSpace)]
+pub struct MinerMetadata {
+    /// Wallet address of the miner
+    pub authority: Pubkey,
+
+    /// Display name, NUL-padded
+    pub name: [u8; 32],
+
+    /// Profile link, NUL-padded (empty = none)
+    pub url: [u8; 64],
+
+    /// Unix timestamp of the latest update
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Self-reported location of a miner (see `report_region`)
+#[account]
+#[derive(InitSpace)]
+pub struct MinerRegion {
+    /// Wallet address of the miner
+    pub authority: Pubkey,
+
+    /// ISO 3166-1 alpha-2 country code (all zeros = not reported)
+    pub region: [u8; 2],
+
+    /// Index into LATENCY_BUCKET_BOUNDS_MS
+    pub latency_bucket: u8,
+
+    /// Unix timestamp of the latest report
+    pub reported_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Idle score decay setting (see `configure_score_decay`)
+#[account]
+#[derive(InitSpace)]
+pub struct ScoreDecay {
+    /// Share of score lost per full idle round, in basis points
+    pub decay_bps: u16,
+
+    /// Unix timestamp of the last `configure_score_decay`; idle time before
+    /// it is not charged
+    pub configured_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Registration gatekeeper setting (see `configure_registration_gate`)
+#[account]
+#[derive(InitSpace)]
+pub struct RegistrationGate {
+    /// Service whose co-signature `initialize_miner` requires
+    pub gatekeeper: Pubkey,
+
+    pub enabled: bool,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// When idle miners may be closed (see `configure_gc_policy`)
+#[account]
+#[derive(InitSpace)]
+pub struct GcPolicy {
+    /// Days without a proof before `gc_miner` may close a Miner (0 = off)
+    pub inactive_days: u16,
+
+    /// Receives the rent of closed Miner accounts
+    pub sweep_wallet: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Cumulative stats of a miner `gc_miner` closed
+///
+/// Rankings and airdrops keep counting it in place of the Miner, and
+/// `initialize_miner` restores it (and closes it) if the authority comes
+/// back, so closing a miner never resets or drops its history.
+#[account]
+#[derive(InitSpace)]
+pub struct MinerArchive {
+    /// Wallet address of the closed miner
+    pub authority: Pubkey,
+
+    /// Miner::total_hashes at close
+    pub total_hashes: u64,
+
+    /// Miner::rounds_completed at close
+    pub rounds_completed: u32,
+
+    /// Miner::last_hash_at at close
+    pub last_hash_at: i64,
+
+    /// Miner::best_difficulty at close
+    pub best_difficulty: u8,
+
+    /// Miner::tier at close
+    pub tier: u8,
+
+    /// Miner::capped_score at close
+    pub capped_score: u64,
+
+    /// Miner::score at close
+    pub score: u64,
+
+    /// Miner::score_converted at close
+    pub score_converted: bool,
+
+    /// Unix timestamp the miner was closed at
+    pub archived_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Where the hash-count to score migration stands (see `migrate_scoring`)
+#[account]
+#[derive(InitSpace)]
+pub struct ScoringMigration {
+    pub phase: ScoringPhase,
+
+    /// Unix timestamp submissions were frozen at
+    pub frozen_at: i64,
+
+    /// Unix timestamp submissions resumed at (0 = still frozen)
+    pub resumed_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Metric accepted proofs are credited to
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoringPhase {
+    /// Before `migrate_scoring`: total_hashes only
+    HashCount,
+
+    /// Between `migrate_scoring` and `resume_submissions`: no proofs accepted
+    Frozen,
+
+    /// After `resume_submissions`: total_hashes and score
+    Score,
+}
+
+/// Round reward pool funded by `submit_pooled_proof` fees
+///
+/// Holds the pot in its own lamports, on top of its rent-exempt minimum.
+#[account]
+#[derive(InitSpace)]
+pub struct RewardPool {
+    /// Fee per pooled proof (0 = closed to new proofs)
+    pub fee_lamports: u64,
+
+    /// Leaders paid out per round (at most MAX_POOL_WINNERS)
+    pub top_k: u8,
+
+    /// Round the pot and leaders belong to
+    pub round_number: u64,
+
+    /// Lamports owed to this round's leaders, including carried-over dust
+    pub pot: u64,
+
+    /// Used entries of `leaders`
+    pub leader_count: u8,
+
+    /// Highest round scores so far, unordered
+    pub leaders: [PoolLeader; MAX_POOL_WINNERS as usize],
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl RewardPool {
+    /// Record `authority`'s new round score among the leaders
+    ///
+    /// Scores only rise one proof at a time, so a miner outside the leaders
+    /// never has more than the lowest leader until this call lets it in.
+    fn record_score(&mut self, authority: Pubkey, score: u64) {
+        let count = self.leader_count as usize;
+        let entry = PoolLeader { authority, score };
+
+        if let Some(leader) = self.leaders[..count]
+            .iter_mut()
+            .find(|leader| leader.authority == authority)
+        {
+            leader.score = score;
+        } else if count < self.top_k as usize {
+            self.leaders[count] = entry;
+            self.leader_count += 1;
+        } else if let Some(lowest) = self.leaders[..count]
+            .iter_mut()
+            .min_by_key(|leader| leader.score)
+        {
+            if score > lowest.score {
+                *lowest = entry;
+            }
+        }
+    }
+}
+
+/// One miner's standing in the round reward pool
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct PoolLeader {
+    pub authority: Pubkey,
+
+    /// Pooled proofs this round
+    pub score: u64,
+}
+
+/// A miner's pooled proofs in the current round
+#[account]
+#[derive(InitSpace)]
+pub struct RoundScore {
+    /// Wallet address of the miner
+    pub authority: Pubkey,
+
+    /// Round `score` was earned in; older scores count as 0
+    pub round_number: u64,
+
+    /// Pooled proofs in `round_number`
+    pub score: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Latest posted merkle root of miner scores
+#[account]
+#[derive(InitSpace)]
+pub struct StatsRoot {
+    /// Merkle root over (authority, ranking score) of every miner (see
+    /// `testore_core::stats_leaf`)
+    pub root: [u8; 32],
+
+    /// Number of miners under `root`
+    pub miner_count: u32,
+
+    /// Slot the root was posted in
+    pub slot: u64,
+
+    /// Unix timestamp the root was posted at
+    pub posted_at: i64,
+
+    /// Incremented on every post
+    pub sequence: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Admin-adjustable GlobalRound parameters; `None` leaves a value unchanged
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct GlobalParams {
+    pub min_difficulty: Option<u8>,
+    pub round_duration: Option<i64>,
+    pub tier_step: Option<u8>,
+    pub admin: Option<Pubkey>,
+    pub max_round_proofs: Option<u32>,
+}
+
+// ============================================================================
+// View Return Types
+// ============================================================================
+
+/// Return data of `get_miner_rank_inputs`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MinerRankInputs {
+    pub authority: Pubkey,
+    pub total_hashes: u64,
+    pub rounds_completed: u32,
+    pub best_difficulty: u8,
+
+    /// Effective tier (stored or earned, whichever is higher)
+    pub tier: u8,
+
+    pub last_hash_at: i64,
+
+    /// Total rounds completed across all miners
+    pub global_total_rounds_completed: u64,
+
+    /// Score the leaderboard ranks by (see `Miner::ranking_score`)
+    pub score: u64,
+}
+
+/// Return data of `preview_round_reward`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RewardPreview {
+    /// Whether a valid proof at this difficulty would be accepted
+    pub counts: bool,
+
+    /// This miner's minimum difficulty (round minimum plus tier floor)
+    pub min_difficulty: u8,
+
+    /// Hashes added to total_hashes
+    pub hashes_credited: u64,
+
+    /// Whether the proof would finish a 10-hash streak
+    pub completes_round: bool,
+
+    /// Whether the proof would raise best_difficulty
+    pub new_best_difficulty: bool,
+
+    /// Seconds until the rate limit allows another submission
+    pub seconds_until_next_submission: i64,
+
+    /// Seconds until the round expires
+    pub seconds_until_round_end: i64,
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// A proof submitted with `submit_tagged_proof`
+#[event]
+#[derive(Debug, PartialEq, Eq)]
+pub struct WorkerProof {
+    pub authority: Pubkey,
+
+    /// Opaque tag chosen by the pool, e.g. a hash of the worker's name
+    pub worker_tag: [u8; 32],
+
+    pub round_number: u64,
+    pub difficulty: u8,
+
+    /// Whether the proof counted toward total_hashes
+    pub counted: bool,
+}
+
+// ============================================================================
+// Utility Functions
+// ============================================================================
+
+/// Validate a proof and update miner and global stats
+///
+/// Shared by the proof-submitting instructions. Returns whether the proof
+/// counted toward total_hashes, i.e. wasn't past the round cap.
+fn apply_proof(
+    miner: &mut Miner,
+    global_round: &mut GlobalRound,
+    clock: &Clock,
+    nonce: u64,
+    difficulty: u8,
+    decay: Option<&ScoreDecay>,
+    phase: ScoringPhase,
+) -> Result<bool> {
+    // No stats may move while the scoring migration converts them
+    require!(phase != ScoringPhase::Frozen, ErrorCode::SubmissionsFrozen);
+
+    // Verify the proof
+    let hash = hash_proof(&miner.authority, &global_round.current_challenge, nonce);
+
+    // Check if hash meets difficulty requirement
+    require!(
+        check_difficulty(&hash, difficulty),
+        ErrorCode::InsufficientDifficulty
+    );
+
+    // Reject submissions against a round that should have rotated
+    require!(
+        !round_expired(
+            global_round.started_at,
+            global_round.round_duration,
+            clock.unix_timestamp
+        ),
+        ErrorCode::RoundExpired
+    );
+
+    // Ensure minimum time between submissions (anti-spam)
+    require!(
+        submission_wait(miner.last_hash_at, clock.unix_timestamp) == 0,
+        ErrorCode::TooManySubmissions
+    );
+
+    // Require minimum difficulty
+    require!(
+        difficulty >= global_round.min_difficulty,
+        ErrorCode::DifficultyTooLow
+    );
+
+    // Higher tiers must clear a higher personal floor
+    require!(
+        difficulty >= personal_min_difficulty(miner, global_round),
+        ErrorCode::BelowTierDifficulty
+    );
+
+    if phase == ScoringPhase::Score && !miner.score_converted {
+        convert_miner(miner);
+    }
+
+    // Charge the score decay for rounds spent idle since the later of the
+    // last proof and the decay being configured, before crediting this proof
+    if let Some(decay) = decay {
+        let idle = idle_rounds(
+            miner.last_hash_at.max(decay.configured_at),
+            clock.unix_timestamp,
+            global_round.round_duration,
+        );
+        miner.score = decayed_score(miner.score, idle, decay.decay_bps);
+    }
+
+    if miner.round_number != global_round.round_number {
+        miner.round_number = global_round.round_number;
+        miner.round_proofs = 0;
+    }
+    miner.round_proofs = miner.round_proofs.saturating_add(1);
+    let counted = within_round_cap(miner.round_proofs, global_round.max_round_proofs);
+
+    // Update miner stats. Lifetime counters saturate: a maxed-out score is
+    // still the top score, and mining shouldn't stop because of it. Proofs
+    // past the round cap still land, but are only tallied in capped_score.
+    if counted {
+        miner.total_hashes = miner.total_hashes.saturating_add(1);
+        if phase == ScoringPhase::Score {
+            miner.score = miner
+                .score
+                .saturating_add(proof_points(difficulty, global_round.min_difficulty));
+        }
+    } else {
+        miner.capped_score = miner.capped_score.saturating_add(1);
+    }
+    miner.last_hash_at = clock.unix_timestamp;
+    miner.current_streak = miner
+        .current_streak
+        .checked_add(1)
+        .ok_or(ErrorCode::Overflow)?;
+
+    if difficulty > miner.best_difficulty {
+        miner.best_difficulty = difficulty;
+    }
+
+    // Check if round completed (10 consecutive hashes)
+    if miner.current_streak >= STREAK_PER_ROUND {
+        miner.rounds_completed = miner.rounds_completed.saturating_add(1);
+        miner.current_streak = 0;
+
+        let earned = earned_tier(miner.rounds_completed);
+        if earned > miner.tier {
+            miner.tier = earned;
+            msg!("🎖️ Miner promoted to tier {}", miner.tier);
+        }
+
+        // Update global round
+        global_round.total_rounds_completed = global_round.total_rounds_completed.saturating_add(1);
+    }
+
+    // Update global stats
+    global_round.total_hashes_submitted = global_round.total_hashes_submitted.saturating_add(1);
+
+    msg!(
+        "⛏️ Proof accepted - Hashes: {}, Capped: {}, Rounds: {}, Difficulty: {}",
+        miner.total_hashes,
+        miner.capped_score,
+        miner.rounds_completed,
+        difficulty
+    );
+
+    Ok(counted)
+}
+
+/// Whether a miner's `round_proofs`-th proof this round counts toward
+/// total_hashes under a cap of `max_round_proofs` (0 = no cap)
+pub fn within_round_cap(round_proofs: u32, max_round_proofs: u32) -> bool {
+    max_round_proofs == 0 || round_proofs <= max_round_proofs
+}
+
+/// Hash a proof using Keccak256 (ORE-compatible)
+/// 
+/// Formula: Keccak256(authority || challenge || nonce)
+pub fn hash_proof(authority: &Pubkey, challenge: &[u8; 32], nonce: u64) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(authority.as_ref());
+    hasher.update(challenge);
+    hasher.update(nonce.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// Slots that must pass between commit_proof and reveal_proof
+pub const REVEAL_DELAY_SLOTS: u64 = 2;
+
+/// Commitment for the commit-reveal flow
+///
+/// Formula: Keccak256(nonce || salt)
+pub fn commitment_hash(nonce: u64, salt: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(nonce.to_le_bytes());
+    hasher.update(salt);
+    hasher.finalize().into()
+}
+
+/// Check if a hash meets the difficulty requirement
+/// 
+/// Difficulty is measured in leading zero bits, counted across byte
+/// boundaries. For example, difficulty 8 means the first 8 bits must be
+/// zero, and difficulty 9 the whole first byte and the top bit of the second.
+pub fn check_difficulty(hash: &[u8; 32], difficulty: u8) -> bool {
+    let mut leading_zeros = 0;
+    for byte in hash {
+        leading_zeros += byte.leading_zeros() as usize;
+        if *byte != 0 {
+            break;
+        }
+    }
+
+    leading_zeros >= difficulty as usize
+}
+
+/// Ceiling for min_difficulty, both for rotation and admin updates
+pub const MAX_MIN_DIFFICULTY: u8 = 16;
+
+/// Default round length in seconds
+pub const DEFAULT_ROUND_DURATION: i64 = 60 * 60;
+
+/// Whether a round started at `started_at` has run past its duration
+fn round_expired(started_at: i64, round_duration: i64, now: i64) -> bool {
+    now > started_at.saturating_add(round_duration)
+}
+
+/// Highest miner tier
+pub const MAX_TIER: u8 = 3;
+
+/// rounds_completed needed to earn tiers 1..=MAX_TIER
+pub const TIER_ROUND_THRESHOLDS: [u32; MAX_TIER as usize] = [100, 1_000, 10_000];
+
+/// Tier a miner has earned through completed rounds
+pub fn earned_tier(rounds_completed: u32) -> u8 {
+    TIER_ROUND_THRESHOLDS
+        .iter()
+        .filter(|&&threshold| rounds_completed >= threshold)
+        .count() as u8
+}
+
+/// Minimum seconds between two accepted submissions from one miner
+pub const MIN_SUBMISSION_INTERVAL: i64 = 1;
+
+/// Seconds until a miner that last submitted at `last_hash_at` may submit again
+///
+/// last_hash_at is always written from the cluster clock, so a value in the
+/// future means the clock has since stepped back. That must not block the
+/// miner until the clock catches up, so it counts as no wait at all.
+pub fn submission_wait(last_hash_at: i64, now: i64) -> i64 {
+    if last_hash_at > now {
+        return 0;
+    }
+
+    // Measure the elapsed time rather than adding the interval to
+    // last_hash_at, which would saturate near i64::MAX and skip the wait
+    let elapsed = now.saturating_sub(last_hash_at);
+    MIN_SUBMISSION_INTERVAL.saturating_sub(elapsed).max(0)
+}
+
+/// Consecutive hashes that make up one completed round
+pub const STREAK_PER_ROUND: u32 = 10;
+
+/// Minimum difficulty for this miner, including its tier floor
+fn personal_min_difficulty(miner: &Miner, global_round: &GlobalRound) -> u8 {
+    let tier = miner.tier.max(earned_tier(miner.rounds_completed));
+    tier_min_difficulty(global_round.min_difficulty, tier, global_round.tier_step)
+}
+
+/// Personal minimum difficulty for a miner of the given tier
+pub fn tier_min_difficulty(min_difficulty: u8, tier: u8, tier_step: u8) -> u8 {
+    min_difficulty.saturating_add(tier.saturating_mul(tier_step))
+}
+
+/// Current MinerAttestation layout version
+///
+/// 2 appended `score`.
+pub const ATTESTATION_VERSION: u8 = 2;
+
+/// Longest a liveness challenge may stay open, in seconds
+///
+/// Keeps issuers from handing out challenges a script could stockpile.
+pub const MAX_LIVENESS_CHALLENGE_SECS: i64 = 5 * 60;
+
+/// Whether a liveness challenge expiring at `expires_at` can be answered now
+pub fn liveness_challenge_open(expires_at: i64, now: i64) -> bool {
+    expires_at > now && expires_at.saturating_sub(now) <= MAX_LIVENESS_CHALLENGE_SECS
+}
+
+/// Basis points in a whole
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Full rounds of `round_duration` between `last_hash_at` and `now`
+///
+/// A last_hash_at in the future (clock skew) counts as no idle time.
+pub fn idle_rounds(last_hash_at: i64, now: i64, round_duration: i64) -> u64 {
+    if round_duration <= 0 {
+        return 0;
+    }
+
+    (now.saturating_sub(last_hash_at).max(0) / round_duration) as u64
+}
+
+/// `score` after losing `decay_bps` per round for `rounds` rounds
+///
+/// Compounds in 64.64 fixed point by squaring, rounding down at every step,
+/// so the cost is logarithmic in `rounds`. testore-core has a copy for the
+/// bridge; both are pinned to the same vectors in their tests.
+pub fn decayed_score(score: u64, rounds: u64, decay_bps: u16) -> u64 {
+    if score == 0 || rounds == 0 || decay_bps == 0 {
+        return score;
+    }
+    if decay_bps >= BPS_DENOMINATOR {
+        return 0;
+    }
+
+    const ONE: u128 = 1 << 64;
+    let mut base = (BPS_DENOMINATOR - decay_bps) as u128 * ONE / BPS_DENOMINATOR as u128;
+    let mut factor = ONE;
+    let mut rounds = rounds;
+    while rounds > 0 && factor > 0 {
+        if rounds & 1 == 1 {
+            factor = (factor * base) >> 64;
+        }
+        base = (base * base) >> 64;
+        rounds >>= 1;
+    }
+
+    ((score as u128 * factor) >> 64) as u64
+}
+
+/// Configured decay, or None while the ScoreDecay account doesn't exist
+///
+/// `account` may only be left off while decay has never been configured.
+fn score_decay(
+    account: Option<&AccountInfo>,
+    global_round: &GlobalRound,
+) -> Result<Option<ScoreDecay>> {
+    let Some(account) = account else {
+        require!(
+            global_round.configs & CONFIG_SCORE_DECAY == 0,
+            ErrorCode::MissingConfigAccount
+        );
+        return Ok(None);
+    };
+    if account.owner != &crate::ID || account.data_is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(ScoreDecay::try_deserialize(
+        &mut &account.data.borrow()[..],
+    )?))
+}
+
+/// Whether a miner whose last proof was at `last_hash_at` has been idle for
+/// at least `inactive_days` at `now`
+pub fn gc_eligible(last_hash_at: i64, now: i64, inactive_days: u16) -> bool {
+    now.saturating_sub(last_hash_at) >= inactive_days as i64 * 24 * 60 * 60
+}
+
+/// Close an account this program owns, moving its rent to `destination`
+///
+/// For accounts not declared with `close`, e.g. the optional PDAs
+/// `gc_miner` sweeps along with a miner.
+fn close_program_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    **destination.try_borrow_mut_lamports()? += account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&system_program::ID);
+    account.realloc(0, false).map_err(Into::into)
+}
+
+/// Key that must sign liveness challenges: the configured issuer, or the
+/// GlobalRound admin while there is none
+fn liveness_issuer(account: Option<&AccountInfo>, global_round: &GlobalRound) -> Result<Pubkey> {
+    let Some(account) = account.filter(|a| a.owner == &crate::ID && !a.data_is_empty()) else {
+        return Ok(global_round.admin);
+    };
+
+    Ok(LivenessIssuer::try_deserialize(&mut &account.data.borrow()[..])?.issuer)
+}
+
+/// Gatekeeper that must co-sign registrations, or None while ungated (or
+/// the gate account was left off)
+fn registration_gatekeeper(account: Option<&AccountInfo>) -> Result<Option<Pubkey>> {
+    let Some(account) = account.filter(|a| a.owner == &crate::ID && !a.data_is_empty()) else {
+        return Ok(None);
+    };
+
+    let gate = RegistrationGate::try_deserialize(&mut &account.data.borrow()[..])?;
+    Ok(gate.enabled.then_some(gate.gatekeeper))
+}
+
+/// Scoring phase, or HashCount while the ScoringMigration account doesn't exist
+///
+/// `account` may only be left off before `migrate_scoring` has run.
+fn scoring_phase(
+    account: Option<&AccountInfo>,
+    global_round: &GlobalRound,
+) -> Result<ScoringPhase> {
+    let Some(account) = account else {
+        require!(
+            global_round.configs & CONFIG_SCORING_MIGRATION == 0,
+            ErrorCode::MissingConfigAccount
+        );
+        return Ok(ScoringPhase::HashCount);
+    };
+    if account.owner != &crate::ID || account.data_is_empty() {
+        return Ok(ScoringPhase::HashCount);
+    }
+
+    Ok(ScoringMigration::try_deserialize(&mut &account.data.borrow()[..])?.phase)
+}
+
+/// Highest number of extra difficulty bits `proof_points` rewards
+pub const MAX_PROOF_POINTS_SHIFT: u8 = 16;
+
+/// Score for one counted proof: doubles with each bit above the round's
+/// minimum difficulty, like the work behind it
+pub fn proof_points(difficulty: u8, min_difficulty: u8) -> u64 {
+    1 << difficulty
+        .saturating_sub(min_difficulty)
+        .min(MAX_PROOF_POINTS_SHIFT)
+}
+
+/// Score a miner starts with when converted from hash counts
+///
+/// Per-proof difficulty was never recorded, so every past counted proof is
+/// worth what a minimum-difficulty proof earns now: one point each.
+pub fn initial_score(total_hashes: u64) -> u64 {
+    total_hashes.saturating_mul(proof_points(0, 0))
+}
+
+fn convert_miner(miner: &mut Miner) {
+    miner.score = initial_score(miner.total_hashes);
+    miner.score_converted = true;
+}
+
+/// Grow an account of this program to `len` zeroed bytes, `payer` topping
+/// up its rent
+///
+/// Returns the length it had before, which tells callers the fields it
+/// predates. Accounts already `len` bytes or longer are left as they are.
+fn grow_program_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    len: usize,
+) -> Result<usize> {
+    require_keys_eq!(
+        *account.owner,
+        crate::ID,
+        anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram
+    );
+    let old_len = account.data_len();
+    if old_len >= len {
+        return Ok(old_len);
+    }
+
+    let shortfall = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.realloc(len, true)?;
+    Ok(old_len)
+}
+
+/// Create a PDA of this program with `len` zeroed bytes, or grow it to
+/// `len` if it exists (see `grow_program_account`)
+///
+/// For accounts whose layout has grown since they were first written, which
+/// `init_if_needed` can't load. `signer_seeds` are the PDA's, bump included.
+fn init_or_grow_program_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    len: usize,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    if account.owner == &crate::ID {
+        return grow_program_account(account, payer, system_program, len).map(|_| ());
+    }
+
+    // Allocate and assign rather than create_account, so lamports sent to
+    // the address beforehand can't block it
+    let shortfall = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    let signer = &[signer_seeds];
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: account.clone(),
+            },
+            signer,
+        ),
+        len as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Assign {
+                account_to_assign: account.clone(),
+            },
+            signer,
+        ),
+        &crate::ID,
+    )
+}
+
+/// Load an account of this program from raw account info, e.g. after
+/// `grow_program_account`
+fn load_program_account<T: AccountDeserialize>(account: &AccountInfo) -> Result<T> {
+    require_keys_eq!(
+        *account.owner,
+        crate::ID,
+        anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram
+    );
+    T::try_deserialize(&mut &account.try_borrow_data()?[..])
+}
+
+/// Write an account loaded with `load_program_account` back
+fn store_program_account<T: AccountSerialize>(account: &AccountInfo, value: &T) -> Result<()> {
+    let mut data = account.try_borrow_mut_data()?;
+    value.try_serialize(&mut &mut data[..])
+}
+
+/// Give the fields a Miner of `old_len` bytes predates the values
+/// `initialize_miner` would
+///
+/// Layouts only ever append fields, so a field is missing exactly when the
+/// old account ends before it does (offsets below include the
+/// discriminator). Growing the account already zeroed the new bytes; this
+/// spells out every default so the non-zero ones can't be missed.
+fn upgrade_miner_fields(miner: &mut Miner, old_len: usize) {
+    let missing = |end: usize| old_len < end;
+    if missing(67) {
+        miner.tier = 0;
+    }
+    if missing(99) {
+        miner.commitment = [0u8; 32];
+    }
+    if missing(107) {
+        miner.committed_slot = 0;
+    }
+    if missing(108) {
+        miner.score_bucket = NO_SCORE_BUCKET;
+    }
+    if missing(116) {
+        miner.round_number = 0;
+    }
+    if missing(120) {
+        miner.round_proofs = 0;
+    }
+    if missing(128) {
+        miner.capped_score = 0;
+    }
+    if missing(136) {
+        miner.score = 0;
+    }
+    if missing(137) {
+        miner.score_converted = false;
+    }
+}
+
+/// Give the fields a GlobalRound of `old_len` bytes predates the values
+/// `initialize_global_round` would (see `upgrade_miner_fields`)
+fn upgrade_global_round_fields(global_round: &mut GlobalRound, old_len: usize) {
+    let missing = |end: usize| old_len < end;
+    if missing(107) {
+        global_round.tier_step = 0;
+    }
+    if missing(115) {
+        global_round.round_duration = DEFAULT_ROUND_DURATION;
+    }
+    if missing(139) {
+        global_round.rejections = RejectionCounts::default();
+    }
+    if missing(143) {
+        global_round.max_round_proofs = 0;
+    }
+    // Config accounts created before the upgrade go unrecorded until their
+    // configure instruction runs again
+    if missing(144) {
+        global_round.configs = 0;
+    }
+}
+
+/// Lamports `set_metadata` charges per update, paid to the GlobalRound admin
+pub const METADATA_UPDATE_FEE: u64 = 10_000_000;
+
+/// Shortest display name `set_metadata` accepts
+pub const MIN_DISPLAY_NAME_LEN: usize = 3;
+
+/// Words a display name may not contain (see `is_profane`)
+const BLOCKED_NAME_WORDS: [&str; 8] = [
+    "fuck", "shit", "cunt", "bitch", "whore", "slut", "nazi", "rapist",
+];
+
+/// Text of a NUL-padded metadata field, if nothing follows the padding and
+/// the text is UTF-8
+pub fn metadata_str(field: &[u8]) -> Option<&str> {
+    let len = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    if field[len..].iter().any(|b| *b != 0) {
+        return None;
+    }
+
+    std::str::from_utf8(&field[..len]).ok()
+}
+
+/// NUL-pad `text` into a metadata field, if it fits
+pub fn pad_metadata<const N: usize>(text: &str) -> Option<[u8; N]> {
+    let mut field = [0u8; N];
+    field
+        .get_mut(..text.len())?
+        .copy_from_slice(text.as_bytes());
+    Some(field)
+}
+
+/// ASCII letters, digits, `_`, `-`, `.` and single inner spaces, at least
+/// MIN_DISPLAY_NAME_LEN long
+pub fn valid_display_name(name: &str) -> bool {
+    name.len() >= MIN_DISPLAY_NAME_LEN
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"_-. ".contains(&b))
+        && !name.starts_with(' ')
+        && !name.ends_with(' ')
+        && !name.contains("  ")
+}
+
+/// Empty, or an https URL without spaces or control characters
+pub fn valid_metadata_url(url: &str) -> bool {
+    url.is_empty()
+        || (url.len() > "https://".len()
+            && url.starts_with("https://")
+            && url.bytes().all(|b| b.is_ascii_graphic()))
+}
+
+/// Whether `name` contains a blocked word, ignoring case, separators and
+/// the usual digit-for-letter swaps
+pub fn is_profane(name: &str) -> bool {
+    let normalized: String = name
+        .chars()
+        .filter_map(|c| match c.to_ascii_lowercase() {
+            '0' => Some('o'),
+            '1' => Some('i'),
+            '3' => Some('e'),
+            '4' => Some('a'),
+            '5' => Some('s'),
+            '7' => Some('t'),
+            c if c.is_ascii_alphabetic() => Some(c),
+            _ => None,
+        })
+        .collect();
+
+    BLOCKED_NAME_WORDS
+        .iter()
+        .any(|word| normalized.contains(word))
+}
+
+fn check_metadata(name: &[u8; 32], url: &[u8; 64]) -> Result<()> {
+    let name = metadata_str(name).ok_or(ErrorCode::InvalidMetadataName)?;
+    require!(valid_display_name(name), ErrorCode::InvalidMetadataName);
+    require!(!is_profane(name), ErrorCode::ProfaneMetadataName);
+
+    let url = metadata_str(url).ok_or(ErrorCode::InvalidMetadataUrl)?;
+    require!(valid_metadata_url(url), ErrorCode::InvalidMetadataUrl);
+
+    Ok(())
+}
+
+/// Upper bounds of the self-reported latency buckets, in milliseconds; the
+/// last bucket holds everything slower
+pub const LATENCY_BUCKET_BOUNDS_MS: [u32; 4] = [50, 100, 250, 500];
+
+/// Number of latency buckets
+pub const LATENCY_BUCKET_COUNT: u8 = LATENCY_BUCKET_BOUNDS_MS.len() as u8 + 1;
+
+/// Latency bucket for a measured round trip
+pub fn latency_bucket(round_trip_ms: u32) -> u8 {
+    LATENCY_BUCKET_BOUNDS_MS
+        .iter()
+        .position(|bound| round_trip_ms < *bound)
+        .unwrap_or(LATENCY_BUCKET_BOUNDS_MS.len()) as u8
+}
+
+/// Two uppercase ASCII letters, or all zeros to clear the report
+pub fn valid_region(region: &[u8; 2]) -> bool {
+    *region == [0; 2] || region.iter().all(u8::is_ascii_uppercase)
+}
+
+/// Most leaders a RewardPool can pay per round
+pub const MAX_POOL_WINNERS: u8 = 10;
+
+/// Lamports each of `leaders` receives from `pot`, in proportion to score
+pub fn pool_payouts(pot: u64, leaders: &[PoolLeader]) -> Vec<u64> {
+    let total: u128 = leaders.iter().map(|leader| leader.score as u128).sum();
+    if total == 0 {
+        return vec![0; leaders.len()];
+    }
+
+    leaders
+        .iter()
+        .map(|leader| (pot as u128 * leader.score as u128 / total) as u64)
+        .collect()
+}
+
+/// Number of ScoreBucket PDAs: one for zero plus one per bit of a u64
+pub const SCORE_BUCKET_COUNT: u8 = 65;
+
+/// `Miner::score_bucket` value for miners not yet counted in any bucket
+pub const NO_SCORE_BUCKET: u8 = u8::MAX;
+
+/// ScoreBucket index for a score: its bit length
+pub fn score_bucket_index(score: u64) -> u8 {
+    (u64::BITS - score.leading_zeros()) as u8
+}
+
+/// Move a miner from its recorded bucket into the one matching its score
+///
+/// Does nothing if `to_bucket` isn't supplied; the miner keeps its recorded
+/// bucket until a later submit or `sync_score_bucket` moves it.
+fn move_score_bucket(
+    miner: &mut Miner,
+    from_bucket: Option<&mut ScoreBucket>,
+    to_bucket: Option<&mut ScoreBucket>,
+) -> Result<()> {
+    let Some(to_bucket) = to_bucket else {
+        return Ok(());
+    };
+
+    let target = score_bucket_index(miner.ranking_score());
+    require!(to_bucket.index == target, ErrorCode::WrongScoreBucket);
+
+    if miner.score_bucket == target {
+        return Ok(());
+    }
+
+    if miner.score_bucket != NO_SCORE_BUCKET {
+        let from_bucket = from_bucket.ok_or(ErrorCode::WrongScoreBucket)?;
+        require!(
+            from_bucket.index == miner.score_bucket,
+            ErrorCode::WrongScoreBucket
+        );
+        from_bucket.member_count = from_bucket.member_count.saturating_sub(1);
+    }
+
+    to_bucket.member_count = to_bucket.member_count.saturating_add(1);
+    miner.score_bucket = target;
+
+    Ok(())
+}
+
+/// Generate a new challenge based on clock data
+/// 
+/// Uses timestamp and slot to create pseudo-random challenge
+fn generate_challenge(clock: &Clock) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(clock.unix_timestamp.to_le_bytes());
+    hasher.update(clock.slot.to_le_bytes());
+    hasher.finalize().into()
+}
+
+// ============================================================================
+// Error Codes
+// ============================================================================
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Submitted hash does not meet minimum difficulty requirement")]
+    InsufficientDifficulty,
+    
+    #[msg("Too many submissions - wait at least 1 second between proofs")]
+    TooManySubmissions,
+    
+    #[msg("Difficulty is below the minimum required for this round")]
+    DifficultyTooLow,
+
+    #[msg("Difficulty is below the minimum required for this miner's tier")]
+    BelowTierDifficulty,
+
+    #[msg("Tier exceeds the maximum miner tier")]
+    InvalidTier,
+
+    #[msg("Round has expired - wait for the next challenge")]
+    RoundExpired,
+
+    #[msg("No pending commitment to reveal")]
+    NoPendingCommitment,
+
+    #[msg("Reveal is too early - wait for the commitment delay to pass")]
+    RevealTooEarly,
+
+    #[msg("Revealed nonce and salt do not match the commitment")]
+    CommitmentMismatch,
+
+    #[msg("Global round is already initialized - use update_global_params")]
+    AlreadyInitialized,
+
+    #[msg("Invalid global round parameters")]
+    InvalidParams,
+
+    #[msg("Score bucket index out of range")]
+    InvalidScoreBucket,
+
+    #[msg("Score bucket does not match the miner's recorded or current score")]
+    WrongScoreBucket,
+
+    #[msg("Arithmetic overflow")]
+    Overflow,
+
+    #[msg("Liveness challenge has expired or is open for too long")]
+    LivenessChallengeExpired,
+
+    #[msg("Reward pool is closed - configure a fee first")]
+    RewardPoolClosed,
+
+    #[msg("Reward pool still holds a finished round - settle it first")]
+    RewardPoolNotSettled,
+
+    #[msg("Round has not rotated yet")]
+    RoundNotOver,
+
+    #[msg("Winner accounts do not match the reward pool leaders")]
+    WrongPoolWinners,
+
+    #[msg("Display name must be 3-32 letters, digits, spaces, '_', '-' or '.'")]
+    InvalidMetadataName,
+
+    #[msg("Display name contains a blocked word")]
+    ProfaneMetadataName,
+
+    #[msg("Metadata URL must be empty or an https URL without spaces")]
+    InvalidMetadataUrl,
+
+    #[msg("Region must be an uppercase two-letter country code and a known latency bucket")]
+    InvalidRegion,
+
+    #[msg("Proof submissions are frozen for the scoring migration")]
+    SubmissionsFrozen,
+
+    #[msg("Miner stats were already converted to score")]
+    AlreadyConverted,
+
+    #[msg("Registration requires the gatekeeper's co-signature")]
+    RegistrationNotApproved,
+
+    #[msg("Miner garbage collection is off - configure a GC policy first")]
+    GcDisabled,
+
+    #[msg("Miner has submitted a proof within the GC policy's inactivity window")]
+    MinerStillActive,
+
+    #[msg("Liveness challenge was not signed by the liveness issuer")]
+    UnknownLivenessIssuer,
+
+    #[msg("A configured optional account was left off the instruction")]
+    MissingConfigAccount,
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_difficulty() {
+        // All zeros should pass any difficulty
+        let easy_hash = [0u8; 32];
+        assert!(check_difficulty(&easy_hash, 8));
+        assert!(check_difficulty(&easy_hash, 16));
+
+        // Hash starting with 0xFF should fail
+        let mut hard_hash = [0u8; 32];
+        hard_hash[0] = 0xFF;
+        assert!(!check_difficulty(&hard_hash, 1));
+
+        // Hash with one leading zero bit
+        let mut medium_hash = [0u8; 32];
         medium_hash[0] = 0b01111111;
         assert!(check_difficulty(&medium_hash, 1));
         assert!(!check_difficulty(&medium_hash, 2));
+
+        // Zero bytes carry over into the next one
+        let mut long_hash = [0u8; 32];
+        long_hash[1] = 0b00111111;
+        assert!(check_difficulty(&long_hash, 9));
+        assert!(check_difficulty(&long_hash, 10));
+        assert!(!check_difficulty(&long_hash, 11));
+        assert!(check_difficulty(&easy_hash, u8::MAX));
+    }
+
+    #[test]
+    fn test_earned_tier() {
+        assert_eq!(earned_tier(0), 0);
+        assert_eq!(earned_tier(99), 0);
+        assert_eq!(earned_tier(100), 1);
+        assert_eq!(earned_tier(9_999), 2);
+        assert_eq!(earned_tier(u32::MAX), MAX_TIER);
+    }
+
+    #[test]
+    fn test_tier_min_difficulty() {
+        // Tiers disabled
+        assert_eq!(tier_min_difficulty(8, 3, 0), 8);
+
+        assert_eq!(tier_min_difficulty(8, 0, 2), 8);
+        assert_eq!(tier_min_difficulty(8, 3, 2), 14);
+
+        // Saturates instead of wrapping
+        assert_eq!(tier_min_difficulty(250, 3, 4), u8::MAX);
+    }
+
+    #[test]
+    fn test_round_expired() {
+        assert!(!round_expired(1_000, 3_600, 1_000));
+        assert!(!round_expired(1_000, 3_600, 4_600));
+        assert!(round_expired(1_000, 3_600, 4_601));
+
+        // No overflow on absurd durations
+        assert!(!round_expired(1_000, i64::MAX, i64::MAX));
+    }
+
+    #[test]
+    fn test_liveness_challenge_open() {
+        assert!(liveness_challenge_open(1_060, 1_000));
+        assert!(liveness_challenge_open(
+            1_000 + MAX_LIVENESS_CHALLENGE_SECS,
+            1_000
+        ));
+
+        // Expired, or open longer than issuers may allow
+        assert!(!liveness_challenge_open(1_000, 1_000));
+        assert!(!liveness_challenge_open(
+            1_001 + MAX_LIVENESS_CHALLENGE_SECS,
+            1_000
+        ));
+        assert!(!liveness_challenge_open(i64::MAX, i64::MIN));
+    }
+
+    #[test]
+    fn test_submission_wait() {
+        assert_eq!(submission_wait(1_000, 1_000), MIN_SUBMISSION_INTERVAL);
+        assert_eq!(submission_wait(1_000, 1_001), 0);
+        assert_eq!(submission_wait(1_000, 5_000), 0);
+
+        // Skewed timestamps in the future don't lock the miner out
+        assert_eq!(submission_wait(5_000, 1_000), 0);
+
+        // No overflow at the extremes
+        assert_eq!(submission_wait(i64::MIN, i64::MAX), 0);
+        assert_eq!(submission_wait(i64::MAX, i64::MAX), MIN_SUBMISSION_INTERVAL);
+        assert_eq!(submission_wait(i64::MAX - 1, i64::MAX), 0);
+        assert_eq!(submission_wait(i64::MIN, i64::MIN), MIN_SUBMISSION_INTERVAL);
+    }
+
+    #[test]
+    fn test_commitment_hash() {
+        let salt = [7u8; 32];
+
+        assert_eq!(commitment_hash(42, &salt), commitment_hash(42, &salt));
+        assert_ne!(commitment_hash(42, &salt), commitment_hash(43, &salt));
+        assert_ne!(commitment_hash(42, &salt), commitment_hash(42, &[8u8; 32]));
+    }
+
+    #[test]
+    fn test_score_bucket_index() {
+        assert_eq!(score_bucket_index(0), 0);
+        assert_eq!(score_bucket_index(1), 1);
+        assert_eq!(score_bucket_index(2), 2);
+        assert_eq!(score_bucket_index(3), 2);
+        assert_eq!(score_bucket_index(4), 3);
+        assert_eq!(score_bucket_index(1 << 20), 21);
+        assert_eq!(score_bucket_index(u64::MAX), SCORE_BUCKET_COUNT - 1);
+    }
+
+    fn test_miner() -> Miner {
+        Miner {
+            authority: Pubkey::default(),
+            total_hashes: 0,
+            rounds_completed: 0,
+            last_hash_at: 0,
+            current_streak: 0,
+            best_difficulty: 0,
+            bump: 0,
+            tier: 0,
+            commitment: [0u8; 32],
+            committed_slot: 0,
+            score_bucket: NO_SCORE_BUCKET,
+            round_number: 0,
+            round_proofs: 0,
+            capped_score: 0,
+            score: 0,
+            score_converted: false,
+        }
+    }
+
+    /// Round that accepts any hash, so apply_proof can run without mining
+    fn test_global_round() -> GlobalRound {
+        GlobalRound {
+            current_challenge: [0u8; 32],
+            round_number: 1,
+            started_at: 0,
+            min_difficulty: 0,
+            total_hashes_submitted: 0,
+            total_rounds_completed: 0,
+            admin: Pubkey::default(),
+            bump: 0,
+            tier_step: 0,
+            round_duration: DEFAULT_ROUND_DURATION,
+            rejections: RejectionCounts::default(),
+            max_round_proofs: 0,
+            configs: 0,
+        }
+    }
+
+    fn test_clock(unix_timestamp: i64) -> Clock {
+        Clock {
+            unix_timestamp,
+            ..Clock::default()
+        }
+    }
+
+    /// First nonce whose proof meets `difficulty` in `global_round`
+    fn mine_test_nonce(miner: &Miner, global_round: &GlobalRound, difficulty: u8) -> u64 {
+        (0..)
+            .find(|nonce| {
+                let hash = hash_proof(&miner.authority, &global_round.current_challenge, *nonce);
+                check_difficulty(&hash, difficulty)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_apply_proof_saturates_lifetime_counters() {
+        let mut miner = Miner {
+            total_hashes: u64::MAX,
+            rounds_completed: u32::MAX,
+            current_streak: STREAK_PER_ROUND - 1,
+            ..test_miner()
+        };
+        let mut global_round = GlobalRound {
+            total_hashes_submitted: u64::MAX,
+            total_rounds_completed: u64::MAX,
+            ..test_global_round()
+        };
+
+        apply_proof(
+            &mut miner,
+            &mut global_round,
+            &test_clock(10),
+            0,
+            0,
+            None,
+            ScoringPhase::HashCount,
+        )
+        .unwrap();
+
+        assert_eq!(miner.total_hashes, u64::MAX);
+        assert_eq!(miner.rounds_completed, u32::MAX);
+        assert_eq!(miner.current_streak, 0);
+        assert_eq!(miner.last_hash_at, 10);
+        assert_eq!(global_round.total_hashes_submitted, u64::MAX);
+        assert_eq!(global_round.total_rounds_completed, u64::MAX);
+    }
+
+    #[test]
+    fn test_apply_proof_overflow_is_an_error() {
+        let mut miner = Miner {
+            current_streak: u32::MAX,
+            ..test_miner()
+        };
+        let mut global_round = test_global_round();
+
+        let err = apply_proof(
+            &mut miner,
+            &mut global_round,
+            &test_clock(10),
+            0,
+            0,
+            None,
+            ScoringPhase::HashCount,
+        )
+        .unwrap_err();
+        assert_eq!(err, error!(ErrorCode::Overflow));
+    }
+
+    #[test]
+    fn test_move_score_bucket() {
+        let mut miner = Miner {
+            total_hashes: 1,
+            ..test_miner()
+        };
+        let bucket = |index| ScoreBucket {
+            index,
+            member_count: 0,
+            bump: 0,
+        };
+
+        // Without buckets nothing moves
+        move_score_bucket(&mut miner, None, None).unwrap();
+        assert_eq!(miner.score_bucket, NO_SCORE_BUCKET);
+
+        // First placement needs no source bucket
+        let mut one = bucket(1);
+        move_score_bucket(&mut miner, None, Some(&mut one)).unwrap();
+        assert_eq!((miner.score_bucket, one.member_count), (1, 1));
+
+        // Same bucket is a no-op
+        move_score_bucket(&mut miner, None, Some(&mut one)).unwrap();
+        assert_eq!(one.member_count, 1);
+
+        // Crossing a power of two requires the recorded source bucket
+        miner.total_hashes = 2;
+        let mut two = bucket(2);
+        assert!(move_score_bucket(&mut miner, None, Some(&mut two)).is_err());
+        assert!(move_score_bucket(&mut miner, Some(&mut one), Some(&mut bucket(3))).is_err());
+
+        move_score_bucket(&mut miner, Some(&mut one), Some(&mut two)).unwrap();
+        assert_eq!(miner.score_bucket, 2);
+        assert_eq!((one.member_count, two.member_count), (0, 1));
+
+        // Converted miners are bucketed by score, not hashes
+        miner.score_converted = true;
+        miner.score = 1;
+        move_score_bucket(&mut miner, Some(&mut two), Some(&mut one)).unwrap();
+        assert_eq!(miner.score_bucket, 1);
+        assert_eq!((one.member_count, two.member_count), (1, 0));
+    }
+
+    /// Shared with testore-core's copy of `decayed_score`; change both
+    /// together or the bridge and the program disagree on scores
+    const DECAY_VECTORS: [(u64, u64, u16, u64); 8] = [
+        (1_000_000, 1, 100, 989_999),
+        (1_000_000, 2, 100, 980_099),
+        (1_000_000, 10, 100, 904_382),
+        (1_000_000, 100, 100, 366_032),
+        (u64::MAX, 1, 100, 18_262_276_632_972_456_098),
+        (12_345, 0, 100, 12_345),
+        (12_345, 7, 0, 12_345),
+        (1_000_000, 10_000, 100, 0),
+    ];
+
+    #[test]
+    fn test_apply_proof_caps_round_score() {
+        let mut miner = test_miner();
+        let mut global_round = GlobalRound {
+            max_round_proofs: 2,
+            ..test_global_round()
+        };
+
+        let counted: Vec<bool> = (1..=4)
+            .map(|t| {
+                apply_proof(
+                    &mut miner,
+                    &mut global_round,
+                    &test_clock(t),
+                    0,
+                    0,
+                    None,
+                    ScoringPhase::HashCount,
+                )
+                .unwrap()
+            })
+            .collect();
+        assert_eq!(counted, vec![true, true, false, false]);
+        assert_eq!((miner.total_hashes, miner.capped_score), (2, 2));
+        assert_eq!(miner.round_proofs, 4);
+        assert_eq!(global_round.total_hashes_submitted, 4);
+
+        // The count starts over with the next round
+        global_round.round_number += 1;
+        assert!(apply_proof(
+            &mut miner,
+            &mut global_round,
+            &test_clock(5),
+            0,
+            0,
+            None,
+            ScoringPhase::HashCount
+        )
+        .unwrap());
+        assert_eq!((miner.total_hashes, miner.capped_score), (3, 2));
+        assert_eq!((miner.round_number, miner.round_proofs), (2, 1));
+
+        assert!(within_round_cap(u32::MAX, 0));
+    }
+
+    #[test]
+    fn test_decayed_score_vectors() {
+        for (score, rounds, decay_bps, expected) in DECAY_VECTORS {
+            assert_eq!(
+                decayed_score(score, rounds, decay_bps),
+                expected,
+                "{} after {} rounds at {} bps",
+                score,
+                rounds,
+                decay_bps
+            );
+        }
+    }
+
+    #[test]
+    fn test_idle_rounds() {
+        assert_eq!(idle_rounds(1_000, 1_000, 3_600), 0);
+        assert_eq!(idle_rounds(1_000, 4_599, 3_600), 0);
+        assert_eq!(idle_rounds(1_000, 4_600, 3_600), 1);
+        assert_eq!(idle_rounds(1_000, 1_000 + 10 * 3_600, 3_600), 10);
+
+        // Skewed timestamps and unset durations never decay
+        assert_eq!(idle_rounds(5_000, 1_000, 3_600), 0);
+        assert_eq!(idle_rounds(1_000, 5_000, 0), 0);
+        assert_eq!(idle_rounds(i64::MIN, i64::MAX, 1), i64::MAX as u64);
+    }
+
+    #[test]
+    fn test_gc_eligible() {
+        let day = 24 * 60 * 60;
+        assert!(!gc_eligible(1_000, 1_000 + 30 * day - 1, 30));
+        assert!(gc_eligible(1_000, 1_000 + 30 * day, 30));
+
+        // Skewed timestamps never make a miner look idle
+        assert!(!gc_eligible(5_000, 1_000, 0));
+        assert!(gc_eligible(i64::MIN, i64::MAX, u16::MAX));
+    }
+
+    #[test]
+    fn test_apply_proof_decays_idle_miners() {
+        let mut miner = Miner {
+            total_hashes: 1_000_000,
+            score: 1_000_000,
+            score_converted: true,
+            ..test_miner()
+        };
+        let ten_rounds = 10 * DEFAULT_ROUND_DURATION;
+        let mut global_round = GlobalRound {
+            started_at: ten_rounds,
+            ..test_global_round()
+        };
+        let decay = ScoreDecay {
+            decay_bps: 100,
+            configured_at: 0,
+            bump: 0,
+        };
+
+        apply_proof(
+            &mut miner,
+            &mut global_round,
+            &test_clock(ten_rounds),
+            0,
+            0,
+            Some(&decay),
+            ScoringPhase::Score,
+        )
+        .unwrap();
+        assert_eq!(miner.score, 904_382 + 1);
+        // total_hashes is a plain count and never decays
+        assert_eq!(miner.total_hashes, 1_000_001);
+
+        // An active miner loses nothing
+        apply_proof(
+            &mut miner,
+            &mut global_round,
+            &test_clock(ten_rounds + 5),
+            0,
+            0,
+            Some(&decay),
+            ScoringPhase::Score,
+        )
+        .unwrap();
+        assert_eq!(miner.score, 904_382 + 2);
+
+        // Idle time from before the decay was configured is not charged
+        let decay = ScoreDecay {
+            configured_at: 2 * ten_rounds,
+            ..decay
+        };
+        global_round.started_at = 2 * ten_rounds;
+        apply_proof(
+            &mut miner,
+            &mut global_round,
+            &test_clock(2 * ten_rounds),
+            0,
+            0,
+            Some(&decay),
+            ScoringPhase::Score,
+        )
+        .unwrap();
+        assert_eq!(miner.score, 904_382 + 3);
+    }
+
+    #[test]
+    fn test_proof_points() {
+        assert_eq!(proof_points(8, 8), 1);
+        assert_eq!(proof_points(10, 8), 4);
+        // Below the minimum never reaches apply_proof, but mustn't underflow
+        assert_eq!(proof_points(5, 8), 1);
+        assert_eq!(proof_points(u8::MAX, 0), 1 << MAX_PROOF_POINTS_SHIFT);
+        assert_eq!(initial_score(1_234), 1_234);
+    }
+
+    #[test]
+    fn test_apply_proof_scoring_phases() {
+        let mut miner = Miner {
+            total_hashes: 5,
+            ..test_miner()
+        };
+        let mut global_round = test_global_round();
+        let nonce = mine_test_nonce(&miner, &global_round, 3);
+
+        let err = apply_proof(
+            &mut miner,
+            &mut global_round,
+            &test_clock(10),
+            nonce,
+            3,
+            None,
+            ScoringPhase::Frozen,
+        )
+        .unwrap_err();
+        assert_eq!(err, ErrorCode::SubmissionsFrozen.into());
+        assert_eq!(miner.total_hashes, 5);
+
+        // The first proof after resuming converts the miner, then scores
+        apply_proof(
+            &mut miner,
+            &mut global_round,
+            &test_clock(10),
+            nonce,
+            3,
+            None,
+            ScoringPhase::Score,
+        )
+        .unwrap();
+        assert!(miner.score_converted);
+        assert_eq!((miner.total_hashes, miner.score), (6, 5 + 8));
+
+        apply_proof(
+            &mut miner,
+            &mut global_round,
+            &test_clock(11),
+            0,
+            0,
+            None,
+            ScoringPhase::Score,
+        )
+        .unwrap();
+        assert_eq!((miner.total_hashes, miner.score), (7, 5 + 8 + 1));
+    }
+
+    #[test]
+    fn test_check_metadata() {
+        let name = |text| pad_metadata::<32>(text).unwrap();
+        let url = |text| pad_metadata::<64>(text).unwrap();
+
+        assert!(check_metadata(&name("Rig Farm_01"), &url("")).is_ok());
+        assert!(check_metadata(&name("rig.farm"), &url("https://example.com/rig")).is_ok());
+
+        for bad in ["ab", " leading", "two  spaces", "emoji🙂", "semi;colon"] {
+            assert!(check_metadata(&name(bad), &url("")).is_err(), "{}", bad);
+        }
+        for bad in ["http://example.com", "https://", "https://a b", "ftp://x"] {
+            assert!(check_metadata(&name("rig"), &url(bad)).is_err(), "{}", bad);
+        }
+
+        // Text after the padding is rejected rather than hidden
+        let mut smuggled = name("rig");
+        smuggled[10] = b'x';
+        assert!(check_metadata(&smuggled, &url("")).is_err());
+
+        assert!(is_profane("SH1T miner"));
+        assert!(is_profane("f.u.c.k"));
+        assert!(!is_profane("grape farm"));
+        assert!(pad_metadata::<32>(&"x".repeat(33)).is_none());
+    }
+
+    #[test]
+    fn test_region_report_validation() {
+        assert!(valid_region(b"DE"));
+        assert!(valid_region(&[0; 2]));
+        assert!(!valid_region(b"de"));
+        assert!(!valid_region(&[b'D', 0]));
+
+        assert_eq!(latency_bucket(0), 0);
+        assert_eq!(latency_bucket(50), 1);
+        assert_eq!(latency_bucket(499), 3);
+        assert_eq!(latency_bucket(u32::MAX), LATENCY_BUCKET_COUNT - 1);
+    }
+
+    #[test]
+    fn test_record_pool_score() {
+        let mut pool = RewardPool {
+            fee_lamports: 1_000,
+            top_k: 2,
+            round_number: 1,
+            pot: 0,
+            leader_count: 0,
+            leaders: Default::default(),
+            bump: 0,
+        };
+        let (a, b, c) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        pool.record_score(a, 1);
+        pool.record_score(b, 1);
+        pool.record_score(a, 2);
+        assert_eq!(pool.leader_count, 2);
+        assert_eq!(
+            pool.leaders[0],
+            PoolLeader {
+                authority: a,
+                score: 2
+            }
+        );
+
+        // Ties keep the incumbent; passing the lowest leader replaces it
+        pool.record_score(c, 1);
+        assert_eq!(pool.leaders[1].authority, b);
+        pool.record_score(c, 2);
+        assert_eq!(
+            pool.leaders[1],
+            PoolLeader {
+                authority: c,
+                score: 2
+            }
+        );
+        assert_eq!(pool.leader_count, 2);
+    }
+
+    #[test]
+    fn test_pool_payouts() {
+        let leader = |score| PoolLeader {
+            authority: Pubkey::new_unique(),
+            score,
+        };
+
+        assert_eq!(pool_payouts(1_000, &[leader(3), leader(1)]), vec![750, 250]);
+
+        // Rounds down; the dust stays in the pot
+        assert_eq!(pool_payouts(100, &[leader(1); 3]), vec![33, 33, 33]);
+
+        assert!(pool_payouts(1_000, &[]).is_empty());
+        assert_eq!(pool_payouts(u64::MAX, &[leader(u64::MAX)]), vec![u64::MAX]);
+    }
+
+    /// Changing these sizes breaks existing accounts, clients and off-chain
+    /// parsers. Update them only together with a migration plan.
+    #[test]
+    fn test_account_sizes() {
+        assert_eq!(8 + Miner::INIT_SPACE, 137);
+        assert_eq!(8 + GlobalRound::INIT_SPACE, 144);
+        assert_eq!(8 + ScoreBucket::INIT_SPACE, 18);
+        assert_eq!(8 + MinerAttestation::INIT_SPACE, 79);
+        assert_eq!(8 + StatsRoot::INIT_SPACE, 69);
+        assert_eq!(8 + LivenessProof::INIT_SPACE, 53);
+        assert_eq!(8 + LivenessIssuer::INIT_SPACE, 41);
+        assert_eq!(8 + RewardPool::INIT_SPACE, 435);
+        assert_eq!(8 + RoundScore::INIT_SPACE, 57);
+        assert_eq!(8 + ScoreDecay::INIT_SPACE, 19);
+        assert_eq!(8 + MinerMetadata::INIT_SPACE, 145);
+        assert_eq!(8 + MinerRegion::INIT_SPACE, 52);
+        assert_eq!(8 + ScoringMigration::INIT_SPACE, 26);
+        assert_eq!(8 + RegistrationGate::INIT_SPACE, 42);
+        assert_eq!(8 + GcPolicy::INIT_SPACE, 43);
+        assert_eq!(8 + MinerArchive::INIT_SPACE, 88);
+    }
+
+    /// Accounts from the first layouts get the defaults `initialize_*` would
+    #[test]
+    fn test_upgrade_fields_from_first_layouts() {
+        let mut miner = test_miner();
+        miner.score_bucket = 0;
+        miner.total_hashes = 40;
+        upgrade_miner_fields(&mut miner, 66);
+        assert_eq!(miner.score_bucket, NO_SCORE_BUCKET);
+        assert_eq!(miner.total_hashes, 40);
+        assert!(!miner.score_converted);
+
+        // Already current: nothing is reset
+        let mut current = test_miner();
+        current.score_bucket = 3;
+        current.score = 9;
+        current.score_converted = true;
+        upgrade_miner_fields(&mut current, 8 + Miner::INIT_SPACE);
+        assert_eq!(current.score_bucket, 3);
+        assert_eq!(current.score, 9);
+        assert!(current.score_converted);
+
+        let mut global_round = test_global_round();
+        global_round.round_duration = 0;
+        global_round.max_round_proofs = 5;
+        upgrade_global_round_fields(&mut global_round, 106);
+        assert_eq!(global_round.round_duration, DEFAULT_ROUND_DURATION);
+        assert_eq!(global_round.max_round_proofs, 0);
+
+        // Predates only the proof cap
+        let mut global_round = test_global_round();
+        global_round.round_duration = 60;
+        global_round.max_round_proofs = 5;
+        upgrade_global_round_fields(&mut global_round, 139);
+        assert_eq!(global_round.round_duration, 60);
+        assert_eq!(global_round.max_round_proofs, 0);
+
+        // Predates only the config bits
+        let mut global_round = test_global_round();
+        global_round.max_round_proofs = 5;
+        global_round.configs = CONFIG_SCORE_DECAY;
+        upgrade_global_round_fields(&mut global_round, 143);
+        assert_eq!(global_round.max_round_proofs, 5);
+        assert_eq!(global_round.configs, 0);
+    }
+
+    #[test]
+    fn test_serialized_sizes_match_init_space() {
+        let miner = Miner {
+            authority: Pubkey::default(),
+            total_hashes: u64::MAX,
+            rounds_completed: u32::MAX,
+            last_hash_at: i64::MAX,
+            current_streak: u32::MAX,
+            best_difficulty: u8::MAX,
+            bump: u8::MAX,
+            tier: MAX_TIER,
+            commitment: [u8::MAX; 32],
+            committed_slot: u64::MAX,
+            score_bucket: u8::MAX,
+            round_number: u64::MAX,
+            round_proofs: u32::MAX,
+            capped_score: u64::MAX,
+            score: u64::MAX,
+            score_converted: true,
+        };
+        assert_eq!(miner.try_to_vec().unwrap().len(), Miner::INIT_SPACE);
+
+        let global_round = GlobalRound {
+            current_challenge: [u8::MAX; 32],
+            round_number: u64::MAX,
+            started_at: i64::MAX,
+            min_difficulty: u8::MAX,
+            total_hashes_submitted: u64::MAX,
+            total_rounds_completed: u64::MAX,
+            admin: Pubkey::default(),
+            bump: u8::MAX,
+            tier_step: u8::MAX,
+            round_duration: i64::MAX,
+            rejections: RejectionCounts {
+                difficulty: u64::MAX,
+                rate_limited: u64::MAX,
+                stale: u64::MAX,
+            },
+            max_round_proofs: u32::MAX,
+            configs: u8::MAX,
+        };
+        assert_eq!(
+            global_round.try_to_vec().unwrap().len(),
+            GlobalRound::INIT_SPACE
+        );
+
+        let bucket = ScoreBucket {
+            index: u8::MAX,
+            member_count: u64::MAX,
+            bump: u8::MAX,
+        };
+        assert_eq!(bucket.try_to_vec().unwrap().len(), ScoreBucket::INIT_SPACE);
+
+        let attestation = MinerAttestation {
+            version: u8::MAX,
+            authority: Pubkey::default(),
+            total_hashes: u64::MAX,
+            rounds_completed: u32::MAX,
+            best_difficulty: u8::MAX,
+            slot: u64::MAX,
+            timestamp: i64::MAX,
+            bump: u8::MAX,
+            score: u64::MAX,
+        };
+        assert_eq!(
+            attestation.try_to_vec().unwrap().len(),
+            MinerAttestation::INIT_SPACE
+        );
+
+        let stats_root = StatsRoot {
+            root: [u8::MAX; 32],
+            miner_count: u32::MAX,
+            slot: u64::MAX,
+            posted_at: i64::MAX,
+            sequence: u64::MAX,
+            bump: u8::MAX,
+        };
+        assert_eq!(
+            stats_root.try_to_vec().unwrap().len(),
+            StatsRoot::INIT_SPACE
+        );
+
+        let liveness = LivenessProof {
+            authority: Pubkey::default(),
+            verified_at: i64::MAX,
+            verifications: u32::MAX,
+            bump: u8::MAX,
+        };
+        assert_eq!(
+            liveness.try_to_vec().unwrap().len(),
+            LivenessProof::INIT_SPACE
+        );
+
+        let issuer = LivenessIssuer {
+            issuer: Pubkey::default(),
+            bump: u8::MAX,
+        };
+        assert_eq!(
+            issuer.try_to_vec().unwrap().len(),
+            LivenessIssuer::INIT_SPACE
+        );
+
+        let pool = RewardPool {
+            fee_lamports: u64::MAX,
+            top_k: MAX_POOL_WINNERS,
+            round_number: u64::MAX,
+            pot: u64::MAX,
+            leader_count: MAX_POOL_WINNERS,
+            leaders: Default::default(),
+            bump: u8::MAX,
+        };
+        assert_eq!(pool.try_to_vec().unwrap().len(), RewardPool::INIT_SPACE);
+
+        let metadata = MinerMetadata {
+            authority: Pubkey::default(),
+            name: [u8::MAX; 32],
+            url: [u8::MAX; 64],
+            updated_at: i64::MAX,
+            bump: u8::MAX,
+        };
+        assert_eq!(
+            metadata.try_to_vec().unwrap().len(),
+            MinerMetadata::INIT_SPACE
+        );
+
+        let region = MinerRegion {
+            authority: Pubkey::default(),
+            region: [u8::MAX; 2],
+            latency_bucket: u8::MAX,
+            reported_at: i64::MAX,
+            bump: u8::MAX,
+        };
+        assert_eq!(region.try_to_vec().unwrap().len(), MinerRegion::INIT_SPACE);
+
+        let score_decay = ScoreDecay {
+            decay_bps: u16::MAX,
+            configured_at: i64::MAX,
+            bump: u8::MAX,
+        };
+        assert_eq!(
+            score_decay.try_to_vec().unwrap().len(),
+            ScoreDecay::INIT_SPACE
+        );
+
+        let gate = RegistrationGate {
+            gatekeeper: Pubkey::default(),
+            enabled: true,
+            bump: u8::MAX,
+        };
+        assert_eq!(
+            gate.try_to_vec().unwrap().len(),
+            RegistrationGate::INIT_SPACE
+        );
+
+        let policy = GcPolicy {
+            inactive_days: u16::MAX,
+            sweep_wallet: Pubkey::default(),
+            bump: u8::MAX,
+        };
+        assert_eq!(policy.try_to_vec().unwrap().len(), GcPolicy::INIT_SPACE);
+
+        let archive = MinerArchive {
+            authority: Pubkey::default(),
+            total_hashes: u64::MAX,
+            rounds_completed: u32::MAX,
+            last_hash_at: i64::MAX,
+            best_difficulty: u8::MAX,
+            tier: MAX_TIER,
+            capped_score: u64::MAX,
+            score: u64::MAX,
+            score_converted: true,
+            archived_at: i64::MAX,
+            bump: u8::MAX,
+        };
+        assert_eq!(
+            archive.try_to_vec().unwrap().len(),
+            MinerArchive::INIT_SPACE
+        );
+
+        let round_score = RoundScore {
+            authority: Pubkey::default(),
+            round_number: u64::MAX,
+            score: u64::MAX,
+            bump: u8::MAX,
+        };
+        assert_eq!(
+            round_score.try_to_vec().unwrap().len(),
+            RoundScore::INIT_SPACE
+        );
     }
 
     #[test]
@@ -0,0 +1,91 @@
+//! `miner <pubkey>`: one miner's stats, rank and projected airdrop
+
+use crate::{format_number, Config};
+use anyhow::{anyhow, Result};
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use testore_core::AllocationParams;
+
+/// Print on-chain stats and leaderboard standing for `authority`
+pub fn run(config: &Config, authority: &Pubkey) -> Result<()> {
+    let client =
+        RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed());
+
+    let params = AllocationParams::default();
+    let ranked =
+        testore_core::fetch_ranked_miners_across(&client, &config.program_ids(), usize::MAX)?;
+
+    let miner = ranked
+        .iter()
+        .find(|m| m.authority == *authority)
+        .ok_or_else(|| anyhow!("No miner account found for {}", authority))?;
+    let standing = testore_core::miner_standing(&ranked, authority, &params)
+        .expect("miner is on the leaderboard");
+
+    println!("{}", "═══ Miner Stats ═══".bright_yellow().bold());
+    println!(
+        "   Authority:       {}",
+        authority.to_string().bright_yellow()
+    );
+    println!(
+        "   Total Hashes:    {}",
+        format_number(miner.total_hashes).bright_cyan()
+    );
+    println!(
+        "   Rounds:          {}",
+        format_number(miner.rounds_completed as u64).bright_cyan()
+    );
+    println!("   Current Streak:  {}", miner.current_streak);
+    println!("   Best Difficulty: {}", miner.best_difficulty);
+    if let Some(tier) = miner.tier {
+        println!("   Tier:            {}", tier);
+    }
+    if let Some(score) = miner.score {
+        println!("   Score:           {}", format_number(score).bright_cyan());
+    }
+    if let Some(capped) = miner.capped_score.filter(|capped| *capped > 0) {
+        println!(
+            "   Over Round Cap:  {} (not counted)",
+            format_number(capped).bright_yellow()
+        );
+    }
+    println!(
+        "   Last Active:     {}",
+        chrono::DateTime::from_timestamp(miner.last_hash_at, 0)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_else(|| "-".to_string())
+    );
+    println!();
+
+    println!("{}", "═══ Standing ═══".bright_yellow().bold());
+    println!(
+        "   Rank:            #{} of {}",
+        standing.rank.to_string().bright_green(),
+        format_number(standing.total_miners as u64)
+    );
+    println!("   Percentile:      top {:.2}%", standing.top_percent);
+    println!(
+        "   Projected:       {} TESTORE",
+        format_number(standing.projected_tokens)
+            .bright_cyan()
+            .bold()
+    );
+
+    if miner.total_hashes < params.minimum_hashes {
+        println!(
+            "   {} {} more hashes to reach the {} hash minimum",
+            "ℹ️".bright_blue(),
+            format_number(params.minimum_hashes - miner.total_hashes),
+            format_number(params.minimum_hashes)
+        );
+    } else if standing.rank > params.top_miners {
+        println!(
+            "   {} Only the top {} miners receive an airdrop",
+            "ℹ️".bright_blue(),
+            format_number(params.top_miners as u64)
+        );
+    }
+
+    Ok(())
+}
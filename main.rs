@@ -1,15 +1,22 @@
 use anyhow::{anyhow, Result};
+use clap::{Args, Parser, Subcommand};
 use colored::*;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    native_token::lamports_to_sol,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    signature::{Keypair, Signature, Signer},
 };
-use std::collections::HashMap;
+use std::collections::BTreeSet;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use testore_core::{
+    AllocationParams, DuplicatePolicy, Ledger, RankingMetric, ScoringPhase, Snapshot,
+    TransferStatus,
+};
 
 /// TestORE Mainnet Airdrop Bridge
 ///
@@ -19,19 +26,335 @@ use std::str::FromStr;
 /// 3. Distributes airdrops to eligible wallets
 ///
 /// ## Configuration
-/// Set these environment variables:
-/// - TESTNET_RPC: Testnet RPC endpoint
+/// Set these environment variables, or run `init` to write them to
+/// testore-bridge.env, which is loaded at startup (see setup.rs for the
+/// precedence between flags, env, that file and the Solana CLI config):
+/// - TESTORE_CLUSTER: Default for --cluster
+/// - TESTNET_RPC: Testnet RPC endpoint, also `--url` (defaults to the Solana CLI's,
+///   then the --cluster profile's)
+/// - HISTORY_RPC: Comma-separated archival RPC endpoints for transaction history
 /// - MAINNET_RPC: Mainnet RPC endpoint  
-/// - AIRDROP_KEYPAIR: Path to mainnet funding wallet
-/// - PROGRAM_ID: TestORE program ID on testnet
+/// - AIRDROP_KEYPAIR: Path to mainnet funding wallet, also `--keypair` (defaults to
+///   the Solana CLI's configured keypair)
+/// - PROGRAM_ID: TestORE program ID on testnet (defaults to the --cluster profile's)
+/// - LEGACY_PROGRAM_IDS: Comma-separated earlier deployments whose miners still count
+/// - TESTORE_MINT: Mainnet TESTORE mint (required to execute airdrops)
+/// - ORACLE_KEYPAIR: Testnet admin wallet for `post-root` (defaults to AIRDROP_KEYPAIR)
+/// - CRANK_KEYPAIR: Testnet admin wallet for `crank` (defaults to AIRDROP_KEYPAIR)
+/// - ALERT_WEBHOOK_URL: Optional webhook for crank failures and `watch` anomalies
+/// - RPC_REQUESTS_PER_SEC / RPC_BURST: Shared request budget per RPC endpoint
+/// - METRICS_TEXTFILE: Write Prometheus metrics here for node_exporter (see metrics.rs)
+/// - EXPECTED_MAINNET_GENESIS: Override the mainnet-beta genesis check for rehearsals
+/// - KEYPAIR_PASSPHRASE: Passphrase for encrypted keypairs (prompted for when unset)
+///
+/// `--cluster testnet|devnet|localnet` picks a profile embedded at build
+/// time (see cluster.rs) and checks TESTNET_RPC's genesis against it.
+///
+/// Exit codes are stable and `--output json` ends with a machine-readable
+/// run summary (see outcome.rs).
+///
+/// Any keypair may be an age passphrase-encrypted file (see `encrypt-keypair`),
+/// a Ledger (`usb://ledger`) or a remote signing service URL (see signer.rs).
+
+mod chaos;
+mod cluster;
+mod costs;
+mod crank;
+mod debug;
+mod distribute;
+mod estimate;
+mod export;
+mod fork;
+mod gc;
+mod history;
+mod lookup;
+mod metrics;
+mod migrate;
+mod mint;
+mod oracle;
+mod outcome;
+mod preflight;
+mod program;
+mod setup;
+mod signer;
+mod sweep;
+mod watch;
+
+use chaos::{Chaos, Fault};
+use distribute::PendingOutcome;
+
+const SNAPSHOT_PATH: &str = "airdrop_snapshot.json";
+const SNAPSHOT_TEXT_PATH: &str = "airdrop_snapshot.txt";
+const LEDGER_PATH: &str = "airdrop_ledger.json";
+const LEADERBOARD_CACHE_PATH: &str = "leaderboard_cache.json";
+
+#[derive(Parser)]
+#[command(name = "testore-bridge", about = "TestORE Mainnet Airdrop Bridge")]
+struct Cli {
+    /// Runs the airdrop when omitted
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Cluster the TestORE program is read from
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t,
+        env = "TESTORE_CLUSTER"
+    )]
+    cluster: cluster::Cluster,
+
+    /// Also print a JSON run summary as the last line of stdout
+    ///
+    /// Goes before the subcommand: `leaderboard export` has its own --output.
+    #[arg(long, value_enum, default_value_t)]
+    output: outcome::OutputFormat,
+
+    /// Funding wallet; also the admin wallet unless ORACLE_/CRANK_KEYPAIR are set
+    #[arg(long, global = true, env = "AIRDROP_KEYPAIR")]
+    keypair: Option<String>,
+
+    /// Testnet RPC endpoint
+    #[arg(long, short = 'u', global = true, env = "TESTNET_RPC")]
+    url: Option<String>,
+
+    #[command(flatten)]
+    airdrop: AirdropArgs,
+}
+
+#[derive(Args)]
+struct AirdropArgs {
+    /// Memo attached to every airdrop transfer
+    #[arg(long)]
+    memo: Option<String>,
+
+    /// JSON map of excluded wallets to the reason sent to them in a memo
+    #[arg(long)]
+    flagged: Option<PathBuf>,
+
+    /// Send the airdrop on mainnet instead of previewing it
+    #[arg(long)]
+    execute: bool,
+
+    /// Rehearse this run's chunk on a local validator forked from mainnet
+    #[arg(long, conflicts_with = "execute")]
+    fork_simulate: bool,
+
+    /// Recipient wallets (largest first) cloned into the fork
+    #[arg(long, default_value_t = 25, requires = "fork_simulate")]
+    fork_sample: usize,
+
+    /// Inject random faults into this share of fork transfers and check the
+    /// retries still pay everyone exactly once
+    #[arg(long, requires = "fork_simulate")]
+    chaos: Option<f64>,
+
+    /// Seed for --chaos, to replay a run's faults
+    #[arg(long, requires = "chaos")]
+    chaos_seed: Option<u64>,
+
+    /// Skip the typed confirmation for --execute (for automation)
+    #[arg(long, requires = "execute")]
+    yes: bool,
+
+    /// How to merge Miner accounts sharing an authority: sum, max or reject
+    #[arg(long, default_value = "sum")]
+    duplicate_policy: DuplicatePolicy,
+
+    /// Extra allocation for miners with a recent liveness proof, in basis points
+    #[arg(long, default_value_t = 0)]
+    liveness_boost_bps: u64,
+
+    /// Leave out miners with no proof in this many days (0 = keep everyone)
+    #[arg(long, default_value_t = 0)]
+    max_inactive_days: u64,
+
+    /// How recent a liveness proof must be to count, in hours
+    #[arg(long, default_value_t = 7 * 24)]
+    liveness_window_hours: i64,
+
+    /// Cap on TESTORE sent by one run; the rest waits for the next run
+    #[arg(long)]
+    max_tokens_per_run: Option<u64>,
+
+    /// Cap on recipients paid by one run; the rest waits for the next run
+    #[arg(long)]
+    max_recipients_per_run: Option<usize>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Post the merkle root of all miner scores to the testnet program
+    PostRoot,
+
+    /// Check the posted stats root against one computed from testnet
+    VerifyRoot,
+
+    /// Rotate GlobalRound whenever it passes its duration
+    Crank(crank::CrankArgs),
+
+    /// Alert on GlobalRound anomalies (stuck rotation, difficulty cap, stalls)
+    Watch(watch::WatchArgs),
+
+    /// Report testnet SOL locked as rent by program accounts
+    Costs(costs::CostsArgs),
+
+    /// Project daily proofs, score and airdrop tokens for a hashrate
+    EstimateEarnings(estimate::EstimateArgs),
+
+    /// Show a miner's stats, rank and projected airdrop
+    Miner {
+        /// Miner wallet address
+        authority: Pubkey,
+    },
 
-const TOKENS_PER_MILLION_HASHES: u64 = 100;
-const MINIMUM_HASHES_FOR_AIRDROP: u64 = 100_000;
-const TOP_MINERS_TO_AIRDROP: usize = 1000;
+    /// Diagnose failed testnet transactions
+    Debug {
+        #[command(subcommand)]
+        command: debug::DebugCommand,
+    },
+
+    /// Migrate leaderboard scoring to difficulty-weighted points
+    MigrateScoring {
+        #[command(subcommand)]
+        command: migrate::MigrateScoringCommand,
+    },
+
+    /// Close idle miners and sweep their rent (see GcPolicy)
+    Gc {
+        #[command(subcommand)]
+        command: gc::GcCommand,
+    },
+
+    /// Cache and export the testnet leaderboard
+    Leaderboard {
+        #[command(subcommand)]
+        command: LeaderboardCommand,
+    },
+
+    /// Create and manage the mainnet TESTORE mint
+    Mint {
+        #[command(subcommand)]
+        command: mint::MintCommand,
+    },
+
+    /// Verify the deployed testnet program and manage its upgrade authority
+    Program {
+        #[command(subcommand)]
+        command: program::ProgramCommand,
+    },
+
+    /// Retry recipients quarantined after permanent transfer failures
+    RetryQuarantined {
+        /// Skip the typed confirmation (for automation)
+        #[arg(long)]
+        yes: bool,
+    },
+
+    /// Recompute a snapshot's allocations and check its published hash
+    Reproduce {
+        #[arg(default_value = SNAPSHOT_PATH)]
+        snapshot: PathBuf,
+    },
+
+    /// Compare allocation parameters against a snapshot's recorded inputs
+    Sweep(sweep::SweepArgs),
+
+    /// Generate Grafana dashboards for the exported metrics
+    Dashboards {
+        #[command(subcommand)]
+        command: metrics::DashboardsCommand,
+    },
+
+    /// Encrypt a plaintext JSON keypair with a passphrase
+    EncryptKeypair {
+        /// Plaintext Solana JSON keypair
+        input: PathBuf,
+
+        /// Where to write the encrypted keypair
+        output: PathBuf,
+    },
+
+    /// Interactively write testore-bridge.env for first-time setup
+    Init,
+
+    /// Print a shell completion script
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+}
+
+#[derive(Subcommand)]
+enum LeaderboardCommand {
+    /// Fetch every miner from testnet into the leaderboard cache
+    Refresh,
+
+    /// Render the cached leaderboard as JSON, RSS or Markdown
+    Export(export::ExportArgs),
+
+    /// Summarize testnet load by miners' self-reported regions
+    Regions {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+}
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> std::process::ExitCode {
     env_logger::init();
+    if let Err(e) = setup::load_env_file(setup::ENV_FILE_PATH) {
+        eprintln!("Error: {:#}", e);
+        return outcome::Status::ConfigError.exit_code().into();
+    }
+    let cli = Cli::parse();
+    let output = cli.output;
+    if output == outcome::OutputFormat::Json {
+        colored::control::set_override(false);
+    }
+
+    let mut summary = outcome::Summary::default();
+    let result = run(cli, &mut summary).await;
+    if let Err(e) = metrics::write_textfile() {
+        log::warn!("Could not write METRICS_TEXTFILE: {:#}", e);
+    }
+
+    outcome::finish(output, result, &summary).into()
+}
+
+async fn run(cli: Cli, summary: &mut outcome::Summary) -> Result<()> {
+    // Exports go to stdout and need neither RPC nor a wallet
+    if let Some(Command::Leaderboard {
+        command: LeaderboardCommand::Export(args),
+    }) = &cli.command
+    {
+        return export::export(args);
+    }
+
+    // Works from the snapshot alone so auditors need no RPC or wallet
+    if let Some(Command::Reproduce { snapshot }) = &cli.command {
+        return reproduce(snapshot);
+    }
+    if let Some(Command::Sweep(args)) = &cli.command {
+        return sweep::run(args);
+    }
+    if let Some(Command::Dashboards { command }) = &cli.command {
+        return metrics::run(command);
+    }
+
+    // Needs no config, and the plaintext keypair must not be loaded as one
+    if let Some(Command::EncryptKeypair { input, output }) = &cli.command {
+        return encrypt_keypair(input, output);
+    }
+    if let Some(Command::Init) = &cli.command {
+        return setup::init();
+    }
+    if let Some(Command::Completions { shell }) = &cli.command {
+        setup::completions(*shell);
+        return Ok(());
+    }
 
     println!(
         "\n{} {}\n",
@@ -40,13 +363,20 @@ async fn main() -> Result<()> {
     );
 
     // Load configuration
-    let config = load_config()?;
+    let config = load_config(cli.cluster, cli.url.as_deref(), cli.keypair.as_deref())
+        .map_err(outcome::config_error)?;
 
     println!("{}", "═".repeat(60).bright_black());
     println!(
         "{} {}",
+        "Cluster:".bright_cyan(),
+        config.cluster.name().bright_white()
+    );
+    println!(
+        "{} {} {}",
         "Testnet RPC:".bright_cyan(),
-        config.testnet_rpc.bright_white()
+        config.testnet_rpc.bright_white(),
+        format!("({})", config.testnet_rpc_source).bright_black()
     );
     println!(
         "{} {}",
@@ -61,28 +391,149 @@ async fn main() -> Result<()> {
     println!(
         "{} {}",
         "Funding Wallet:".bright_cyan(),
-        config.keypair.pubkey().to_string().bright_yellow()
+        config.signer.pubkey().to_string().bright_yellow()
     );
     println!("{}", "═".repeat(60).bright_black());
     println!();
 
+    // Mint management only touches mainnet
+    if let Some(Command::Mint { command }) = &cli.command {
+        return mint::run(&config, command);
+    }
+
+    // Works even if the deployment is too broken to pass the testnet check
+    if let Some(Command::Program { command }) = &cli.command {
+        return program::run(&config, command);
+    }
+
+    // Every other command reads testnet state; fail fast if it isn't there
+    preflight::check_testnet(
+        &RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed()),
+        &config.program_id,
+        config.cluster,
+    )
+    .map_err(outcome::config_error)?;
+
+    match cli.command {
+        None => run_airdrop(&config, &cli.airdrop, summary).await,
+        Some(Command::PostRoot) => oracle::post_root(&config),
+        Some(Command::VerifyRoot) => oracle::verify_root(&config),
+        Some(Command::Miner { authority }) => lookup::run(&config, &authority),
+        Some(Command::EstimateEarnings(args)) => estimate::run(&config, &args),
+        Some(Command::Costs(args)) => costs::run(&config, &args),
+        Some(Command::Debug { command }) => debug::run(&config, &command),
+        Some(Command::MigrateScoring { command }) => migrate::run(&config, &command),
+        Some(Command::Gc { command }) => gc::run(&config, &command),
+        Some(Command::Crank(args)) => crank::run(&config, &args).await,
+        Some(Command::Watch(args)) => watch::run(&config, &args).await,
+        Some(Command::RetryQuarantined { yes }) => {
+            retry_quarantined(&config, &cli.airdrop, yes, summary).await
+        }
+        Some(Command::Leaderboard { command }) => match command {
+            LeaderboardCommand::Refresh => export::refresh_cache(&config),
+            LeaderboardCommand::Regions { json } => export::regions(&config, json),
+            LeaderboardCommand::Export(_) => unreachable!("handled before loading config"),
+        },
+        Some(Command::Mint { .. }) | Some(Command::Program { .. }) => {
+            unreachable!("handled before the testnet check")
+        }
+        Some(Command::Reproduce { .. })
+        | Some(Command::Sweep(_))
+        | Some(Command::Dashboards { .. })
+        | Some(Command::EncryptKeypair { .. })
+        | Some(Command::Init)
+        | Some(Command::Completions { .. }) => {
+            unreachable!("handled before loading config")
+        }
+    }
+}
+
+async fn run_airdrop(
+    config: &Config,
+    args: &AirdropArgs,
+    summary: &mut outcome::Summary,
+) -> Result<()> {
     // Create RPC clients
     let testnet_client = RpcClient::new_with_commitment(
         config.testnet_rpc.clone(),
         CommitmentConfig::confirmed(),
     );
 
-    let _mainnet_client = RpcClient::new_with_commitment(
-        config.mainnet_rpc.clone(),
-        CommitmentConfig::confirmed(),
-    );
+    let mainnet_client =
+        RpcClient::new_with_commitment(config.mainnet_rpc.clone(), CommitmentConfig::confirmed());
+
+    preflight::check_mainnet(&mainnet_client, config.mint.as_ref())
+        .map_err(outcome::config_error)?;
+    println!();
 
     // Step 1: Fetch leaderboard from testnet
     println!(
         "{} Fetching testnet leaderboard...\n",
         "📊".bright_cyan()
     );
-    let leaderboard = fetch_testnet_leaderboard(&testnet_client, &config.program_id)?;
+    // Once the scoring migration resumes submissions, proofs are credited to
+    // score and the airdrop follows; the metric is recorded with the params
+    let phase = testore_core::fetch_scoring_migration(&testnet_client, &config.program_id)?
+        .map_or(ScoringPhase::HashCount, |migration| migration.phase);
+    let params = AllocationParams {
+        duplicate_policy: args.duplicate_policy,
+        liveness_boost_bps: args.liveness_boost_bps,
+        max_inactive_days: args.max_inactive_days,
+        metric: RankingMetric::for_phase(phase),
+        ..AllocationParams::default()
+    };
+    // Recorded in the snapshot so the run can be traced back to the chain
+    let round = testore_core::fetch_global_round(&testnet_client, &config.program_id)?;
+    testore_core::throttle(&testnet_client);
+    let slot = testnet_client.get_slot()?;
+    let (miners, breakdown) =
+        testore_core::fetch_miners_across(&testnet_client, &config.program_ids())?;
+    let (mut leaderboard, merges) =
+        testore_core::consolidate_duplicates(miners, params.duplicate_policy)?;
+    for miner in &leaderboard {
+        metrics::metrics()
+            .miner_best_difficulty
+            .observe(miner.best_difficulty as f64);
+    }
+
+    // last_hash_at is cluster time, so idleness is measured against it too
+    let now = crank::cluster_time(&testnet_client)?;
+    let inactive = testore_core::exclude_inactive(&mut leaderboard, &params, now);
+    if let Some(inactive) = &inactive {
+        println!(
+            "{} Excluded {} miners ({} hashes) with no proof in {} days",
+            "💤".bright_yellow(),
+            inactive.miners.to_string().bright_yellow(),
+            format_number(inactive.total_hashes),
+            inactive.max_inactive_days
+        );
+    }
+
+    // On-chain decay is only charged on a miner's next proof, so charge idle
+    // miners up to now before ranking them
+    let decay = testore_core::fetch_score_decay(&testnet_client, &config.program_id)?;
+    if let Some(decay) = decay.filter(|decay| decay.decay_bps > 0) {
+        let decayed =
+            testore_core::apply_score_decay(&mut leaderboard, &decay, now, round.round_duration);
+        println!(
+            "{} Decayed {} idle miners' scores at {} bps per round",
+            "📉".bright_yellow(),
+            decayed.to_string().bright_yellow(),
+            decay.decay_bps
+        );
+    }
+    testore_core::rank_miners_by(&mut leaderboard, params.metric);
+    leaderboard.truncate(params.top_miners);
+
+    for merge in &merges {
+        println!(
+            "{} Merged {} Miner accounts for {} ({} hashes)",
+            "🔗".bright_yellow(),
+            merge.accounts,
+            merge.authority.to_string().bright_yellow(),
+            format_number(merge.total_hashes)
+        );
+    }
 
     if leaderboard.is_empty() {
         println!("{} No miners found on testnet yet.", "ℹ️".bright_yellow());
@@ -97,7 +548,43 @@ async fn main() -> Result<()> {
 
     // Step 2: Calculate airdrop allocations
     println!("{} Calculating airdrop allocations...\n", "🧮".bright_cyan());
-    let allocations = calculate_allocations(&leaderboard);
+    let mut allocations = testore_core::calculate_allocations(&leaderboard, &params);
+
+    let live = if params.liveness_boost_bps > 0 {
+        let cutoff = chrono::Utc::now().timestamp() - args.liveness_window_hours * 3600;
+        testore_core::fetch_liveness(&testnet_client, &config.program_id)?
+            .into_iter()
+            .filter(|(_, verified_at)| *verified_at >= cutoff)
+            .map(|(authority, _)| authority)
+            .collect()
+    } else {
+        BTreeSet::new()
+    };
+    let boosted = testore_core::apply_liveness_boost(&mut allocations, &live, &params);
+    if boosted > 0 {
+        println!(
+            "{} Boosted {} verified-live miners by {} bps\n",
+            "💓".bright_green(),
+            boosted.to_string().bright_green(),
+            params.liveness_boost_bps
+        );
+    }
+
+    let flagged = match &args.flagged {
+        Some(path) => distribute::load_flagged(path)?,
+        None => Default::default(),
+    };
+    let excluded: Vec<_> = flagged
+        .iter()
+        .filter(|(wallet, _)| allocations.remove(wallet).is_some())
+        .collect();
+    if !excluded.is_empty() {
+        println!(
+            "{} Excluded {} flagged wallets\n",
+            "🚩".bright_red(),
+            excluded.len().to_string().bright_red()
+        );
+    }
 
     let total_tokens: u64 = allocations.values().sum();
     let eligible_count = allocations.len();
@@ -128,187 +615,637 @@ async fn main() -> Result<()> {
     }
     println!();
 
-    // Step 3: Execute airdrops (DRY RUN for now)
+    // Step 3: Plan this run's chunk. Recipients already paid in an earlier
+    // run are in the ledger and are skipped.
+    let mut ledger = Ledger::load_or_default(LEDGER_PATH)?;
+    let outstanding = ledger.outstanding(&allocations);
+    let chunk = testore_core::plan_chunk(
+        &outstanding,
+        args.max_tokens_per_run,
+        args.max_recipients_per_run,
+    )?;
+    let chunk_number = ledger.next_chunk();
+    let chunk_tokens: u64 = chunk.iter().map(|(_, amount)| amount).sum();
+    summary.chunk = Some(chunk_number);
+    summary.recipients = chunk.len();
+    summary.tokens = chunk_tokens;
+    summary.dry_run = !args.execute;
+
+    println!("{}", "═══ This Run ═══".bright_yellow().bold());
     println!(
-        "{} {}",
-        "⚠️".bright_yellow(),
-        "DRY RUN MODE - No transactions sent".bright_yellow().bold()
+        "   Already Sent: {} recipients",
+        allocations
+            .keys()
+            .filter(|recipient| ledger.is_sent(recipient))
+            .count()
+            .to_string()
+            .bright_green()
     );
     println!(
-        "   To execute real airdrops, set EXECUTE_AIRDROPS=true\n"
+        "   Chunk #{}: {} recipients, {} TESTORE",
+        chunk_number,
+        chunk.len().to_string().bright_green(),
+        format_number(chunk_tokens).bright_cyan().bold()
     );
+    println!();
+
+    // Step 4: Execute airdrops (only with --execute)
+    if args.fork_simulate {
+        fork::simulate(config, args, &chunk, chunk_number, &excluded, &ledger).await?;
+    } else if args.execute && chunk.is_empty() {
+        println!("{} Nothing outstanding to send\n", "ℹ️".bright_blue());
+    } else if args.execute {
+        confirm_execution(chunk_tokens, chunk.len(), args.yes)?;
 
-    if std::env::var("EXECUTE_AIRDROPS").unwrap_or_default() == "true" {
         println!(
             "{} Executing mainnet airdrops...\n",
             "🚀".bright_green().bold()
         );
 
-        for (pubkey, amount) in allocations.iter() {
+        let failures = send_chunk(
+            &mainnet_client,
+            config,
+            args,
+            &chunk,
+            chunk_number,
+            &excluded,
+            &mut ledger,
+            Some(LEDGER_PATH),
+            None,
+        )
+        .await?;
+        summary.failed_transactions = failures;
+        summary.sent = chunk
+            .iter()
+            .filter(|(recipient, _)| ledger.is_sent(recipient))
+            .count();
+        summary.fee_lamports = ledger.fees.get(&chunk_number).copied().unwrap_or(0);
+
+        if failures > 0 {
             println!(
-                "   {} Would send {} TESTORE to {}",
-                "💸".bright_cyan(),
-                format_number(*amount).bright_cyan(),
-                pubkey.to_string()[..8].bright_yellow()
+                "\n{} Chunk #{} finished with {} failed transactions",
+                "⚠️".bright_yellow(),
+                chunk_number,
+                failures.to_string().bright_red()
+            );
+        } else {
+            println!(
+                "\n{} Chunk #{} complete!",
+                "🎉".bright_green().bold(),
+                chunk_number
             );
-
-            // Rate limiting
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
         }
-
-        println!("\n{} Airdrop complete!", "🎉".bright_green().bold());
     } else {
+        println!(
+            "{} {}",
+            "⚠️".bright_yellow(),
+            "DRY RUN MODE - No transactions sent".bright_yellow().bold()
+        );
+        println!("   To execute real airdrops, pass --execute\n");
         println!(
             "{} Preview mode - no airdrops executed\n",
             "ℹ️".bright_blue()
         );
     }
 
-    // Step 4: Save snapshot for records
-    save_snapshot(&allocations)?;
+    let remaining = ledger.outstanding(&allocations);
+    println!("{}", "═══ Outstanding ═══".bright_yellow().bold());
+    println!(
+        "   Recipients: {}",
+        remaining.len().to_string().bright_yellow()
+    );
+    println!(
+        "   TESTORE:    {}",
+        format_number(remaining.iter().map(|(_, amount)| amount).sum()).bright_cyan()
+    );
+    let quarantined = ledger.quarantined()?;
+    summary.outstanding_recipients = Some(remaining.len());
+    summary.outstanding_tokens = Some(remaining.iter().map(|(_, amount)| amount).sum());
+    summary.quarantined = Some(quarantined.len());
+    if !quarantined.is_empty() {
+        println!(
+            "   Quarantined: {} (see retry-quarantined)",
+            quarantined.len().to_string().bright_red()
+        );
+    }
+    println!(
+        "   Fees:       {} SOL this run, {} SOL to date",
+        lamports_to_sol(summary.fee_lamports),
+        lamports_to_sol(ledger.total_fees())
+    );
+    println!("   Ledger:     {}", LEDGER_PATH.bright_yellow());
+    println!();
+
+    // Step 5: Save snapshot for records, plus a text copy for reviewing diffs
+    let snapshot = Snapshot::new(&allocations, &params)
+        .with_inputs(&leaderboard, &live)
+        .with_round(&round, slot)
+        .with_inactive(inactive)
+        .with_excluded(excluded.iter().copied())
+        .with_breakdown(&breakdown);
+    snapshot.save(SNAPSHOT_PATH)?;
+    snapshot.save_text(SNAPSHOT_TEXT_PATH)?;
+    summary.snapshot = Some(SNAPSHOT_PATH.to_string());
 
     println!(
-        "{} Snapshot saved to: {}",
+        "{} Snapshot saved to: {} ({})",
         "💾".bright_cyan(),
-        "airdrop_snapshot.json".bright_yellow()
+        SNAPSHOT_PATH.bright_yellow(),
+        SNAPSHOT_TEXT_PATH.bright_yellow()
+    );
+    println!();
+
+    Ok(())
+}
+
+/// Recompute a snapshot's allocations from its recorded inputs
+fn reproduce(path: &Path) -> Result<()> {
+    let snapshot = Snapshot::load(path)?;
+    let digest = snapshot.reproduce()?;
+
+    println!(
+        "{} Reproduced {} allocations ({} tokens) from {} ranked miners",
+        "✅".bright_green(),
+        format_number(snapshot.total_miners as u64),
+        format_number(snapshot.total_tokens),
+        format_number(snapshot.inputs.len() as u64)
     );
+    if let (Some(round), Some(slot)) = (snapshot.round_number, snapshot.slot) {
+        println!("   Round:  #{} at slot {}", round, slot);
+    }
+    println!("   Hash:   {}", digest.to_string().bright_yellow());
+
+    Ok(())
+}
+
+/// Re-send quarantined recipients once the operator has resolved them
+async fn retry_quarantined(
+    config: &Config,
+    args: &AirdropArgs,
+    yes: bool,
+    summary: &mut outcome::Summary,
+) -> Result<()> {
+    let mainnet_client =
+        RpcClient::new_with_commitment(config.mainnet_rpc.clone(), CommitmentConfig::confirmed());
+    preflight::check_mainnet(&mainnet_client, config.mint.as_ref())
+        .map_err(outcome::config_error)?;
+
+    let mut ledger = Ledger::load_or_default(LEDGER_PATH)?;
+    let retry = ledger.quarantined()?;
+    if retry.is_empty() {
+        println!("{} No quarantined recipients", "ℹ️".bright_blue());
+        return Ok(());
+    }
+
+    println!("{}", "═══ Quarantined ═══".bright_yellow().bold());
+    for (recipient, tokens) in &retry {
+        println!(
+            "   {} → {} TESTORE: {}",
+            recipient.to_string().bright_yellow(),
+            format_number(*tokens).bright_cyan(),
+            ledger.entries[&recipient.to_string()].detail
+        );
+    }
     println!();
 
+    confirm_execution(
+        retry.iter().map(|(_, tokens)| tokens).sum(),
+        retry.len(),
+        yes,
+    )?;
+
+    let chunk_number = ledger.next_chunk();
+    let failures = send_chunk(
+        &mainnet_client,
+        config,
+        args,
+        &retry,
+        chunk_number,
+        &[],
+        &mut ledger,
+        Some(LEDGER_PATH),
+        None,
+    )
+    .await?;
+    summary.chunk = Some(chunk_number);
+    summary.recipients = retry.len();
+    summary.tokens = retry.iter().map(|(_, tokens)| tokens).sum();
+    summary.sent = retry
+        .iter()
+        .filter(|(recipient, _)| ledger.is_sent(recipient))
+        .count();
+    summary.failed_transactions = failures;
+    summary.fee_lamports = ledger.fees.get(&chunk_number).copied().unwrap_or(0);
+
+    println!(
+        "\n{} Retried {} recipients, {} still failing ({} SOL in fees)",
+        "🔁".bright_cyan(),
+        retry.len(),
+        failures.to_string().bright_red(),
+        lamports_to_sol(summary.fee_lamports)
+    );
     Ok(())
 }
 
+/// Send one chunk of transfers and any pending exclusion notices
+///
+/// Every result is recorded in `ledger`, which is saved to `ledger_path`
+/// after each transaction when given. Returns the number of failed
+/// transactions.
+#[allow(clippy::too_many_arguments)]
+async fn send_chunk(
+    client: &RpcClient,
+    config: &Config,
+    args: &AirdropArgs,
+    chunk: &[(Pubkey, u64)],
+    chunk_number: u32,
+    excluded: &[(&Pubkey, &String)],
+    ledger: &mut Ledger,
+    ledger_path: Option<&str>,
+    mut chaos: Option<&mut Chaos>,
+) -> Result<usize> {
+    let mint = config
+        .mint
+        .ok_or_else(|| anyhow!("TESTORE_MINT must be set to execute airdrops"))?;
+    let decimals = distribute::mint_decimals(client, &mint)?;
+    let funding = config.signer.pubkey();
+    let mut failures = 0;
+
+    for (pubkey, amount) in chunk {
+        let fault = chaos.as_deref_mut().and_then(Chaos::roll);
+        let result = pay(
+            client,
+            config,
+            args,
+            &mint,
+            decimals,
+            pubkey,
+            *amount,
+            chunk_number,
+            ledger,
+            ledger_path,
+            fault,
+        );
+
+        match result {
+            Ok(signature) => {
+                println!(
+                    "   {} Sent {} TESTORE to {}: {}",
+                    "💸".bright_cyan(),
+                    format_number(*amount).bright_cyan(),
+                    pubkey.to_string()[..8].bright_yellow(),
+                    signature
+                );
+                ledger.record(
+                    pubkey,
+                    *amount,
+                    chunk_number,
+                    TransferStatus::Sent,
+                    signature.to_string(),
+                );
+                ledger.record_fee(chunk_number, distribute::TRANSACTION_FEE_LAMPORTS);
+            }
+            Err(e) => {
+                failures += 1;
+                // Park recipients we can never pay so they don't fail every run
+                let status = if distribute::is_permanent_failure(&e) {
+                    TransferStatus::Quarantined
+                } else {
+                    TransferStatus::Failed
+                };
+                println!(
+                    "   {} {} {} TESTORE to {}: {}",
+                    "❌".bright_red(),
+                    match status {
+                        TransferStatus::Quarantined => "Quarantined",
+                        _ => "Failed to send",
+                    },
+                    format_number(*amount),
+                    pubkey.to_string().bright_yellow(),
+                    e
+                );
+                ledger.record(pubkey, *amount, chunk_number, status, e.to_string());
+            }
+        }
+
+        // Save after every transfer so a crash can't cause a double payment
+        if let Some(path) = ledger_path {
+            ledger.save(path)?;
+
+            // Rate limiting
+            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        }
+
+        if fault == Some(Fault::Crash) {
+            return Err(anyhow!("chaos: crashed after {}", pubkey));
+        }
+    }
+
+    // Tell excluded wallets why instead of leaving them guessing
+    for (wallet, reason) in excluded {
+        if ledger.notified.contains(&wallet.to_string()) {
+            continue;
+        }
+
+        let memo = distribute::exclusion_memo(args.memo.as_deref(), reason);
+        let ixs = distribute::notice_instructions(&funding, wallet, &memo);
+
+        match signer::send(client, &*config.signer, &ixs) {
+            Ok(_) => {
+                ledger.notified.insert(wallet.to_string());
+                ledger.record_fee(chunk_number, distribute::TRANSACTION_FEE_LAMPORTS);
+                if let Some(path) = ledger_path {
+                    ledger.save(path)?;
+                }
+            }
+            Err(e) => {
+                failures += 1;
+                println!(
+                    "   {} Failed to notify {}: {}",
+                    "❌".bright_red(),
+                    wallet.to_string().bright_yellow(),
+                    e
+                );
+            }
+        }
+    }
+
+    Ok(failures)
+}
+
+/// Pay one recipient, settling any earlier unconfirmed transfer first
+#[allow(clippy::too_many_arguments)]
+fn pay(
+    client: &RpcClient,
+    config: &Config,
+    args: &AirdropArgs,
+    mint: &Pubkey,
+    decimals: u8,
+    recipient: &Pubkey,
+    tokens: u64,
+    chunk_number: u32,
+    ledger: &mut Ledger,
+    ledger_path: Option<&str>,
+    fault: Option<Fault>,
+) -> Result<Signature> {
+    if let Some(pending) = ledger.pending(recipient) {
+        match distribute::check_pending(client, pending)? {
+            PendingOutcome::Landed(signature) => return Ok(signature),
+            PendingOutcome::Rebroadcast(tx) => return chaos::submit(client, &tx, fault),
+            PendingOutcome::Failed(e) => {
+                let e = anyhow::Error::from(e);
+                if distribute::is_permanent_failure(&e) {
+                    return Err(e);
+                }
+            }
+            PendingOutcome::Expired => {}
+        }
+    }
+
+    let ixs = distribute::transfer_instructions(
+        &config.signer.pubkey(),
+        mint,
+        decimals,
+        recipient,
+        tokens,
+        args.memo.as_deref(),
+    )?;
+    let tx = signer::sign(&*config.signer, &[], &ixs, chaos::blockhash(client, fault)?)?;
+
+    ledger.record_pending(
+        recipient,
+        tokens,
+        chunk_number,
+        distribute::encode_pending(&tx)?,
+    );
+    if let Some(path) = ledger_path {
+        ledger.save(path)?;
+    }
+
+    let started = std::time::Instant::now();
+    let signature = chaos::submit(client, &tx, fault)?;
+    metrics::metrics()
+        .transfer_confirmation
+        .observe(started.elapsed().as_secs_f64());
+    Ok(signature)
+}
+
 // ============================================================================
 // Core Functions
 // ============================================================================
 
-#[derive(Debug)]
 struct Config {
+    cluster: cluster::Cluster,
     testnet_rpc: String,
+
+    /// Where `testnet_rpc` came from, for the startup banner
+    testnet_rpc_source: &'static str,
+    history_rpcs: Vec<String>,
     mainnet_rpc: String,
     program_id: Pubkey,
-    keypair: Keypair,
+    legacy_program_ids: Vec<Pubkey>,
+    signer: Box<dyn signer::TxSigner>,
+    mint: Option<Pubkey>,
 }
 
-fn load_config() -> Result<Config> {
-    let testnet_rpc = std::env::var("TESTNET_RPC")
-        .unwrap_or_else(|_| "https://api.testnet.solana.com".to_string());
+impl Config {
+    /// Every deployment miners are read from, current one first
+    fn program_ids(&self) -> Vec<Pubkey> {
+        std::iter::once(self.program_id)
+            .chain(self.legacy_program_ids.iter().copied())
+            .collect()
+    }
+}
 
-    let mainnet_rpc = std::env::var("MAINNET_RPC")
-        .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
+fn load_config(
+    cluster: cluster::Cluster,
+    url: Option<&str>,
+    keypair: Option<&str>,
+) -> Result<Config> {
+    let profile = cluster.profile();
+    let solana_cli = setup::solana_cli_config();
+    let (testnet_rpc, testnet_rpc_source) = match url {
+        Some(url) => (url.to_string(), "--url / TESTNET_RPC"),
+        None => match setup::solana_cli_testnet_rpc(&solana_cli) {
+            Some(url) => (url, "Solana CLI config"),
+            None => (profile.default_rpc.to_string(), "--cluster profile"),
+        },
+    };
 
-    let program_id = std::env::var("PROGRAM_ID")
-        .unwrap_or_else(|_| "TESTORE11111111111111111111111111111111111".to_string());
-    let program_id = Pubkey::from_str(&program_id)?;
+    let history_rpcs = std::env::var("HISTORY_RPC")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(String::from)
+        .collect();
 
-    let keypair_path = std::env::var("AIRDROP_KEYPAIR")
-        .unwrap_or_else(|_| "~/.config/solana/id.json".to_string());
+    let mainnet_rpc =
+        std::env::var("MAINNET_RPC").unwrap_or_else(|_| setup::MAINNET_BETA_RPC.to_string());
 
-    let keypair = load_keypair(&keypair_path)?;
+    let program_id = match std::env::var("PROGRAM_ID") {
+        Ok(program_id) => Pubkey::from_str(&program_id)?,
+        Err(_) => cluster.program_id()?,
+    };
+    if program_id != cluster.program_id()? {
+        println!(
+            "{} PROGRAM_ID {} overrides the {} build's {}",
+            "⚠️".bright_yellow(),
+            program_id,
+            cluster.name(),
+            profile.program_id
+        );
+    }
+
+    let legacy_program_ids = std::env::var("LEGACY_PROGRAM_IDS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(Pubkey::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let keypair_path = keypair
+        .map(str::to_string)
+        .unwrap_or(solana_cli.keypair_path);
+
+    let signer = signer::load_signer(&keypair_path)?;
+
+    let mint = std::env::var("TESTORE_MINT")
+        .ok()
+        .map(|mint| Pubkey::from_str(&mint))
+        .transpose()?;
 
     Ok(Config {
+        cluster,
         testnet_rpc,
+        testnet_rpc_source,
+        history_rpcs,
         mainnet_rpc,
         program_id,
-        keypair,
+        legacy_program_ids,
+        signer,
+        mint,
     })
 }
 
-#[derive(Debug, Clone)]
-struct MinerStats {
-    pubkey: Pubkey,
-    total_hashes: u64,
-}
+// ============================================================================
+// Utilities
+// ============================================================================
 
-fn fetch_testnet_leaderboard(client: &RpcClient, program_id: &Pubkey) -> Result<Vec<MinerStats>> {
-    let accounts = client.get_program_accounts(program_id)?;
+fn load_keypair(path: &str) -> Result<Keypair> {
+    let expanded_path = signer::expand_path(path);
 
-    let mut miners: Vec<MinerStats> = accounts
-        .iter()
-        .filter_map(|(_pda, account)| {
-            if account.data.len() >= 48 {
-                // Extract authority (actual miner pubkey)
-                let mut authority_bytes = [0u8; 32];
-                authority_bytes.copy_from_slice(&account.data[8..40]);
-                let authority = Pubkey::new_from_array(authority_bytes);
-
-                let total_hashes =
-                    u64::from_le_bytes(account.data[40..48].try_into().ok()?);
-
-                Some(MinerStats {
-                    pubkey: authority,
-                    total_hashes,
-                })
-            } else {
-                None
-            }
-        })
-        .collect();
+    if !expanded_path.exists() {
+        return Err(anyhow!(
+            "Keypair file not found: {}",
+            expanded_path.display()
+        ));
+    }
 
-    miners.sort_by(|a, b| b.total_hashes.cmp(&a.total_hashes));
+    let data = fs::read(&expanded_path)?;
+    if testore_core::is_encrypted_keypair(&data) {
+        let passphrase = passphrase(&format!("Passphrase for {}: ", expanded_path.display()))?;
+        return testore_core::decrypt_keypair(&data, &passphrase);
+    }
 
-    Ok(miners.into_iter().take(TOP_MINERS_TO_AIRDROP).collect())
-}
+    let keypair_bytes: Vec<u8> = serde_json::from_slice(&data)?;
 
-fn calculate_allocations(leaderboard: &[MinerStats]) -> HashMap<Pubkey, u64> {
-    let mut allocations = HashMap::new();
+    Ok(Keypair::from_bytes(&keypair_bytes)?)
+}
 
-    for miner in leaderboard {
-        if miner.total_hashes >= MINIMUM_HASHES_FOR_AIRDROP {
-            let tokens = (miner.total_hashes / 1_000_000) * TOKENS_PER_MILLION_HASHES;
+/// KEYPAIR_PASSPHRASE, or an interactive prompt when it is unset
+fn passphrase(message: &str) -> Result<String> {
+    if let Ok(passphrase) = std::env::var("KEYPAIR_PASSPHRASE") {
+        return Ok(passphrase);
+    }
 
-            if tokens > 0 {
-                allocations.insert(miner.pubkey, tokens);
-            }
-        }
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "Keypair is encrypted; set KEYPAIR_PASSPHRASE when not running interactively"
+        ));
     }
 
-    allocations
+    Ok(rpassword::prompt_password(message)?)
 }
 
-fn save_snapshot(allocations: &HashMap<Pubkey, u64>) -> Result<()> {
-    use chrono::Utc;
+fn encrypt_keypair(input: &Path, output: &Path) -> Result<()> {
+    if output.exists() {
+        return Err(anyhow!("{} already exists", output.display()));
+    }
 
-    let snapshot = serde_json::json!({
-        "timestamp": Utc::now().to_rfc3339(),
-        "total_miners": allocations.len(),
-        "total_tokens": allocations.values().sum::<u64>(),
-        "allocations": allocations
-            .iter()
-            .map(|(k, v)| (k.to_string(), v))
-            .collect::<HashMap<_, _>>()
-    });
+    let keypair = load_keypair(&input.to_string_lossy())?;
+    let passphrase = passphrase("New passphrase: ")?;
+    if std::env::var("KEYPAIR_PASSPHRASE").is_err()
+        && rpassword::prompt_password("Repeat passphrase: ")? != passphrase
+    {
+        return Err(anyhow!("Passphrases did not match"));
+    }
 
-    fs::write("airdrop_snapshot.json", serde_json::to_string_pretty(&snapshot)?)?;
+    fs::write(
+        output,
+        testore_core::encrypt_keypair(&keypair, &passphrase)?,
+    )?;
 
+    println!(
+        "{} Encrypted {} to {}",
+        "🔒".bright_green(),
+        keypair.pubkey().to_string().bright_yellow(),
+        output.display()
+    );
+    println!("   Delete {} once you have a backup", input.display());
     Ok(())
 }
 
-// ============================================================================
-// Utilities
-// ============================================================================
-
-fn load_keypair(path: &str) -> Result<Keypair> {
-    let expanded_path = if path.starts_with("~/") {
-        if let Some(home) = std::env::var_os("HOME") {
-            PathBuf::from(home).join(&path[2..])
-        } else {
-            PathBuf::from(path)
-        }
-    } else {
-        PathBuf::from(path)
-    };
+/// Make the operator retype the total and recipient count before sending
+fn confirm_execution(total_tokens: u64, recipients: usize, yes: bool) -> Result<()> {
+    if yes {
+        return Ok(());
+    }
 
-    if !expanded_path.exists() {
+    if !std::io::stdin().is_terminal() {
         return Err(anyhow!(
-            "Keypair file not found: {}",
-            expanded_path.display()
+            "--execute needs an interactive confirmation; pass --yes to skip it"
         ));
     }
 
-    let keypair_bytes = fs::read_to_string(&expanded_path)?;
-    let keypair_bytes: Vec<u8> = serde_json::from_str(&keypair_bytes)?;
+    println!(
+        "{} About to send {} TESTORE to {} wallets on mainnet.",
+        "⚠️".bright_yellow(),
+        format_number(total_tokens).bright_cyan().bold(),
+        recipients.to_string().bright_cyan().bold()
+    );
 
-    Ok(Keypair::from_bytes(&keypair_bytes)?)
+    let amount = prompt("   Type the total TESTORE amount to confirm: ")?;
+    let count = prompt("   Type the number of recipients to confirm: ")?;
+
+    if !confirmation_matches(&amount, total_tokens)
+        || !confirmation_matches(&count, recipients as u64)
+    {
+        return Err(anyhow!("Confirmation did not match - airdrop aborted"));
+    }
+
+    println!();
+    Ok(())
+}
+
+fn prompt(message: &str) -> Result<String> {
+    print!("{}", message);
+    std::io::stdout().flush()?;
+
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line)
+}
+
+/// Typed number equals `expected`, ignoring whitespace and thousands separators
+fn confirmation_matches(input: &str, expected: u64) -> bool {
+    let digits: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ',' && *c != '_')
+        .collect();
+
+    !digits.is_empty() && digits.parse::<u64>().ok() == Some(expected)
 }
 
 fn format_number(n: u64) -> String {
@@ -320,3 +1257,25 @@ fn format_number(n: u64) -> String {
         .collect::<Vec<_>>()
         .join(",")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_confirmation_matches() {
+        assert!(confirmation_matches("1500\n", 1500));
+        assert!(confirmation_matches(" 1,500 ", 1500));
+        assert!(confirmation_matches("1_500", 1500));
+
+        assert!(!confirmation_matches("150", 1500));
+        assert!(!confirmation_matches("", 0));
+        assert!(!confirmation_matches("yes", 1500));
+    }
+}
@@ -0,0 +1,291 @@
+//! Prometheus metrics and the Grafana dashboard built from them
+//!
+//! Long-running commands (`watch`) and airdrop runs record histograms and
+//! gauges here. Set METRICS_TEXTFILE to have them written in the Prometheus
+//! text format after every `watch` poll and at the end of each run, for
+//! node_exporter's textfile collector to pick up.
+//!
+//! `dashboards export` writes a Grafana dashboard whose panels are generated
+//! from [`DEFINITIONS`], so a new metric gets a panel without hand-editing
+//! JSON.
+
+use anyhow::Result;
+use clap::Subcommand;
+use prometheus::{Encoder, Histogram, HistogramOpts, IntGauge, Registry, TextEncoder};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// How a metric is drawn in the generated dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Chart {
+    /// Value over time
+    Gauge,
+
+    /// p50/p95/p99 over time
+    Quantiles,
+
+    /// Distribution over time
+    Heatmap,
+}
+
+struct Definition {
+    name: &'static str,
+    help: &'static str,
+
+    /// Grafana unit id
+    unit: &'static str,
+    chart: Chart,
+}
+
+const DEFINITIONS: [Definition; 6] = [
+    Definition {
+        name: "testore_transfer_confirmation_seconds",
+        help: "Time from broadcasting an airdrop transfer to its confirmation",
+        unit: "s",
+        chart: Chart::Quantiles,
+    },
+    Definition {
+        name: "testore_round_poll_seconds",
+        help: "Time to read GlobalRound from the testnet RPC",
+        unit: "s",
+        chart: Chart::Quantiles,
+    },
+    Definition {
+        name: "testore_miner_best_difficulty",
+        help: "Best proof difficulty of each ranked miner at snapshot time",
+        unit: "none",
+        chart: Chart::Heatmap,
+    },
+    Definition {
+        name: "testore_round_number",
+        help: "Current GlobalRound number",
+        unit: "none",
+        chart: Chart::Gauge,
+    },
+    Definition {
+        name: "testore_round_min_difficulty",
+        help: "Current round minimum difficulty",
+        unit: "none",
+        chart: Chart::Gauge,
+    },
+    Definition {
+        name: "testore_total_hashes_submitted",
+        help: "Proofs accepted across all miners",
+        unit: "short",
+        chart: Chart::Gauge,
+    },
+];
+
+pub struct Metrics {
+    registry: Registry,
+    pub transfer_confirmation: Histogram,
+    pub round_poll: Histogram,
+    pub miner_best_difficulty: Histogram,
+    pub round_number: IntGauge,
+    pub round_min_difficulty: IntGauge,
+    pub total_hashes_submitted: IntGauge,
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new();
+        let histogram = |index: usize, buckets: Vec<f64>| {
+            let definition = &DEFINITIONS[index];
+            let histogram = Histogram::with_opts(
+                HistogramOpts::new(definition.name, definition.help).buckets(buckets),
+            )?;
+            registry.register(Box::new(histogram.clone()))?;
+            Ok::<_, prometheus::Error>(histogram)
+        };
+        let gauge = |index: usize| {
+            let definition = &DEFINITIONS[index];
+            let gauge = IntGauge::new(definition.name, definition.help)?;
+            registry.register(Box::new(gauge.clone()))?;
+            Ok::<_, prometheus::Error>(gauge)
+        };
+
+        Ok(Self {
+            transfer_confirmation: histogram(
+                0,
+                vec![0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 30.0, 60.0, 90.0],
+            )?,
+            round_poll: histogram(1, vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0])?,
+            miner_best_difficulty: histogram(2, (8..=32).step_by(2).map(f64::from).collect())?,
+            round_number: gauge(3)?,
+            round_min_difficulty: gauge(4)?,
+            total_hashes_submitted: gauge(5)?,
+            registry,
+        })
+    }
+
+    /// Everything recorded so far, in the Prometheus text format
+    pub fn render(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+/// The process-wide metrics
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics::new().expect("metric definitions are valid"))
+}
+
+/// Write the metrics to METRICS_TEXTFILE, if set
+///
+/// Written to a temporary file and renamed, so the collector never reads a
+/// half-written file.
+pub fn write_textfile() -> Result<()> {
+    let Ok(path) = std::env::var("METRICS_TEXTFILE") else {
+        return Ok(());
+    };
+
+    let partial = format!("{}.tmp", path);
+    fs::write(&partial, metrics().render()?)?;
+    fs::rename(&partial, &path)?;
+    Ok(())
+}
+
+#[derive(Subcommand)]
+pub enum DashboardsCommand {
+    /// Write the Grafana dashboard JSON for the exported metrics
+    Export {
+        /// Write to this file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+pub fn run(command: &DashboardsCommand) -> Result<()> {
+    match command {
+        DashboardsCommand::Export { out } => {
+            let rendered = serde_json::to_string_pretty(&dashboard())?;
+            match out {
+                Some(path) => fs::write(path, rendered + "\n")?,
+                None => println!("{}", rendered),
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Grafana dashboard with one panel per metric, two panels per row
+fn dashboard() -> Value {
+    let panels: Vec<Value> = DEFINITIONS
+        .iter()
+        .enumerate()
+        .map(|(index, definition)| panel(index, definition))
+        .collect();
+
+    json!({
+        "title": "TestORE",
+        "uid": "testore",
+        "schemaVersion": 39,
+        "editable": true,
+        "time": { "from": "now-24h", "to": "now" },
+        "refresh": "1m",
+        "templating": {
+            "list": [{
+                "name": "datasource",
+                "label": "Data source",
+                "type": "datasource",
+                "query": "prometheus",
+            }],
+        },
+        "panels": panels,
+    })
+}
+
+fn panel(index: usize, definition: &Definition) -> Value {
+    let name = definition.name;
+    let (kind, targets) = match definition.chart {
+        Chart::Gauge => ("timeseries", vec![target(name.to_string(), name, "time_series")]),
+        Chart::Quantiles => (
+            "timeseries",
+            [("0.5", "p50"), ("0.95", "p95"), ("0.99", "p99")]
+                .iter()
+                .map(|(quantile, legend)| {
+                    target(
+                        format!(
+                            "histogram_quantile({}, sum(rate({}_bucket[$__rate_interval])) by (le))",
+                            quantile, name
+                        ),
+                        legend,
+                        "time_series",
+                    )
+                })
+                .collect(),
+        ),
+        Chart::Heatmap => (
+            "heatmap",
+            vec![target(
+                format!("sum(increase({}_bucket[$__rate_interval])) by (le)", name),
+                "{{le}}",
+                "heatmap",
+            )],
+        ),
+    };
+
+    json!({
+        "id": index + 1,
+        "type": kind,
+        "title": definition.help,
+        "datasource": { "type": "prometheus", "uid": "${datasource}" },
+        "gridPos": { "x": (index % 2) * 12, "y": (index / 2) * 8, "w": 12, "h": 8 },
+        "fieldConfig": { "defaults": { "unit": definition.unit }, "overrides": [] },
+        "targets": targets,
+    })
+}
+
+fn target(expr: String, legend: &str, format: &str) -> Value {
+    json!({
+        "datasource": { "type": "prometheus", "uid": "${datasource}" },
+        "expr": expr,
+        "legendFormat": legend,
+        "format": format,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_definitions_match_registry() {
+        let metrics = Metrics::new().unwrap();
+        metrics.transfer_confirmation.observe(1.5);
+        metrics.round_number.set(7);
+
+        let mut registered: Vec<String> = metrics
+            .registry
+            .gather()
+            .iter()
+            .map(|family| family.get_name().to_string())
+            .collect();
+        registered.sort();
+        let mut defined: Vec<&str> = DEFINITIONS.iter().map(|d| d.name).collect();
+        defined.sort();
+        assert_eq!(registered, defined);
+
+        let rendered = metrics.render().unwrap();
+        assert!(rendered.contains("testore_transfer_confirmation_seconds_count 1"));
+        assert!(rendered.contains("testore_round_number 7"));
+    }
+
+    #[test]
+    fn test_dashboard_has_a_panel_per_metric() {
+        let dashboard = dashboard();
+        let panels = dashboard["panels"].as_array().unwrap();
+        assert_eq!(panels.len(), DEFINITIONS.len());
+
+        for (panel, definition) in panels.iter().zip(&DEFINITIONS) {
+            let expr = panel["targets"][0]["expr"].as_str().unwrap();
+            assert!(expr.contains(definition.name), "{}", expr);
+        }
+        assert_eq!(panels[2]["type"], "heatmap");
+        assert_eq!(panels[3]["gridPos"]["x"], 12);
+    }
+}
@@ -0,0 +1,202 @@
+//! `migrate-scoring`: move leaderboard scoring from raw hash counts to
+//! difficulty-weighted points
+//!
+//! `start` freezes proof submissions (admin), `convert` sends the
+//! permissionless `convert_stats` for every miner still on hash counts, and
+//! `resume` reopens submissions with scores credited from then on (admin).
+//! `resume` refuses while miners are left unconverted unless `--force`: the
+//! program converts stragglers on their next proof anyway, and until then
+//! the airdrop ranks them at the score their conversion will give them, with
+//! no idle decay charged.
+//!
+//! Miners still in a layout from before the score fields are included;
+//! `convert_stats` grows them first, with the signer paying the extra rent.
+
+use crate::signer::{self, TxSigner};
+use crate::Config;
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use testore_core::{ScoringMigrationAccount, ScoringPhase};
+use testore_program::client::{ConvertStats, MigrateScoring, ResumeSubmissions, ToInstruction};
+
+#[derive(Subcommand)]
+pub enum MigrateScoringCommand {
+    /// Freeze submissions so miners can be converted (admin)
+    Start,
+
+    /// Convert every miner still scored by hash count
+    Convert {
+        /// convert_stats instructions per transaction
+        #[arg(long, default_value_t = 10)]
+        batch: usize,
+    },
+
+    /// Reopen submissions under the new scoring (admin)
+    Resume {
+        /// Resume even though some miners are unconverted
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Show the migration phase and how many miners are left
+    Status,
+}
+
+pub fn run(config: &Config, command: &MigrateScoringCommand) -> Result<()> {
+    let client =
+        RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed());
+
+    match command {
+        MigrateScoringCommand::Start => start(&client, config),
+        MigrateScoringCommand::Convert { batch } => convert(&client, config, *batch),
+        MigrateScoringCommand::Resume { force } => resume(&client, config, *force),
+        MigrateScoringCommand::Status => status(&client, config),
+    }
+}
+
+fn start(client: &RpcClient, config: &Config) -> Result<()> {
+    if let Some(migration) = testore_core::fetch_scoring_migration(client, &config.program_id)? {
+        return Err(anyhow!(
+            "Scoring migration already started at {} ({:?})",
+            migration.frozen_at,
+            migration.phase
+        ));
+    }
+
+    with_admin(client, config, |client, admin| {
+        let ix = MigrateScoring::new(admin.pubkey()).instruction_for(&config.program_id);
+        let signature = signer::send(client, admin, &[ix])?;
+        println!(
+            "{} Submissions frozen for the scoring migration: {}",
+            "🧊".bright_cyan(),
+            signature
+        );
+        Ok(())
+    })
+}
+
+fn convert(client: &RpcClient, config: &Config, batch: usize) -> Result<()> {
+    require_phase(client, config, ScoringPhase::Frozen)?;
+
+    let pending = pending_miners(client, config)?;
+    if pending.is_empty() {
+        println!("{} Every miner is already converted", "✅".bright_green());
+        return Ok(());
+    }
+
+    println!(
+        "{} Converting {} miners, {} per transaction",
+        "🔁".bright_cyan(),
+        pending.len(),
+        batch.max(1)
+    );
+    for chunk in pending.chunks(batch.max(1)) {
+        let instructions: Vec<_> = chunk
+            .iter()
+            .map(|authority| {
+                ConvertStats::new(config.signer.pubkey(), *authority)
+                    .instruction_for(&config.program_id)
+            })
+            .collect();
+        let signature = signer::send(client, &*config.signer, &instructions)?;
+        println!("   {} miners: {}", chunk.len(), signature);
+    }
+
+    Ok(())
+}
+
+fn resume(client: &RpcClient, config: &Config, force: bool) -> Result<()> {
+    require_phase(client, config, ScoringPhase::Frozen)?;
+
+    let pending = pending_miners(client, config)?;
+    if !pending.is_empty() {
+        if !force {
+            return Err(anyhow!(
+                "{} miners are still unconverted; run `migrate-scoring convert` or pass --force",
+                pending.len()
+            ));
+        }
+        println!(
+            "{} Resuming with {} unconverted miners; they convert on their next proof",
+            "⚠️".bright_yellow(),
+            pending.len()
+        );
+    }
+
+    with_admin(client, config, |client, admin| {
+        let ix = ResumeSubmissions::new(admin.pubkey()).instruction_for(&config.program_id);
+        let signature = signer::send(client, admin, &[ix])?;
+        println!(
+            "{} Submissions resumed under difficulty-weighted scoring: {}",
+            "▶️".bright_green(),
+            signature
+        );
+        Ok(())
+    })
+}
+
+fn status(client: &RpcClient, config: &Config) -> Result<()> {
+    let Some(migration) = testore_core::fetch_scoring_migration(client, &config.program_id)? else {
+        println!("Phase: {:?} (not started)", ScoringPhase::HashCount);
+        return Ok(());
+    };
+
+    println!("Phase:       {:?}", migration.phase);
+    println!("Frozen at:   {}", migration.frozen_at);
+    if migration.resumed_at > 0 {
+        println!("Resumed at:  {}", migration.resumed_at);
+    }
+    println!("Unconverted: {}", pending_miners(client, config)?.len());
+
+    Ok(())
+}
+
+fn require_phase(
+    client: &RpcClient,
+    config: &Config,
+    phase: ScoringPhase,
+) -> Result<ScoringMigrationAccount> {
+    let migration = testore_core::fetch_scoring_migration(client, &config.program_id)?
+        .ok_or_else(|| anyhow!("Scoring migration not started; run `migrate-scoring start`"))?;
+    if migration.phase != phase {
+        return Err(anyhow!(
+            "Scoring migration is {:?}, expected {:?}",
+            migration.phase,
+            phase
+        ));
+    }
+
+    Ok(migration)
+}
+
+fn pending_miners(client: &RpcClient, config: &Config) -> Result<Vec<Pubkey>> {
+    let miners = testore_core::fetch_miners(client, &config.program_id)?;
+    Ok(testore_core::unconverted_miners(&miners))
+}
+
+/// Run `f` with the GlobalRound admin, from CRANK_KEYPAIR or the main signer
+pub(crate) fn with_admin<T>(
+    client: &RpcClient,
+    config: &Config,
+    f: impl FnOnce(&RpcClient, &dyn TxSigner) -> Result<T>,
+) -> Result<T> {
+    let crank_signer = match std::env::var("CRANK_KEYPAIR") {
+        Ok(path) => Some(signer::load_signer(&path)?),
+        Err(_) => None,
+    };
+    let admin = crank_signer.as_deref().unwrap_or(&*config.signer);
+
+    let round = testore_core::fetch_global_round(client, &config.program_id)?;
+    if round.admin != admin.pubkey() {
+        return Err(anyhow!(
+            "Wallet {} is not the GlobalRound admin {}",
+            admin.pubkey(),
+            round.admin
+        ));
+    }
+
+    f(client, admin)
+}
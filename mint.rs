@@ -0,0 +1,260 @@
+//! `mint`: TESTORE mainnet mint lifecycle
+//!
+//! Creates the mint with Metaplex metadata, hands its authorities over, and
+//! finally revokes minting so the supply is fixed. All commands run against
+//! MAINNET_RPC with the AIRDROP_KEYPAIR signer; `authority` and `disable` act
+//! on TESTORE_MINT.
+
+use crate::{prompt, signer, Config};
+use anyhow::{anyhow, Result};
+use clap::{Args, Subcommand};
+use colored::*;
+use mpl_token_metadata::{
+    accounts::Metadata, instructions::CreateMetadataAccountV3Builder, types::DataV2,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+};
+use spl_token::instruction::AuthorityType;
+
+#[derive(Subcommand)]
+pub enum MintCommand {
+    /// Create the TESTORE mint and its token metadata
+    Create(CreateArgs),
+
+    /// Manage TESTORE_MINT's authorities
+    Authority {
+        #[command(subcommand)]
+        command: AuthorityCommand,
+    },
+
+    /// Permanently revoke TESTORE_MINT's mint authority, fixing the supply
+    Disable {
+        /// Skip the typed confirmation (for automation)
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Args)]
+pub struct CreateArgs {
+    #[arg(long, default_value_t = 9)]
+    decimals: u8,
+
+    #[arg(long, default_value = "TestORE")]
+    name: String,
+
+    #[arg(long, default_value = "TESTORE")]
+    symbol: String,
+
+    /// Off-chain metadata JSON (image, description)
+    #[arg(long)]
+    uri: String,
+}
+
+#[derive(Subcommand)]
+pub enum AuthorityCommand {
+    /// Move the mint authority to another wallet
+    ///
+    /// The mint has no freeze authority (see `create`), so there is none to
+    /// move.
+    Transfer {
+        new_authority: Pubkey,
+
+        /// Skip the typed confirmation (for automation)
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+pub fn run(config: &Config, command: &MintCommand) -> Result<()> {
+    let client =
+        RpcClient::new_with_commitment(config.mainnet_rpc.clone(), CommitmentConfig::confirmed());
+
+    match command {
+        MintCommand::Create(args) => {
+            crate::preflight::check_mainnet(&client, None)?;
+            create(&client, config, args)
+        }
+        MintCommand::Authority {
+            command: AuthorityCommand::Transfer { new_authority, yes },
+        } => {
+            let mint = configured_mint(&client, config)?;
+            if !yes {
+                println!(
+                    "{} Only {} will be able to mint {} after this.",
+                    "⚠️".bright_yellow(),
+                    new_authority.to_string().bright_yellow(),
+                    mint
+                );
+                let typed = prompt("   Type the new authority to confirm: ")?;
+                if typed.trim() != new_authority.to_string() {
+                    return Err(anyhow!(
+                        "Confirmation did not match - mint authority unchanged"
+                    ));
+                }
+            }
+            set_authority(
+                &client,
+                config,
+                &mint,
+                AuthorityType::MintTokens,
+                Some(new_authority),
+            )
+        }
+        MintCommand::Disable { yes } => {
+            let mint = configured_mint(&client, config)?;
+            if !yes {
+                println!(
+                    "{} This permanently disables minting for {}.",
+                    "⚠️".bright_yellow(),
+                    mint.to_string().bright_yellow()
+                );
+                let typed = prompt("   Type the mint address to confirm: ")?;
+                if typed.trim() != mint.to_string() {
+                    return Err(anyhow!("Confirmation did not match - mint left enabled"));
+                }
+            }
+            set_authority(&client, config, &mint, AuthorityType::MintTokens, None)
+        }
+    }
+}
+
+fn create(client: &RpcClient, config: &Config, args: &CreateArgs) -> Result<()> {
+    let mint = Keypair::new();
+    let authority = config.signer.pubkey();
+
+    testore_core::throttle(client);
+    let lamports = client.get_minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN)?;
+    let ixs = create_instructions(&authority, &mint.pubkey(), args, lamports)?;
+    let signature = signer::send_with_keypairs(client, &*config.signer, &[&mint], &ixs)?;
+
+    println!(
+        "{} Created mint {}: {}",
+        "🪙".bright_green(),
+        mint.pubkey().to_string().bright_yellow(),
+        signature
+    );
+    println!("   Set TESTORE_MINT={}", mint.pubkey());
+    Ok(())
+}
+
+/// Account creation, mint initialization and metadata for a new mint
+///
+/// `authority` pays for everything and holds the mint and metadata update
+/// authorities; there is no freeze authority.
+fn create_instructions(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    args: &CreateArgs,
+    lamports: u64,
+) -> Result<Vec<Instruction>> {
+    let metadata = CreateMetadataAccountV3Builder::new()
+        .metadata(Metadata::find_pda(mint).0)
+        .mint(*mint)
+        .mint_authority(*authority)
+        .payer(*authority)
+        .update_authority(*authority, true)
+        .data(DataV2 {
+            name: args.name.clone(),
+            symbol: args.symbol.clone(),
+            uri: args.uri.clone(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        })
+        .is_mutable(true)
+        .instruction();
+
+    Ok(vec![
+        system_instruction::create_account(
+            authority,
+            mint,
+            lamports,
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        spl_token::instruction::initialize_mint2(
+            &spl_token::id(),
+            mint,
+            authority,
+            None,
+            args.decimals,
+        )?,
+        metadata,
+    ])
+}
+
+fn set_authority(
+    client: &RpcClient,
+    config: &Config,
+    mint: &Pubkey,
+    kind: AuthorityType,
+    new_authority: Option<&Pubkey>,
+) -> Result<()> {
+    let ix = spl_token::instruction::set_authority(
+        &spl_token::id(),
+        mint,
+        new_authority,
+        kind.clone(),
+        &config.signer.pubkey(),
+        &[],
+    )?;
+    let signature = signer::send(client, &*config.signer, &[ix])?;
+
+    match new_authority {
+        Some(new_authority) => println!(
+            "{} {:?} authority moved to {}: {}",
+            "✅".bright_green(),
+            kind,
+            new_authority.to_string().bright_yellow(),
+            signature
+        ),
+        None => println!(
+            "{} {:?} authority revoked: {}",
+            "🔒".bright_green(),
+            kind,
+            signature
+        ),
+    }
+    Ok(())
+}
+
+/// TESTORE_MINT, after checking the cluster and the mint
+fn configured_mint(client: &RpcClient, config: &Config) -> Result<Pubkey> {
+    let mint = config
+        .mint
+        .ok_or_else(|| anyhow!("TESTORE_MINT must be set"))?;
+    crate::preflight::check_mainnet(client, Some(&mint))?;
+    Ok(mint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_instructions() {
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let args = CreateArgs {
+            decimals: 9,
+            name: "TestORE".to_string(),
+            symbol: "TESTORE".to_string(),
+            uri: "https://example.com/testore.json".to_string(),
+        };
+
+        let ixs = create_instructions(&authority, &mint, &args, 1_461_600).unwrap();
+        assert_eq!(ixs[0].program_id, solana_sdk::system_program::id());
+        assert_eq!(ixs[1].program_id, spl_token::id());
+        assert_eq!(ixs[2].program_id, mpl_token_metadata::ID);
+        assert_eq!(ixs[2].accounts[0].pubkey, Metadata::find_pda(&mint).0);
+    }
+}
@@ -0,0 +1,85 @@
+//! Cross-cluster stats commitments
+//!
+//! Miner stats live on testnet while the airdrop happens on mainnet.
+//! `post-root` publishes a merkle root of every miner's score (closed
+//! miners' from their MinerArchive) to the program's StatsRoot PDA, where a
+//! bridge or oracle relayer can carry it across. `verify-root` recomputes
+//! the root from testnet and checks it against the posted one.
+//!
+//! Both commands read live state, so verify before mining moves on (or while
+//! the round is frozen); a mismatch after further proofs is expected.
+
+use crate::{signer, Config};
+use anyhow::{anyhow, Result};
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use testore_program::client::{PostStatsRoot, ToInstruction};
+
+/// Compute the stats root from testnet and post it
+pub fn post_root(config: &Config) -> Result<()> {
+    let client = testnet_client(config);
+
+    // The program only accepts roots from the GlobalRound admin, which is
+    // usually not the mainnet funding wallet
+    let oracle_signer = match std::env::var("ORACLE_KEYPAIR") {
+        Ok(path) => Some(signer::load_signer(&path)?),
+        Err(_) => None,
+    };
+    let oracle = oracle_signer.as_deref().unwrap_or(&*config.signer);
+
+    let miners = testore_core::fetch_miners(&client, &config.program_id)?;
+    let root = testore_core::stats_merkle_root(&miners);
+
+    println!(
+        "{} Posting root {} for {} miners...",
+        "🌳".bright_cyan(),
+        bs58::encode(root).into_string().bright_yellow(),
+        miners.len().to_string().bright_cyan()
+    );
+
+    let ix = PostStatsRoot::new(oracle.pubkey(), root, miners.len() as u32)
+        .instruction_for(&config.program_id);
+    let signature = signer::send(&client, oracle, &[ix])?;
+
+    println!("{} Posted: {}", "✅".bright_green(), signature);
+    Ok(())
+}
+
+/// Recompute the stats root and compare it with the posted one
+pub fn verify_root(config: &Config) -> Result<()> {
+    let client = testnet_client(config);
+
+    let address = testore_core::stats_root_pda(&config.program_id);
+    testore_core::throttle(&client);
+    let account = client.get_account(&address)?;
+    let posted = testore_core::parse_stats_root(&account.data)
+        .ok_or_else(|| anyhow!("{} is not a StatsRoot account", address))?;
+
+    let miners = testore_core::fetch_miners(&client, &config.program_id)?;
+    let root = testore_core::stats_merkle_root(&miners);
+
+    println!(
+        "   Posted:   {} ({} miners, #{} at slot {})",
+        bs58::encode(posted.root).into_string().bright_yellow(),
+        posted.miner_count,
+        posted.sequence,
+        posted.slot
+    );
+    println!(
+        "   Computed: {} ({} miners)",
+        bs58::encode(root).into_string().bright_yellow(),
+        miners.len()
+    );
+
+    if root != posted.root || miners.len() != posted.miner_count as usize {
+        return Err(anyhow!("Posted stats root does not match testnet state"));
+    }
+
+    println!("{} Stats root verified", "✅".bright_green());
+    Ok(())
+}
+
+fn testnet_client(config: &Config) -> RpcClient {
+    RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed())
+}
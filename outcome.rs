@@ -0,0 +1,165 @@
+//! Exit codes and `--output json` run summaries for automation
+//!
+//! Every run exits with a stable code so schedulers can branch on it:
+//! - 0: success
+//! - 1: any other failure
+//! - 2: finished, but some transfers failed (see the ledger)
+//! - 3: configuration error (bad env var, keypair, cluster or program)
+//! - 4: RPC exhausted (unreachable, or still rate limited after retries)
+//!
+//! With `--output json` the last line of stdout is a [`Report`] and colors
+//! are off; the progress log above it is for humans only.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// How a run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Success,
+    Failed,
+    PartialFailure,
+    ConfigError,
+    RpcExhausted,
+}
+
+impl Status {
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Status::Success => 0,
+            Status::Failed => 1,
+            Status::PartialFailure => 2,
+            Status::ConfigError => 3,
+            Status::RpcExhausted => 4,
+        }
+    }
+}
+
+/// Context marking an error as the operator's configuration, not the run's
+#[derive(Debug)]
+pub struct ConfigError;
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "configuration error")
+    }
+}
+
+/// Mark `error` as a configuration error (exit code 3)
+pub fn config_error(error: anyhow::Error) -> anyhow::Error {
+    error.context(ConfigError)
+}
+
+/// Counts from a run that sends transfers; empty for other commands
+#[derive(Debug, Default, Serialize)]
+pub struct Summary {
+    pub dry_run: bool,
+    pub chunk: Option<u32>,
+    pub recipients: usize,
+    pub tokens: u64,
+    pub sent: usize,
+    pub failed_transactions: usize,
+
+    /// Lamports spent on fees by transactions confirmed this run
+    pub fee_lamports: u64,
+    pub outstanding_recipients: Option<usize>,
+    pub outstanding_tokens: Option<u64>,
+    pub quarantined: Option<usize>,
+    pub snapshot: Option<String>,
+}
+
+/// The `--output json` line
+#[derive(Debug, Serialize)]
+pub struct Report<'a> {
+    pub status: Status,
+    pub exit_code: u8,
+    pub error: Option<String>,
+    pub summary: &'a Summary,
+}
+
+/// Classify a finished run
+pub fn status(result: &Result<()>, summary: &Summary) -> Status {
+    match result {
+        Ok(()) if summary.failed_transactions > 0 => Status::PartialFailure,
+        Ok(()) => Status::Success,
+        Err(e) if rpc_exhausted(e) => Status::RpcExhausted,
+        Err(e) if e.downcast_ref::<ConfigError>().is_some() => Status::ConfigError,
+        Err(_) => Status::Failed,
+    }
+}
+
+/// Whether the run died on the RPC transport rather than on a transaction
+///
+/// The RPC client already retries rate limited requests, so a transport
+/// error reaching here means the endpoint gave out.
+fn rpc_exhausted(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<ClientError>().map(|e| e.kind()),
+            Some(ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_))
+        )
+    })
+}
+
+/// Report how the run ended and return its exit code
+pub fn finish(format: OutputFormat, result: Result<()>, summary: &Summary) -> u8 {
+    let status = status(&result, summary);
+
+    match format {
+        OutputFormat::Text => {
+            if let Err(e) = &result {
+                eprintln!("Error: {:?}", e);
+            }
+        }
+        OutputFormat::Json => {
+            let report = Report {
+                status,
+                exit_code: status.exit_code(),
+                error: result.err().map(|e| format!("{:#}", e)),
+                summary,
+            };
+            println!(
+                "{}",
+                serde_json::to_string(&report).expect("reports always serialize")
+            );
+        }
+    }
+
+    status.exit_code()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_status() {
+        let mut summary = Summary::default();
+        assert_eq!(status(&Ok(()), &summary), Status::Success);
+
+        summary.failed_transactions = 3;
+        assert_eq!(status(&Ok(()), &summary), Status::PartialFailure);
+
+        let config = Err(config_error(anyhow!("PROGRAM_ID not found")));
+        assert_eq!(status(&config, &summary), Status::ConfigError);
+
+        let timeout = std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out");
+        let rpc = Err(anyhow::Error::from(ClientError::from(timeout)).context("fetching miners"));
+        assert_eq!(status(&rpc, &summary), Status::RpcExhausted);
+
+        assert_eq!(status(&Err(anyhow!("boom")), &summary), Status::Failed);
+        assert_eq!(Status::RpcExhausted.exit_code(), 4);
+    }
+}
@@ -0,0 +1,119 @@
+//! Pre-flight checks that the configured clusters are the ones we think
+//!
+//! A wrong PROGRAM_ID or TESTNET_RPC otherwise yields an empty leaderboard,
+//! and a wrong MAINNET_RPC could send real tokens on the wrong cluster.
+
+use crate::cluster::Cluster;
+use anyhow::{anyhow, Result};
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{hash::Hash, program_pack::Pack, pubkey::Pubkey};
+use std::str::FromStr;
+
+/// Genesis hash of mainnet-beta
+pub const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+
+/// Check that `client` is `cluster` and hosts the TestORE program and its
+/// GlobalRound
+pub fn check_testnet(client: &RpcClient, program_id: &Pubkey, cluster: Cluster) -> Result<()> {
+    if let Some(expected) = cluster.genesis_hash()? {
+        testore_core::throttle(client);
+        check_genesis(
+            "TESTNET_RPC",
+            &client.get_genesis_hash()?,
+            &expected,
+            &client.url(),
+        )
+        .map_err(|e| anyhow!("{} (is --cluster {} right?)", e, cluster.name()))?;
+    }
+
+    testore_core::throttle(client);
+    let program = client.get_account(program_id).map_err(|e| {
+        anyhow!(
+            "PROGRAM_ID {} not found on {}: {}",
+            program_id,
+            client.url(),
+            e
+        )
+    })?;
+    if !program.executable {
+        return Err(anyhow!(
+            "PROGRAM_ID {} on {} is not a program",
+            program_id,
+            client.url()
+        ));
+    }
+
+    let round = testore_core::fetch_global_round(client, program_id).map_err(|e| {
+        anyhow!(
+            "GlobalRound for {} is missing or unreadable on {}: {}",
+            program_id,
+            client.url(),
+            e
+        )
+    })?;
+
+    println!(
+        "{} Testnet program found (round #{})",
+        "✅".bright_green(),
+        round.round_number
+    );
+    Ok(())
+}
+
+/// Check that `client` is mainnet-beta and, if given, that `mint` exists on it
+///
+/// Set EXPECTED_MAINNET_GENESIS to rehearse against another cluster.
+pub fn check_mainnet(client: &RpcClient, mint: Option<&Pubkey>) -> Result<()> {
+    let expected = std::env::var("EXPECTED_MAINNET_GENESIS")
+        .unwrap_or_else(|_| MAINNET_GENESIS_HASH.to_string());
+    let expected = Hash::from_str(&expected)
+        .map_err(|e| anyhow!("Invalid EXPECTED_MAINNET_GENESIS '{}': {}", expected, e))?;
+
+    testore_core::throttle(client);
+    check_genesis(
+        "MAINNET_RPC",
+        &client.get_genesis_hash()?,
+        &expected,
+        &client.url(),
+    )?;
+
+    if let Some(mint) = mint {
+        testore_core::throttle(client);
+        let account = client
+            .get_account(mint)
+            .map_err(|e| anyhow!("TESTORE_MINT {} not found on {}: {}", mint, client.url(), e))?;
+        spl_token::state::Mint::unpack(&account.data)
+            .map_err(|e| anyhow!("TESTORE_MINT {} is not an SPL mint: {}", mint, e))?;
+    }
+
+    println!("{} Mainnet cluster verified", "✅".bright_green());
+    Ok(())
+}
+
+fn check_genesis(name: &str, actual: &Hash, expected: &Hash, url: &str) -> Result<()> {
+    if actual != expected {
+        return Err(anyhow!(
+            "{} {} has genesis {}, expected {}; refusing to continue",
+            name,
+            url,
+            actual,
+            expected
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_genesis() {
+        let mainnet = Hash::from_str(MAINNET_GENESIS_HASH).unwrap();
+
+        assert!(check_genesis("MAINNET_RPC", &mainnet, &mainnet, "rpc").is_ok());
+        assert!(check_genesis("MAINNET_RPC", &Hash::new_unique(), &mainnet, "rpc").is_err());
+    }
+}
@@ -0,0 +1,462 @@
+//! `program`: testnet program deployment checks
+//!
+//! Lets airdrop participants confirm the deployed PROGRAM_ID matches the
+//! public source: `verify` compares the on-chain program bytes with a local
+//! verifiable build (`anchor build --verifiable`), and `authority` shows who
+//! can still upgrade it. `authority transfer` hands the upgrade authority to
+//! another key, typically a multisig vault.
+//!
+//! `idl publish` uploads the IDL written by `anchor build` to the program's
+//! Anchor IDL account (through the Anchor CLI), so explorers and third-party
+//! clients can decode Miner and GlobalRound without our hand-rolled offsets;
+//! `idl verify` checks the on-chain copy still matches the local one.
+
+use crate::{prompt, signer, Config};
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use clap::Subcommand;
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    commitment_config::CommitmentConfig,
+    hash::{hash, Hash},
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Seed Anchor derives a program's IDL account address with
+const IDL_SEED: &str = "anchor:idl";
+
+/// Discriminator, authority and data length ahead of the compressed IDL
+const IDL_HEADER_LEN: usize = 8 + 32 + 4;
+
+#[derive(Subcommand)]
+pub enum ProgramCommand {
+    /// Check the deployed program against a locally built artifact
+    Verify {
+        /// Program built with `anchor build --verifiable`
+        #[arg(default_value = "target/verifiable/testore_program.so")]
+        artifact: PathBuf,
+    },
+
+    /// Show or transfer PROGRAM_ID's upgrade authority
+    Authority {
+        #[command(subcommand)]
+        command: Option<UpgradeAuthorityCommand>,
+    },
+
+    /// Publish or check the program's on-chain Anchor IDL
+    Idl {
+        #[command(subcommand)]
+        command: IdlCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum IdlCommand {
+    /// Create or upgrade the IDL account from a local IDL (needs `anchor`)
+    Publish {
+        #[arg(long, default_value = "target/idl/testore_program.json")]
+        idl: PathBuf,
+
+        /// Keypair file of the IDL authority (the Solana CLI's keypair by default)
+        #[arg(long)]
+        wallet: Option<String>,
+    },
+
+    /// Compare the on-chain IDL with a local one
+    Verify {
+        #[arg(long, default_value = "target/idl/testore_program.json")]
+        idl: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum UpgradeAuthorityCommand {
+    /// Move the upgrade authority to another key (e.g. a multisig vault)
+    ///
+    /// Signs with AIRDROP_KEYPAIR if it is the current authority; otherwise
+    /// prints the unsigned transaction for the current authority to sign.
+    Transfer {
+        new_authority: Pubkey,
+
+        /// Skip the typed confirmation (for automation)
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+/// ProgramData account state for a deployed program
+struct Deployment {
+    programdata: Pubkey,
+    upgrade_authority: Option<Pubkey>,
+    deployed_slot: u64,
+    bytes: Vec<u8>,
+}
+
+pub fn run(config: &Config, command: &ProgramCommand) -> Result<()> {
+    let client =
+        RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed());
+    let deployment = fetch_deployment(&client, &config.program_id)?;
+
+    match command {
+        ProgramCommand::Verify { artifact } => verify(config, &deployment, artifact),
+        ProgramCommand::Authority { command: None } => {
+            show_authority(config, &deployment);
+            Ok(())
+        }
+        ProgramCommand::Authority {
+            command: Some(UpgradeAuthorityCommand::Transfer { new_authority, yes }),
+        } => transfer_authority(&client, config, &deployment, new_authority, *yes),
+        ProgramCommand::Idl {
+            command: IdlCommand::Publish { idl, wallet },
+        } => {
+            publish_idl(&client, config, idl, wallet.as_deref())?;
+            verify_idl(&client, config, idl)
+        }
+        ProgramCommand::Idl {
+            command: IdlCommand::Verify { idl },
+        } => verify_idl(&client, config, idl),
+    }
+}
+
+fn verify(config: &Config, deployment: &Deployment, artifact: &Path) -> Result<()> {
+    let local = artifact_hash(&std::fs::read(artifact)?);
+    let onchain = artifact_hash(&deployment.bytes);
+
+    println!(
+        "   On-chain:  {} (slot {})",
+        onchain.to_string().bright_yellow(),
+        deployment.deployed_slot
+    );
+    println!(
+        "   Local:     {} ({})",
+        local.to_string().bright_yellow(),
+        artifact.display()
+    );
+
+    if local != onchain {
+        return Err(anyhow!(
+            "{} does not match {}",
+            config.program_id,
+            artifact.display()
+        ));
+    }
+
+    println!(
+        "{} {} matches the local build",
+        "✅".bright_green(),
+        config.program_id.to_string().bright_yellow()
+    );
+    Ok(())
+}
+
+fn show_authority(config: &Config, deployment: &Deployment) {
+    println!(
+        "   Program:      {}",
+        config.program_id.to_string().bright_yellow()
+    );
+    println!("   ProgramData:  {}", deployment.programdata);
+    println!("   Last deploy:  slot {}", deployment.deployed_slot);
+    match deployment.upgrade_authority {
+        Some(authority) => println!(
+            "   Upgradeable:  by {}",
+            authority.to_string().bright_yellow()
+        ),
+        None => println!("   Upgradeable:  {}", "no (immutable)".bright_green()),
+    }
+}
+
+fn transfer_authority(
+    client: &RpcClient,
+    config: &Config,
+    deployment: &Deployment,
+    new_authority: &Pubkey,
+    yes: bool,
+) -> Result<()> {
+    let current = deployment
+        .upgrade_authority
+        .ok_or_else(|| anyhow!("{} is immutable", config.program_id))?;
+    let ix = bpf_loader_upgradeable::set_upgrade_authority(
+        &config.program_id,
+        &current,
+        Some(new_authority),
+    );
+
+    // The current authority is already a multisig or another wallet
+    if current != config.signer.pubkey() {
+        let tx = Transaction::new_with_payer(&[ix], Some(&current));
+        println!(
+            "{} {} is not the upgrade authority; have {} sign this transaction:",
+            "ℹ️".bright_blue(),
+            config.signer.pubkey(),
+            current.to_string().bright_yellow()
+        );
+        println!(
+            "{}",
+            base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&tx)?)
+        );
+        return Ok(());
+    }
+
+    if !yes {
+        println!(
+            "{} Only {} will be able to upgrade {} after this.",
+            "⚠️".bright_yellow(),
+            new_authority.to_string().bright_yellow(),
+            config.program_id
+        );
+        let typed = prompt("   Type the new authority to confirm: ")?;
+        if typed.trim() != new_authority.to_string() {
+            return Err(anyhow!(
+                "Confirmation did not match - upgrade authority unchanged"
+            ));
+        }
+    }
+
+    let signature = signer::send(client, &*config.signer, &[ix])?;
+    println!(
+        "{} Upgrade authority moved to {}: {}",
+        "✅".bright_green(),
+        new_authority.to_string().bright_yellow(),
+        signature
+    );
+    Ok(())
+}
+
+fn publish_idl(
+    client: &RpcClient,
+    config: &Config,
+    idl: &Path,
+    wallet: Option<&str>,
+) -> Result<()> {
+    // Anchor doesn't expand `~` on every OS
+    let wallet = signer::expand_path(
+        &wallet
+            .map(str::to_string)
+            .unwrap_or_else(signer::default_keypair_path),
+    );
+
+    let action = match fetch_idl(client, &config.program_id)? {
+        Some(_) => "upgrade",
+        None => "init",
+    };
+    println!(
+        "{} Publishing {} ({})...",
+        "📤".bright_cyan(),
+        idl.display(),
+        action
+    );
+
+    let status = Command::new("anchor")
+        .args(["idl", action, "--filepath"])
+        .arg(idl)
+        .args(["--provider.cluster", &config.testnet_rpc])
+        .arg("--provider.wallet")
+        .arg(&wallet)
+        .arg(config.program_id.to_string())
+        .status()
+        .map_err(|e| anyhow!("running anchor (is the Anchor CLI installed?): {}", e))?;
+    if !status.success() {
+        return Err(anyhow!("anchor idl {} failed: {}", action, status));
+    }
+
+    Ok(())
+}
+
+fn verify_idl(client: &RpcClient, config: &Config, idl: &Path) -> Result<()> {
+    let local: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(idl)?)?;
+    let onchain = fetch_idl(client, &config.program_id)?
+        .ok_or_else(|| anyhow!("{} has no IDL account", config.program_id))?;
+
+    let differences = idl_differences(&onchain, &local);
+    if !differences.is_empty() {
+        for difference in &differences {
+            println!("   {} {}", "✗".bright_red(), difference);
+        }
+        return Err(anyhow!(
+            "On-chain IDL for {} does not match {}",
+            config.program_id,
+            idl.display()
+        ));
+    }
+
+    println!(
+        "{} On-chain IDL at {} matches {}",
+        "✅".bright_green(),
+        idl_address(&config.program_id).to_string().bright_yellow(),
+        idl.display()
+    );
+    Ok(())
+}
+
+/// Address of the Anchor IDL account for `program_id`
+fn idl_address(program_id: &Pubkey) -> Pubkey {
+    let base = Pubkey::find_program_address(&[], program_id).0;
+    Pubkey::create_with_seed(&base, IDL_SEED, program_id).expect("IDL seed is short enough")
+}
+
+/// The program's published IDL, if it has an IDL account
+fn fetch_idl(client: &RpcClient, program_id: &Pubkey) -> Result<Option<serde_json::Value>> {
+    testore_core::throttle(client);
+    let account = client
+        .get_account_with_commitment(&idl_address(program_id), client.commitment())?
+        .value;
+
+    account
+        .map(|account| parse_idl_account(&account.data))
+        .transpose()
+}
+
+/// IDL JSON stored in an Anchor IDL account (zlib-compressed after the header)
+fn parse_idl_account(data: &[u8]) -> Result<serde_json::Value> {
+    if data.len() < IDL_HEADER_LEN {
+        return Err(anyhow!("IDL account is too short"));
+    }
+    let len = u32::from_le_bytes(data[40..44].try_into()?) as usize;
+    let compressed = data
+        .get(IDL_HEADER_LEN..IDL_HEADER_LEN + len)
+        .ok_or_else(|| anyhow!("IDL account data is truncated"))?;
+
+    let mut json = String::new();
+    flate2::read::ZlibDecoder::new(compressed).read_to_string(&mut json)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Sections of two IDLs that differ, ignoring the deploy-time `metadata`
+fn idl_differences(onchain: &serde_json::Value, local: &serde_json::Value) -> Vec<String> {
+    let mut differences = Vec::new();
+
+    for section in [
+        "version",
+        "name",
+        "instructions",
+        "accounts",
+        "types",
+        "events",
+        "errors",
+    ] {
+        let (theirs, ours) = (&onchain[section], &local[section]);
+        if theirs == ours {
+            continue;
+        }
+
+        let (Some(theirs), Some(ours)) = (theirs.as_array(), ours.as_array()) else {
+            differences.push(format!("{} differs", section));
+            continue;
+        };
+        // Name the changed entries of list sections
+        for entry in ours.iter().filter(|entry| !theirs.contains(entry)) {
+            differences.push(format!(
+                "{} {} differs or is unpublished",
+                section, entry["name"]
+            ));
+        }
+        for entry in theirs
+            .iter()
+            .filter(|entry| !ours.iter().any(|o| o["name"] == entry["name"]))
+        {
+            differences.push(format!("{} {} is only on-chain", section, entry["name"]));
+        }
+    }
+
+    differences
+}
+
+fn fetch_deployment(client: &RpcClient, program_id: &Pubkey) -> Result<Deployment> {
+    testore_core::throttle(client);
+    let program = client.get_account(program_id)?;
+    if program.owner != bpf_loader_upgradeable::id() {
+        return Err(anyhow!("{} is not an upgradeable program", program_id));
+    }
+    let UpgradeableLoaderState::Program {
+        programdata_address,
+    } = bincode::deserialize(&program.data)?
+    else {
+        return Err(anyhow!("{} is not a program account", program_id));
+    };
+
+    testore_core::throttle(client);
+    let data = client.get_account_data(&programdata_address)?;
+    let UpgradeableLoaderState::ProgramData {
+        slot,
+        upgrade_authority_address,
+    } = bincode::deserialize(&data)?
+    else {
+        return Err(anyhow!(
+            "{} is not a ProgramData account",
+            programdata_address
+        ));
+    };
+
+    Ok(Deployment {
+        programdata: programdata_address,
+        upgrade_authority: upgrade_authority_address,
+        deployed_slot: slot,
+        bytes: data[UpgradeableLoaderState::size_of_programdata_metadata()..].to_vec(),
+    })
+}
+
+/// SHA-256 of a program binary without trailing zero padding, which
+/// ProgramData accounts carry when deployed with room to grow
+fn artifact_hash(bytes: &[u8]) -> Hash {
+    let end = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    hash(&bytes[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifact_hash_ignores_padding() {
+        let elf = vec![0x7f, b'E', b'L', b'F', 0, 1, 2];
+        let mut deployed = elf.clone();
+        deployed.resize(4096, 0);
+
+        assert_eq!(artifact_hash(&elf), artifact_hash(&deployed));
+        assert_ne!(artifact_hash(&elf), artifact_hash(&elf[..6]));
+    }
+
+    #[test]
+    fn test_idl_account_and_differences() {
+        use flate2::{write::ZlibEncoder, Compression};
+        use std::io::Write;
+
+        let local = serde_json::json!({
+            "version": "0.1.0",
+            "name": "testore_program",
+            "instructions": [{ "name": "submitHash", "args": [] }],
+            "accounts": [{ "name": "Miner" }, { "name": "GlobalRound" }],
+        });
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(local.to_string().as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut data = vec![0; 40];
+        data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        data.extend_from_slice(&compressed);
+        data.resize(data.len() + 64, 0);
+
+        let mut onchain = parse_idl_account(&data).unwrap();
+        assert!(idl_differences(&onchain, &local).is_empty());
+
+        onchain["metadata"] =
+            serde_json::json!({ "address": "TESTORE11111111111111111111111111111111111" });
+        assert!(idl_differences(&onchain, &local).is_empty());
+
+        onchain["accounts"] = serde_json::json!([{ "name": "Miner" }, { "name": "Stale" }]);
+        assert_eq!(
+            idl_differences(&onchain, &local),
+            vec![
+                "accounts \"GlobalRound\" differs or is unpublished",
+                "accounts \"Stale\" is only on-chain",
+            ]
+        );
+        assert!(parse_idl_account(&data[..20]).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+//! First-time setup: `init` and `completions`, and where settings come from
+//!
+//! `init` asks for the cluster, RPC endpoints, program ID and funding
+//! keypair, checks each against the chain, and writes them to
+//! [`ENV_FILE_PATH`]. Every run loads that file at startup; variables already
+//! set in the environment win, so one-off overrides still work.
+//!
+//! Each setting is taken from the first of:
+//! 1. its flag (`--url`, `--keypair`, `--cluster`)
+//! 2. its environment variable
+//! 3. [`ENV_FILE_PATH`]
+//! 4. the Solana CLI config (`solana config get`): `keypair_path` for the
+//!    funding wallet, and `json_rpc_url` for TESTNET_RPC unless it is the
+//!    public mainnet-beta endpoint
+//! 5. the `--cluster` profile's default
+//!
+//! `completions <shell>` prints a completion script for the bridge.
+
+use crate::cluster::Cluster;
+use crate::{preflight, prompt, signer, Cli};
+use anyhow::{anyhow, Result};
+use clap::{CommandFactory, ValueEnum};
+use clap_complete::Shell;
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::fs;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Settings written by `init`, in the working directory like the ledger
+pub const ENV_FILE_PATH: &str = "testore-bridge.env";
+
+/// Public mainnet-beta endpoint, MAINNET_RPC's default
+pub const MAINNET_BETA_RPC: &str = "https://api.mainnet-beta.solana.com";
+
+/// Set every variable in `path` that isn't already set
+pub fn load_env_file(path: impl AsRef<Path>) -> Result<()> {
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+
+    for (key, value) in parse_env_file(&raw)? {
+        if std::env::var_os(&key).is_none() {
+            std::env::set_var(key, value);
+        }
+    }
+    Ok(())
+}
+
+/// `KEY=value` lines; blank lines, `#` comments and `export ` are allowed
+fn parse_env_file(raw: &str) -> Result<Vec<(String, String)>> {
+    raw.lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                anyhow!("{} line {}: expected KEY=value", ENV_FILE_PATH, index + 1)
+            })?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            Ok((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn render_env_file(settings: &[(&str, String)]) -> String {
+    let mut out =
+        String::from("# Written by `testore-bridge init`; environment variables override it\n");
+    for (key, value) in settings {
+        out.push_str(&format!("{}=\"{}\"\n", key, value));
+    }
+    out
+}
+
+/// The Solana CLI's config, or its defaults when there is none
+pub fn solana_cli_config() -> solana_cli_config::Config {
+    solana_cli_config::CONFIG_FILE
+        .as_ref()
+        .and_then(|file| solana_cli_config::Config::load(file).ok())
+        .unwrap_or_default()
+}
+
+/// The Solana CLI's RPC URL, if it can stand in for TESTNET_RPC
+///
+/// Operators often point the CLI at mainnet-beta for the funding wallet;
+/// that URL is MAINNET_RPC's default already and would fail the genesis check.
+pub fn solana_cli_testnet_rpc(config: &solana_cli_config::Config) -> Option<String> {
+    let url = config.json_rpc_url.trim_end_matches('/');
+    (url != MAINNET_BETA_RPC).then(|| url.to_string())
+}
+
+/// Print a completion script for `shell`
+pub fn completions(shell: Shell) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+}
+
+/// Walk through the settings and write [`ENV_FILE_PATH`]
+pub fn init() -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow!("`init` is interactive; run it from a terminal"));
+    }
+    if Path::new(ENV_FILE_PATH).exists()
+        && !yes(&format!("{} exists. Overwrite it? [y/N] ", ENV_FILE_PATH))?
+    {
+        return Ok(());
+    }
+
+    let cluster = loop {
+        let answer = ask(
+            "Cluster (testnet, devnet, localnet)",
+            &current("TESTORE_CLUSTER", Cluster::Testnet.name()),
+        )?;
+        match Cluster::from_str(&answer, true) {
+            Ok(cluster) => break cluster,
+            Err(_) => println!("   {} Unknown cluster '{}'", "❌".bright_red(), answer),
+        }
+    };
+    let profile = cluster.profile();
+
+    let solana_cli_rpc = solana_cli_testnet_rpc(&solana_cli_config());
+    let testnet_rpc = ask(
+        "Testnet RPC",
+        &current(
+            "TESTNET_RPC",
+            solana_cli_rpc.as_deref().unwrap_or(profile.default_rpc),
+        ),
+    )?;
+    let program_id = loop {
+        let answer = ask("Program ID", &current("PROGRAM_ID", profile.program_id))?;
+        match Pubkey::from_str(&answer) {
+            Ok(program_id) => break program_id,
+            Err(e) => println!("   {} {}", "❌".bright_red(), e),
+        }
+    };
+
+    // The same checks every command runs before touching testnet
+    let client = RpcClient::new_with_commitment(testnet_rpc.clone(), CommitmentConfig::confirmed());
+    match preflight::check_testnet(&client, &program_id, cluster) {
+        Ok(()) => println!(
+            "   {} Program found on {}",
+            "✅".bright_green(),
+            testnet_rpc
+        ),
+        Err(e) => {
+            println!("   {} {:#}", "❌".bright_red(), e);
+            if !yes("   Save these settings anyway? [y/N] ")? {
+                return Err(anyhow!("Setup aborted; nothing written"));
+            }
+        }
+    }
+
+    let keypair = loop {
+        let answer = ask(
+            "Funding wallet keypair",
+            &current("AIRDROP_KEYPAIR", &signer::default_keypair_path()),
+        )?;
+        match signer::load_signer(&answer) {
+            Ok(signer) => {
+                println!(
+                    "   {} Funding wallet {}",
+                    "✅".bright_green(),
+                    signer.pubkey()
+                );
+                break answer;
+            }
+            Err(e) => println!("   {} {:#}", "❌".bright_red(), e),
+        }
+    };
+
+    let mainnet_rpc = ask("Mainnet RPC", &current("MAINNET_RPC", MAINNET_BETA_RPC))?;
+    let mint = ask(
+        "TESTORE mint (blank until it exists)",
+        &current("TESTORE_MINT", ""),
+    )?;
+
+    let mut settings = vec![
+        ("TESTORE_CLUSTER", cluster.name().to_string()),
+        ("TESTNET_RPC", testnet_rpc),
+        ("PROGRAM_ID", program_id.to_string()),
+        ("AIRDROP_KEYPAIR", keypair),
+        ("MAINNET_RPC", mainnet_rpc),
+    ];
+    if !mint.is_empty() {
+        settings.push(("TESTORE_MINT", mint));
+    }
+    fs::write(ENV_FILE_PATH, render_env_file(&settings))?;
+
+    println!(
+        "\n{} Saved to {}; run `testore-bridge` from this directory to preview the airdrop",
+        "💾".bright_cyan(),
+        ENV_FILE_PATH.bright_yellow()
+    );
+    Ok(())
+}
+
+/// The value already configured, so re-running `init` only changes what's typed
+fn current(key: &str, fallback: &str) -> String {
+    std::env::var(key).unwrap_or_else(|_| fallback.to_string())
+}
+
+/// Prompt for a value, returning `default` for an empty answer
+fn ask(question: &str, default: &str) -> Result<String> {
+    let answer = if default.is_empty() {
+        prompt(&format!("{}: ", question))?
+    } else {
+        prompt(&format!("{} [{}]: ", question, default))?
+    };
+    let answer = answer.trim();
+
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
+
+fn yes(question: &str) -> Result<bool> {
+    Ok(prompt(question)?.trim().eq_ignore_ascii_case("y"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_file_round_trip() {
+        let settings = vec![
+            ("TESTNET_RPC", "https://api.testnet.solana.com".to_string()),
+            ("AIRDROP_KEYPAIR", "~/keys/funding.json".to_string()),
+        ];
+        let parsed = parse_env_file(&render_env_file(&settings)).unwrap();
+        assert_eq!(
+            parsed,
+            settings
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect::<Vec<_>>()
+        );
+
+        let parsed = parse_env_file("\n# comment\nexport PROGRAM_ID = abc\nMINT=x=y\n").unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                ("PROGRAM_ID".to_string(), "abc".to_string()),
+                ("MINT".to_string(), "x=y".to_string()),
+            ]
+        );
+        assert!(parse_env_file("not a setting").is_err());
+    }
+
+    #[test]
+    fn test_solana_cli_testnet_rpc() {
+        let mut config = solana_cli_config::Config {
+            json_rpc_url: "https://api.testnet.solana.com".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            solana_cli_testnet_rpc(&config).as_deref(),
+            Some("https://api.testnet.solana.com")
+        );
+
+        config.json_rpc_url = format!("{}/", MAINNET_BETA_RPC);
+        assert_eq!(solana_cli_testnet_rpc(&config), None);
+    }
+
+    #[test]
+    fn test_completions_cover_subcommands() {
+        let mut script = Vec::new();
+        clap_complete::generate(
+            Shell::Bash,
+            &mut Cli::command(),
+            "testore-bridge",
+            &mut script,
+        );
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("retry-quarantined"));
+        assert!(script.contains("completions"));
+    }
+}
@@ -0,0 +1,266 @@
+//! Transaction signers
+//!
+//! Every sending path signs through [`TxSigner`], so the funding, oracle and
+//! crank wallets can each be:
+//! - a JSON keypair file, plaintext or encrypted (any other path)
+//! - a Ledger (`usb://ledger`, optionally `usb://ledger?key=0/0`)
+//! - a remote signing service (`https://...`), e.g. a KMS or custody API
+//!   behind a small adapter
+//!
+//! A remote signer must answer `GET <url>/pubkey` with `{"pubkey": "<base58>"}`
+//! and `POST <url>/sign` with `{"pubkey", "message": "<base64>"}` by returning
+//! `{"signature": "<base58>"}`. REMOTE_SIGNER_TOKEN, if set, is sent as a
+//! bearer token.
+//!
+//! Keypair paths may start with `~`, resolved to the home directory on every
+//! OS. Without AIRDROP_KEYPAIR or `--keypair`, the funding wallet is the
+//! Solana CLI's configured keypair (`solana config get`).
+
+use crate::load_keypair;
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use solana_client::rpc_client::RpcClient;
+use solana_remote_wallet::{
+    locator::Locator, remote_keypair::generate_remote_keypair, remote_wallet::maybe_wallet_manager,
+};
+use solana_sdk::{
+    derivation_path::DerivationPath,
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Something that can sign transaction messages for one wallet
+pub trait TxSigner {
+    fn pubkey(&self) -> Pubkey;
+
+    fn sign_message(&self, message: &[u8]) -> Result<Signature>;
+}
+
+/// Local keypairs and Ledgers, via the Solana SDK's own signer trait
+impl<T: Signer> TxSigner for T {
+    fn pubkey(&self) -> Pubkey {
+        Signer::pubkey(self)
+    }
+
+    fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(self.try_sign_message(message)?)
+    }
+}
+
+/// `path` with a leading `~` replaced by the user's home directory
+///
+/// Accepts either separator after `~` on Windows; other paths are untouched.
+pub fn expand_path(path: &str) -> PathBuf {
+    let rest = match path.strip_prefix('~') {
+        Some("") => "",
+        Some(rest) => match rest.strip_prefix(std::path::is_separator) {
+            Some(rest) => rest,
+            None => return PathBuf::from(path),
+        },
+        None => return PathBuf::from(path),
+    };
+
+    match dirs::home_dir() {
+        Some(home) if rest.is_empty() => home,
+        Some(home) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// The keypair the Solana CLI is configured with, or its default location
+pub fn default_keypair_path() -> String {
+    crate::setup::solana_cli_config().keypair_path
+}
+
+/// Load the signer described by `path` (see the module docs)
+pub fn load_signer(path: &str) -> Result<Box<dyn TxSigner>> {
+    if path.starts_with("usb://") {
+        return load_ledger(path);
+    }
+
+    if path.starts_with("https://") || path.starts_with("http://") {
+        return Ok(Box::new(RemoteSigner::connect(path)?));
+    }
+
+    Ok(Box::new(load_keypair(path)?))
+}
+
+fn load_ledger(path: &str) -> Result<Box<dyn TxSigner>> {
+    let (locator, key) = path.split_once("?key=").unwrap_or((path, ""));
+    let derivation_path = if key.is_empty() {
+        DerivationPath::default()
+    } else {
+        DerivationPath::from_key_str(key)?
+    };
+
+    let wallet_manager =
+        maybe_wallet_manager()?.ok_or_else(|| anyhow!("No hardware wallet found for {}", path))?;
+    let keypair = generate_remote_keypair(
+        Locator::new_from_path(locator)?,
+        derivation_path,
+        &wallet_manager,
+        false,
+        "ledger",
+    )?;
+
+    Ok(Box::new(keypair))
+}
+
+/// HTTP signing service holding the key
+pub struct RemoteSigner {
+    url: String,
+    pubkey: Pubkey,
+    client: reqwest::blocking::Client,
+}
+
+impl RemoteSigner {
+    fn connect(url: &str) -> Result<Self> {
+        let url = url.trim_end_matches('/').to_string();
+        let client = reqwest::blocking::Client::new();
+
+        let response = tokio::task::block_in_place(|| {
+            authorize(client.get(format!("{}/pubkey", url)))
+                .send()?
+                .error_for_status()?
+                .json::<serde_json::Value>()
+        })?;
+        let pubkey = response["pubkey"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Remote signer {} returned no pubkey", url))?;
+
+        Ok(Self {
+            pubkey: Pubkey::from_str(pubkey)?,
+            url,
+            client,
+        })
+    }
+}
+
+impl TxSigner for RemoteSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        let body = serde_json::json!({
+            "pubkey": self.pubkey.to_string(),
+            "message": base64::engine::general_purpose::STANDARD.encode(message),
+        });
+
+        let response = tokio::task::block_in_place(|| {
+            authorize(self.client.post(format!("{}/sign", self.url)))
+                .json(&body)
+                .send()?
+                .error_for_status()?
+                .json::<serde_json::Value>()
+        })?;
+        let signature = Signature::from_str(
+            response["signature"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Remote signer {} returned no signature", self.url))?,
+        )?;
+
+        // Don't broadcast whatever a misbehaving service hands back
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(anyhow!(
+                "Remote signer {} returned a bad signature",
+                self.url
+            ));
+        }
+
+        Ok(signature)
+    }
+}
+
+fn authorize(request: reqwest::blocking::RequestBuilder) -> reqwest::blocking::RequestBuilder {
+    match std::env::var("REMOTE_SIGNER_TOKEN") {
+        Ok(token) => request.bearer_auth(token),
+        Err(_) => request,
+    }
+}
+
+/// Sign `instructions` with `signer` as fee payer and send them
+pub fn send(
+    client: &RpcClient,
+    signer: &dyn TxSigner,
+    instructions: &[Instruction],
+) -> Result<Signature> {
+    send_with_keypairs(client, signer, &[], instructions)
+}
+
+/// Like [`send`], with extra local co-signers such as a new account's keypair
+pub fn send_with_keypairs(
+    client: &RpcClient,
+    signer: &dyn TxSigner,
+    keypairs: &[&Keypair],
+    instructions: &[Instruction],
+) -> Result<Signature> {
+    testore_core::throttle(client);
+    let blockhash = client.get_latest_blockhash()?;
+    let tx = sign(signer, keypairs, instructions, blockhash)?;
+
+    submit(client, &tx)
+}
+
+/// Sign `instructions` with `signer` as fee payer, without sending them
+pub fn sign(
+    signer: &dyn TxSigner,
+    keypairs: &[&Keypair],
+    instructions: &[Instruction],
+    blockhash: Hash,
+) -> Result<Transaction> {
+    let mut tx = Transaction::new_with_payer(instructions, Some(&signer.pubkey()));
+
+    tx.try_partial_sign(keypairs, blockhash)?;
+    // The fee payer always signs first
+    tx.signatures[0] = signer.sign_message(&tx.message_data())?;
+
+    Ok(tx)
+}
+
+/// Send a signed transaction and wait for confirmation
+pub fn submit(client: &RpcClient, tx: &Transaction) -> Result<Signature> {
+    testore_core::throttle(client);
+    Ok(client.send_and_confirm_transaction(tx)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::system_instruction;
+
+    #[test]
+    fn test_keypair_signs_transactions() {
+        let keypair = Keypair::new();
+        let signer: &dyn TxSigner = &keypair;
+        let ix = system_instruction::transfer(&signer.pubkey(), &Pubkey::new_unique(), 1);
+
+        let mut tx = Transaction::new_with_payer(&[ix], Some(&signer.pubkey()));
+        tx.signatures = vec![signer.sign_message(&tx.message_data()).unwrap()];
+
+        assert!(tx.verify().is_ok());
+    }
+
+    #[test]
+    fn test_expand_path() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_path("~"), home);
+        assert_eq!(
+            expand_path("~/.config/solana/id.json"),
+            home.join(".config/solana/id.json")
+        );
+        assert_eq!(expand_path("keys/id.json"), PathBuf::from("keys/id.json"));
+        assert_eq!(
+            expand_path("~alice/id.json"),
+            PathBuf::from("~alice/id.json")
+        );
+        if cfg!(windows) {
+            assert_eq!(expand_path(r"~\keys\id.json"), home.join(r"keys\id.json"));
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! `sweep`: compare allocation parameters against one snapshot
+//!
+//! Recomputes the snapshot's allocations from its recorded inputs for every
+//! combination of the given parameter values and reports how many wallets
+//! each gets paid, the total, and how concentrated it is (Gini, top-10
+//! share). Any parameter left out keeps the snapshot's recorded value.
+
+use crate::{format_number, SNAPSHOT_PATH};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use colored::*;
+use serde::Serialize;
+use std::path::PathBuf;
+use testore_core::{AllocationMetrics, AllocationParams, Snapshot};
+
+#[derive(Args)]
+pub struct SweepArgs {
+    /// Snapshot whose recorded inputs are re-allocated
+    #[arg(long, default_value = SNAPSHOT_PATH)]
+    snapshot: PathBuf,
+
+    /// TESTORE per million hashes, e.g. 50,100,200
+    #[arg(long, value_delimiter = ',')]
+    tokens_per_million: Vec<u64>,
+
+    /// Minimum hash counts to try
+    #[arg(long, value_delimiter = ',')]
+    minimum_hashes: Vec<u64>,
+
+    /// Recipient caps (top N miners) to try
+    #[arg(long, value_delimiter = ',')]
+    top_miners: Vec<usize>,
+
+    /// Liveness boosts to try, in basis points
+    #[arg(long, value_delimiter = ',')]
+    liveness_boost_bps: Vec<u64>,
+
+    /// Print JSON instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+/// One grid point and what it would pay out
+#[derive(Debug, Serialize)]
+struct SweepRow {
+    params: AllocationParams,
+
+    #[serde(flatten)]
+    metrics: AllocationMetrics,
+}
+
+pub fn run(args: &SweepArgs) -> Result<()> {
+    let snapshot = Snapshot::load(&args.snapshot)?;
+    let recorded = snapshot
+        .params
+        .ok_or_else(|| anyhow!("Snapshot predates recorded parameters"))?;
+
+    let grid = grid(args, &recorded);
+    let rows = grid
+        .into_iter()
+        .map(|params| {
+            Ok(SweepRow {
+                metrics: testore_core::allocation_metrics(&snapshot.recompute(&params)?),
+                params,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} {} parameter sets over {} ranked miners\n",
+        "🧮".bright_cyan(),
+        rows.len(),
+        format_number(snapshot.inputs.len() as u64)
+    );
+    println!(
+        "{:>8} {:>12} {:>8} {:>8} {:>11} {:>16} {:>6} {:>7}",
+        "Per 1M", "Min hashes", "Top N", "Boost", "Recipients", "Tokens", "Gini", "Top 10"
+    );
+    for row in &rows {
+        let line = format!(
+            "{:>8} {:>12} {:>8} {:>8} {:>11} {:>16} {:>6.3} {:>6.1}%",
+            row.params.tokens_per_million_hashes,
+            format_number(row.params.minimum_hashes),
+            row.params.top_miners,
+            row.params.liveness_boost_bps,
+            format_number(row.metrics.recipients as u64),
+            format_number(row.metrics.total_tokens),
+            row.metrics.gini,
+            row.metrics.top10_share * 100.0
+        );
+        if row.params == recorded {
+            println!("{} (recorded)", line.bright_green());
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    Ok(())
+}
+
+/// Every combination of the swept values, unswept ones fixed at `recorded`
+fn grid(args: &SweepArgs, recorded: &AllocationParams) -> Vec<AllocationParams> {
+    fn or_recorded<T: Copy>(values: &[T], recorded: T) -> Vec<T> {
+        if values.is_empty() {
+            vec![recorded]
+        } else {
+            values.to_vec()
+        }
+    }
+
+    let mut grid = Vec::new();
+    for tokens_per_million_hashes in
+        or_recorded(&args.tokens_per_million, recorded.tokens_per_million_hashes)
+    {
+        for minimum_hashes in or_recorded(&args.minimum_hashes, recorded.minimum_hashes) {
+            for top_miners in or_recorded(&args.top_miners, recorded.top_miners) {
+                for liveness_boost_bps in
+                    or_recorded(&args.liveness_boost_bps, recorded.liveness_boost_bps)
+                {
+                    grid.push(AllocationParams {
+                        tokens_per_million_hashes,
+                        minimum_hashes,
+                        top_miners,
+                        liveness_boost_bps,
+                        ..*recorded
+                    });
+                }
+            }
+        }
+    }
+
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let args = SweepArgs {
+            snapshot: PathBuf::from(SNAPSHOT_PATH),
+            tokens_per_million: vec![50, 100, 200],
+            minimum_hashes: vec![0, 1_000_000],
+            top_miners: Vec::new(),
+            liveness_boost_bps: Vec::new(),
+            json: false,
+        };
+        let recorded = AllocationParams::default();
+
+        let grid = grid(&args, &recorded);
+        assert_eq!(grid.len(), 6);
+        assert!(grid.iter().all(|p| p.top_miners == recorded.top_miners));
+        assert!(grid.contains(&AllocationParams {
+            tokens_per_million_hashes: 200,
+            minimum_hashes: 0,
+            ..recorded
+        }));
+    }
+}
@@ -0,0 +1,3538 @@
+//! TestORE Core
+//!
+//! Shared logic for everything that reads TestORE state off-chain:
+//! - Miner, miner metadata and GlobalRound account parsing
+//! - Per-region load from self-reported miner regions
+//! - Score bucket percentile estimates
+//! - Stats merkle roots for cross-cluster verification
+//! - Liveness proofs, the round reward pool and idle score decay
+//! - Airdrop allocation math
+//! - Snapshot, distribution ledger and leaderboard cache file formats
+//! - RPC helpers and a shared per-endpoint request budget
+//! - Passphrase-encrypted keypair files
+//!
+//! The bridge, leaderboard and third-party tools should go through this
+//! crate instead of hand-rolling account offsets.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::{hash, Hash, Hasher},
+    keccak::hashv,
+    pubkey::Pubkey,
+    signature::Keypair,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+// ============================================================================
+// Miner Accounts
+// ============================================================================
+
+/// Serialized size of a [`MinerLayout::V1`] Miner account, including the
+/// 8-byte discriminator; the smallest account [`parse_miner_account`] accepts
+///
+/// Format: [discriminator: 8] [authority: 32] [total_hashes: 8] [rounds: 4] [last_hash: 8] [streak: 4] [best_diff: 1] [bump: 1]
+pub const MINER_ACCOUNT_LEN: usize = 66;
+
+/// Serialized size of a [`MinerLayout::V2`] Miner account
+///
+/// Format: [V1: 66] [tier: 1] [commitment: 32] [committed_slot: 8] [score_bucket: 1]
+pub const MINER_ACCOUNT_LEN_V2: usize = 108;
+
+/// Serialized size of a [`MinerLayout::V3`] Miner account
+///
+/// Format: [V2: 108] [round_number: 8] [round_proofs: 4] [capped_score: 8]
+pub const MINER_ACCOUNT_LEN_V3: usize = 128;
+
+/// Serialized size of a [`MinerLayout::V4`] Miner account
+///
+/// Format: [V3: 128] [score: 8] [score_converted: 1]
+pub const MINER_ACCOUNT_LEN_V4: usize = 137;
+
+/// Miner account layouts, told apart by account size
+///
+/// Program upgrades only ever append fields, so every layout starts with the
+/// previous one and accounts larger than the newest known layout are decoded
+/// as that layout. Accounts created before an upgrade keep their old size,
+/// and the program refuses to load them, until `upgrade_miner` reallocates
+/// them to the current layout; until then they decode here as their old
+/// layout, with the newer fields `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MinerLayout {
+    /// Original layout, through `bump`
+    V1,
+
+    /// Adds tier, the commit-reveal commitment and the score bucket
+    V2,
+
+    /// Adds the per-round proof count and capped_score
+    V3,
+
+    /// Adds the difficulty-weighted score from the scoring migration
+    V4,
+}
+
+impl MinerLayout {
+    /// Newest layout that fits in `len` bytes
+    pub fn detect(len: usize) -> Option<Self> {
+        match len {
+            len if len >= MINER_ACCOUNT_LEN_V4 => Some(Self::V4),
+            len if len >= MINER_ACCOUNT_LEN_V3 => Some(Self::V3),
+            len if len >= MINER_ACCOUNT_LEN_V2 => Some(Self::V2),
+            len if len >= MINER_ACCOUNT_LEN => Some(Self::V1),
+            _ => None,
+        }
+    }
+}
+
+/// Decoded on-chain Miner account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinerAccount {
+    /// Wallet address of the miner
+    pub authority: Pubkey,
+
+    /// Total number of valid hashes submitted
+    pub total_hashes: u64,
+
+    /// Number of completed rounds (10 consecutive hashes)
+    pub rounds_completed: u32,
+
+    /// Unix timestamp of last hash submission
+    pub last_hash_at: i64,
+
+    /// Current consecutive hash streak
+    pub current_streak: u32,
+
+    /// Highest difficulty achieved
+    pub best_difficulty: u8,
+
+    /// Layout the account was decoded from
+    pub layout: MinerLayout,
+
+    /// Tier (None for accounts predating [`MinerLayout::V2`])
+    pub tier: Option<u8>,
+
+    /// Proofs past the per-round cap, not counted in total_hashes (None for
+    /// accounts predating [`MinerLayout::V3`])
+    pub capped_score: Option<u64>,
+
+    /// Difficulty-weighted score (None until the scoring migration has
+    /// converted the miner)
+    pub score: Option<u64>,
+
+    /// ScoreBucket the miner is counted in (None if in none, or predating
+    /// [`MinerLayout::V2`])
+    pub score_bucket: Option<u8>,
+
+    /// Unix timestamp `gc_miner` closed the miner at, for stats read from
+    /// its MinerArchive (None for live Miner accounts)
+    pub archived_at: Option<i64>,
+}
+
+/// Anchor account discriminator for `Miner`
+pub fn miner_discriminator() -> [u8; 8] {
+    hash(b"account:Miner").to_bytes()[..8].try_into().unwrap()
+}
+
+/// Parse a Miner account of any known layout, rejecting other program
+/// accounts and truncated data
+pub fn parse_miner_account(data: &[u8]) -> Option<MinerAccount> {
+    let layout = MinerLayout::detect(data.len())?;
+    if data[..8] != miner_discriminator() {
+        return None;
+    }
+    let capped_score = match layout {
+        MinerLayout::V3 | MinerLayout::V4 => {
+            Some(u64::from_le_bytes(data[120..128].try_into().ok()?))
+        }
+        _ => None,
+    };
+    let score = match layout {
+        MinerLayout::V4 if data[136] != 0 => {
+            Some(u64::from_le_bytes(data[128..136].try_into().ok()?))
+        }
+        _ => None,
+    };
+
+    Some(MinerAccount {
+        authority: Pubkey::new_from_array(data[8..40].try_into().ok()?),
+        total_hashes: u64::from_le_bytes(data[40..48].try_into().ok()?),
+        rounds_completed: u32::from_le_bytes(data[48..52].try_into().ok()?),
+        last_hash_at: i64::from_le_bytes(data[52..60].try_into().ok()?),
+        current_streak: u32::from_le_bytes(data[60..64].try_into().ok()?),
+        best_difficulty: data[64],
+        layout,
+        tier: (layout >= MinerLayout::V2).then(|| data[66]),
+        capped_score,
+        score,
+        score_bucket: (layout >= MinerLayout::V2)
+            .then(|| data[107])
+            .filter(|index| *index != NO_SCORE_BUCKET),
+        archived_at: None,
+    })
+}
+
+impl MinerAccount {
+    /// The stat `metric` ranks and allocates this miner by
+    ///
+    /// Miners the scoring migration hasn't converted yet count at the score
+    /// their conversion will give them (the program's `initial_score`, one
+    /// point per counted hash).
+    pub fn metric_value(&self, metric: RankingMetric) -> u64 {
+        match metric {
+            RankingMetric::Hashes => self.total_hashes,
+            RankingMetric::Score => self.score.unwrap_or(self.total_hashes),
+        }
+    }
+}
+
+/// Sort miners by total hashes (primary) and rounds completed (secondary)
+///
+/// Same as [`rank_miners_by`] with [`RankingMetric::Hashes`].
+pub fn rank_miners(miners: &mut [MinerAccount]) {
+    rank_miners_by(miners, RankingMetric::Hashes);
+}
+
+/// Sort miners by `metric` (primary) and rounds completed (secondary)
+///
+/// Remaining ties go to the lower authority, so the order never depends on
+/// the order RPC returned the accounts in.
+pub fn rank_miners_by(miners: &mut [MinerAccount], metric: RankingMetric) {
+    miners.sort_by(|a, b| {
+        b.metric_value(metric)
+            .cmp(&a.metric_value(metric))
+            .then(b.rounds_completed.cmp(&a.rounds_completed))
+            .then(a.authority.cmp(&b.authority))
+    });
+}
+
+// ============================================================================
+// Global Round
+// ============================================================================
+
+/// Serialized size of the GlobalRound fields parsed below, including the discriminator
+///
+/// Format: [discriminator: 8] [challenge: 32] [round_number: 8] [started_at: 8] [min_diff: 1] [hashes: 8] [rounds: 8] [admin: 32] [bump: 1] [tier_step: 1] [round_duration: 8]
+pub const GLOBAL_ROUND_ACCOUNT_LEN: usize = 115;
+
+/// Size of a GlobalRound as first deployed, ending at the bump
+///
+/// Rounds not yet grown by `upgrade_global_round` still parse; tier_step and
+/// round_duration read as the defaults the upgrade would write.
+pub const GLOBAL_ROUND_V1_ACCOUNT_LEN: usize = 106;
+
+/// round_duration of a GlobalRound that predates the field (the program's
+/// `DEFAULT_ROUND_DURATION`)
+pub const DEFAULT_ROUND_DURATION: i64 = 60 * 60;
+
+/// Offset of max_round_proofs, after the 24 bytes of rejection counters
+///
+/// Read only when present: GlobalRounds created before the cap existed end
+/// at or before [`GLOBAL_ROUND_ACCOUNT_LEN`] and are uncapped.
+pub const GLOBAL_ROUND_MAX_PROOFS_OFFSET: usize = 139;
+
+/// Decoded on-chain GlobalRound account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalRoundAccount {
+    pub current_challenge: [u8; 32],
+    pub round_number: u64,
+    pub started_at: i64,
+    pub min_difficulty: u8,
+    pub total_hashes_submitted: u64,
+    pub total_rounds_completed: u64,
+    pub admin: Pubkey,
+    pub tier_step: u8,
+    pub round_duration: i64,
+
+    /// Proofs per miner per round that count toward total_hashes (0 = no cap)
+    pub max_round_proofs: u32,
+}
+
+impl GlobalRoundAccount {
+    /// Unix timestamp after which proofs are refused until the round rotates
+    pub fn ends_at(&self) -> i64 {
+        self.started_at.saturating_add(self.round_duration)
+    }
+}
+
+/// Anchor account discriminator for `GlobalRound`
+pub fn global_round_discriminator() -> [u8; 8] {
+    hash(b"account:GlobalRound").to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// PDA of the GlobalRound account
+pub fn global_round_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"global_round"], program_id).0
+}
+
+/// Parse the GlobalRound account
+pub fn parse_global_round(data: &[u8]) -> Option<GlobalRoundAccount> {
+    if data.len() < GLOBAL_ROUND_V1_ACCOUNT_LEN || data[..8] != global_round_discriminator() {
+        return None;
+    }
+
+    Some(GlobalRoundAccount {
+        current_challenge: data[8..40].try_into().ok()?,
+        round_number: u64::from_le_bytes(data[40..48].try_into().ok()?),
+        started_at: i64::from_le_bytes(data[48..56].try_into().ok()?),
+        min_difficulty: data[56],
+        total_hashes_submitted: u64::from_le_bytes(data[57..65].try_into().ok()?),
+        total_rounds_completed: u64::from_le_bytes(data[65..73].try_into().ok()?),
+        admin: Pubkey::new_from_array(data[73..105].try_into().ok()?),
+        tier_step: data.get(106).copied().unwrap_or(0),
+        round_duration: data
+            .get(107..GLOBAL_ROUND_ACCOUNT_LEN)
+            .map(|bytes| i64::from_le_bytes(bytes.try_into().unwrap()))
+            .unwrap_or(DEFAULT_ROUND_DURATION),
+        max_round_proofs: data
+            .get(GLOBAL_ROUND_MAX_PROOFS_OFFSET..GLOBAL_ROUND_MAX_PROOFS_OFFSET + 4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .unwrap_or(0),
+    })
+}
+
+// ============================================================================
+// Score Buckets
+// ============================================================================
+
+/// Number of on-chain ScoreBucket PDAs
+pub const SCORE_BUCKET_COUNT: usize = 65;
+
+/// Miner::score_bucket of a miner not yet counted in any bucket
+pub const NO_SCORE_BUCKET: u8 = u8::MAX;
+
+/// Serialized size of a ScoreBucket account, including the discriminator
+///
+/// Format: [discriminator: 8] [index: 1] [member_count: 8] [bump: 1]
+pub const SCORE_BUCKET_ACCOUNT_LEN: usize = 18;
+
+/// Anchor account discriminator for `ScoreBucket`
+pub fn score_bucket_discriminator() -> [u8; 8] {
+    hash(b"account:ScoreBucket").to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// ScoreBucket index for a score (its bit length), matching the program
+pub fn score_bucket_index(score: u64) -> usize {
+    (u64::BITS - score.leading_zeros()) as usize
+}
+
+/// PDA of the ScoreBucket with the given index
+pub fn score_bucket_pda(program_id: &Pubkey, index: u8) -> Pubkey {
+    Pubkey::find_program_address(&[b"score_bucket", &[index]], program_id).0
+}
+
+/// Parse a ScoreBucket account into `(index, member_count)`
+pub fn parse_score_bucket(data: &[u8]) -> Option<(u8, u64)> {
+    if data.len() < SCORE_BUCKET_ACCOUNT_LEN || data[..8] != score_bucket_discriminator() {
+        return None;
+    }
+
+    Some((data[8], u64::from_le_bytes(data[9..17].try_into().ok()?)))
+}
+
+/// Estimated percentile (0.0-100.0) of `score` given member counts per bucket
+///
+/// Counts everyone in lower buckets as below the miner and half of its own
+/// bucket, since positions within a bucket are unknown. Returns `None` when
+/// the buckets are empty.
+pub fn estimate_percentile(counts: &[u64; SCORE_BUCKET_COUNT], score: u64) -> Option<f64> {
+    let total: u64 = counts.iter().sum();
+    if total == 0 {
+        return None;
+    }
+
+    let index = score_bucket_index(score);
+    let below: u64 = counts[..index].iter().sum();
+    let within = counts[index] as f64 / 2.0;
+
+    Some((below as f64 + within) / total as f64 * 100.0)
+}
+
+// ============================================================================
+// Stats Commitments
+// ============================================================================
+
+/// Serialized size of the StatsRoot account, including the discriminator
+///
+/// Format: [discriminator: 8] [root: 32] [miner_count: 4] [slot: 8] [posted_at: 8] [sequence: 8] [bump: 1]
+pub const STATS_ROOT_ACCOUNT_LEN: usize = 69;
+
+/// Decoded on-chain StatsRoot account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsRootAccount {
+    /// Merkle root over every miner's score (see [`stats_merkle_root`])
+    pub root: [u8; 32],
+
+    /// Number of leaves under `root`
+    pub miner_count: u32,
+
+    /// Testnet slot the root was posted in
+    pub slot: u64,
+
+    /// Unix timestamp the root was posted at
+    pub posted_at: i64,
+
+    /// Incremented on every post
+    pub sequence: u64,
+}
+
+/// Anchor account discriminator for `StatsRoot`
+pub fn stats_root_discriminator() -> [u8; 8] {
+    hash(b"account:StatsRoot").to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// PDA of the StatsRoot account
+pub fn stats_root_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"stats_root"], program_id).0
+}
+
+/// Parse the StatsRoot account
+pub fn parse_stats_root(data: &[u8]) -> Option<StatsRootAccount> {
+    if data.len() < STATS_ROOT_ACCOUNT_LEN || data[..8] != stats_root_discriminator() {
+        return None;
+    }
+
+    Some(StatsRootAccount {
+        root: data[8..40].try_into().ok()?,
+        miner_count: u32::from_le_bytes(data[40..44].try_into().ok()?),
+        slot: u64::from_le_bytes(data[44..52].try_into().ok()?),
+        posted_at: i64::from_le_bytes(data[52..60].try_into().ok()?),
+        sequence: u64::from_le_bytes(data[60..68].try_into().ok()?),
+    })
+}
+
+/// Merkle leaf for one miner: keccak(0x00 || authority || score)
+///
+/// The score is the one the leaderboard ranks by
+/// ([`RankingMetric::Score`]), so leaves of unconverted miners, and every
+/// root posted before the scoring migration, are unchanged.
+pub fn stats_leaf(miner: &MinerAccount) -> [u8; 32] {
+    hashv(&[
+        &[0],
+        miner.authority.as_ref(),
+        &miner.metric_value(RankingMetric::Score).to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Merkle root over the scores of `miners`, independent of input order
+///
+/// Leaves are sorted by authority and paired with keccak(0x01 || left ||
+/// right); an odd node at the end of a level is carried up unchanged. The
+/// root of no miners is all zeros.
+pub fn stats_merkle_root(miners: &[MinerAccount]) -> [u8; 32] {
+    let mut sorted: Vec<&MinerAccount> = miners.iter().collect();
+    sorted.sort_by_key(|m| m.authority);
+
+    let mut level: Vec<[u8; 32]> = sorted.into_iter().map(stats_leaf).collect();
+    if level.is_empty() {
+        return [0; 32];
+    }
+
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hashv(&[&[1], left, right]).to_bytes(),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    level[0]
+}
+
+// ============================================================================
+// Liveness
+// ============================================================================
+
+/// Serialized size of the LivenessProof account, including the discriminator
+///
+/// Format: [discriminator: 8] [authority: 32] [verified_at: 8] [verifications: 4] [bump: 1]
+pub const LIVENESS_PROOF_ACCOUNT_LEN: usize = 53;
+
+/// Anchor account discriminator for `LivenessProof`
+pub fn liveness_proof_discriminator() -> [u8; 8] {
+    hash(b"account:LivenessProof").to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// Parse a LivenessProof account into (authority, verified_at)
+pub fn parse_liveness_proof(data: &[u8]) -> Option<(Pubkey, i64)> {
+    if data.len() < LIVENESS_PROOF_ACCOUNT_LEN || data[..8] != liveness_proof_discriminator() {
+        return None;
+    }
+
+    Some((
+        Pubkey::new_from_array(data[8..40].try_into().ok()?),
+        i64::from_le_bytes(data[40..48].try_into().ok()?),
+    ))
+}
+
+// ============================================================================
+// Reward Pool
+// ============================================================================
+
+/// Serialized size of the RewardPool account, including the discriminator
+///
+/// Format: [discriminator: 8] [fee_lamports: 8] [top_k: 1] [round_number: 8] [pot: 8] [leader_count: 1] [leaders: 10 x (authority: 32, score: 8)] [bump: 1]
+pub const REWARD_POOL_ACCOUNT_LEN: usize = 435;
+
+/// Anchor account discriminator for `RewardPool`
+pub fn reward_pool_discriminator() -> [u8; 8] {
+    hash(b"account:RewardPool").to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// PDA of the RewardPool account
+pub fn reward_pool_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"reward_pool"], program_id).0
+}
+
+/// Round reward pool as stored on-chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewardPoolAccount {
+    /// Fee per pooled proof (0 = closed)
+    pub fee_lamports: u64,
+
+    /// Leaders paid out per round
+    pub top_k: u8,
+
+    /// Round the pot and leaders belong to
+    pub round_number: u64,
+
+    /// Lamports owed to the round's leaders
+    pub pot: u64,
+
+    /// (authority, round score) of each leader, in on-chain order
+    pub leaders: Vec<(Pubkey, u64)>,
+}
+
+/// Parse the RewardPool account
+pub fn parse_reward_pool(data: &[u8]) -> Option<RewardPoolAccount> {
+    if data.len() < REWARD_POOL_ACCOUNT_LEN || data[..8] != reward_pool_discriminator() {
+        return None;
+    }
+
+    let leader_count = data[33] as usize;
+    let leaders = data[34..434]
+        .chunks_exact(40)
+        .take(leader_count)
+        .map(|leader| {
+            Some((
+                Pubkey::new_from_array(leader[..32].try_into().ok()?),
+                u64::from_le_bytes(leader[32..].try_into().ok()?),
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(RewardPoolAccount {
+        fee_lamports: u64::from_le_bytes(data[8..16].try_into().ok()?),
+        top_k: data[16],
+        round_number: u64::from_le_bytes(data[17..25].try_into().ok()?),
+        pot: u64::from_le_bytes(data[25..33].try_into().ok()?),
+        leaders,
+    })
+}
+
+// ============================================================================
+// Score Decay
+// ============================================================================
+
+/// Serialized size of the ScoreDecay account, including the discriminator
+///
+/// Format: [discriminator: 8] [decay_bps: 2] [configured_at: 8] [bump: 1]
+pub const SCORE_DECAY_ACCOUNT_LEN: usize = 19;
+
+/// Basis points in a whole
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Anchor account discriminator for `ScoreDecay`
+pub fn score_decay_discriminator() -> [u8; 8] {
+    hash(b"account:ScoreDecay").to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// PDA of the ScoreDecay account
+pub fn score_decay_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"score_decay"], program_id).0
+}
+
+/// Decoded on-chain ScoreDecay account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoreDecayAccount {
+    /// Share of score lost per full idle round, in basis points
+    pub decay_bps: u16,
+
+    /// When decay was last configured; idle time before it is not charged
+    pub configured_at: i64,
+}
+
+/// Parse the ScoreDecay account
+pub fn parse_score_decay(data: &[u8]) -> Option<ScoreDecayAccount> {
+    if data.len() < SCORE_DECAY_ACCOUNT_LEN || data[..8] != score_decay_discriminator() {
+        return None;
+    }
+
+    Some(ScoreDecayAccount {
+        decay_bps: u16::from_le_bytes(data[8..10].try_into().ok()?),
+        configured_at: i64::from_le_bytes(data[10..18].try_into().ok()?),
+    })
+}
+
+/// Full rounds of `round_duration` between `last_hash_at` and `now`
+///
+/// Mirrors the program's `idle_rounds`.
+pub fn idle_rounds(last_hash_at: i64, now: i64, round_duration: i64) -> u64 {
+    if round_duration <= 0 {
+        return 0;
+    }
+
+    (now.saturating_sub(last_hash_at).max(0) / round_duration) as u64
+}
+
+/// `score` after losing `decay_bps` per round for `rounds` rounds
+///
+/// Must stay bit-for-bit identical to the program's `decayed_score`, or
+/// the airdrop and the chain disagree on who earned what. Both are pinned
+/// to the same vectors in their tests.
+pub fn decayed_score(score: u64, rounds: u64, decay_bps: u16) -> u64 {
+    if score == 0 || rounds == 0 || decay_bps == 0 {
+        return score;
+    }
+    if decay_bps >= BPS_DENOMINATOR {
+        return 0;
+    }
+
+    const ONE: u128 = 1 << 64;
+    let mut base = (BPS_DENOMINATOR - decay_bps) as u128 * ONE / BPS_DENOMINATOR as u128;
+    let mut factor = ONE;
+    let mut rounds = rounds;
+    while rounds > 0 && factor > 0 {
+        if rounds & 1 == 1 {
+            factor = (factor * base) >> 64;
+        }
+        base = (base * base) >> 64;
+        rounds >>= 1;
+    }
+
+    ((score as u128 * factor) >> 64) as u64
+}
+
+/// Apply the decay each miner would be charged if it submitted at `now`
+///
+/// On-chain scores only decay when the miner next submits, so a snapshot
+/// has to charge idle miners itself. Like the program, only converted
+/// scores decay, and only for idle time since the later of the last proof
+/// and `decay.configured_at`; total_hashes is left alone. Returns how many
+/// scores went down.
+pub fn apply_score_decay(
+    miners: &mut [MinerAccount],
+    decay: &ScoreDecayAccount,
+    now: i64,
+    round_duration: i64,
+) -> usize {
+    let mut decayed = 0;
+    for miner in miners.iter_mut() {
+        let Some(score) = miner.score.as_mut() else {
+            continue;
+        };
+        let idle = idle_rounds(
+            miner.last_hash_at.max(decay.configured_at),
+            now,
+            round_duration,
+        );
+        let charged = decayed_score(*score, idle, decay.decay_bps);
+        if charged < *score {
+            *score = charged;
+            decayed += 1;
+        }
+    }
+
+    decayed
+}
+
+// ============================================================================
+// Scoring Migration
+// ============================================================================
+
+/// Serialized size of the ScoringMigration account, including the
+/// discriminator
+///
+/// Format: [discriminator: 8] [phase: 1] [frozen_at: 8] [resumed_at: 8] [bump: 1]
+pub const SCORING_MIGRATION_ACCOUNT_LEN: usize = 26;
+
+/// Metric the program credits proofs to (mirrors the program's enum)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ScoringPhase {
+    /// total_hashes only; also what a missing ScoringMigration account means
+    HashCount,
+
+    /// Submissions refused while miners are converted
+    Frozen,
+
+    /// total_hashes and score
+    Score,
+}
+
+/// Decoded ScoringMigration account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoringMigrationAccount {
+    pub phase: ScoringPhase,
+    pub frozen_at: i64,
+
+    /// 0 while still frozen
+    pub resumed_at: i64,
+}
+
+/// Anchor account discriminator for `ScoringMigration`
+pub fn scoring_migration_discriminator() -> [u8; 8] {
+    hash(b"account:ScoringMigration").to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// PDA of the ScoringMigration account
+pub fn scoring_migration_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"scoring_migration"], program_id).0
+}
+
+/// Parse a ScoringMigration account
+pub fn parse_scoring_migration(data: &[u8]) -> Option<ScoringMigrationAccount> {
+    if data.len() < SCORING_MIGRATION_ACCOUNT_LEN || data[..8] != scoring_migration_discriminator()
+    {
+        return None;
+    }
+
+    let phase = match data[8] {
+        0 => ScoringPhase::HashCount,
+        1 => ScoringPhase::Frozen,
+        2 => ScoringPhase::Score,
+        _ => return None,
+    };
+
+    Some(ScoringMigrationAccount {
+        phase,
+        frozen_at: i64::from_le_bytes(data[9..17].try_into().ok()?),
+        resumed_at: i64::from_le_bytes(data[17..25].try_into().ok()?),
+    })
+}
+
+/// Miners `convert_stats` still has to run for
+///
+/// Includes accounts predating [`MinerLayout::V4`]: `convert_stats` grows
+/// them to the current layout before converting. Archived miners have no
+/// account to convert; their first proof after re-registering does it.
+pub fn unconverted_miners(miners: &[MinerAccount]) -> Vec<Pubkey> {
+    miners
+        .iter()
+        .filter(|miner| miner.score.is_none() && miner.archived_at.is_none())
+        .map(|miner| miner.authority)
+        .collect()
+}
+
+// ============================================================================
+// GC Policy
+// ============================================================================
+
+/// Serialized size of the GcPolicy account, including the discriminator
+///
+/// Format: [discriminator: 8] [inactive_days: 2] [sweep_wallet: 32] [bump: 1]
+pub const GC_POLICY_ACCOUNT_LEN: usize = 43;
+
+/// Decoded GcPolicy account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GcPolicyAccount {
+    /// Days without a proof before a Miner may be closed (0 = off)
+    pub inactive_days: u16,
+
+    /// Receives the rent of closed Miner accounts
+    pub sweep_wallet: Pubkey,
+}
+
+/// Anchor account discriminator for `GcPolicy`
+pub fn gc_policy_discriminator() -> [u8; 8] {
+    hash(b"account:GcPolicy").to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// PDA of the GcPolicy account
+pub fn gc_policy_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"gc_policy"], program_id).0
+}
+
+/// Parse a GcPolicy account
+pub fn parse_gc_policy(data: &[u8]) -> Option<GcPolicyAccount> {
+    if data.len() < GC_POLICY_ACCOUNT_LEN || data[..8] != gc_policy_discriminator() {
+        return None;
+    }
+
+    Some(GcPolicyAccount {
+        inactive_days: u16::from_le_bytes(data[8..10].try_into().ok()?),
+        sweep_wallet: Pubkey::new_from_array(data[10..42].try_into().ok()?),
+    })
+}
+
+/// Live miners `gc_miner` may close now
+pub fn collectable_miners(
+    miners: &[MinerAccount],
+    policy: &GcPolicyAccount,
+    now: i64,
+) -> Vec<MinerAccount> {
+    if policy.inactive_days == 0 {
+        return Vec::new();
+    }
+
+    let idle_secs = policy.inactive_days as i64 * 24 * 60 * 60;
+    miners
+        .iter()
+        .filter(|miner| miner.archived_at.is_none())
+        .filter(|miner| now.saturating_sub(miner.last_hash_at) >= idle_secs)
+        .cloned()
+        .collect()
+}
+
+// ============================================================================
+// Miner Archives
+// ============================================================================
+
+/// Serialized size of the MinerArchive account, including the discriminator
+///
+/// Format: [discriminator: 8] [authority: 32] [total_hashes: 8] [rounds: 4] [last_hash: 8] [best_diff: 1] [tier: 1] [capped_score: 8] [score: 8] [score_converted: 1] [archived_at: 8] [bump: 1]
+pub const MINER_ARCHIVE_ACCOUNT_LEN: usize = 88;
+
+/// Anchor account discriminator for `MinerArchive`
+pub fn miner_archive_discriminator() -> [u8; 8] {
+    hash(b"account:MinerArchive").to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// PDA of the MinerArchive `gc_miner` leaves for an authority
+pub fn miner_archive_pda(program_id: &Pubkey, authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"miner_archive", authority.as_ref()], program_id).0
+}
+
+/// Parse a MinerArchive into the miner it was closed from
+///
+/// The result ranks and allocates like the live miner did; only the
+/// round-local fields (streak, score bucket) are gone.
+pub fn parse_miner_archive(data: &[u8]) -> Option<MinerAccount> {
+    if data.len() < MINER_ARCHIVE_ACCOUNT_LEN || data[..8] != miner_archive_discriminator() {
+        return None;
+    }
+
+    Some(MinerAccount {
+        authority: Pubkey::new_from_array(data[8..40].try_into().ok()?),
+        total_hashes: u64::from_le_bytes(data[40..48].try_into().ok()?),
+        rounds_completed: u32::from_le_bytes(data[48..52].try_into().ok()?),
+        last_hash_at: i64::from_le_bytes(data[52..60].try_into().ok()?),
+        current_streak: 0,
+        best_difficulty: data[60],
+        layout: MinerLayout::V4,
+        tier: Some(data[61]),
+        capped_score: Some(u64::from_le_bytes(data[62..70].try_into().ok()?)),
+        score: (data[78] != 0).then_some(u64::from_le_bytes(data[70..78].try_into().ok()?)),
+        score_bucket: None,
+        archived_at: Some(i64::from_le_bytes(data[79..87].try_into().ok()?)),
+    })
+}
+
+// ============================================================================
+// Miner Metadata
+// ============================================================================
+
+/// Serialized size of the MinerMetadata account, including the discriminator
+///
+/// Format: [discriminator: 8] [authority: 32] [name: 32] [url: 64] [updated_at: 8] [bump: 1]
+pub const MINER_METADATA_ACCOUNT_LEN: usize = 145;
+
+/// Anchor account discriminator for `MinerMetadata`
+pub fn miner_metadata_discriminator() -> [u8; 8] {
+    hash(b"account:MinerMetadata").to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// PDA of a miner's MinerMetadata account
+pub fn miner_metadata_pda(program_id: &Pubkey, authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"metadata", authority.as_ref()], program_id).0
+}
+
+/// Display name and link a miner registered with `set_metadata`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinerMetadataAccount {
+    pub authority: Pubkey,
+    pub name: String,
+
+    /// Profile link (None if left empty)
+    pub url: Option<String>,
+
+    /// Unix timestamp of the latest update
+    pub updated_at: i64,
+}
+
+/// Parse a MinerMetadata account
+pub fn parse_miner_metadata(data: &[u8]) -> Option<MinerMetadataAccount> {
+    if data.len() < MINER_METADATA_ACCOUNT_LEN || data[..8] != miner_metadata_discriminator() {
+        return None;
+    }
+
+    let url = padded_str(&data[72..136])?;
+    Some(MinerMetadataAccount {
+        authority: Pubkey::new_from_array(data[8..40].try_into().ok()?),
+        name: padded_str(&data[40..72])?.to_string(),
+        url: (!url.is_empty()).then(|| url.to_string()),
+        updated_at: i64::from_le_bytes(data[136..144].try_into().ok()?),
+    })
+}
+
+/// Text of a NUL-padded field (see the program's `metadata_str`)
+fn padded_str(field: &[u8]) -> Option<&str> {
+    let len = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    if field[len..].iter().any(|b| *b != 0) {
+        return None;
+    }
+
+    std::str::from_utf8(&field[..len]).ok()
+}
+
+// ============================================================================
+// Miner Regions
+// ============================================================================
+
+/// Serialized size of the MinerRegion account, including the discriminator
+///
+/// Format: [discriminator: 8] [authority: 32] [region: 2] [latency_bucket: 1] [reported_at: 8] [bump: 1]
+pub const MINER_REGION_ACCOUNT_LEN: usize = 52;
+
+/// [`region_stats`] key for miners without a region report
+pub const UNREPORTED_REGION: &str = "unreported";
+
+/// Anchor account discriminator for `MinerRegion`
+pub fn miner_region_discriminator() -> [u8; 8] {
+    hash(b"account:MinerRegion").to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// PDA of a miner's MinerRegion account
+pub fn miner_region_pda(program_id: &Pubkey, authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"region", authority.as_ref()], program_id).0
+}
+
+/// A miner's self-reported region and latency bucket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinerRegionAccount {
+    pub authority: Pubkey,
+
+    /// ISO 3166-1 alpha-2 code (None if the report was cleared)
+    pub region: Option<String>,
+
+    /// Index into the program's LATENCY_BUCKET_BOUNDS_MS
+    pub latency_bucket: u8,
+
+    /// Unix timestamp of the latest report
+    pub reported_at: i64,
+}
+
+/// Parse a MinerRegion account
+pub fn parse_miner_region(data: &[u8]) -> Option<MinerRegionAccount> {
+    if data.len() < MINER_REGION_ACCOUNT_LEN || data[..8] != miner_region_discriminator() {
+        return None;
+    }
+
+    let region = padded_str(&data[40..42])?;
+    Some(MinerRegionAccount {
+        authority: Pubkey::new_from_array(data[8..40].try_into().ok()?),
+        region: (!region.is_empty()).then(|| region.to_string()),
+        latency_bucket: data[42],
+        reported_at: i64::from_le_bytes(data[43..51].try_into().ok()?),
+    })
+}
+
+/// Aggregate load from one region
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RegionStats {
+    pub miners: usize,
+    pub total_hashes: u64,
+
+    /// Mean of the miners' best_difficulty
+    pub mean_best_difficulty: f64,
+
+    /// Miners per latency bucket
+    pub latency_buckets: BTreeMap<u8, usize>,
+}
+
+/// Group `miners` by self-reported region
+///
+/// Miners without a report count under [`UNREPORTED_REGION`]; their latency
+/// is unknown, so they are left out of `latency_buckets`.
+pub fn region_stats(
+    miners: &[MinerAccount],
+    regions: &HashMap<Pubkey, MinerRegionAccount>,
+) -> BTreeMap<String, RegionStats> {
+    let mut stats: BTreeMap<String, RegionStats> = BTreeMap::new();
+    let mut difficulty_sums: HashMap<String, u64> = HashMap::new();
+
+    for miner in miners {
+        let report = regions
+            .get(&miner.authority)
+            .filter(|report| report.region.is_some());
+        let region = report
+            .and_then(|report| report.region.clone())
+            .unwrap_or_else(|| UNREPORTED_REGION.to_string());
+
+        let entry = stats.entry(region.clone()).or_default();
+        entry.miners += 1;
+        entry.total_hashes = entry.total_hashes.saturating_add(miner.total_hashes);
+        if let Some(report) = report {
+            *entry
+                .latency_buckets
+                .entry(report.latency_bucket)
+                .or_default() += 1;
+        }
+        *difficulty_sums.entry(region).or_default() += miner.best_difficulty as u64;
+    }
+
+    for (region, entry) in &mut stats {
+        entry.mean_best_difficulty = difficulty_sums[region] as f64 / entry.miners as f64;
+    }
+
+    stats
+}
+
+// ============================================================================
+// Allocation
+// ============================================================================
+
+/// Parameters of the mainnet airdrop allocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllocationParams {
+    /// TESTORE tokens per full million hashes
+    pub tokens_per_million_hashes: u64,
+
+    /// Miners below this hash count receive nothing
+    pub minimum_hashes: u64,
+
+    /// Only the top N miners by hash count are considered
+    pub top_miners: usize,
+
+    /// How Miner accounts sharing an authority are merged
+    #[serde(default)]
+    pub duplicate_policy: DuplicatePolicy,
+
+    /// Extra allocation for recently verified-live miners, in basis points
+    /// (0 = liveness is ignored)
+    #[serde(default)]
+    pub liveness_boost_bps: u64,
+
+    /// Miners whose last proof is older than this many days at snapshot time
+    /// are left out before ranking (0 = no recency rule)
+    #[serde(default)]
+    pub max_inactive_days: u64,
+
+    /// Stat miners are ranked and allocated by; `tokens_per_million_hashes`
+    /// and `minimum_hashes` apply to it, hashes or score points alike
+    #[serde(default)]
+    pub metric: RankingMetric,
+}
+
+/// Stat the airdrop ranks and allocates miners by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RankingMetric {
+    /// total_hashes, the only stat before the scoring migration
+    #[default]
+    Hashes,
+
+    /// Difficulty-weighted score (see [`MinerAccount::metric_value`])
+    Score,
+}
+
+impl RankingMetric {
+    /// Metric matching what the program credits proofs to in `phase`
+    ///
+    /// Scores only take over once submissions resume; while frozen, miners
+    /// are still mid-conversion.
+    pub fn for_phase(phase: ScoringPhase) -> Self {
+        match phase {
+            ScoringPhase::Score => Self::Score,
+            ScoringPhase::HashCount | ScoringPhase::Frozen => Self::Hashes,
+        }
+    }
+}
+
+impl Default for AllocationParams {
+    fn default() -> Self {
+        Self {
+            tokens_per_million_hashes: 100,
+            minimum_hashes: 100_000,
+            top_miners: 1000,
+            duplicate_policy: DuplicatePolicy::default(),
+            liveness_boost_bps: 0,
+            max_inactive_days: 0,
+            metric: RankingMetric::default(),
+        }
+    }
+}
+
+/// Miners left out by [`exclude_inactive`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InactiveExclusion {
+    /// [`AllocationParams::max_inactive_days`] the rule ran with
+    pub max_inactive_days: u64,
+
+    /// Unix timestamp a miner's last proof had to be at or after
+    pub cutoff: i64,
+
+    /// Miners excluded
+    pub miners: usize,
+
+    /// Their combined hash count
+    pub total_hashes: u64,
+}
+
+/// Drop miners whose last proof is older than `params.max_inactive_days` at
+/// `now` (cluster time), before ranking so active miners fill the top N
+///
+/// Returns what was dropped, or None when the rule is off.
+pub fn exclude_inactive(
+    leaderboard: &mut Vec<MinerAccount>,
+    params: &AllocationParams,
+    now: i64,
+) -> Option<InactiveExclusion> {
+    if params.max_inactive_days == 0 {
+        return None;
+    }
+
+    let window = i64::try_from(params.max_inactive_days)
+        .unwrap_or(i64::MAX)
+        .saturating_mul(24 * 60 * 60);
+    let cutoff = now.saturating_sub(window);
+    let mut exclusion = InactiveExclusion {
+        max_inactive_days: params.max_inactive_days,
+        cutoff,
+        miners: 0,
+        total_hashes: 0,
+    };
+
+    leaderboard.retain(|miner| {
+        let active = miner.last_hash_at >= cutoff;
+        if !active {
+            exclusion.miners += 1;
+            exclusion.total_hashes = exclusion.total_hashes.saturating_add(miner.total_hashes);
+        }
+        active
+    });
+
+    Some(exclusion)
+}
+
+/// Merge rule for Miner accounts that share an authority
+///
+/// The program derives one Miner PDA per authority, so duplicates shouldn't
+/// exist; this keeps a future close/reopen path from silently dropping stats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    /// Add hashes and rounds; keep the best difficulty and latest activity
+    #[default]
+    Sum,
+
+    /// Keep only the account ranked highest by [`rank_miners`]
+    Max,
+
+    /// Refuse to build a snapshot
+    Reject,
+}
+
+impl std::str::FromStr for DuplicatePolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "sum" => Ok(Self::Sum),
+            "max" => Ok(Self::Max),
+            "reject" => Ok(Self::Reject),
+            _ => Err(anyhow::anyhow!(
+                "unknown duplicate policy '{}' (sum, max, reject)",
+                s
+            )),
+        }
+    }
+}
+
+/// Report of one authority whose accounts were merged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateMerge {
+    pub authority: Pubkey,
+
+    /// Number of Miner accounts found for the authority
+    pub accounts: usize,
+
+    /// total_hashes of the merged entry
+    pub total_hashes: u64,
+}
+
+/// Collapse miners sharing an authority into one entry per `policy`
+///
+/// Returns the consolidated miners (in first-seen order) and one report per
+/// merged authority.
+pub fn consolidate_duplicates(
+    miners: Vec<MinerAccount>,
+    policy: DuplicatePolicy,
+) -> Result<(Vec<MinerAccount>, Vec<DuplicateMerge>)> {
+    let mut index: HashMap<Pubkey, usize> = HashMap::new();
+    let mut counts: Vec<usize> = Vec::new();
+    let mut merged: Vec<MinerAccount> = Vec::new();
+
+    for miner in miners {
+        let Some(&i) = index.get(&miner.authority) else {
+            index.insert(miner.authority, merged.len());
+            counts.push(1);
+            merged.push(miner);
+            continue;
+        };
+
+        counts[i] += 1;
+        let existing = &mut merged[i];
+
+        match policy {
+            DuplicatePolicy::Reject => {
+                return Err(anyhow::anyhow!(
+                    "Multiple Miner accounts for authority {}",
+                    miner.authority
+                ));
+            }
+            DuplicatePolicy::Max => {
+                let mut pair = [existing.clone(), miner];
+                rank_miners(&mut pair);
+                let [best, _] = pair;
+                *existing = best;
+            }
+            DuplicatePolicy::Sum => {
+                if miner.last_hash_at > existing.last_hash_at {
+                    existing.last_hash_at = miner.last_hash_at;
+                    existing.current_streak = miner.current_streak;
+                }
+                existing.total_hashes = existing.total_hashes.saturating_add(miner.total_hashes);
+                existing.rounds_completed = existing
+                    .rounds_completed
+                    .saturating_add(miner.rounds_completed);
+                existing.best_difficulty = existing.best_difficulty.max(miner.best_difficulty);
+                existing.tier = existing.tier.max(miner.tier);
+                existing.capped_score = match (existing.capped_score, miner.capped_score) {
+                    (Some(a), Some(b)) => Some(a.saturating_add(b)),
+                    (a, b) => a.or(b),
+                };
+                existing.score = match (existing.score, miner.score) {
+                    (Some(a), Some(b)) => Some(a.saturating_add(b)),
+                    (a, b) => a.or(b),
+                };
+                existing.archived_at = existing.archived_at.and(miner.archived_at);
+            }
+        }
+    }
+
+    let reports = merged
+        .iter()
+        .zip(&counts)
+        .filter(|(_, &count)| count > 1)
+        .map(|(miner, &accounts)| DuplicateMerge {
+            authority: miner.authority,
+            accounts,
+            total_hashes: miner.total_hashes,
+        })
+        .collect();
+
+    Ok((merged, reports))
+}
+
+/// Tokens owed to a single miner under `params`
+///
+/// Integer math only, so every platform computes the same allocation.
+pub fn allocation_for(total_hashes: u64, params: &AllocationParams) -> u64 {
+    if total_hashes < params.minimum_hashes {
+        return 0;
+    }
+
+    (total_hashes / 1_000_000) * params.tokens_per_million_hashes
+}
+
+/// Calculate allocations for a ranked leaderboard
+///
+/// Expects `leaderboard` to be ranked by `params.metric` already (see
+/// [`rank_miners_by`]); only the first `params.top_miners` entries are
+/// considered.
+pub fn calculate_allocations(
+    leaderboard: &[MinerAccount],
+    params: &AllocationParams,
+) -> HashMap<Pubkey, u64> {
+    let mut allocations = HashMap::new();
+
+    for miner in leaderboard.iter().take(params.top_miners) {
+        let tokens = allocation_for(miner.metric_value(params.metric), params);
+
+        if tokens > 0 {
+            allocations.insert(miner.authority, tokens);
+        }
+    }
+
+    allocations
+}
+
+/// Raise the allocations of `live` miners by `params.liveness_boost_bps`
+///
+/// Returns how many allocations were boosted.
+pub fn apply_liveness_boost(
+    allocations: &mut HashMap<Pubkey, u64>,
+    live: &BTreeSet<Pubkey>,
+    params: &AllocationParams,
+) -> usize {
+    let mut boosted = 0;
+    for (authority, tokens) in allocations.iter_mut() {
+        if params.liveness_boost_bps == 0 || !live.contains(authority) {
+            continue;
+        }
+
+        let bonus = *tokens as u128 * params.liveness_boost_bps as u128 / 10_000;
+        *tokens = tokens.saturating_add(bonus.min(u64::MAX as u128) as u64);
+        boosted += 1;
+    }
+
+    boosted
+}
+
+/// SHA-256 over every (authority, tokens) pair in authority order
+///
+/// Published with each snapshot so anyone can check a reproduction
+/// bit-for-bit (see [`Snapshot::reproduce`]).
+pub fn allocations_hash(allocations: &HashMap<Pubkey, u64>) -> Hash {
+    let sorted: BTreeMap<&Pubkey, &u64> = allocations.iter().collect();
+    let mut hasher = Hasher::default();
+    for (authority, tokens) in sorted {
+        hasher.hash(authority.as_ref());
+        hasher.hash(&tokens.to_le_bytes());
+    }
+
+    hasher.result()
+}
+
+/// Where a miner stands on the ranked leaderboard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinerStanding {
+    /// 1-based leaderboard rank
+    pub rank: usize,
+
+    /// Miners on the leaderboard
+    pub total_miners: usize,
+
+    /// Rank as a share of all miners (1.0 = top 1%)
+    pub top_percent: f64,
+
+    /// Tokens this miner would receive if the airdrop ran now
+    pub projected_tokens: u64,
+}
+
+/// Standing of `authority` within `ranked` (see [`rank_miners`]), if present
+pub fn miner_standing(
+    ranked: &[MinerAccount],
+    authority: &Pubkey,
+    params: &AllocationParams,
+) -> Option<MinerStanding> {
+    let index = ranked.iter().position(|m| m.authority == *authority)?;
+    let rank = index + 1;
+
+    let projected_tokens = if rank <= params.top_miners {
+        allocation_for(ranked[index].metric_value(params.metric), params)
+    } else {
+        0
+    };
+
+    Some(MinerStanding {
+        rank,
+        total_miners: ranked.len(),
+        top_percent: rank as f64 / ranked.len() as f64 * 100.0,
+        projected_tokens,
+    })
+}
+
+/// How concentrated a set of allocations is
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AllocationMetrics {
+    /// Wallets with a non-zero allocation
+    pub recipients: usize,
+
+    /// Sum of all allocations
+    pub total_tokens: u64,
+
+    /// Gini coefficient of the allocations (0 = equal, near 1 = one wallet
+    /// holds everything)
+    pub gini: f64,
+
+    /// Share of all tokens going to the 10 largest allocations (0.0 - 1.0)
+    pub top10_share: f64,
+}
+
+/// Recipient count, total, Gini and top-10 share of `allocations`
+pub fn allocation_metrics(allocations: &HashMap<Pubkey, u64>) -> AllocationMetrics {
+    let mut tokens: Vec<u64> = allocations.values().copied().filter(|t| *t > 0).collect();
+    tokens.sort_unstable();
+
+    let n = tokens.len();
+    let total: u128 = tokens.iter().map(|t| *t as u128).sum();
+    if total == 0 {
+        return AllocationMetrics {
+            recipients: n,
+            total_tokens: 0,
+            gini: 0.0,
+            top10_share: 0.0,
+        };
+    }
+
+    // G = 2 * sum(i * x_i) / (n * sum(x)) - (n + 1) / n, x ascending, i from 1
+    let weighted: u128 = tokens
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (i as u128 + 1) * *t as u128)
+        .sum();
+    let gini = 2.0 * weighted as f64 / (n as f64 * total as f64) - (n as f64 + 1.0) / n as f64;
+    let top10: u128 = tokens.iter().rev().take(10).map(|t| *t as u128).sum();
+
+    AllocationMetrics {
+        recipients: n,
+        total_tokens: total.min(u64::MAX as u128) as u64,
+        gini: gini.max(0.0),
+        top10_share: top10 as f64 / total as f64,
+    }
+}
+
+// ============================================================================
+// Snapshots
+// ============================================================================
+
+/// Schema version written by [`Snapshot::save`]
+pub const SNAPSHOT_SCHEMA_VERSION: u32 = 3;
+
+/// Airdrop snapshot as written to `airdrop_snapshot.json`
+///
+/// Schema 1 had no `schema_version` field and none of the round, parameter
+/// or exclusion metadata; schema 2 lacked the inputs and allocations hash
+/// needed by [`Snapshot::reproduce`]. [`Snapshot::load`] upgrades both.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub schema_version: u32,
+
+    /// RFC 3339 time the snapshot was taken
+    pub timestamp: String,
+
+    /// GlobalRound number when the miners were read
+    pub round_number: Option<u64>,
+
+    /// Testnet slot the miners were read at
+    pub slot: Option<u64>,
+
+    /// Parameters the allocations were calculated with
+    pub params: Option<AllocationParams>,
+
+    /// Excluded wallets and the reason for each (base58)
+    #[serde(default)]
+    pub excluded: BTreeMap<String, String>,
+
+    /// Number of miners with a non-zero allocation
+    pub total_miners: usize,
+
+    /// Sum of all allocations
+    pub total_tokens: u64,
+
+    /// Allocation per miner authority (base58)
+    pub allocations: BTreeMap<String, u64>,
+
+    /// [`allocations_hash`] of `allocations` (base58)
+    pub allocations_hash: Option<String>,
+
+    /// Miners left out by the recency rule; `inputs` are what remained
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inactive: Option<InactiveExclusion>,
+
+    /// Ranked leaderboard the allocations were calculated from
+    #[serde(default)]
+    pub inputs: Vec<SnapshotInput>,
+
+    /// Hashes per program ID for each allocated authority (all base58), when
+    /// miners were read from more than one deployment
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub program_breakdown: HashMap<String, BTreeMap<String, u64>>,
+}
+
+impl Snapshot {
+    pub fn new(allocations: &HashMap<Pubkey, u64>, params: &AllocationParams) -> Self {
+        Self {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            round_number: None,
+            slot: None,
+            params: Some(*params),
+            excluded: BTreeMap::new(),
+            total_miners: allocations.len(),
+            total_tokens: allocations.values().sum(),
+            allocations: allocations
+                .iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect(),
+            allocations_hash: Some(allocations_hash(allocations).to_string()),
+            inactive: None,
+            inputs: Vec::new(),
+            program_breakdown: HashMap::new(),
+        }
+    }
+
+    /// Record the ranked leaderboard the allocations were calculated from
+    /// and which of its miners counted as live
+    pub fn with_inputs(mut self, leaderboard: &[MinerAccount], live: &BTreeSet<Pubkey>) -> Self {
+        self.inputs = leaderboard
+            .iter()
+            .map(|miner| SnapshotInput {
+                authority: miner.authority.to_string(),
+                total_hashes: miner.total_hashes,
+                rounds_completed: miner.rounds_completed,
+                score: miner.score,
+                live: live.contains(&miner.authority),
+            })
+            .collect();
+        self
+    }
+
+    /// Recompute the allocations from the recorded inputs, parameters and
+    /// exclusions, failing unless they match the recorded allocations and
+    /// hash exactly
+    pub fn reproduce(&self) -> Result<Hash> {
+        let params = self
+            .params
+            .ok_or_else(|| anyhow::anyhow!("Snapshot predates recorded parameters"))?;
+        let recorded = self
+            .allocations_hash
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Snapshot predates recorded inputs"))?;
+
+        let allocations = self.recompute(&params)?;
+        let reproduced: BTreeMap<String, u64> = allocations
+            .iter()
+            .map(|(k, v)| (k.to_string(), *v))
+            .collect();
+        if reproduced != self.allocations {
+            return Err(anyhow::anyhow!(
+                "Reproduced allocations differ from the snapshot"
+            ));
+        }
+
+        let digest = allocations_hash(&allocations);
+        if digest.to_string() != recorded {
+            return Err(anyhow::anyhow!(
+                "Reproduced allocations hash {} does not match recorded {}",
+                digest,
+                recorded
+            ));
+        }
+
+        Ok(digest)
+    }
+
+    /// Allocations the recorded inputs and exclusions would get under `params`
+    ///
+    /// With the recorded parameters this is what [`Snapshot::reproduce`]
+    /// checks; with others it answers "what if" questions about them.
+    pub fn recompute(&self, params: &AllocationParams) -> Result<HashMap<Pubkey, u64>> {
+        if self.inputs.is_empty() && !self.allocations.is_empty() {
+            return Err(anyhow::anyhow!("Snapshot predates recorded inputs"));
+        }
+
+        let live: BTreeSet<Pubkey> = self
+            .inputs
+            .iter()
+            .filter(|input| input.live)
+            .map(|input| input.authority.parse())
+            .collect::<Result<_, _>>()?;
+        let mut leaderboard = self
+            .inputs
+            .iter()
+            .map(|input| {
+                Ok(MinerAccount {
+                    authority: input.authority.parse()?,
+                    total_hashes: input.total_hashes,
+                    rounds_completed: input.rounds_completed,
+                    last_hash_at: 0,
+                    current_streak: 0,
+                    best_difficulty: 0,
+                    layout: MinerLayout::V1,
+                    tier: None,
+                    capped_score: None,
+                    score: input.score,
+                    score_bucket: None,
+                    archived_at: None,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        rank_miners_by(&mut leaderboard, params.metric);
+
+        let mut allocations = calculate_allocations(&leaderboard, params);
+        apply_liveness_boost(&mut allocations, &live, params);
+        allocations.retain(|authority, _| !self.excluded.contains_key(&authority.to_string()));
+
+        Ok(allocations)
+    }
+
+    /// Record the miners the recency rule left out
+    pub fn with_inactive(mut self, inactive: Option<InactiveExclusion>) -> Self {
+        self.inactive = inactive;
+        self
+    }
+
+    /// Record the round and testnet slot the miners were read at
+    pub fn with_round(mut self, round: &GlobalRoundAccount, slot: u64) -> Self {
+        self.round_number = Some(round.round_number);
+        self.slot = Some(slot);
+        self
+    }
+
+    /// Record the excluded wallets and why each was excluded
+    pub fn with_excluded<'a>(
+        mut self,
+        excluded: impl IntoIterator<Item = (&'a Pubkey, &'a String)>,
+    ) -> Self {
+        self.excluded = excluded
+            .into_iter()
+            .map(|(wallet, reason)| (wallet.to_string(), reason.clone()))
+            .collect();
+        self
+    }
+
+    /// Attach `breakdown` for the allocated authorities if it spans more than
+    /// one program
+    pub fn with_breakdown(mut self, breakdown: &ProgramBreakdown) -> Self {
+        let programs: BTreeSet<&Pubkey> = breakdown.values().flat_map(|p| p.keys()).collect();
+        if programs.len() < 2 {
+            return self;
+        }
+
+        self.program_breakdown = breakdown
+            .iter()
+            .filter(|(authority, _)| self.allocations.contains_key(&authority.to_string()))
+            .map(|(authority, programs)| {
+                (
+                    authority.to_string(),
+                    programs
+                        .iter()
+                        .map(|(program, hashes)| (program.to_string(), *hashes))
+                        .collect(),
+                )
+            })
+            .collect();
+        self
+    }
+
+    /// Load a snapshot of any known schema, upgraded to the current one
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let raw = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Ok(serde_json::from_value(migrate_snapshot(raw)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Canonical text form for reviewing allocation changes with `git diff`
+    ///
+    /// One fixed-width line per recipient, sorted by authority, after a few
+    /// `#` header lines. The timestamp is left out so that re-running an
+    /// unchanged airdrop produces an identical file.
+    pub fn to_text(&self) -> Result<String> {
+        let mut text = format!(
+            "# TestORE airdrop snapshot (schema {})\n",
+            self.schema_version
+        );
+        if let (Some(round), Some(slot)) = (self.round_number, self.slot) {
+            text += &format!("# round {} slot {}\n", round, slot);
+        }
+        if let Some(params) = &self.params {
+            text += &format!("# params {}\n", serde_json::to_string(params)?);
+        }
+        text += &format!(
+            "# {} miners, {} tokens, hash {}\n",
+            self.total_miners,
+            self.total_tokens,
+            self.allocations_hash.as_deref().unwrap_or("-")
+        );
+        if let Some(inactive) = &self.inactive {
+            text += &format!(
+                "# inactive > {} days (before {}): {} miners, {} hashes excluded\n",
+                inactive.max_inactive_days, inactive.cutoff, inactive.miners, inactive.total_hashes
+            );
+        }
+
+        for (authority, tokens) in &self.allocations {
+            text += &format!("{:<44} {:>20}\n", authority, tokens);
+        }
+
+        if !self.excluded.is_empty() {
+            text += "# excluded\n";
+            for (wallet, reason) in &self.excluded {
+                text += &format!("{:<44} {}\n", wallet, reason);
+            }
+        }
+
+        Ok(text)
+    }
+
+    pub fn save_text(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_text()?)?;
+        Ok(())
+    }
+}
+
+/// One ranked miner as fed into the allocation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotInput {
+    pub authority: String,
+    pub total_hashes: u64,
+    pub rounds_completed: u32,
+
+    /// Score, for miners the scoring migration had converted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<u64>,
+
+    /// Verified live at snapshot time (see [`apply_liveness_boost`])
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub live: bool,
+}
+
+/// Upgrade raw snapshot JSON to [`SNAPSHOT_SCHEMA_VERSION`], one version at
+/// a time
+pub fn migrate_snapshot(mut raw: serde_json::Value) -> Result<serde_json::Value> {
+    loop {
+        let version = match raw.get("schema_version") {
+            None => 1,
+            Some(v) => v
+                .as_u64()
+                .ok_or_else(|| anyhow::anyhow!("Invalid snapshot schema_version {}", v))?,
+        };
+        let fields = raw
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("Snapshot is not a JSON object"))?;
+
+        match version {
+            1 => {
+                fields.insert("schema_version".into(), 2.into());
+                fields.insert("round_number".into(), serde_json::Value::Null);
+                fields.insert("slot".into(), serde_json::Value::Null);
+                fields.insert("params".into(), serde_json::Value::Null);
+                fields.insert("excluded".into(), serde_json::json!({}));
+            }
+            // Not reproducible: neither the inputs nor the hash were kept
+            2 => {
+                fields.insert("schema_version".into(), 3.into());
+                fields.insert("allocations_hash".into(), serde_json::Value::Null);
+                fields.insert("inputs".into(), serde_json::json!([]));
+            }
+            v if v == SNAPSHOT_SCHEMA_VERSION as u64 => return Ok(raw),
+            v => {
+                return Err(anyhow::anyhow!(
+                    "Snapshot schema_version {} is newer than supported ({})",
+                    v,
+                    SNAPSHOT_SCHEMA_VERSION
+                ))
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Distribution Ledger
+// ============================================================================
+
+/// Outcome of the latest transfer attempt to a recipient
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferStatus {
+    Sent,
+    Failed,
+
+    /// Failed in a way retrying won't fix (e.g. a frozen token account);
+    /// skipped by chunk planning until retried by hand
+    Quarantined,
+}
+
+/// Latest transfer attempt to one recipient
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub tokens: u64,
+
+    /// Execution run (1-based) the attempt belonged to
+    pub chunk: u32,
+
+    pub status: TransferStatus,
+
+    /// Transaction signature when sent, error message otherwise
+    pub detail: String,
+
+    /// RFC 3339 time of the attempt
+    pub updated_at: String,
+
+    /// Transfer signed for this recipient but not confirmed either way
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending: Option<PendingTransfer>,
+}
+
+/// Signed transfer that may have been broadcast without being confirmed
+///
+/// Kept until the transfer is confirmed to have landed or failed, so a retry
+/// after a timeout rebroadcasts the same transaction (or sees it expired)
+/// instead of paying twice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingTransfer {
+    pub signature: String,
+
+    /// Base64 bincode of the signed transaction
+    pub transaction: String,
+}
+
+/// Record of every distribution transfer, kept across runs
+///
+/// Recipients marked sent are never paid again, which makes it safe to split
+/// a distribution into chunks or re-run after a crash; a transfer whose
+/// outcome is unknown stays pending on its entry until resolved. A recipient's
+/// allocation growing after they were paid is not topped up. Quarantined
+/// recipients are likewise left out of new chunks until retried.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ledger {
+    /// Latest attempt per recipient (base58)
+    pub entries: BTreeMap<String, LedgerEntry>,
+
+    /// Excluded wallets already sent an exclusion notice (base58)
+    #[serde(default)]
+    pub notified: BTreeSet<String>,
+
+    /// Lamports spent on transaction fees, by execution run
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fees: BTreeMap<u32, u64>,
+}
+
+impl Ledger {
+    /// Load the ledger, or start an empty one if the file doesn't exist yet
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(raw) => Ok(serde_json::from_str(&raw)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Chunk number for the next execution run
+    pub fn next_chunk(&self) -> u32 {
+        self.entries.values().map(|e| e.chunk).max().unwrap_or(0) + 1
+    }
+
+    pub fn is_sent(&self, recipient: &Pubkey) -> bool {
+        self.entries
+            .get(&recipient.to_string())
+            .map_or(false, |e| e.status == TransferStatus::Sent)
+    }
+
+    pub fn record(
+        &mut self,
+        recipient: &Pubkey,
+        tokens: u64,
+        chunk: u32,
+        status: TransferStatus,
+        detail: impl Into<String>,
+    ) {
+        // Only a confirmed outcome settles a pending transfer
+        let pending = match status {
+            TransferStatus::Sent | TransferStatus::Quarantined => None,
+            TransferStatus::Failed => self.pending(recipient).cloned(),
+        };
+
+        self.entries.insert(
+            recipient.to_string(),
+            LedgerEntry {
+                tokens,
+                chunk,
+                status,
+                detail: detail.into(),
+                updated_at: chrono::Utc::now().to_rfc3339(),
+                pending,
+            },
+        );
+    }
+
+    /// Record a signed transfer before broadcasting it
+    ///
+    /// Save the ledger before sending: if the process dies mid-send, the next
+    /// run finds the transfer here rather than paying again.
+    pub fn record_pending(
+        &mut self,
+        recipient: &Pubkey,
+        tokens: u64,
+        chunk: u32,
+        pending: PendingTransfer,
+    ) {
+        self.entries.insert(
+            recipient.to_string(),
+            LedgerEntry {
+                tokens,
+                chunk,
+                status: TransferStatus::Failed,
+                detail: format!("unconfirmed {}", pending.signature),
+                updated_at: chrono::Utc::now().to_rfc3339(),
+                pending: Some(pending),
+            },
+        );
+    }
+
+    /// Add a confirmed transaction's fee to `chunk`'s spend
+    pub fn record_fee(&mut self, chunk: u32, lamports: u64) {
+        *self.fees.entry(chunk).or_default() += lamports;
+    }
+
+    /// Lamports spent on fees across every run
+    pub fn total_fees(&self) -> u64 {
+        self.fees.values().sum()
+    }
+
+    pub fn pending(&self, recipient: &Pubkey) -> Option<&PendingTransfer> {
+        self.entries
+            .get(&recipient.to_string())
+            .and_then(|e| e.pending.as_ref())
+    }
+
+    /// Allocations neither sent nor quarantined, largest first (ties by address)
+    pub fn outstanding(&self, allocations: &HashMap<Pubkey, u64>) -> Vec<(Pubkey, u64)> {
+        let mut outstanding: Vec<(Pubkey, u64)> = allocations
+            .iter()
+            .filter(|(recipient, _)| {
+                !matches!(
+                    self.entries.get(&recipient.to_string()).map(|e| e.status),
+                    Some(TransferStatus::Sent | TransferStatus::Quarantined)
+                )
+            })
+            .map(|(recipient, tokens)| (*recipient, *tokens))
+            .collect();
+        outstanding.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        outstanding
+    }
+
+    /// Quarantined recipients with their recorded tokens, largest first
+    pub fn quarantined(&self) -> Result<Vec<(Pubkey, u64)>> {
+        let mut quarantined = self
+            .entries
+            .iter()
+            .filter(|(_, e)| e.status == TransferStatus::Quarantined)
+            .map(|(recipient, e)| Ok((recipient.parse()?, e.tokens)))
+            .collect::<Result<Vec<(Pubkey, u64)>>>()?;
+        quarantined.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Ok(quarantined)
+    }
+}
+
+/// Next chunk of outstanding allocations that fits the per-run caps
+///
+/// Takes recipients in [`Ledger::outstanding`] order and stops at the first
+/// one that would exceed either cap, so every run covers a predictable slice.
+pub fn plan_chunk(
+    outstanding: &[(Pubkey, u64)],
+    max_tokens: Option<u64>,
+    max_recipients: Option<usize>,
+) -> Result<Vec<(Pubkey, u64)>> {
+    let max_tokens = max_tokens.unwrap_or(u64::MAX);
+    let max_recipients = max_recipients.unwrap_or(usize::MAX);
+
+    let mut chunk = Vec::new();
+    let mut tokens = 0u64;
+
+    for &(recipient, amount) in outstanding.iter().take(max_recipients) {
+        match tokens.checked_add(amount) {
+            Some(total) if total <= max_tokens => {
+                tokens = total;
+                chunk.push((recipient, amount));
+            }
+            _ => break,
+        }
+    }
+
+    if chunk.is_empty() && max_recipients > 0 {
+        if let Some((recipient, amount)) = outstanding.first() {
+            return Err(anyhow::anyhow!(
+                "Allocation of {} to {} exceeds the per-run token cap of {}",
+                amount,
+                recipient,
+                max_tokens
+            ));
+        }
+    }
+
+    Ok(chunk)
+}
+
+// ============================================================================
+// Leaderboard Cache
+// ============================================================================
+
+/// Ranked leaderboard as cached on disk, so exports don't hit RPC
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderboardCache {
+    /// RFC 3339 time the miners were fetched
+    pub fetched_at: String,
+
+    /// Testnet slot the miners were read at (None for caches predating it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+
+    /// Entries in rank order
+    pub entries: Vec<LeaderboardRow>,
+}
+
+/// One ranked miner in a [`LeaderboardCache`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderboardRow {
+    /// 1-based rank
+    pub rank: usize,
+
+    /// Miner authority (base58)
+    pub authority: String,
+
+    pub total_hashes: u64,
+    pub rounds_completed: u32,
+    pub best_difficulty: u8,
+
+    /// Unix timestamp of the last accepted proof
+    pub last_hash_at: i64,
+
+    /// Display name registered with `set_metadata`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Profile link registered with `set_metadata`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl LeaderboardCache {
+    /// Build a cache from miners already ranked by [`rank_miners`]
+    pub fn new(ranked: &[MinerAccount]) -> Self {
+        Self {
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            slot: None,
+            entries: ranked
+                .iter()
+                .enumerate()
+                .map(|(i, miner)| LeaderboardRow {
+                    rank: i + 1,
+                    authority: miner.authority.to_string(),
+                    total_hashes: miner.total_hashes,
+                    rounds_completed: miner.rounds_completed,
+                    best_difficulty: miner.best_difficulty,
+                    last_hash_at: miner.last_hash_at,
+                    name: None,
+                    url: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Fill in display names from [`fetch_miner_metadata`]
+    pub fn with_metadata(mut self, metadata: &HashMap<Pubkey, MinerMetadataAccount>) -> Self {
+        for row in &mut self.entries {
+            let Some(registered) = row
+                .authority
+                .parse()
+                .ok()
+                .and_then(|authority: Pubkey| metadata.get(&authority))
+            else {
+                continue;
+            };
+            row.name = Some(registered.name.clone());
+            row.url = registered.url.clone();
+        }
+        self
+    }
+
+    /// Record the testnet slot the miners were read at
+    pub fn with_slot(mut self, slot: u64) -> Self {
+        self.slot = Some(slot);
+        self
+    }
+
+    /// Seconds between fetching the miners and `now`
+    pub fn age_secs(&self, now: chrono::DateTime<chrono::Utc>) -> Result<i64> {
+        let fetched_at = chrono::DateTime::parse_from_rfc3339(&self.fetched_at)?;
+        Ok((now - fetched_at.with_timezone(&chrono::Utc)).num_seconds())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// RPC Budget
+// ============================================================================
+
+/// Token bucket limiting requests to one RPC endpoint
+///
+/// Holds up to `burst` tokens, refilled at `requests_per_sec`; every request
+/// takes one token and waits when none are left.
+#[derive(Debug)]
+pub struct RpcBudget {
+    requests_per_sec: f64,
+    burst: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RpcBudget {
+    pub fn new(requests_per_sec: f64, burst: u32) -> Self {
+        let burst = burst.max(1) as f64;
+        Self {
+            requests_per_sec: requests_per_sec.max(f64::MIN_POSITIVE),
+            burst,
+            state: Mutex::new((burst, Instant::now())),
+        }
+    }
+
+    /// Take a token, blocking until one is available
+    pub fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Take a token as of `now`, returning how long the caller must wait
+    /// before using it. Tokens may go negative so concurrent callers queue up
+    /// instead of racing for the same refill.
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let (tokens, last) = *state;
+
+        let elapsed = now.saturating_duration_since(last).as_secs_f64();
+        let tokens = (tokens + elapsed * self.requests_per_sec).min(self.burst) - 1.0;
+        *state = (tokens, now.max(last));
+
+        if tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-tokens / self.requests_per_sec)
+        }
+    }
+}
+
+/// Requests per second allowed per endpoint (env `RPC_REQUESTS_PER_SEC`)
+pub const DEFAULT_RPC_REQUESTS_PER_SEC: f64 = 10.0;
+
+/// Burst size per endpoint (env `RPC_BURST`)
+pub const DEFAULT_RPC_BURST: u32 = 20;
+
+/// Process-wide budget for `endpoint`, shared by every caller using it
+pub fn rpc_budget(endpoint: &str) -> Arc<RpcBudget> {
+    static BUDGETS: OnceLock<Mutex<HashMap<String, Arc<RpcBudget>>>> = OnceLock::new();
+
+    BUDGETS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap()
+        .entry(endpoint.to_string())
+        .or_insert_with(|| {
+            let requests_per_sec = std::env::var("RPC_REQUESTS_PER_SEC")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_RPC_REQUESTS_PER_SEC);
+            let burst = std::env::var("RPC_BURST")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_RPC_BURST);
+
+            Arc::new(RpcBudget::new(requests_per_sec, burst))
+        })
+        .clone()
+}
+
+/// Wait for budget before sending a request through `client`
+pub fn throttle(client: &RpcClient) {
+    rpc_budget(&client.url()).acquire();
+}
+
+// ============================================================================
+// RPC Helpers
+// ============================================================================
+
+/// Fetch and parse every Miner account owned by `program_id`, plus the
+/// MinerArchive of every miner `gc_miner` closed
+///
+/// Archived miners keep counting in rankings and airdrops; filter on
+/// [`MinerAccount::archived_at`] where only live accounts will do.
+pub fn fetch_miners(client: &RpcClient, program_id: &Pubkey) -> Result<Vec<MinerAccount>> {
+    throttle(client);
+    let accounts = client.get_program_accounts(program_id)?;
+
+    Ok(accounts
+        .iter()
+        .filter_map(|(_pda, account)| {
+            parse_miner_account(&account.data).or_else(|| parse_miner_archive(&account.data))
+        })
+        .collect())
+}
+
+/// Fetch and parse the GlobalRound account
+pub fn fetch_global_round(client: &RpcClient, program_id: &Pubkey) -> Result<GlobalRoundAccount> {
+    let address = global_round_pda(program_id);
+    throttle(client);
+    let account = client.get_account(&address)?;
+
+    parse_global_round(&account.data)
+        .ok_or_else(|| anyhow::anyhow!("{} is not a GlobalRound account", address))
+}
+
+/// Fetch member counts of every ScoreBucket; missing buckets count as empty
+pub fn fetch_score_buckets(
+    client: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<[u64; SCORE_BUCKET_COUNT]> {
+    let addresses: Vec<Pubkey> = (0..SCORE_BUCKET_COUNT as u8)
+        .map(|index| score_bucket_pda(program_id, index))
+        .collect();
+
+    throttle(client);
+    let mut counts = [0u64; SCORE_BUCKET_COUNT];
+    for account in client
+        .get_multiple_accounts(&addresses)?
+        .into_iter()
+        .flatten()
+    {
+        if let Some((index, members)) = parse_score_bucket(&account.data) {
+            if let Some(count) = counts.get_mut(index as usize) {
+                *count = members;
+            }
+        }
+    }
+
+    Ok(counts)
+}
+
+/// total_hashes per program ID for each authority
+pub type ProgramBreakdown = HashMap<Pubkey, BTreeMap<Pubkey, u64>>;
+
+/// Fetch Miner accounts from every deployment in `program_ids`
+///
+/// Testnet redeploys leave earlier stats under the old program ID. The
+/// returned miners are not consolidated, so an authority active on several
+/// deployments appears once per program; merge them with
+/// [`consolidate_duplicates`].
+pub fn fetch_miners_across(
+    client: &RpcClient,
+    program_ids: &[Pubkey],
+) -> Result<(Vec<MinerAccount>, ProgramBreakdown)> {
+    let mut miners = Vec::new();
+    let mut breakdown = ProgramBreakdown::new();
+
+    for program_id in program_ids {
+        for miner in fetch_miners(client, program_id)? {
+            let hashes = breakdown
+                .entry(miner.authority)
+                .or_default()
+                .entry(*program_id)
+                .or_default();
+            *hashes = hashes.saturating_add(miner.total_hashes);
+            miners.push(miner);
+        }
+    }
+
+    Ok((miners, breakdown))
+}
+
+/// Fetch miners ranked by [`rank_miners`], truncated to `limit`
+///
+/// Duplicate authorities are merged with [`DuplicatePolicy::default`]; use
+/// [`consolidate_duplicates`] directly to choose a policy or see the merges.
+pub fn fetch_ranked_miners(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    limit: usize,
+) -> Result<Vec<MinerAccount>> {
+    fetch_ranked_miners_across(client, std::slice::from_ref(program_id), limit)
+}
+
+/// Latest liveness verification time for every miner that has one
+pub fn fetch_liveness(client: &RpcClient, program_id: &Pubkey) -> Result<HashMap<Pubkey, i64>> {
+    throttle(client);
+    let accounts = client.get_program_accounts(program_id)?;
+
+    Ok(accounts
+        .iter()
+        .filter_map(|(_pda, account)| parse_liveness_proof(&account.data))
+        .collect())
+}
+
+/// Fetch every miner's registered display name, by authority
+pub fn fetch_miner_metadata(
+    client: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<HashMap<Pubkey, MinerMetadataAccount>> {
+    throttle(client);
+    let accounts = client.get_program_accounts(program_id)?;
+
+    Ok(accounts
+        .iter()
+        .filter_map(|(_pda, account)| parse_miner_metadata(&account.data))
+        .map(|metadata| (metadata.authority, metadata))
+        .collect())
+}
+
+/// Fetch every miner's region report, by authority
+pub fn fetch_miner_regions(
+    client: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<HashMap<Pubkey, MinerRegionAccount>> {
+    throttle(client);
+    let accounts = client.get_program_accounts(program_id)?;
+
+    Ok(accounts
+        .iter()
+        .filter_map(|(_pda, account)| parse_miner_region(&account.data))
+        .map(|report| (report.authority, report))
+        .collect())
+}
+
+/// Fetch the RewardPool account, if the pool has been configured
+pub fn fetch_reward_pool(
+    client: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<Option<RewardPoolAccount>> {
+    let address = reward_pool_pda(program_id);
+    throttle(client);
+    let Some(account) = client
+        .get_account_with_commitment(&address, client.commitment())?
+        .value
+    else {
+        return Ok(None);
+    };
+
+    parse_reward_pool(&account.data)
+        .map(Some)
+        .ok_or_else(|| anyhow::anyhow!("{} is not a RewardPool account", address))
+}
+
+/// Fetch the score decay setting, or None while decay was never configured
+pub fn fetch_score_decay(
+    client: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<Option<ScoreDecayAccount>> {
+    let address = score_decay_pda(program_id);
+    throttle(client);
+    let Some(account) = client
+        .get_account_with_commitment(&address, client.commitment())?
+        .value
+    else {
+        return Ok(None);
+    };
+
+    parse_score_decay(&account.data)
+        .map(Some)
+        .ok_or_else(|| anyhow::anyhow!("{} is not a ScoreDecay account", address))
+}
+
+/// Fetch the GcPolicy, or None while garbage collection was never configured
+pub fn fetch_gc_policy(client: &RpcClient, program_id: &Pubkey) -> Result<Option<GcPolicyAccount>> {
+    let address = gc_policy_pda(program_id);
+    throttle(client);
+    let Some(account) = client
+        .get_account_with_commitment(&address, client.commitment())?
+        .value
+    else {
+        return Ok(None);
+    };
+
+    parse_gc_policy(&account.data)
+        .map(Some)
+        .ok_or_else(|| anyhow::anyhow!("{} is not a GcPolicy account", address))
+}
+
+/// Fetch the ScoringMigration account, or None before `migrate_scoring`
+pub fn fetch_scoring_migration(
+    client: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<Option<ScoringMigrationAccount>> {
+    let address = scoring_migration_pda(program_id);
+    throttle(client);
+    let Some(account) = client
+        .get_account_with_commitment(&address, client.commitment())?
+        .value
+    else {
+        return Ok(None);
+    };
+
+    parse_scoring_migration(&account.data)
+        .map(Some)
+        .ok_or_else(|| anyhow::anyhow!("{} is not a ScoringMigration account", address))
+}
+
+/// [`fetch_ranked_miners`] over several deployments (see [`fetch_miners_across`])
+pub fn fetch_ranked_miners_across(
+    client: &RpcClient,
+    program_ids: &[Pubkey],
+    limit: usize,
+) -> Result<Vec<MinerAccount>> {
+    let (miners, _) = fetch_miners_across(client, program_ids)?;
+    let (mut miners, _) = consolidate_duplicates(miners, DuplicatePolicy::default())?;
+    rank_miners(&mut miners);
+    miners.truncate(limit);
+
+    Ok(miners)
+}
+
+// ============================================================================
+// Keypairs
+// ============================================================================
+
+/// Whether `data` is an age-encrypted file (binary or ASCII-armored)
+pub fn is_encrypted_keypair(data: &[u8]) -> bool {
+    data.starts_with(b"age-encryption.org/v1")
+        || data.starts_with(b"-----BEGIN AGE ENCRYPTED FILE-----")
+}
+
+/// Encrypt a Solana JSON keypair with an age passphrase
+///
+/// The output is a standard age file, so `age --decrypt` recovers the usual
+/// JSON keypair and `age --passphrase` can produce files this crate reads.
+pub fn encrypt_keypair(keypair: &Keypair, passphrase: &str) -> Result<Vec<u8>> {
+    let plaintext = serde_json::to_vec(&keypair.to_bytes().to_vec())?;
+    let encryptor =
+        age::Encryptor::with_user_passphrase(age::secrecy::Secret::new(passphrase.to_owned()));
+
+    let mut encrypted = Vec::new();
+    let mut writer = encryptor.wrap_output(&mut encrypted)?;
+    writer.write_all(&plaintext)?;
+    writer.finish()?;
+
+    Ok(encrypted)
+}
+
+/// Decrypt a keypair written by [`encrypt_keypair`] or `age --passphrase`
+pub fn decrypt_keypair(data: &[u8], passphrase: &str) -> Result<Keypair> {
+    let reader = age::armor::ArmoredReader::new(data);
+    let decryptor = match age::Decryptor::new(reader)? {
+        age::Decryptor::Passphrase(d) => d,
+        _ => return Err(anyhow::anyhow!("Keypair is not passphrase-encrypted")),
+    };
+
+    let mut plaintext = Vec::new();
+    decryptor
+        .decrypt(&age::secrecy::Secret::new(passphrase.to_owned()), None)
+        .map_err(|e| anyhow::anyhow!("Could not decrypt keypair: {}", e))?
+        .read_to_end(&mut plaintext)?;
+
+    let bytes: Vec<u8> = serde_json::from_slice(&plaintext)?;
+    Ok(Keypair::from_bytes(&bytes)?)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn encode_miner(authority: [u8; 32], total_hashes: u64, rounds: u32, best: u8) -> Vec<u8> {
+        let mut data = miner_discriminator().to_vec();
+        data.extend_from_slice(&authority);
+        data.extend_from_slice(&total_hashes.to_le_bytes());
+        data.extend_from_slice(&rounds.to_le_bytes());
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.push(best);
+        data.push(255);
+        data
+    }
+
+    fn miner(total_hashes: u64, rounds_completed: u32) -> MinerAccount {
+        MinerAccount {
+            authority: Pubkey::new_unique(),
+            total_hashes,
+            rounds_completed,
+            last_hash_at: 0,
+            current_streak: 0,
+            best_difficulty: 0,
+            layout: MinerLayout::V1,
+            tier: None,
+            capped_score: None,
+            score: None,
+            score_bucket: None,
+            archived_at: None,
+        }
+    }
+
+    proptest! {
+        /// Idling longer never leaves a miner with more, and a long enough
+        /// absence wipes out any score without overflowing on the way
+        #[test]
+        fn decay_is_monotonic_over_long_idle_periods(
+            score in any::<u64>(),
+            rounds in any::<u64>(),
+            extra in any::<u64>(),
+            decay_bps in 1..BPS_DENOMINATOR,
+        ) {
+            let decayed = decayed_score(score, rounds, decay_bps);
+            prop_assert!(decayed <= score);
+            prop_assert!(decayed_score(score, rounds.saturating_add(extra), decay_bps) <= decayed);
+            if rounds >= 500_000 {
+                prop_assert_eq!(decayed, 0);
+            }
+        }
+
+        #[test]
+        fn parse_never_panics(data in proptest::collection::vec(any::<u8>(), 0..256)) {
+            let _ = parse_miner_account(&data);
+        }
+
+        #[test]
+        fn parse_roundtrips_valid_accounts(
+            authority in any::<[u8; 32]>(),
+            total_hashes in any::<u64>(),
+            rounds in any::<u32>(),
+            best in any::<u8>(),
+        ) {
+            let data = encode_miner(authority, total_hashes, rounds, best);
+            let miner = parse_miner_account(&data).unwrap();
+            prop_assert_eq!(miner.authority, Pubkey::new_from_array(authority));
+            prop_assert_eq!(miner.total_hashes, total_hashes);
+            prop_assert_eq!(miner.rounds_completed, rounds);
+            prop_assert_eq!(miner.last_hash_at, 1_700_000_000);
+            prop_assert_eq!(miner.current_streak, 3);
+            prop_assert_eq!(miner.best_difficulty, best);
+        }
+
+        #[test]
+        fn parse_rejects_truncated_accounts(len in 0..MINER_ACCOUNT_LEN) {
+            let data = encode_miner([7; 32], 1, 1, 1);
+            prop_assert!(parse_miner_account(&data[..len]).is_none());
+        }
+
+        #[test]
+        fn parse_rejects_foreign_discriminators(
+            discriminator in any::<[u8; 8]>().prop_filter("miner", |d| *d != miner_discriminator()),
+        ) {
+            let mut data = encode_miner([7; 32], 1, 1, 1);
+            data[..8].copy_from_slice(&discriminator);
+            prop_assert!(parse_miner_account(&data).is_none());
+        }
+    }
+
+    #[test]
+    fn parse_detects_layouts() {
+        let v1 = encode_miner([7; 32], 5, 1, 9);
+        let miner = parse_miner_account(&v1).unwrap();
+        assert_eq!(miner.layout, MinerLayout::V1);
+        assert_eq!(miner.tier, None);
+
+        let mut v2 = v1.clone();
+        v2.push(3); // tier
+        v2.resize(MINER_ACCOUNT_LEN_V2, 0);
+        let migrated = parse_miner_account(&v2).unwrap();
+        assert_eq!(migrated.layout, MinerLayout::V2);
+        assert_eq!(migrated.tier, Some(3));
+        assert_eq!(migrated.capped_score, None);
+        assert_eq!(migrated.score_bucket, Some(0));
+        v2[107] = NO_SCORE_BUCKET;
+        assert_eq!(parse_miner_account(&v2).unwrap().score_bucket, None);
+        assert_eq!(migrated.total_hashes, miner.total_hashes);
+
+        let mut v3 = v2.clone();
+        v3.extend_from_slice(&4u64.to_le_bytes()); // round_number
+        v3.extend_from_slice(&9u32.to_le_bytes()); // round_proofs
+        v3.extend_from_slice(&6u64.to_le_bytes()); // capped_score
+        assert_eq!(v3.len(), MINER_ACCOUNT_LEN_V3);
+        let capped = parse_miner_account(&v3).unwrap();
+        assert_eq!(capped.layout, MinerLayout::V3);
+        assert_eq!(capped.capped_score, Some(6));
+        assert_eq!(capped.tier, Some(3));
+
+        let mut v4 = v3.clone();
+        v4.extend_from_slice(&11u64.to_le_bytes()); // score
+        v4.push(0); // score_converted
+        assert_eq!(v4.len(), MINER_ACCOUNT_LEN_V4);
+        let unconverted = parse_miner_account(&v4).unwrap();
+        assert_eq!(unconverted.layout, MinerLayout::V4);
+        assert_eq!(unconverted.score, None);
+        v4[136] = 1;
+        let scored = parse_miner_account(&v4).unwrap();
+        assert_eq!(scored.score, Some(11));
+        assert_eq!(scored.capped_score, Some(6));
+
+        // Fields appended by a future upgrade are ignored
+        v4.resize(MINER_ACCOUNT_LEN_V4 + 40, 0xff);
+        assert_eq!(parse_miner_account(&v4).unwrap(), scored);
+    }
+
+    #[test]
+    fn test_parse_scoring_migration() {
+        let mut data = scoring_migration_discriminator().to_vec();
+        data.push(1);
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        data.extend_from_slice(&0i64.to_le_bytes());
+        data.push(254);
+        assert_eq!(data.len(), SCORING_MIGRATION_ACCOUNT_LEN);
+
+        assert_eq!(
+            parse_scoring_migration(&data),
+            Some(ScoringMigrationAccount {
+                phase: ScoringPhase::Frozen,
+                frozen_at: 1_700_000_000,
+                resumed_at: 0,
+            })
+        );
+        data[8] = 3;
+        assert_eq!(parse_scoring_migration(&data), None);
+        assert_eq!(parse_scoring_migration(&data[..20]), None);
+    }
+
+    #[test]
+    fn test_collectable_miners() {
+        let sweep_wallet = Pubkey::new_unique();
+        let mut data = gc_policy_discriminator().to_vec();
+        data.extend_from_slice(&30u16.to_le_bytes());
+        data.extend_from_slice(sweep_wallet.as_ref());
+        data.push(254);
+        assert_eq!(data.len(), GC_POLICY_ACCOUNT_LEN);
+        let policy = parse_gc_policy(&data).unwrap();
+        assert_eq!(policy.inactive_days, 30);
+        assert_eq!(policy.sweep_wallet, sweep_wallet);
+        assert_eq!(parse_gc_policy(&data[..GC_POLICY_ACCOUNT_LEN - 1]), None);
+
+        let day = 24 * 60 * 60;
+        let now = 100 * day;
+        let idle = |last_hash_at| MinerAccount {
+            last_hash_at,
+            ..miner(5, 0)
+        };
+        let miners = [
+            idle(now - 31 * day),
+            idle(now - 29 * day),
+            MinerAccount {
+                archived_at: Some(now - day),
+                ..idle(now - 60 * day)
+            },
+        ];
+
+        // Idle, and not already archived
+        let collectable = collectable_miners(&miners, &policy, now);
+        assert_eq!(collectable, vec![miners[0].clone()]);
+
+        let off = GcPolicyAccount {
+            inactive_days: 0,
+            ..policy
+        };
+        assert!(collectable_miners(&miners, &off, now).is_empty());
+    }
+
+    #[test]
+    fn test_parse_miner_archive() {
+        let authority = Pubkey::new_unique();
+        let mut data = miner_archive_discriminator().to_vec();
+        data.extend_from_slice(authority.as_ref());
+        data.extend_from_slice(&500u64.to_le_bytes());
+        data.extend_from_slice(&7u32.to_le_bytes());
+        data.extend_from_slice(&1_000i64.to_le_bytes());
+        data.push(14);
+        data.push(2);
+        data.extend_from_slice(&9u64.to_le_bytes());
+        data.extend_from_slice(&640u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&2_000i64.to_le_bytes());
+        data.push(254);
+        assert_eq!(data.len(), MINER_ARCHIVE_ACCOUNT_LEN);
+
+        let archived = parse_miner_archive(&data).unwrap();
+        assert_eq!(archived.authority, authority);
+        assert_eq!(archived.total_hashes, 500);
+        assert_eq!(archived.rounds_completed, 7);
+        assert_eq!(archived.last_hash_at, 1_000);
+        assert_eq!(archived.best_difficulty, 14);
+        assert_eq!(archived.tier, Some(2));
+        assert_eq!(archived.capped_score, Some(9));
+        assert_eq!(archived.score, Some(640));
+        assert_eq!(archived.archived_at, Some(2_000));
+
+        // Archives are not Miner accounts, and unconverted ones need no
+        // convert_stats
+        assert_eq!(parse_miner_account(&data), None);
+        data[78] = 0;
+        let unconverted = parse_miner_archive(&data).unwrap();
+        assert_eq!(unconverted.score, None);
+        assert!(unconverted_miners(&[unconverted]).is_empty());
+        assert_eq!(
+            parse_miner_archive(&data[..MINER_ARCHIVE_ACCOUNT_LEN - 1]),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_rejects_global_round_account() {
+        let mut data = hash(b"account:GlobalRound").to_bytes()[..8].to_vec();
+        data.resize(106, 0);
+        assert!(parse_miner_account(&data).is_none());
+    }
+
+    #[test]
+    fn test_estimate_percentile() {
+        let mut counts = [0u64; SCORE_BUCKET_COUNT];
+        assert_eq!(estimate_percentile(&counts, 10), None);
+
+        // 1 miner with no hashes, 2 in [2, 4), 1 in [1024, 2048)
+        counts[0] = 1;
+        counts[2] = 2;
+        counts[11] = 1;
+
+        assert_eq!(estimate_percentile(&counts, 0), Some(12.5));
+        assert_eq!(estimate_percentile(&counts, 3), Some(50.0));
+        assert_eq!(estimate_percentile(&counts, 1500), Some(87.5));
+        assert_eq!(estimate_percentile(&counts, u64::MAX), Some(100.0));
+    }
+
+    #[test]
+    fn test_parse_score_bucket() {
+        let mut data = score_bucket_discriminator().to_vec();
+        data.push(7);
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.push(255);
+
+        assert_eq!(parse_score_bucket(&data), Some((7, 42)));
+        assert_eq!(parse_score_bucket(&data[..17]), None);
+        assert!(parse_miner_account(&data).is_none());
+    }
+
+    #[test]
+    fn test_stats_merkle_root() {
+        assert_eq!(stats_merkle_root(&[]), [0; 32]);
+
+        let miners = vec![miner(10, 0), miner(20, 0), miner(30, 0)];
+        let single = &miners[..1];
+        assert_eq!(stats_merkle_root(single), stats_leaf(&miners[0]));
+
+        // Order of the input doesn't matter
+        let root = stats_merkle_root(&miners);
+        let mut reversed = miners.clone();
+        reversed.reverse();
+        assert_eq!(stats_merkle_root(&reversed), root);
+
+        // Any score change does
+        let mut changed = miners.clone();
+        changed[2].total_hashes += 1;
+        assert_ne!(stats_merkle_root(&changed), root);
+
+        // Once converted, the score is what's committed to
+        let mut converted = miners.clone();
+        converted[2].score = Some(30);
+        assert_eq!(stats_merkle_root(&converted), root);
+        converted[2].score = Some(31);
+        assert_ne!(stats_merkle_root(&converted), root);
+    }
+
+    #[test]
+    fn test_parse_stats_root() {
+        let mut data = stats_root_discriminator().to_vec();
+        data.extend_from_slice(&[9; 32]);
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(&100u64.to_le_bytes());
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        data.extend_from_slice(&2u64.to_le_bytes());
+        data.push(255);
+
+        let stats = parse_stats_root(&data).unwrap();
+        assert_eq!(stats.root, [9; 32]);
+        assert_eq!(stats.miner_count, 3);
+        assert_eq!(stats.slot, 100);
+        assert_eq!(stats.posted_at, 1_700_000_000);
+        assert_eq!(stats.sequence, 2);
+        assert!(parse_stats_root(&data[..STATS_ROOT_ACCOUNT_LEN - 1]).is_none());
+    }
+
+    #[test]
+    fn test_parse_global_round() {
+        let admin = Pubkey::new_unique();
+        let mut data = global_round_discriminator().to_vec();
+        data.extend_from_slice(&[3; 32]);
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.extend_from_slice(&1_000i64.to_le_bytes());
+        data.push(8);
+        data.extend_from_slice(&50u64.to_le_bytes());
+        data.extend_from_slice(&5u64.to_le_bytes());
+        data.extend_from_slice(admin.as_ref());
+        data.push(255);
+        data.push(2);
+        data.extend_from_slice(&3_600i64.to_le_bytes());
+        data.extend_from_slice(&[0; 24]);
+        data.extend_from_slice(&40u32.to_le_bytes());
+
+        let round = parse_global_round(&data).unwrap();
+        assert_eq!(round.current_challenge, [3; 32]);
+        assert_eq!(round.round_number, 7);
+        assert_eq!(round.min_difficulty, 8);
+        assert_eq!(round.total_hashes_submitted, 50);
+        assert_eq!(round.total_rounds_completed, 5);
+        assert_eq!(round.admin, admin);
+        assert_eq!(round.tier_step, 2);
+        assert_eq!(round.ends_at(), 4_600);
+        assert_eq!(round.max_round_proofs, 40);
+
+        let legacy = parse_global_round(&data[..GLOBAL_ROUND_ACCOUNT_LEN]).unwrap();
+        assert_eq!(legacy.max_round_proofs, 0);
+
+        // Not yet upgraded: tier_step and round_duration are defaults
+        let v1 = parse_global_round(&data[..GLOBAL_ROUND_V1_ACCOUNT_LEN]).unwrap();
+        assert_eq!(v1.admin, admin);
+        assert_eq!(v1.tier_step, 0);
+        assert_eq!(v1.round_duration, DEFAULT_ROUND_DURATION);
+        assert!(parse_global_round(&data[..GLOBAL_ROUND_V1_ACCOUNT_LEN - 1]).is_none());
+        assert!(parse_miner_account(&data).is_none());
+    }
+
+    #[test]
+    fn test_rpc_budget_burst_then_rate() {
+        let budget = RpcBudget::new(10.0, 3);
+        let start = Instant::now();
+        *budget.state.lock().unwrap() = (3.0, start);
+
+        // Burst goes through immediately
+        for _ in 0..3 {
+            assert_eq!(budget.reserve(start), Duration::ZERO);
+        }
+
+        // Then callers queue at the refill rate
+        assert_eq!(budget.reserve(start), Duration::from_millis(100));
+        assert_eq!(budget.reserve(start), Duration::from_millis(200));
+
+        // Idle time refills, capped at the burst size
+        let later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert_eq!(budget.reserve(later), Duration::ZERO);
+        }
+        assert!(budget.reserve(later) > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_rpc_budget_is_shared_per_endpoint() {
+        let a = rpc_budget("http://a.example");
+        assert!(Arc::ptr_eq(&a, &rpc_budget("http://a.example")));
+        assert!(!Arc::ptr_eq(&a, &rpc_budget("http://b.example")));
+    }
+
+    #[test]
+    fn test_keypair_encryption_roundtrip() {
+        use solana_sdk::signature::Signer;
+
+        let keypair = Keypair::new();
+        let encrypted = encrypt_keypair(&keypair, "correct horse").unwrap();
+
+        assert!(is_encrypted_keypair(&encrypted));
+        assert!(!is_encrypted_keypair(b"[1,2,3]"));
+
+        let decrypted = decrypt_keypair(&encrypted, "correct horse").unwrap();
+        assert_eq!(decrypted.pubkey(), keypair.pubkey());
+
+        assert!(decrypt_keypair(&encrypted, "battery staple").is_err());
+    }
+
+    #[test]
+    fn test_snapshot_migration() {
+        let v1 = serde_json::json!({
+            "timestamp": "2024-05-01T00:00:00+00:00",
+            "total_miners": 1,
+            "total_tokens": 500,
+            "allocations": { "11111111111111111111111111111111": 500 },
+        });
+        let upgraded: Snapshot = serde_json::from_value(migrate_snapshot(v1).unwrap()).unwrap();
+        assert_eq!(upgraded.schema_version, SNAPSHOT_SCHEMA_VERSION);
+        assert_eq!(upgraded.total_tokens, 500);
+        assert_eq!(upgraded.params, None);
+
+        let wallet = Pubkey::new_unique();
+        let reason = "sybil cluster".to_string();
+        let current = Snapshot::new(&HashMap::new(), &AllocationParams::default())
+            .with_excluded([(&wallet, &reason)]);
+        let raw = serde_json::to_value(&current).unwrap();
+        let reloaded: Snapshot =
+            serde_json::from_value(migrate_snapshot(raw.clone()).unwrap()).unwrap();
+        assert_eq!(reloaded, current);
+
+        let mut future = raw;
+        future["schema_version"] = (SNAPSHOT_SCHEMA_VERSION + 1).into();
+        assert!(migrate_snapshot(future).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_reproduce() {
+        let params = AllocationParams {
+            liveness_boost_bps: 1_000,
+            ..AllocationParams::default()
+        };
+        let (kept, flagged) = (miner(3_000_000, 1), miner(2_000_000, 1));
+        let mut leaderboard = vec![kept.clone(), flagged.clone(), miner(50_000, 1)];
+        rank_miners(&mut leaderboard);
+        let live = BTreeSet::from([kept.authority]);
+
+        let mut allocations = calculate_allocations(&leaderboard, &params);
+        apply_liveness_boost(&mut allocations, &live, &params);
+        allocations.remove(&flagged.authority);
+        let reason = "sybil cluster".to_string();
+        let snapshot = Snapshot::new(&allocations, &params)
+            .with_inputs(&leaderboard, &live)
+            .with_excluded([(&flagged.authority, &reason)]);
+
+        assert_eq!(
+            snapshot.reproduce().unwrap(),
+            allocations_hash(&allocations)
+        );
+
+        let mut tampered = snapshot.clone();
+        tampered.inputs[0].total_hashes += 1_000_000;
+        assert!(tampered.reproduce().is_err());
+
+        let mut legacy = snapshot;
+        legacy.allocations_hash = None;
+        assert!(legacy.reproduce().is_err());
+    }
+
+    #[test]
+    fn test_score_metric_ranks_and_reproduces() {
+        let params = AllocationParams {
+            metric: RankingMetric::Score,
+            ..AllocationParams::default()
+        };
+        let (mut hasher, mut scorer) = (miner(5_000_000, 1), miner(3_000_000, 1));
+        hasher.score = Some(1_000_000);
+        scorer.score = Some(9_000_000);
+        let unconverted = miner(2_000_000, 1);
+        let mut leaderboard = vec![hasher.clone(), scorer.clone(), unconverted.clone()];
+        rank_miners_by(&mut leaderboard, params.metric);
+
+        let order: Vec<_> = leaderboard.iter().map(|m| m.authority).collect();
+        assert_eq!(
+            order,
+            vec![scorer.authority, unconverted.authority, hasher.authority]
+        );
+        let allocations = calculate_allocations(&leaderboard, &params);
+        assert_eq!(allocations[&scorer.authority], 900);
+        assert_eq!(allocations[&unconverted.authority], 200);
+        assert_eq!(allocations[&hasher.authority], 100);
+
+        let snapshot =
+            Snapshot::new(&allocations, &params).with_inputs(&leaderboard, &BTreeSet::new());
+        assert_eq!(
+            snapshot.reproduce().unwrap(),
+            allocations_hash(&allocations)
+        );
+
+        // The recorded metric is what makes it reproducible
+        let mut by_hashes = snapshot;
+        by_hashes.params = Some(AllocationParams::default());
+        assert!(by_hashes.reproduce().is_err());
+    }
+
+    #[test]
+    fn test_unconverted_miners_include_old_layouts() {
+        let (old, mut converted) = (miner(10, 0), miner(10, 0));
+        converted.layout = MinerLayout::V4;
+        converted.score = Some(10);
+        let mut fresh = miner(0, 0);
+        fresh.layout = MinerLayout::V4;
+
+        assert_eq!(
+            unconverted_miners(&[old.clone(), converted, fresh.clone()]),
+            vec![old.authority, fresh.authority]
+        );
+        assert_eq!(
+            RankingMetric::for_phase(ScoringPhase::Frozen),
+            RankingMetric::Hashes
+        );
+        assert_eq!(
+            RankingMetric::for_phase(ScoringPhase::Score),
+            RankingMetric::Score
+        );
+    }
+
+    #[test]
+    fn test_snapshot_text_is_canonical() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let allocations = HashMap::from([(a, 1_500), (b, 20)]);
+        let params = AllocationParams::default();
+
+        let first = Snapshot::new(&allocations, &params);
+        let mut second = Snapshot::new(&allocations, &params);
+        second.timestamp = "2024-05-01T00:00:00+00:00".into();
+        assert_eq!(first.to_text().unwrap(), second.to_text().unwrap());
+
+        let text = first.to_text().unwrap();
+        let rows: Vec<&str> = text.lines().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row.len() == 65));
+        assert!(rows.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(rows
+            .iter()
+            .any(|row| row.starts_with(&a.to_string()) && row.ends_with(" 1500")));
+    }
+
+    #[test]
+    fn test_snapshot_breakdown_only_for_multiple_programs() {
+        let authority = Pubkey::new_unique();
+        let (v1, v2) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let allocations = HashMap::from([(authority, 100)]);
+
+        let single = ProgramBreakdown::from([(authority, BTreeMap::from([(v1, 5_000_000)]))]);
+        assert!(Snapshot::new(&allocations, &AllocationParams::default())
+            .with_breakdown(&single)
+            .program_breakdown
+            .is_empty());
+
+        let unallocated = Pubkey::new_unique();
+        let multiple = ProgramBreakdown::from([
+            (
+                authority,
+                BTreeMap::from([(v1, 2_000_000), (v2, 3_000_000)]),
+            ),
+            (unallocated, BTreeMap::from([(v2, 10)])),
+        ]);
+        let snapshot =
+            Snapshot::new(&allocations, &AllocationParams::default()).with_breakdown(&multiple);
+        assert_eq!(snapshot.program_breakdown.len(), 1);
+        assert_eq!(
+            snapshot.program_breakdown[&authority.to_string()][&v2.to_string()],
+            3_000_000
+        );
+    }
+
+    #[test]
+    fn test_ledger_tracks_sent_and_chunks() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let allocations = HashMap::from([(a, 500), (b, 100)]);
+
+        let mut ledger = Ledger::default();
+        assert_eq!(ledger.next_chunk(), 1);
+        assert_eq!(ledger.outstanding(&allocations), vec![(a, 500), (b, 100)]);
+
+        ledger.record(&a, 500, 1, TransferStatus::Sent, "sig");
+        ledger.record(&b, 100, 1, TransferStatus::Failed, "blockhash expired");
+        assert_eq!(ledger.next_chunk(), 2);
+        assert!(ledger.is_sent(&a));
+
+        // Failed transfers stay outstanding
+        assert_eq!(ledger.outstanding(&allocations), vec![(b, 100)]);
+        assert!(ledger.quarantined().unwrap().is_empty());
+
+        // Quarantined ones wait for a manual retry
+        ledger.record(&b, 100, 2, TransferStatus::Quarantined, "account frozen");
+        assert!(ledger.outstanding(&allocations).is_empty());
+        assert_eq!(ledger.quarantined().unwrap(), vec![(b, 100)]);
+
+        // Fee spend survives a save and reload
+        ledger.record_fee(1, 5_000);
+        ledger.record_fee(1, 5_000);
+        ledger.record_fee(2, 5_000);
+        let reloaded: Ledger =
+            serde_json::from_str(&serde_json::to_string(&ledger).unwrap()).unwrap();
+        assert_eq!(reloaded.fees, BTreeMap::from([(1, 10_000), (2, 5_000)]));
+        assert_eq!(reloaded.total_fees(), 15_000);
+    }
+
+    #[test]
+    fn test_ledger_keeps_pending_until_confirmed() {
+        let recipient = Pubkey::new_unique();
+        let pending = PendingTransfer {
+            signature: "sig".to_string(),
+            transaction: "tx".to_string(),
+        };
+
+        let mut ledger = Ledger::default();
+        ledger.record_pending(&recipient, 100, 1, pending.clone());
+        assert!(!ledger.is_sent(&recipient));
+
+        // A timeout doesn't say whether the transfer landed
+        ledger.record(&recipient, 100, 1, TransferStatus::Failed, "timed out");
+        assert_eq!(ledger.pending(&recipient), Some(&pending));
+
+        ledger.record(&recipient, 100, 2, TransferStatus::Sent, "sig");
+        assert_eq!(ledger.pending(&recipient), None);
+    }
+
+    #[test]
+    fn test_plan_chunk_caps() {
+        let outstanding: Vec<_> = [400, 300, 200, 100]
+            .into_iter()
+            .map(|tokens| (Pubkey::new_unique(), tokens))
+            .collect();
+
+        assert_eq!(plan_chunk(&outstanding, None, None).unwrap(), outstanding);
+        assert_eq!(
+            plan_chunk(&outstanding, None, Some(2)).unwrap(),
+            outstanding[..2]
+        );
+
+        // Stops at the first recipient that doesn't fit, rather than skipping
+        assert_eq!(
+            plan_chunk(&outstanding, Some(800), None).unwrap(),
+            outstanding[..2]
+        );
+
+        assert!(plan_chunk(&outstanding, Some(399), None).is_err());
+        assert!(plan_chunk(&[], Some(1), None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_allocation_thresholds() {
+        let params = AllocationParams::default();
+
+        assert_eq!(allocation_for(99_999, &params), 0);
+        assert_eq!(allocation_for(100_000, &params), 0);
+        assert_eq!(allocation_for(1_000_000, &params), 100);
+        assert_eq!(allocation_for(10_999_999, &params), 1000);
+    }
+
+    #[test]
+    fn test_allocations_respect_top_miners() {
+        let params = AllocationParams {
+            top_miners: 2,
+            ..AllocationParams::default()
+        };
+        let mut miners = vec![
+            miner(1_000_000, 0),
+            miner(3_000_000, 0),
+            miner(2_000_000, 0),
+        ];
+        rank_miners(&mut miners);
+
+        let allocations = calculate_allocations(&miners, &params);
+        assert_eq!(allocations.len(), 2);
+        assert_eq!(allocations.values().sum::<u64>(), 500);
+    }
+
+    #[test]
+    fn test_liveness_boost() {
+        let params = AllocationParams {
+            liveness_boost_bps: 2_500,
+            ..AllocationParams::default()
+        };
+        let (live, idle) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut allocations = HashMap::from([(live, 400), (idle, 400)]);
+
+        let boosted = apply_liveness_boost(&mut allocations, &BTreeSet::from([live]), &params);
+        assert_eq!(boosted, 1);
+        assert_eq!((allocations[&live], allocations[&idle]), (500, 400));
+
+        let disabled = AllocationParams::default();
+        assert_eq!(
+            apply_liveness_boost(&mut allocations, &BTreeSet::from([live]), &disabled),
+            0
+        );
+    }
+
+    #[test]
+    fn test_parse_liveness_proof() {
+        let authority = Pubkey::new_unique();
+        let mut data = liveness_proof_discriminator().to_vec();
+        data.extend_from_slice(authority.as_ref());
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.push(255);
+
+        assert_eq!(
+            parse_liveness_proof(&data),
+            Some((authority, 1_700_000_000))
+        );
+        assert_eq!(parse_liveness_proof(&data[..52]), None);
+        assert_eq!(parse_miner_account(&data), None);
+    }
+
+    #[test]
+    fn test_parse_reward_pool() {
+        let leader = Pubkey::new_unique();
+        let mut data = reward_pool_discriminator().to_vec();
+        data.extend_from_slice(&5_000u64.to_le_bytes());
+        data.push(3);
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.extend_from_slice(&15_000u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(leader.as_ref());
+        data.extend_from_slice(&12u64.to_le_bytes());
+        data.resize(REWARD_POOL_ACCOUNT_LEN, 0);
+
+        let pool = parse_reward_pool(&data).unwrap();
+        assert_eq!(
+            (pool.fee_lamports, pool.top_k, pool.round_number, pool.pot),
+            (5_000, 3, 7, 15_000)
+        );
+        assert_eq!(pool.leaders, vec![(leader, 12)]);
+        assert_eq!(
+            parse_reward_pool(&data[..REWARD_POOL_ACCOUNT_LEN - 1]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_score_decay() {
+        let mut data = score_decay_discriminator().to_vec();
+        data.extend_from_slice(&250u16.to_le_bytes());
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        data.push(254);
+
+        assert_eq!(data.len(), SCORE_DECAY_ACCOUNT_LEN);
+        assert_eq!(
+            parse_score_decay(&data),
+            Some(ScoreDecayAccount {
+                decay_bps: 250,
+                configured_at: 1_700_000_000,
+            })
+        );
+        assert_eq!(parse_score_decay(&data[..18]), None);
+    }
+
+    /// Same vectors as the program's test_decayed_score_vectors
+    #[test]
+    fn test_decayed_score_vectors() {
+        let vectors: [(u64, u64, u16, u64); 8] = [
+            (1_000_000, 1, 100, 989_999),
+            (1_000_000, 2, 100, 980_099),
+            (1_000_000, 10, 100, 904_382),
+            (1_000_000, 100, 100, 366_032),
+            (u64::MAX, 1, 100, 18_262_276_632_972_456_098),
+            (12_345, 0, 100, 12_345),
+            (12_345, 7, 0, 12_345),
+            (1_000_000, 10_000, 100, 0),
+        ];
+
+        for (score, rounds, decay_bps, expected) in vectors {
+            assert_eq!(decayed_score(score, rounds, decay_bps), expected);
+        }
+    }
+
+    #[test]
+    fn test_apply_score_decay() {
+        let mut miners = vec![
+            miner(1_000_000, 0),
+            miner(1_000_000, 0),
+            miner(0, 0),
+            miner(1_000_000, 0),
+            miner(1_000_000, 0),
+        ];
+        for miner in &mut miners[..4] {
+            miner.score = Some(miner.total_hashes);
+        }
+        miners[0].last_hash_at = 100_000 - 10 * 3_600;
+        miners[1].last_hash_at = 100_000 - 60;
+        let decay = ScoreDecayAccount {
+            decay_bps: 100,
+            configured_at: 100_000 - 10 * 3_600,
+        };
+
+        assert_eq!(apply_score_decay(&mut miners, &decay, 100_000, 3_600), 2);
+        assert_eq!(miners[0].score, Some(904_382));
+        assert_eq!(miners[1].score, Some(1_000_000));
+        assert_eq!(miners[2].score, Some(0));
+        // Idle since long before decay existed: charged from configured_at
+        assert_eq!(miners[3].score, Some(904_382));
+        // Unconverted miners have no score to decay
+        assert_eq!(miners[4].score, None);
+        assert_eq!(miners[0].total_hashes, 1_000_000);
+
+        // Disabled decay leaves everyone alone
+        let disabled = ScoreDecayAccount {
+            decay_bps: 0,
+            ..decay
+        };
+        assert_eq!(
+            apply_score_decay(&mut miners, &disabled, i64::MAX, 3_600),
+            0
+        );
+    }
+
+    #[test]
+    fn test_parse_miner_metadata() {
+        let authority = Pubkey::new_unique();
+        let mut data = miner_metadata_discriminator().to_vec();
+        data.extend_from_slice(authority.as_ref());
+        let mut name = b"Rig Farm".to_vec();
+        name.resize(32, 0);
+        data.extend_from_slice(&name);
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        data.push(254);
+        assert_eq!(data.len(), MINER_METADATA_ACCOUNT_LEN);
+
+        let metadata = parse_miner_metadata(&data).unwrap();
+        assert_eq!(metadata.authority, authority);
+        assert_eq!(metadata.name, "Rig Farm");
+        assert_eq!(metadata.url, None);
+
+        let cache = LeaderboardCache::new(&[MinerAccount {
+            authority,
+            ..miner(5, 0)
+        }])
+        .with_metadata(&HashMap::from([(authority, metadata)]));
+        assert_eq!(cache.entries[0].name.as_deref(), Some("Rig Farm"));
+
+        let cache = cache.with_slot(42);
+        let fetched_at = chrono::DateTime::parse_from_rfc3339(&cache.fetched_at).unwrap();
+        let later = fetched_at.with_timezone(&chrono::Utc) + chrono::Duration::seconds(90);
+        assert_eq!(cache.age_secs(later).unwrap(), 90);
+        let reloaded: LeaderboardCache =
+            serde_json::from_str(&serde_json::to_string(&cache).unwrap()).unwrap();
+        assert_eq!(reloaded.slot, Some(42));
+
+        // Text hidden after the padding doesn't parse
+        data[60] = b'x';
+        assert_eq!(parse_miner_metadata(&data), None);
+    }
+
+    #[test]
+    fn test_region_stats() {
+        let region = |authority: &Pubkey, code: &[u8; 2], latency_bucket| {
+            let mut data = miner_region_discriminator().to_vec();
+            data.extend_from_slice(authority.as_ref());
+            data.extend_from_slice(code);
+            data.push(latency_bucket);
+            data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+            data.push(254);
+            assert_eq!(data.len(), MINER_REGION_ACCOUNT_LEN);
+            parse_miner_region(&data).unwrap()
+        };
+        let miners = vec![
+            MinerAccount {
+                best_difficulty: 10,
+                ..miner(100, 0)
+            },
+            MinerAccount {
+                best_difficulty: 13,
+                ..miner(50, 0)
+            },
+            miner(7, 0),
+            miner(1, 0),
+        ];
+        let regions: HashMap<_, _> = [
+            region(&miners[0].authority, b"DE", 1),
+            region(&miners[1].authority, b"DE", 3),
+            region(&miners[3].authority, &[0; 2], 0),
+        ]
+        .into_iter()
+        .map(|report| (report.authority, report))
+        .collect();
+
+        let stats = region_stats(&miners, &regions);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(
+            stats["DE"],
+            RegionStats {
+                miners: 2,
+                total_hashes: 150,
+                mean_best_difficulty: 11.5,
+                latency_buckets: BTreeMap::from([(1, 1), (3, 1)]),
+            }
+        );
+        // Cleared reports count as unreported
+        assert_eq!(stats[UNREPORTED_REGION].miners, 2);
+        assert!(stats[UNREPORTED_REGION].latency_buckets.is_empty());
+    }
+
+    #[test]
+    fn test_allocation_metrics() {
+        let allocations = |tokens: &[u64]| -> HashMap<Pubkey, u64> {
+            tokens.iter().map(|t| (Pubkey::new_unique(), *t)).collect()
+        };
+
+        let equal = allocation_metrics(&allocations(&[100; 20]));
+        assert_eq!(equal.recipients, 20);
+        assert_eq!(equal.total_tokens, 2_000);
+        assert!(equal.gini.abs() < 1e-9);
+        assert!((equal.top10_share - 0.5).abs() < 1e-9);
+
+        // Zero allocations aren't recipients
+        let single = allocation_metrics(&allocations(&[0, 0, 0, 400]));
+        assert_eq!(single.recipients, 1);
+        assert!(single.gini.abs() < 1e-9);
+
+        let uneven = allocation_metrics(&allocations(&[1, 1, 1, 400]));
+        assert!((uneven.gini - 0.7426).abs() < 1e-3);
+        assert_eq!(uneven.top10_share, 1.0);
+
+        assert_eq!(allocation_metrics(&HashMap::new()).gini, 0.0);
+    }
+
+    #[test]
+    fn test_exclude_inactive() {
+        let now = 100 * 86_400;
+        let mut miners = vec![
+            miner(1_000_000, 0),
+            miner(2_000_000, 0),
+            miner(3_000_000, 0),
+        ];
+        miners[0].last_hash_at = now - 86_400;
+        miners[1].last_hash_at = now - 30 * 86_400;
+        miners[2].last_hash_at = now - 7 * 86_400;
+
+        let off = AllocationParams::default();
+        assert_eq!(exclude_inactive(&mut miners.clone(), &off, now), None);
+
+        let params = AllocationParams {
+            max_inactive_days: 7,
+            ..AllocationParams::default()
+        };
+        let excluded = exclude_inactive(&mut miners, &params, now).unwrap();
+        assert_eq!(
+            excluded,
+            InactiveExclusion {
+                max_inactive_days: 7,
+                cutoff: now - 7 * 86_400,
+                miners: 1,
+                total_hashes: 2_000_000,
+            }
+        );
+        // Exactly at the cutoff still counts as active
+        assert_eq!(miners.len(), 2);
+        assert!(miners.iter().all(|m| m.total_hashes != 2_000_000));
+    }
+
+    #[test]
+    fn test_miner_standing() {
+        let params = AllocationParams {
+            top_miners: 2,
+            ..AllocationParams::default()
+        };
+        let mut miners = vec![
+            miner(1_000_000, 0),
+            miner(3_000_000, 0),
+            miner(2_000_000, 0),
+            miner(4_000_000, 0),
+        ];
+        rank_miners(&mut miners);
+
+        let first = miner_standing(&miners, &miners[0].authority, &params).unwrap();
+        assert_eq!(first.rank, 1);
+        assert_eq!(first.total_miners, 4);
+        assert_eq!(first.top_percent, 25.0);
+        assert_eq!(first.projected_tokens, 400);
+
+        // Outside top_miners projects nothing even above the minimum
+        let third = miner_standing(&miners, &miners[2].authority, &params).unwrap();
+        assert_eq!(third.rank, 3);
+        assert_eq!(third.projected_tokens, 0);
+
+        assert!(miner_standing(&miners, &Pubkey::new_unique(), &params).is_none());
+    }
+
+    #[test]
+    fn test_consolidate_duplicates() {
+        let authority = Pubkey::new_unique();
+        let older = MinerAccount {
+            authority,
+            last_hash_at: 100,
+            current_streak: 4,
+            best_difficulty: 12,
+            ..miner(300, 3)
+        };
+        let newer = MinerAccount {
+            authority,
+            last_hash_at: 200,
+            current_streak: 1,
+            best_difficulty: 9,
+            ..miner(500, 1)
+        };
+        let other = miner(50, 0);
+        let miners = vec![older.clone(), other.clone(), newer.clone()];
+
+        let (merged, reports) =
+            consolidate_duplicates(miners.clone(), DuplicatePolicy::Sum).unwrap();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[1], other);
+        assert_eq!(
+            merged[0],
+            MinerAccount {
+                authority,
+                total_hashes: 800,
+                rounds_completed: 4,
+                last_hash_at: 200,
+                current_streak: 1,
+                best_difficulty: 12,
+                layout: MinerLayout::V1,
+                tier: None,
+                capped_score: None,
+                score: None,
+                score_bucket: None,
+                archived_at: None,
+            }
+        );
+        assert_eq!(
+            reports,
+            vec![DuplicateMerge {
+                authority,
+                accounts: 2,
+                total_hashes: 800,
+            }]
+        );
+
+        let (merged, _) = consolidate_duplicates(miners.clone(), DuplicatePolicy::Max).unwrap();
+        assert_eq!(merged[0], newer);
+
+        assert!(consolidate_duplicates(miners, DuplicatePolicy::Reject).is_err());
+
+        // Nothing to merge, nothing to report
+        let (merged, reports) =
+            consolidate_duplicates(vec![older, other], DuplicatePolicy::Reject).unwrap();
+        assert_eq!(merged.len(), 2);
+        assert!(reports.is_empty());
+    }
+
+    #[test]
+    fn test_rank_breaks_ties_by_rounds() {
+        let mut miners = vec![miner(5, 1), miner(5, 2), miner(6, 0)];
+        rank_miners(&mut miners);
+
+        let order: Vec<_> = miners
+            .iter()
+            .map(|m| (m.total_hashes, m.rounds_completed))
+            .collect();
+        assert_eq!(order, vec![(6, 0), (5, 2), (5, 1)]);
+
+        let mut tied = vec![miner(5, 1), miner(5, 1)];
+        let mut reversed = vec![tied[1].clone(), tied[0].clone()];
+        rank_miners(&mut tied);
+        rank_miners(&mut reversed);
+        assert_eq!(tied, reversed);
+    }
+}
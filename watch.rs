@@ -0,0 +1,280 @@
+//! GlobalRound watcher
+//!
+//! Polls GlobalRound and alerts through the crank's ALERT_WEBHOOK_URL when
+//! the incentive program is quietly broken:
+//! - the round is overdue for rotation (the crank is down or failing)
+//! - min_difficulty has reached its cap
+//! - no hashes have landed for a while
+//! - the GlobalRound account is missing or no longer parses
+//!
+//! Each anomaly alerts once when it starts and once when it clears, so a
+//! long outage doesn't flood the channel. Every poll also updates the round
+//! metrics (see metrics.rs).
+
+use crate::crank::{alert, cluster_time, post_webhook};
+use crate::{metrics, Config};
+use anyhow::Result;
+use clap::Args;
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::time::{Duration, Instant};
+use testore_core::GlobalRoundAccount;
+use testore_program::MAX_MIN_DIFFICULTY;
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// Seconds between GlobalRound checks
+    #[arg(long, default_value_t = 60)]
+    poll_secs: u64,
+
+    /// Seconds past the round's end before an unrotated round alerts
+    #[arg(long, default_value_t = 300)]
+    rotation_grace_secs: i64,
+
+    /// Minutes without a new hash before throughput alerts
+    #[arg(long, default_value_t = 10)]
+    stall_minutes: i64,
+}
+
+/// Something wrong with GlobalRound
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Anomaly {
+    Unreadable,
+    RotationOverdue {
+        round_number: u64,
+        overdue_secs: i64,
+    },
+    DifficultyAtCap {
+        min_difficulty: u8,
+    },
+    HashesStalled {
+        idle_secs: i64,
+    },
+}
+
+impl Anomaly {
+    /// Stable identity, so a growing `overdue_secs` isn't a new anomaly
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Unreadable => "unreadable",
+            Self::RotationOverdue { .. } => "rotation_overdue",
+            Self::DifficultyAtCap { .. } => "difficulty_at_cap",
+            Self::HashesStalled { .. } => "hashes_stalled",
+        }
+    }
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unreadable => write!(f, "GlobalRound is missing or failed to parse"),
+            Self::RotationOverdue {
+                round_number,
+                overdue_secs,
+            } => write!(
+                f,
+                "round #{} should have rotated {}s ago",
+                round_number, overdue_secs
+            ),
+            Self::DifficultyAtCap { min_difficulty } => {
+                write!(f, "min_difficulty is at its cap ({})", min_difficulty)
+            }
+            Self::HashesStalled { idle_secs } => {
+                write!(f, "no hashes submitted for {}s", idle_secs)
+            }
+        }
+    }
+}
+
+/// Last time the round's hash count moved
+struct Progress {
+    round_number: u64,
+    total_hashes: u64,
+    at: i64,
+}
+
+impl Progress {
+    fn update(progress: &mut Option<Self>, round: &GlobalRoundAccount, now: i64) {
+        let moved = !matches!(progress, Some(last)
+            if last.round_number == round.round_number
+                && last.total_hashes == round.total_hashes_submitted);
+
+        if moved {
+            *progress = Some(Self {
+                round_number: round.round_number,
+                total_hashes: round.total_hashes_submitted,
+                at: now,
+            });
+        }
+    }
+}
+
+/// Watch GlobalRound until interrupted
+pub async fn run(config: &Config, args: &WatchArgs) -> Result<()> {
+    let client =
+        RpcClient::new_with_commitment(config.testnet_rpc.clone(), CommitmentConfig::confirmed());
+
+    println!(
+        "{} Watching GlobalRound - checking every {}s",
+        "👀".bright_cyan(),
+        args.poll_secs
+    );
+
+    let mut progress = None;
+    let mut firing: BTreeSet<&'static str> = BTreeSet::new();
+
+    loop {
+        let started = Instant::now();
+        let round = testore_core::fetch_global_round(&client, &config.program_id);
+        record(&round, started.elapsed());
+        let now = match cluster_time(&client) {
+            Ok(now) => now,
+            Err(e) => {
+                log::warn!("Could not read the cluster clock: {:#}", e);
+                tokio::time::sleep(Duration::from_secs(args.poll_secs)).await;
+                continue;
+            }
+        };
+
+        if let Ok(round) = &round {
+            Progress::update(&mut progress, round, now);
+        }
+        let current = anomalies(round.as_ref().ok(), progress.as_ref(), now, args);
+
+        for anomaly in &current {
+            if firing.insert(anomaly.kind()) {
+                alert(&format!("TestORE watch: {}", anomaly)).await;
+            }
+        }
+        let cleared: Vec<_> = firing
+            .iter()
+            .filter(|kind| !current.iter().any(|a| a.kind() == **kind))
+            .copied()
+            .collect();
+        for kind in cleared {
+            firing.remove(kind);
+            resolved(kind).await;
+        }
+
+        tokio::time::sleep(Duration::from_secs(args.poll_secs)).await;
+    }
+}
+
+/// Update the round metrics and rewrite METRICS_TEXTFILE
+fn record(round: &Result<GlobalRoundAccount>, elapsed: Duration) {
+    let metrics = metrics::metrics();
+    metrics.round_poll.observe(elapsed.as_secs_f64());
+    if let Ok(round) = round {
+        metrics.round_number.set(round.round_number as i64);
+        metrics
+            .round_min_difficulty
+            .set(round.min_difficulty as i64);
+        metrics
+            .total_hashes_submitted
+            .set(round.total_hashes_submitted as i64);
+    }
+
+    if let Err(e) = metrics::write_textfile() {
+        log::warn!("Could not write METRICS_TEXTFILE: {:#}", e);
+    }
+}
+
+fn anomalies(
+    round: Option<&GlobalRoundAccount>,
+    progress: Option<&Progress>,
+    now: i64,
+    args: &WatchArgs,
+) -> Vec<Anomaly> {
+    let Some(round) = round else {
+        return vec![Anomaly::Unreadable];
+    };
+    let mut found = Vec::new();
+
+    let overdue_secs = now.saturating_sub(round.ends_at());
+    if overdue_secs > args.rotation_grace_secs {
+        found.push(Anomaly::RotationOverdue {
+            round_number: round.round_number,
+            overdue_secs,
+        });
+    }
+
+    if round.min_difficulty >= MAX_MIN_DIFFICULTY {
+        found.push(Anomaly::DifficultyAtCap {
+            min_difficulty: round.min_difficulty,
+        });
+    }
+
+    if let Some(progress) = progress {
+        let idle_secs = now.saturating_sub(progress.at);
+        if idle_secs > args.stall_minutes.saturating_mul(60) {
+            found.push(Anomaly::HashesStalled { idle_secs });
+        }
+    }
+
+    found
+}
+
+async fn resolved(kind: &str) {
+    let message = format!("TestORE watch: {} resolved", kind);
+    log::info!("{}", message);
+    println!("{} {}", "✅".bright_green(), message);
+    post_webhook(&message).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_anomalies() {
+        let args = WatchArgs {
+            poll_secs: 60,
+            rotation_grace_secs: 300,
+            stall_minutes: 10,
+        };
+        let round = GlobalRoundAccount {
+            current_challenge: [0; 32],
+            round_number: 7,
+            started_at: 1_000,
+            min_difficulty: 8,
+            total_hashes_submitted: 50,
+            total_rounds_completed: 0,
+            admin: Pubkey::new_unique(),
+            tier_step: 0,
+            round_duration: 3_600,
+            max_round_proofs: 0,
+        };
+        let mut progress = None;
+        Progress::update(&mut progress, &round, 1_000);
+
+        assert_eq!(
+            anomalies(None, None, 1_000, &args),
+            vec![Anomaly::Unreadable]
+        );
+        assert!(anomalies(Some(&round), progress.as_ref(), 1_600, &args).is_empty());
+
+        // Same hash count over an hour later, and past the round's grace period
+        let found = anomalies(Some(&round), progress.as_ref(), 5_000, &args);
+        let kinds: Vec<_> = found.iter().map(Anomaly::kind).collect();
+        assert_eq!(kinds, vec!["rotation_overdue", "hashes_stalled"]);
+
+        // A new hash resets the stall clock
+        let busier = GlobalRoundAccount {
+            total_hashes_submitted: 51,
+            min_difficulty: MAX_MIN_DIFFICULTY,
+            ..round
+        };
+        Progress::update(&mut progress, &busier, 2_000);
+        let found = anomalies(Some(&busier), progress.as_ref(), 2_100, &args);
+        assert_eq!(
+            found,
+            vec![Anomaly::DifficultyAtCap {
+                min_difficulty: MAX_MIN_DIFFICULTY
+            }]
+        );
+    }
+}